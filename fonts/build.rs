@@ -1,54 +1,43 @@
-use std::{
-    error::Error,
-    fs::{read_dir, read_to_string, File},
-    io::Write,
-    ops::Shl,
-    path::Path,
-};
+use std::{error::Error, fs::File, io::Write, path::Path};
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let src_path = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/src"));
-
-    eprintln!("Reading {}", src_path.to_str().unwrap());
-
-    for res in read_dir(src_path)? {
-        let file = res?;
-        if file.file_type()?.is_file() {
-            let name = file.file_name().into_string().unwrap();
-            let input = if name.starts_with("font_") && name.ends_with(".txt") {
-                eprintln!("Reading {}", file.path().to_str().unwrap());
-                read_to_string(file.path())?
-            } else {
-                continue;
-            };
-
-            let mut out_path = Path::new(&std::env::var("OUT_DIR").unwrap()).to_path_buf();
-            out_path.push(&name);
-            out_path.set_extension("raw");
-            let mut writer = File::create(out_path)?;
+#[path = "src/import.rs"]
+mod import;
 
-            let bits: Vec<bool> = input
-                .chars()
-                .filter_map(|c| match c {
-                    '0' => Some(false),
-                    '1' => Some(true),
-                    ' ' => None,
-                    '\n' => None,
-                    '\r' => None,
-                    _ => panic!("Bad character in file {}: {}", name, c),
-                })
-                .collect();
-            assert_eq!(bits.len() % 8, 0);
-
-            let bytes: Vec<u8> = bits
-                .chunks(8)
-                .map(|chunk| chunk.iter().fold(0u8, |acc, x| acc.shl(1) + u8::from(*x)))
-                .collect();
-
-            writer.write_all(&bytes)?;
-
-            println!("cargo:rerun-if-changed={}", file.path().to_str().unwrap());
-        }
+fn main() -> Result<(), Box<dyn Error>> {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+
+    println!("cargo:rerun-if-changed=src/import.rs");
+    for font in import::FONTS {
+        let hand_drawn = font.hand_drawn_path(manifest_dir);
+        let bdf = font.bdf_path(manifest_dir);
+        eprintln!(
+            "Reading {} and {}",
+            hand_drawn.to_str().unwrap(),
+            bdf.to_str().unwrap()
+        );
+
+        let glyphs = font.load_glyphs(manifest_dir)?;
+        let mut out_path = Path::new(&out_dir).join(font.name);
+        out_path.set_extension("raw");
+        File::create(out_path)?.write_all(&import::to_raw(&glyphs, font.width, font.height))?;
+
+        let columns: Vec<_> = glyphs
+            .iter()
+            .map(|glyph| format!("{:?}", import::glyph_columns(glyph)))
+            .collect();
+        let path = Path::new(&out_dir).join(format!("{}_columns.rs", font.name));
+        write!(File::create(path)?, "&[{}]", columns.join(", "))?;
+
+        let kerning: Vec<_> = import::kerning_pairs(&glyphs)
+            .iter()
+            .map(|(left, right, kerning)| format!("(b{left:?}, b{right:?}, {kerning})"))
+            .collect();
+        let path = Path::new(&out_dir).join(format!("{}_kerning.rs", font.name));
+        write!(File::create(path)?, "&[{}]", kerning.join(", "))?;
+
+        println!("cargo:rerun-if-changed={}", hand_drawn.to_str().unwrap());
+        println!("cargo:rerun-if-changed={}", bdf.to_str().unwrap());
     }
     Ok(())
 }
@@ -17,7 +17,7 @@ height = int(match.group(2))
 data = array('B')
 
 with open(path, "rb") as f:
-    data.fromfile(f, width * height * 6)
+    data.frombytes(f.read())
 
 data_string = ""
 
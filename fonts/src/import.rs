@@ -0,0 +1,320 @@
+//! Turns the font sources into the raw images that the fonts are drawn from. The glyphs drawn by
+//! hand in `src/font_*.txt` are used where there are any, and the rest of Latin-1 is imported from
+//! the BDF fonts in `bdf/`, scaled up to fit. Used by the build script, and by the tests to check
+//! the raw images against the sources.
+
+use std::{collections::BTreeMap, error::Error, fs::read_to_string, path::Path};
+
+/// The characters drawn by hand in the `font_*.txt` files, in the order they are drawn
+pub const HAND_DRAWN: &str = " #-/0123456789:ABCDEFGHIJKLMNOPQRSTUVWXYZ[]_?.";
+
+/// Glyphs are laid out in rows of this many, in both the hand drawn sources and the raw images
+pub const GLYPHS_PER_ROW: usize = 8;
+
+/// A glyph as rows of pixels, `true` where the glyph is drawn
+pub type Glyph = Vec<Vec<bool>>;
+
+pub struct FontSource {
+    pub name: &'static str,
+    pub width: usize,
+    pub height: usize,
+    /// The first row below the hand drawn capitals
+    pub baseline: usize,
+    /// The BDF font in `bdf/` that the glyphs that aren't drawn by hand come from
+    pub bdf: &'static str,
+}
+
+pub const FONTS: [FontSource; 6] = [
+    FontSource::new("font_5x8", 5, 8, 7, "5x8.bdf"),
+    FontSource::new("font_7x15", 7, 15, 14, "7x14.bdf"),
+    FontSource::new("font_10x25", 10, 25, 24, "10x20.bdf"),
+    FontSource::new("font_14x31", 14, 31, 29, "7x14.bdf"),
+    FontSource::new("font_20x46", 20, 46, 44, "10x20.bdf"),
+    FontSource::new("font_28x64", 28, 64, 60, "7x14.bdf"),
+];
+
+/// The characters in the raw images, in order. This matches `mono_font::mapping::ISO_8859_1`
+pub fn latin1() -> impl Iterator<Item = char> {
+    ('\u{20}'..='\u{7f}').chain('\u{a0}'..='\u{ff}')
+}
+
+impl FontSource {
+    const fn new(
+        name: &'static str,
+        width: usize,
+        height: usize,
+        baseline: usize,
+        bdf: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            width,
+            height,
+            baseline,
+            bdf,
+        }
+    }
+
+    pub fn hand_drawn_path(&self, manifest_dir: &Path) -> std::path::PathBuf {
+        manifest_dir.join("src").join(format!("{}.txt", self.name))
+    }
+
+    pub fn bdf_path(&self, manifest_dir: &Path) -> std::path::PathBuf {
+        manifest_dir.join("bdf").join(self.bdf)
+    }
+
+    /// Every glyph of the font, in the order of `latin1()`
+    pub fn load_glyphs(&self, manifest_dir: &Path) -> Result<Vec<Glyph>, Box<dyn Error>> {
+        let hand_drawn = parse_hand_drawn(
+            &read_to_string(self.hand_drawn_path(manifest_dir))?,
+            self.width,
+            self.height,
+        )
+        .map_err(|e| format!("{}: {e}", self.name))?;
+        let bdf = BdfFont::parse(&read_to_string(self.bdf_path(manifest_dir))?)
+            .map_err(|e| format!("{}: {e}", self.bdf))?;
+
+        let scale = (self.width / bdf.width).min(self.height / (bdf.ascent + bdf.descent));
+        if scale == 0 {
+            return Err(format!("{} is too big for {}", self.bdf, self.name).into());
+        }
+        // Line up the baselines, unless that would cut off the bottom of the BDF glyphs
+        let baseline = self.baseline.min(self.height - bdf.descent * scale);
+        let top = baseline as isize - (bdf.ascent * scale) as isize;
+        let left = (self.width - bdf.width * scale) / 2;
+
+        Ok(latin1()
+            .map(|c| {
+                if let Some(glyph) = hand_drawn.get(&c) {
+                    return glyph.clone();
+                }
+                let mut glyph = vec![vec![false; self.width]; self.height];
+                if let Some(source) = bdf.glyphs.get(&c) {
+                    for (y, row) in glyph.iter_mut().enumerate() {
+                        let source_y = y as isize - top;
+                        if source_y < 0 || source_y as usize >= source.len() * scale {
+                            continue;
+                        }
+                        let source_row = &source[source_y as usize / scale];
+                        for (x, pixel) in row.iter_mut().enumerate().skip(left) {
+                            *pixel = source_row.get((x - left) / scale).copied().unwrap_or(false);
+                        }
+                    }
+                }
+                glyph
+            })
+            .collect())
+    }
+}
+
+/// Reads the glyphs of `HAND_DRAWN` from a `font_*.txt` file, which holds a `1` for each pixel
+/// drawn and a `0` for each pixel not drawn. Spaces and line breaks are ignored
+pub fn parse_hand_drawn(
+    text: &str,
+    width: usize,
+    height: usize,
+) -> Result<BTreeMap<char, Glyph>, Box<dyn Error>> {
+    let bits = text
+        .chars()
+        .filter_map(|c| match c {
+            '0' => Some(Ok(false)),
+            '1' => Some(Ok(true)),
+            ' ' | '\n' | '\r' => None,
+            _ => Some(Err(format!("bad character {c:?}"))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let row_len = GLYPHS_PER_ROW * width * height;
+    let glyph_rows = HAND_DRAWN.chars().count().div_ceil(GLYPHS_PER_ROW);
+    if bits.len() != glyph_rows * row_len {
+        return Err(format!(
+            "expected {} pixels, found {}",
+            glyph_rows * row_len,
+            bits.len()
+        )
+        .into());
+    }
+
+    Ok(HAND_DRAWN
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let start = (i / GLYPHS_PER_ROW) * row_len + (i % GLYPHS_PER_ROW) * width;
+            let glyph = (0..height)
+                .map(|y| {
+                    let row_start = start + y * GLYPHS_PER_ROW * width;
+                    bits[row_start..row_start + width].to_vec()
+                })
+                .collect();
+            (c, glyph)
+        })
+        .collect())
+}
+
+/// The parts of a BDF font that are needed to import its glyphs
+pub struct BdfFont {
+    pub width: usize,
+    pub ascent: usize,
+    pub descent: usize,
+    /// Each glyph placed in the font's cell, which is `width` wide and `ascent + descent` high
+    pub glyphs: BTreeMap<char, Glyph>,
+}
+
+impl BdfFont {
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        fn numbers<const N: usize>(line: &str) -> Result<[isize; N], Box<dyn Error>> {
+            let values = line
+                .split_whitespace()
+                .skip(1)
+                .map(|value| value.parse())
+                .collect::<Result<Vec<_>, _>>()?;
+            values
+                .try_into()
+                .map_err(|_| format!("expected {N} numbers: {line}").into())
+        }
+
+        let mut lines = text.lines();
+        let mut width = None;
+        let mut x_offset = 0;
+        let mut ascent = None;
+        let mut descent = None;
+        let mut glyphs = BTreeMap::new();
+
+        while let Some(line) = lines.next() {
+            match line.split_whitespace().next() {
+                Some("FONTBOUNDINGBOX") => {
+                    let [w, _, x, _] = numbers(line)?;
+                    width = Some(w as usize);
+                    x_offset = x;
+                }
+                Some("FONT_ASCENT") => ascent = Some(numbers::<1>(line)?[0] as usize),
+                Some("FONT_DESCENT") => descent = Some(numbers::<1>(line)?[0] as usize),
+                Some("STARTCHAR") => {
+                    let (Some(width), Some(ascent), Some(descent)) = (width, ascent, descent)
+                    else {
+                        return Err("glyphs before the font's size".into());
+                    };
+                    let mut encoding = None;
+                    let mut bbx = [0; 4];
+                    for line in lines.by_ref() {
+                        match line.split_whitespace().next() {
+                            Some("ENCODING") => encoding = Some(numbers::<1>(line)?[0]),
+                            Some("BBX") => bbx = numbers(line)?,
+                            Some("BITMAP") => break,
+                            _ => {}
+                        }
+                    }
+
+                    let [w, h, x, y] = bbx;
+                    let mut glyph = vec![vec![false; width]; ascent + descent];
+                    for row in 0..h {
+                        let hex = lines.next().ok_or("glyph bitmap ends early")?;
+                        let bits = u32::from_str_radix(hex.trim(), 16)?;
+                        let row_bits = hex.trim().len() as isize * 4;
+                        let cell_y = ascent as isize - (y + h) + row;
+                        for col in 0..w {
+                            let cell_x = x - x_offset + col;
+                            if bits & (1 << (row_bits - 1 - col)) == 0 {
+                                continue;
+                            }
+                            let pixel = usize::try_from(cell_y)
+                                .ok()
+                                .zip(usize::try_from(cell_x).ok())
+                                .and_then(|(cell_y, cell_x)| glyph.get_mut(cell_y)?.get_mut(cell_x))
+                                .ok_or("glyph outside the font's bounding box")?;
+                            *pixel = true;
+                        }
+                    }
+
+                    if let Some(c) = encoding
+                        .and_then(|encoding| u32::try_from(encoding).ok())
+                        .and_then(char::from_u32)
+                    {
+                        glyphs.insert(c, glyph);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            width: width.ok_or("no FONTBOUNDINGBOX")?,
+            ascent: ascent.ok_or("no FONT_ASCENT")?,
+            descent: descent.ok_or("no FONT_DESCENT")?,
+            glyphs,
+        })
+    }
+}
+
+/// Packs `glyphs` into a raw image, `GLYPHS_PER_ROW` glyphs wide, as `ImageRaw` reads it
+pub fn to_raw(glyphs: &[Glyph], width: usize, height: usize) -> Vec<u8> {
+    let image_width = GLYPHS_PER_ROW * width;
+    let bytes_per_row = image_width.div_ceil(8);
+    let mut raw = Vec::new();
+    for glyph_row in glyphs.chunks(GLYPHS_PER_ROW) {
+        for y in 0..height {
+            let mut bytes = vec![0u8; bytes_per_row];
+            for (i, glyph) in glyph_row.iter().enumerate() {
+                for (x, &pixel) in glyph[y].iter().enumerate() {
+                    if pixel {
+                        let image_x = i * width + x;
+                        bytes[image_x / 8] |= 0x80 >> (image_x % 8);
+                    }
+                }
+            }
+            raw.extend(bytes);
+        }
+    }
+    raw
+}
+
+/// The columns of `glyph` that a proportional font draws, as the first column and how many there
+/// are. Blank glyphs, like the space, are drawn as half the width of the cell
+pub fn glyph_columns(glyph: &Glyph) -> (usize, usize) {
+    let width = glyph.first().map_or(0, Vec::len);
+    let inked = |x: &usize| glyph.iter().any(|row| row[*x]);
+    match ((0..width).find(inked), (0..width).rev().find(inked)) {
+        (Some(first), Some(last)) => (first, last - first + 1),
+        _ => (0, width.div_ceil(2)),
+    }
+}
+
+/// How many pixels closer `right` can be drawn after `left` in a proportional font, without any
+/// row of them coming closer than they are drawn without kerning. Only rows that both glyphs are
+/// drawn in are compared, and glyphs never move closer than half the width of the narrower one
+pub fn kerning(left: &Glyph, right: &Glyph) -> usize {
+    let (left_first, left_width) = glyph_columns(left);
+    let (right_first, right_width) = glyph_columns(right);
+    let left_last = left_first + left_width - 1;
+
+    let closest = left
+        .iter()
+        .zip(right)
+        .filter_map(|(left_row, right_row)| {
+            let left_gap = left_last - left_row.iter().rposition(|&pixel| pixel)?;
+            let right_gap = right_row.iter().position(|&pixel| pixel)? - right_first;
+            Some(left_gap + right_gap)
+        })
+        .min();
+    closest.map_or(0, |closest| closest.min(left_width.min(right_width) / 2))
+}
+
+/// Every pair of the hand drawn characters that can be drawn closer together in a proportional
+/// font, with how many pixels closer, in order. `glyphs` are in the order of `latin1()`. The
+/// glyphs imported from BDF fonts are spaced as they were designed
+pub fn kerning_pairs(glyphs: &[Glyph]) -> Vec<(char, char, usize)> {
+    let hand_drawn: Vec<_> = latin1()
+        .zip(glyphs)
+        .filter(|(c, _)| HAND_DRAWN.contains(*c))
+        .collect();
+    let mut pairs = Vec::new();
+    for (left_char, left) in &hand_drawn {
+        for (right_char, right) in &hand_drawn {
+            let kerning = kerning(left, right);
+            if kerning > 0 {
+                pairs.push((*left_char, *right_char, kerning));
+            }
+        }
+    }
+    pairs
+}
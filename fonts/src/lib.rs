@@ -1,4 +1,9 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
+
+#[cfg(test)]
+mod import;
+mod proportional;
+pub use proportional::*;
 
 pub mod fonts {
     macro_rules! font {
@@ -9,10 +14,7 @@ pub mod fonts {
                         core::include_bytes!(core::concat!(core::env!("OUT_DIR"), "/", $file)),
                         $w * 8,
                     ),
-                    glyph_mapping: &embedded_graphics::mono_font::mapping::StrGlyphMapping::new(
-                        " #-/0123456789:ABCDEFGHIJKLMNOPQRSTUVWXYZ[]_",
-                        44,
-                    ),
+                    glyph_mapping: &embedded_graphics::mono_font::mapping::ISO_8859_1,
                     character_size: embedded_graphics::geometry::Size::new($w, $h),
                     character_spacing: $spacing,
                     baseline: $base,
@@ -32,4 +34,262 @@ pub mod fonts {
     font!(FONT_14X31, "font_14x31.raw", 14, 31, 2, 29);
     font!(FONT_20X46, "font_20x46.raw", 20, 46, 2, 44);
     font!(FONT_28X64, "font_28x64.raw", 28, 64, 4, 60);
+
+    macro_rules! proportional {
+        ($name:ident, $font:ident, $columns:literal, $kerning:literal) => {
+            pub const $name: crate::ProportionalFont = crate::ProportionalFont {
+                font: &$font,
+                glyph_columns: core::include!(core::concat!(core::env!("OUT_DIR"), "/", $columns)),
+                kerning: core::include!(core::concat!(core::env!("OUT_DIR"), "/", $kerning)),
+            };
+        };
+    }
+
+    proportional!(
+        PROPORTIONAL_5X8,
+        FONT_5X8,
+        "font_5x8_columns.rs",
+        "font_5x8_kerning.rs"
+    );
+    proportional!(
+        PROPORTIONAL_7X15,
+        FONT_7X15,
+        "font_7x15_columns.rs",
+        "font_7x15_kerning.rs"
+    );
+    proportional!(
+        PROPORTIONAL_10X25,
+        FONT_10X25,
+        "font_10x25_columns.rs",
+        "font_10x25_kerning.rs"
+    );
+    proportional!(
+        PROPORTIONAL_14X31,
+        FONT_14X31,
+        "font_14x31_columns.rs",
+        "font_14x31_kerning.rs"
+    );
+    proportional!(
+        PROPORTIONAL_20X46,
+        FONT_20X46,
+        "font_20x46_columns.rs",
+        "font_20x46_kerning.rs"
+    );
+    proportional!(
+        PROPORTIONAL_28X64,
+        FONT_28X64,
+        "font_28x64_columns.rs",
+        "font_28x64_kerning.rs"
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::{fonts::*, import::*, ProportionalFont, ProportionalTextStyle};
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{mapping::ISO_8859_1, MonoFont, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        text::{Baseline, Text},
+    };
+    use std::path::Path;
+
+    const ALL: [MonoFont; 6] = [
+        FONT_5X8, FONT_7X15, FONT_10X25, FONT_14X31, FONT_20X46, FONT_28X64,
+    ];
+
+    const PROPORTIONAL: [ProportionalFont; 6] = [
+        PROPORTIONAL_5X8,
+        PROPORTIONAL_7X15,
+        PROPORTIONAL_10X25,
+        PROPORTIONAL_14X31,
+        PROPORTIONAL_20X46,
+        PROPORTIONAL_28X64,
+    ];
+
+    fn manifest_dir() -> &'static Path {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_fonts_match_sources() {
+        let latin1: Vec<_> = latin1().collect();
+        assert_eq!(ISO_8859_1.chars().collect::<Vec<_>>(), latin1);
+        for (font, source) in ALL.iter().zip(FONTS) {
+            assert_eq!(
+                font.character_size,
+                Size::new(source.width as u32, source.height as u32),
+                "{}",
+                source.name
+            );
+            assert_eq!(font.baseline as usize, source.baseline, "{}", source.name);
+            assert_eq!(
+                font.image.size().height as usize,
+                latin1.len().div_ceil(GLYPHS_PER_ROW) * source.height,
+                "{}",
+                source.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_glyphs_round_trip() {
+        for (font, source) in ALL.iter().zip(FONTS) {
+            let glyphs = source.load_glyphs(manifest_dir()).unwrap();
+            let raw = Path::new(env!("OUT_DIR")).join(format!("{}.raw", source.name));
+            assert_eq!(
+                std::fs::read(raw).unwrap(),
+                to_raw(&glyphs, source.width, source.height),
+                "{}",
+                source.name
+            );
+            let style = MonoTextStyle::new(font, BinaryColor::On);
+            for (c, glyph) in latin1().zip(glyphs) {
+                let mut display = MockDisplay::new();
+                let mut text = [0; 4];
+                Text::with_baseline(
+                    c.encode_utf8(&mut text),
+                    Point::zero(),
+                    style,
+                    Baseline::Top,
+                )
+                .draw(&mut display)
+                .unwrap();
+                for (y, row) in glyph.iter().enumerate() {
+                    for (x, &pixel) in row.iter().enumerate() {
+                        let point = Point::new(x as i32, y as i32);
+                        assert_eq!(
+                            display.get_pixel(point) == Some(BinaryColor::On),
+                            pixel,
+                            "{} {c:?} at {point:?}",
+                            source.name
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_glyph_sources() {
+        for source in FONTS {
+            let glyphs = source.load_glyphs(manifest_dir()).unwrap();
+            let text = std::fs::read_to_string(source.hand_drawn_path(manifest_dir())).unwrap();
+            let hand_drawn = parse_hand_drawn(&text, source.width, source.height).unwrap();
+            for (c, glyph) in latin1().zip(glyphs) {
+                if let Some(hand_drawn) = hand_drawn.get(&c) {
+                    assert_eq!(&glyph, hand_drawn, "{} {c:?}", source.name);
+                } else if !c.is_whitespace() && c != '\u{7f}' {
+                    assert!(
+                        glyph.iter().flatten().any(|&pixel| pixel),
+                        "{} {c:?} is blank",
+                        source.name
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_proportional_widths() {
+        for (font, source) in PROPORTIONAL.iter().zip(FONTS) {
+            let spacing = font.font.character_spacing;
+            assert_eq!(
+                font.glyph_columns.len(),
+                latin1().count(),
+                "{}",
+                source.name
+            );
+            let glyphs = source.load_glyphs(manifest_dir()).unwrap();
+            let columns: Vec<_> = glyphs
+                .iter()
+                .map(|glyph| {
+                    let (first, width) = glyph_columns(glyph);
+                    (first as u8, width as u8)
+                })
+                .collect();
+            assert_eq!(font.glyph_columns, columns, "{}", source.name);
+            let kerning: Vec<_> = kerning_pairs(&glyphs)
+                .into_iter()
+                .map(|(left, right, kerning)| (left as u8, right as u8, kerning as u8))
+                .collect();
+            assert_eq!(font.kerning, kerning, "{}", source.name);
+            assert!(font.kerning.windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(font.text_width(""), 0);
+            assert!(
+                font.text_width(":") < font.text_width("W"),
+                "{}",
+                source.name
+            );
+            // Narrower than the same text in the font the glyphs come from
+            let mono_width = 4 * (source.width as u32 + spacing) - spacing;
+            assert!(font.text_width("4:59") < mono_width, "{}", source.name);
+            for text in ["#1", "T.", "4:59", "WHITE"] {
+                let mut chars = text.chars();
+                let first = chars.next().unwrap();
+                let rest = chars.as_str();
+                assert_eq!(
+                    font.text_width(text),
+                    font.text_width(&text[..first.len_utf8()]) + spacing + font.text_width(rest)
+                        - font.kerning(first, rest.chars().next().unwrap()),
+                    "{} {text:?}",
+                    source.name
+                );
+            }
+        }
+        assert_eq!(PROPORTIONAL_7X15.kerning('\u{e9}', 'T'), 0);
+    }
+
+    #[test]
+    fn test_proportional_drawing() {
+        for (font, source) in PROPORTIONAL.iter().zip(FONTS) {
+            let glyphs: Vec<_> = latin1()
+                .zip(source.load_glyphs(manifest_dir()).unwrap())
+                .collect();
+            let glyph = |c| &glyphs.iter().find(|(other, _)| *other == c).unwrap().1;
+
+            let text = "T.";
+            let mut display = MockDisplay::new();
+            display.set_allow_out_of_bounds_drawing(true);
+            let next = Text::with_baseline(
+                text,
+                Point::zero(),
+                ProportionalTextStyle::new(font, BinaryColor::On),
+                Baseline::Top,
+            )
+            .draw(&mut display)
+            .unwrap();
+            assert_eq!(
+                next.x as u32,
+                font.text_width(text) + font.font.character_spacing,
+                "{}",
+                source.name
+            );
+
+            let mut x = 0;
+            let mut previous = None;
+            for c in text.chars() {
+                let (first, width) = glyph_columns(glyph(c));
+                if let Some((previous, previous_width)) = previous {
+                    x += previous_width + font.font.character_spacing as usize;
+                    x -= font.kerning(previous, c) as usize;
+                }
+                for (y, row) in glyph(c).iter().enumerate() {
+                    for column in 0..width {
+                        if row[first + column] {
+                            let point = Point::new((x + column) as i32, y as i32);
+                            assert_eq!(
+                                display.get_pixel(point),
+                                Some(BinaryColor::On),
+                                "{} {c:?} at {point:?}",
+                                source.name
+                            );
+                        }
+                    }
+                }
+                previous = Some((c, width));
+            }
+        }
+    }
 }
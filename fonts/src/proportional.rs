@@ -0,0 +1,199 @@
+use embedded_graphics::{
+    image::{Image, ImageDrawable, ImageDrawableExt},
+    mono_font::MonoFont,
+    pixelcolor::{BinaryColor, PixelColor},
+    prelude::*,
+    primitives::Rectangle,
+    text::{
+        renderer::{TextMetrics, TextRenderer},
+        Baseline,
+    },
+};
+
+/// A font drawn from the glyphs of a `MonoFont`, with each glyph only as wide as it is drawn, and
+/// pairs of glyphs that fit together drawn closer
+#[derive(Debug, Clone, Copy)]
+pub struct ProportionalFont<'a> {
+    pub font: &'a MonoFont<'a>,
+    /// The columns of each glyph's cell that are drawn, as the first column and how many there
+    /// are, in the order of the font's glyph mapping
+    pub glyph_columns: &'a [(u8, u8)],
+    /// Pairs of ASCII characters that are drawn closer together, and by how many pixels. Sorted
+    /// by the pair of characters
+    pub kerning: &'a [(u8, u8, u8)],
+}
+
+impl ProportionalFont<'_> {
+    /// The columns of `c`'s glyph that are drawn
+    fn columns(&self, c: char) -> (u32, u32) {
+        let index = self.font.glyph_mapping.index(c);
+        let (first, width) = self
+            .glyph_columns
+            .get(index)
+            .copied()
+            .unwrap_or((0, self.font.character_size.width as u8));
+        (first as u32, width as u32)
+    }
+
+    /// How many pixels closer `right` is drawn after `left`
+    pub fn kerning(&self, left: char, right: char) -> u32 {
+        let (Ok(left), Ok(right)) = (u8::try_from(left), u8::try_from(right)) else {
+            return 0;
+        };
+        self.kerning
+            .binary_search_by_key(&(left, right), |&(left, right, _)| (left, right))
+            .map_or(0, |i| self.kerning[i].2 as u32)
+    }
+
+    /// The width of a single line of `text`
+    pub fn text_width(&self, text: &str) -> u32 {
+        self.layout(text, 0).last().map_or(0, |(x, c)| {
+            let (_, width) = self.columns(c);
+            (x + width as i32) as u32
+        })
+    }
+
+    /// The offset of each character of `text` from `x`, after the spacing and kerning before it
+    fn layout<'t>(&'t self, text: &'t str, x: i32) -> impl Iterator<Item = (i32, char)> + 't {
+        let mut previous: Option<char> = None;
+        let mut next_x = x;
+        text.chars().map(move |c| {
+            if let Some(previous) = previous {
+                let (_, width) = self.columns(previous);
+                next_x += (width + self.font.character_spacing) as i32;
+                next_x -= self.kerning(previous, c) as i32;
+            }
+            previous = Some(c);
+            (next_x, c)
+        })
+    }
+
+    /// The glyph of `c`, cropped to the columns that are drawn
+    fn glyph(&self, c: char) -> impl ImageDrawable<Color = BinaryColor> + '_ {
+        let size = self.font.character_size;
+        let glyphs_per_row = (self.font.image.size().width / size.width.max(1)).max(1);
+        let index = self.font.glyph_mapping.index(c) as u32;
+        let (first, width) = self.columns(c);
+        self.font.image.sub_image(&Rectangle::new(
+            Point::new(
+                ((index % glyphs_per_row) * size.width + first) as i32,
+                ((index / glyphs_per_row) * size.height) as i32,
+            ),
+            Size::new(width, size.height),
+        ))
+    }
+}
+
+/// Draws text in a `ProportionalFont`, in a single color
+#[derive(Debug, Clone, Copy)]
+pub struct ProportionalTextStyle<'a, C> {
+    pub font: &'a ProportionalFont<'a>,
+    pub text_color: C,
+}
+
+impl<'a, C> ProportionalTextStyle<'a, C> {
+    pub const fn new(font: &'a ProportionalFont<'a>, text_color: C) -> Self {
+        Self { font, text_color }
+    }
+
+    fn baseline_offset(&self, baseline: Baseline) -> i32 {
+        let font = self.font.font;
+        match baseline {
+            Baseline::Top => 0,
+            Baseline::Bottom => font.character_size.height.saturating_sub(1) as i32,
+            Baseline::Middle => (font.character_size.height.saturating_sub(1) / 2) as i32,
+            Baseline::Alphabetic => font.baseline as i32,
+        }
+    }
+}
+
+/// Draws the lit pixels of a glyph in the text color
+struct Foreground<'d, D: DrawTarget> {
+    target: &'d mut D,
+    color: D::Color,
+}
+
+impl<D: DrawTarget> Dimensions for Foreground<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Foreground<'_, D> {
+    type Color = BinaryColor;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let color = self.color;
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .filter(|Pixel(_, pixel)| pixel.is_on())
+                .map(|Pixel(point, _)| Pixel(point, color)),
+        )
+    }
+}
+
+impl<C: PixelColor> TextRenderer for ProportionalTextStyle<'_, C> {
+    type Color = C;
+
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        if text.is_empty() {
+            return Ok(position);
+        }
+        let top_left = position - Point::new(0, self.baseline_offset(baseline));
+        let mut foreground = Foreground {
+            target,
+            color: self.text_color,
+        };
+        for (x, c) in self.font.layout(text, top_left.x) {
+            Image::new(&self.font.glyph(c), Point::new(x, top_left.y)).draw(&mut foreground)?;
+        }
+
+        Ok(self.measure_string(text, position, baseline).next_position
+            + Point::new(self.font.font.character_spacing as i32, 0))
+    }
+
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        _baseline: Baseline,
+        _target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        Ok(position + Point::new(width as i32, 0))
+    }
+
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let size = Size::new(
+            self.font.text_width(text),
+            self.font.font.character_size.height,
+        );
+        TextMetrics {
+            bounding_box: Rectangle::new(
+                position - Point::new(0, self.baseline_offset(baseline)),
+                size,
+            ),
+            next_position: position + size.x_axis(),
+        }
+    }
+
+    fn line_height(&self) -> u32 {
+        self.font.font.character_size.height
+    }
+}
@@ -0,0 +1,144 @@
+use arrayvec::ArrayString;
+use core::fmt::Write;
+use embedded_graphics::{
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{rectangle::Rectangle, PrimitiveStyle},
+};
+use uwh_common::game_snapshot::*;
+
+use crate::{
+    draw_text, element_text, period_text, secs_to_time_string, team_name, PanelColors, PanelLayout,
+};
+
+/// How long the goal banner takes to open out from the middle of the panels
+const GOAL_OPEN_MILLIS: u32 = 400;
+
+/// How long the goal banner shows each way round once it is open
+const GOAL_SWAP_MILLIS: u32 = 400;
+
+/// How long the timeout banner takes to drop into place
+const TIMEOUT_DROP_MILLIS: u32 = 500;
+
+/// How long the zeroed clock is shown, and then hidden, at the end of a period
+const PERIOD_END_BLINK_MILLIS: u32 = 250;
+
+/// Draws the frame of `animation` that is `millis` after it started, in place of the game. Once
+/// `millis` reaches `animation.millis()` the animation is over, and the panels should go back to
+/// drawing the game with `draw_panels_frame`
+///
+/// - A goal opens a banner of the scoring team's color out from the middle of the panels, then
+///   shows `GOAL` and the team and cap number, swapping the colors of the banner and the text
+/// - A timeout drops the kind of timeout into place from above, in the timeout's color
+/// - The end of a period blinks the zeroed clock, with the period that follows it
+pub fn draw_animation_frame<D: DrawTarget<Color = Rgb888>>(
+    display: &mut D,
+    state: &GameSnapshotNoHeap,
+    animation: PanelAnimation,
+    layout: &PanelLayout,
+    colors: &PanelColors,
+    millis: u32,
+) -> Result<(), D::Error> {
+    match animation {
+        PanelAnimation::Goal(color, player_number) => {
+            let (team_color, team) = match color {
+                Color::Black => (
+                    colors.black_team,
+                    team_name(state.b_team_code.as_ref(), "BLACK"),
+                ),
+                Color::White => (
+                    colors.white_team,
+                    team_name(state.w_team_code.as_ref(), "WHITE"),
+                ),
+            };
+            draw_goal(display, layout, team_color, team, player_number, millis)
+        }
+        PanelAnimation::Timeout => {
+            let (color, kind, label) = match state.timeout {
+                TimeoutSnapshot::Black(_) => (
+                    colors.black_team,
+                    team_name(state.b_team_code.as_ref(), "BLACK"),
+                    "T/O",
+                ),
+                TimeoutSnapshot::White(_) => (
+                    colors.white_team,
+                    team_name(state.w_team_code.as_ref(), "WHITE"),
+                    "T/O",
+                ),
+                TimeoutSnapshot::Ref(_) => (colors.pause, "REF", "T/O"),
+                TimeoutSnapshot::PenaltyShot(_) => (colors.alert, "PENALTY", "SHOT"),
+                TimeoutSnapshot::None => (colors.pause, "", "T/O"),
+            };
+            // Everything starts above the panels, and falls the height of the layout
+            let drop = layout.size.height * TIMEOUT_DROP_MILLIS.saturating_sub(millis)
+                / TIMEOUT_DROP_MILLIS;
+            let offset = Point::new(0, -(drop as i32));
+            Rectangle::new(offset, layout.size)
+                .into_styled(PrimitiveStyle::with_stroke(color, 1))
+                .draw(display)?;
+            element_text(kind, &layout.period, color)
+                .translate(offset)
+                .draw(display)?;
+            element_text(label, &layout.clock, color)
+                .translate(offset)
+                .draw(display)?;
+            Ok(())
+        }
+        PanelAnimation::PeriodEnd => {
+            let next = if state.current_period == GamePeriod::BetweenGames {
+                "GAME OVER"
+            } else {
+                period_text(state.current_period, false, &layout.period)
+            };
+            draw_text(display, next, &layout.period, colors.alert)?;
+            if (millis / PERIOD_END_BLINK_MILLIS).is_multiple_of(2) {
+                draw_text(
+                    display,
+                    &secs_to_time_string(0u16),
+                    &layout.clock,
+                    colors.alert,
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn draw_goal<D: DrawTarget<Color = Rgb888>>(
+    display: &mut D,
+    layout: &PanelLayout,
+    team_color: Rgb888,
+    team: &str,
+    player_number: u8,
+    millis: u32,
+) -> Result<(), D::Error> {
+    if millis < GOAL_OPEN_MILLIS {
+        let width = layout.size.width * millis / GOAL_OPEN_MILLIS;
+        let left = (layout.size.width - width) / 2;
+        return Rectangle::new(
+            Point::new(left as i32, 0),
+            Size::new(width, layout.size.height),
+        )
+        .into_styled(PrimitiveStyle::with_fill(team_color))
+        .draw(display);
+    }
+
+    let filled = ((millis - GOAL_OPEN_MILLIS) / GOAL_SWAP_MILLIS).is_multiple_of(2);
+    let text_color = if filled {
+        Rectangle::new(Point::zero(), layout.size)
+            .into_styled(PrimitiveStyle::with_fill(team_color))
+            .draw(display)?;
+        Rgb888::BLACK
+    } else {
+        team_color
+    };
+
+    let mut label = ArrayString::<{ TEAM_CODE_CAPACITY + 4 }>::new();
+    write!(&mut label, "{team}").unwrap();
+    // Cap numbers of scorers aren't always collected, 0 means there isn't one
+    if player_number != 0 {
+        write!(&mut label, " #{player_number}").unwrap();
+    }
+    draw_text(display, "GOAL", &layout.clock, text_color)?;
+    draw_text(display, &label, &layout.period, text_color)
+}
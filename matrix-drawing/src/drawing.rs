@@ -4,18 +4,24 @@ use core::{
     ops::{Div, Rem},
 };
 use embedded_graphics::{
-    geometry::{Point, Size},
+    geometry::Point,
     mono_font::MonoTextStyle,
     pixelcolor::Rgb888,
     prelude::*,
     primitives::{rectangle::Rectangle, PrimitiveStyle},
-    text::{Alignment, Baseline, LineHeight, Text, TextStyle, TextStyleBuilder},
+    text::{
+        renderer::{TextMetrics, TextRenderer},
+        Alignment, Baseline, LineHeight, Text, TextStyle, TextStyleBuilder,
+    },
 };
-use fonts::fonts::{FONT_10X25, FONT_14X31, FONT_20X46, FONT_28X64, FONT_5X8, FONT_7X15};
+use fonts::ProportionalTextStyle;
 use more_asserts::*;
 use uwh_common::{drawing_support::*, game_snapshot::*};
 
-/// Draws all the details of the game onto the provided display. Assumes the dispaly is 256x64
+use crate::{PanelColors, PanelFont, PanelLayout, TextElement};
+
+/// Draws all the details of the game onto the provided display. Assumes the display is 256x64, see
+/// `draw_panels_with_layout` for other sizes
 ///
 /// Assumes the penalties have already been sorted
 pub fn draw_panels<D: DrawTarget<Color = Rgb888>>(
@@ -24,196 +30,167 @@ pub fn draw_panels<D: DrawTarget<Color = Rgb888>>(
     white_on_right: bool,
     flash: bool,
 ) -> Result<(), D::Error> {
-    const RED: Rgb888 = Rgb888::RED;
-    const YELLOW: Rgb888 = Rgb888::YELLOW;
-    const GREEN: Rgb888 = Rgb888::GREEN;
-    const BLUE: Rgb888 = Rgb888::new(64, 128, 255); //purple (225, 0, 255)
-    const WHITE: Rgb888 = Rgb888::WHITE;
-    const FLASH_COLOR: Rgb888 = Rgb888::new(0, 200, 200);
-
-    const CENTERED: TextStyle = TextStyleBuilder::new()
-        .alignment(Alignment::Center)
-        .baseline(Baseline::Top)
-        .line_height(LineHeight::Percent(100))
-        .build();
-
-    const LEFT_ALGN: TextStyle = TextStyleBuilder::new()
-        .alignment(Alignment::Left)
-        .baseline(Baseline::Top)
-        .line_height(LineHeight::Percent(100))
-        .build();
+    draw_panels_with_layout(
+        display,
+        state,
+        white_on_right,
+        flash,
+        &PanelLayout::STANDARD_256X64,
+    )
+}
 
-    const RIGHT_ALGN: TextStyle = TextStyleBuilder::new()
-        .alignment(Alignment::Right)
-        .baseline(Baseline::Top)
-        .line_height(LineHeight::Percent(100))
-        .build();
+/// Draws all the details of the game onto the provided display, positioned by `layout`. The
+/// display should be at least `layout.size`. A flash fills all of `layout.size` (before layouts,
+/// the last column of a 256x64 display was left dark). Everything is drawn in
+/// `PanelColors::STANDARD`
+///
+/// If either team has a code, both teams are named above their scores, using the color of a team
+/// without a code. Otherwise the scores fill their panels as they always have
+///
+/// Assumes the penalties have already been sorted
+pub fn draw_panels_with_layout<D: DrawTarget<Color = Rgb888>>(
+    display: &mut D,
+    state: GameSnapshotNoHeap,
+    white_on_right: bool,
+    flash: bool,
+    layout: &PanelLayout,
+) -> Result<(), D::Error> {
+    draw_panels_frame(
+        display,
+        state,
+        white_on_right,
+        flash,
+        layout,
+        &PanelColors::STANDARD,
+        0,
+    )
+}
 
+/// Draws one frame of the panels in `colors`, the same as `draw_panels_with_layout` apart from
+/// the message. When there is a message and no timeout, it is drawn instead of the period. A
+/// message that is too wide for `layout.message` scrolls, and `message_millis` is how long it has
+/// been shown. Receivers should keep drawing frames while `layout.message.fits()` is false for
+/// the message
+///
+/// Assumes the penalties have already been sorted
+pub fn draw_panels_frame<D: DrawTarget<Color = Rgb888>>(
+    display: &mut D,
+    state: GameSnapshotNoHeap,
+    white_on_right: bool,
+    flash: bool,
+    layout: &PanelLayout,
+    colors: &PanelColors,
+    message_millis: u32,
+) -> Result<(), D::Error> {
     if flash {
-        Rectangle::new(Point::new(0, 0), Size::new(255, 64))
-            .into_styled(PrimitiveStyle::with_fill(FLASH_COLOR))
+        Rectangle::new(Point::zero(), layout.size)
+            .into_styled(PrimitiveStyle::with_fill(colors.flash))
             .draw(display)?;
         return Ok(());
     }
 
     let game_color = match state.timeout {
-        TimeoutSnapshot::PenaltyShot(_) => RED,
-        TimeoutSnapshot::Ref(_) => YELLOW,
+        TimeoutSnapshot::PenaltyShot(_) => colors.alert,
+        TimeoutSnapshot::Ref(_) => colors.pause,
         _ => match state.current_period {
             GamePeriod::FirstHalf
             | GamePeriod::SecondHalf
             | GamePeriod::OvertimeFirstHalf
-            | GamePeriod::OvertimeSecondHalf => GREEN,
+            | GamePeriod::OvertimeSecondHalf => colors.play,
             GamePeriod::BetweenGames
             | GamePeriod::HalfTime
             | GamePeriod::PreOvertime
             | GamePeriod::OvertimeHalfTime
-            | GamePeriod::PreSuddenDeath => YELLOW,
-            GamePeriod::SuddenDeath => RED,
+            | GamePeriod::PreSuddenDeath => colors.pause,
+            GamePeriod::SuddenDeath => colors.alert,
         },
     };
 
     let timeout_color = match state.timeout {
-        TimeoutSnapshot::White(_) => WHITE,
-        TimeoutSnapshot::Black(_) => BLUE,
-        TimeoutSnapshot::Ref(_) => YELLOW,
-        _ => RED,
+        TimeoutSnapshot::White(_) => colors.white_team,
+        TimeoutSnapshot::Black(_) => colors.black_team,
+        TimeoutSnapshot::Ref(_) => colors.pause,
+        _ => colors.alert,
     };
 
     // EVERYTHING TO BE DISPLAYED ON THE CENTER 2 TIME PANELS
     match state.timeout {
         TimeoutSnapshot::None => {
-            Text::with_text_style(
-                &secs_to_time_string(state.secs_in_period),
-                Point::new(127, 18),
-                MonoTextStyle::new(&FONT_20X46, game_color),
-                CENTERED,
-            )
-            .draw(display)?;
+            draw_text(
+                display,
+                &game_clock_string(&state),
+                &layout.clock,
+                game_color,
+            )?;
 
-            let text = match state.current_period {
-                GamePeriod::BetweenGames => "NEXT GAME IN",
-                GamePeriod::FirstHalf => "1ST HALF",
-                GamePeriod::HalfTime => "HALF TIME",
-                GamePeriod::SecondHalf => "2ND HALF",
-                GamePeriod::PreOvertime => "PRE-OVERTIME",
-                GamePeriod::OvertimeFirstHalf => "O/T 1ST HALF",
-                GamePeriod::OvertimeHalfTime => "O/T HALF TIME",
-                GamePeriod::OvertimeSecondHalf => "O/T 2ND HALF",
-                GamePeriod::PreSuddenDeath => "PRE-SUDDEN DEATH",
-                GamePeriod::SuddenDeath => "SUDDEN DEATH",
-            };
-
-            Text::with_text_style(
-                text,
-                Point::new(127, 2),
-                MonoTextStyle::new(&FONT_7X15, game_color),
-                CENTERED,
-            )
-            .draw(display)?;
+            if let Some(message) = state.message {
+                draw_message(
+                    display,
+                    message.as_str(),
+                    &layout.message,
+                    game_color,
+                    message_millis,
+                )?;
+            } else {
+                let text = period_text(
+                    state.current_period,
+                    state.clock_stopped_by_rule,
+                    &layout.period,
+                );
+                draw_text(display, text, &layout.period, game_color)?;
+            }
         }
 
         _ => {
             // There is currently a timeout
-            Text::with_text_style(
-                &secs_to_time_string(state.secs_in_period),
-                Point::new(152, 33),
-                MonoTextStyle::new(&FONT_14X31, game_color),
-                CENTERED,
-            )
-            .draw(display)?;
+            draw_text(
+                display,
+                &game_clock_string(&state),
+                &layout.timeout_clock,
+                game_color,
+            )?;
 
-            let period_text = match state.current_period {
-                GamePeriod::BetweenGames => "NEXT\nGAME",
-                GamePeriod::FirstHalf => "1ST\nHALF",
-                GamePeriod::HalfTime => "HALF\nTIME",
-                GamePeriod::SecondHalf => "2ND\nHALF",
-                GamePeriod::PreOvertime => "PRE OT\nBREAK",
-                GamePeriod::OvertimeFirstHalf => "OT 1ST\nHALF",
-                GamePeriod::OvertimeHalfTime => "OT HLF\nTIME",
-                GamePeriod::OvertimeSecondHalf => "OT 2ND\nHALF",
-                GamePeriod::PreSuddenDeath => "PRE SD\nBREAK",
-                GamePeriod::SuddenDeath => "SUDDEN\nDEATH",
-            };
-
-            Text::with_text_style(
-                period_text,
-                Point::new(87, 33),
-                MonoTextStyle::new(&FONT_7X15, game_color),
-                CENTERED,
-            )
-            .draw(display)?;
+            draw_text(
+                display,
+                timeout_period_text(state.current_period),
+                &layout.timeout_period,
+                game_color,
+            )?;
 
             match state.timeout {
-                TimeoutSnapshot::White(secs) => {
-                    Text::with_text_style(
-                        "WHITE\nTIMEOUT",
-                        Point::new(99, 2),
-                        MonoTextStyle::new(&FONT_7X15, timeout_color),
-                        CENTERED,
-                    )
-                    .draw(display)?;
-                    Text::with_text_style(
+                TimeoutSnapshot::White(secs) | TimeoutSnapshot::Black(secs) => {
+                    let team = if matches!(state.timeout, TimeoutSnapshot::White(_)) {
+                        team_name(state.w_team_code.as_ref(), "WHITE")
+                    } else {
+                        team_name(state.b_team_code.as_ref(), "BLACK")
+                    };
+                    let mut label = ArrayString::<{ TEAM_CODE_CAPACITY + 8 }>::new();
+                    write!(&mut label, "{team}\nTIMEOUT").unwrap();
+                    draw_text(display, &label, &layout.team_timeout_label, timeout_color)?;
+                    draw_text(
+                        display,
                         &secs_to_short_time_string(secs),
-                        Point::new(138, 2),
-                        MonoTextStyle::new(&FONT_14X31, timeout_color),
-                        LEFT_ALGN,
-                    )
-                    .draw(display)?;
-                }
-
-                TimeoutSnapshot::Black(secs) => {
-                    Text::with_text_style(
-                        "BLACK\nTIMEOUT",
-                        Point::new(99, 2),
-                        MonoTextStyle::new(&FONT_7X15, timeout_color),
-                        CENTERED,
-                    )
-                    .draw(display)?;
-                    Text::with_text_style(
-                        &secs_to_short_time_string(secs),
-                        Point::new(138, 2),
-                        MonoTextStyle::new(&FONT_14X31, timeout_color),
-                        LEFT_ALGN,
-                    )
-                    .draw(display)?;
+                        &layout.team_timeout_time,
+                        timeout_color,
+                    )?;
                 }
 
                 TimeoutSnapshot::Ref(_) => {
-                    Text::with_text_style(
-                        "REF TIMEOUT",
-                        Point::new(127, 3),
-                        MonoTextStyle::new(&FONT_10X25, timeout_color),
-                        CENTERED,
-                    )
-                    .draw(display)?;
+                    draw_text(
+                        display,
+                        ref_timeout_text(&layout.ref_timeout),
+                        &layout.ref_timeout,
+                        timeout_color,
+                    )?;
                 }
 
                 TimeoutSnapshot::PenaltyShot(_) => {
-                    Text::with_text_style(
-                        "PENALTY",
-                        Point::new(64, 3),
-                        MonoTextStyle::new(&FONT_10X25, timeout_color),
-                        LEFT_ALGN,
-                    )
-                    .draw(display)?;
-                    Text::with_text_style(
-                        "SHOT",
-                        Point::new(149, 3),
-                        MonoTextStyle::new(&FONT_10X25, timeout_color),
-                        LEFT_ALGN,
-                    )
-                    .draw(display)?;
+                    draw_text(display, "PENALTY", &layout.penalty_shot[0], timeout_color)?;
+                    draw_text(display, "SHOT", &layout.penalty_shot[1], timeout_color)?;
                 }
 
                 _ => {
-                    Text::with_text_style(
-                        "T/O ERROR",
-                        Point::new(127, 3),
-                        MonoTextStyle::new(&FONT_10X25, RED),
-                        CENTERED,
-                    )
-                    .draw(display)?;
+                    draw_text(display, "T/O ERROR", &layout.ref_timeout, colors.alert)?;
                 }
             };
         }
@@ -225,153 +202,359 @@ pub fn draw_panels<D: DrawTarget<Color = Rgb888>>(
     let right_score;
     let left_color;
     let right_color;
+    let left_team;
+    let right_team;
+
+    let black_team = team_name(state.b_team_code.as_ref(), "BLACK");
+    let white_team = team_name(state.w_team_code.as_ref(), "WHITE");
 
     if white_on_right {
         left_penalties = state.b_penalties;
         right_penalties = state.w_penalties;
         left_score = state.b_score;
         right_score = state.w_score;
-        left_color = BLUE;
-        right_color = WHITE;
+        left_color = colors.black_team;
+        right_color = colors.white_team;
+        left_team = black_team;
+        right_team = white_team;
     } else {
         left_penalties = state.w_penalties;
         right_penalties = state.b_penalties;
         left_score = state.w_score;
         right_score = state.b_score;
-        left_color = WHITE;
-        right_color = BLUE;
+        left_color = colors.white_team;
+        right_color = colors.black_team;
+        left_team = white_team;
+        right_team = black_team;
     }
 
-    // Score on Left Score Panel
-    let mut left_score_string = ArrayString::<2>::new();
-    write!(&mut left_score_string, "{}", left_score).unwrap();
-    if left_penalties.is_empty() {
-        Text::with_text_style(
-            &left_score_string,
-            Point::new(31, 2),
-            MonoTextStyle::new(&FONT_28X64, left_color),
-            CENTERED,
-        )
-        .draw(display)?;
-    } else if left_score < 10 {
-        // Full Size Left Score, Single Digit - Justified Right/Inside/Towards Time Panels
-        Text::with_text_style(
-            &left_score_string,
-            Point::new(61, 2),
-            MonoTextStyle::new(&FONT_28X64, left_color),
-            RIGHT_ALGN,
-        )
-        .draw(display)?;
+    let show_teams = state.b_team_code.is_some() || state.w_team_code.is_some();
+    let (left_side, right_side) = if show_teams {
+        draw_text(display, left_team, &layout.left_with_team.team, left_color)?;
+        draw_text(
+            display,
+            right_team,
+            &layout.right_with_team.team,
+            right_color,
+        )?;
+        (&layout.left_with_team.score, &layout.right_with_team.score)
     } else {
-        // 3/4 Size Left Score (Double Digit - Centered on Score Panel)
-        Text::with_text_style(
-            &left_score_string,
-            Point::new(31, 2),
-            MonoTextStyle::new(&FONT_20X46, left_color),
-            CENTERED,
-        )
-        .draw(display)?;
+        (&layout.left, &layout.right)
     };
 
-    // Score on Right Score Panel
-    let mut right_score_string = ArrayString::<2>::new();
-    write!(&mut right_score_string, "{}", right_score).unwrap();
-    if right_penalties.is_empty() {
-        Text::with_text_style(
-            &right_score_string,
-            Point::new(223, 2),
-            MonoTextStyle::new(&FONT_28X64, right_color),
-            CENTERED,
-        )
-        .draw(display)?;
-    } else if right_score < 10 {
-        // Full Size Right Score, Single Digit - Justified Left/Inside/Towards Time Panels
-        Text::with_text_style(
-            &right_score_string,
-            Point::new(194, 2),
-            MonoTextStyle::new(&FONT_28X64, right_color),
-            LEFT_ALGN,
-        )
-        .draw(display)?;
+    for (side, score, penalties, color) in [
+        (left_side, left_score, &left_penalties, left_color),
+        (right_side, right_score, &right_penalties, right_color),
+    ] {
+        let mut score_string = ArrayString::<2>::new();
+        write!(&mut score_string, "{}", score).unwrap();
+        let (score_element, penalty_list) = if penalties.is_empty() {
+            let alone = if score < 10 {
+                &side.alone
+            } else {
+                &side.alone_two_digits
+            };
+            (alone, &side.penalties_beside)
+        } else if score < 10 {
+            // Single Digit Score - Justified Towards the Time Panels, with the Penalties in a
+            // Column on the Outside. Penalties "Fall-Off" the Bottom as they run out
+            (&side.beside_penalties, &side.penalties_beside)
+        } else {
+            // Double Digit Score - Smaller, with the Penalties in a Row Below. Penalties
+            // "Fall-Off" the Outside as they run out
+            (&side.above_penalties, &side.penalties_below)
+        };
+        draw_text(display, &score_string, score_element, color)?;
+
+        for (position, penalty) in penalty_list.positions().zip(penalties.iter()) {
+            draw_penalty(
+                display,
+                position,
+                penalty_list.font,
+                color,
+                colors.alert,
+                penalty,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// The game clock as it is drawn, to the tenth of a second when the snapshot has the tenths
+fn game_clock_string(state: &GameSnapshotNoHeap) -> ArrayString<5> {
+    match state.tenths_in_period {
+        Some(tenths) if state.secs_in_period <= 99 => {
+            ArrayString::from(&secs_to_tenths_string(state.secs_in_period, tenths)).unwrap()
+        }
+        _ => secs_to_time_string(state.secs_in_period),
+    }
+}
+
+/// The name of `period` drawn in `element` above the clock, abbreviated if the full name is too
+/// wide for the element
+pub(crate) fn period_text(
+    period: GamePeriod,
+    clock_stopped_by_rule: bool,
+    element: &TextElement,
+) -> &'static str {
+    let (full, short) = match period {
+        GamePeriod::FirstHalf
+        | GamePeriod::SecondHalf
+        | GamePeriod::OvertimeFirstHalf
+        | GamePeriod::OvertimeSecondHalf
+            if clock_stopped_by_rule =>
+        {
+            ("CLOCK STOPPED", "CLOCK STOP")
+        }
+        GamePeriod::BetweenGames => ("NEXT GAME IN", "NEXT GAME"),
+        GamePeriod::FirstHalf => ("1ST HALF", "1ST HALF"),
+        GamePeriod::HalfTime => ("HALF TIME", "HALF TIME"),
+        GamePeriod::SecondHalf => ("2ND HALF", "2ND HALF"),
+        GamePeriod::PreOvertime => ("PRE-OVERTIME", "PRE OT"),
+        GamePeriod::OvertimeFirstHalf => ("O/T 1ST HALF", "OT 1ST HLF"),
+        GamePeriod::OvertimeHalfTime => ("O/T HALF TIME", "OT H/TIME"),
+        GamePeriod::OvertimeSecondHalf => ("O/T 2ND HALF", "OT 2ND HLF"),
+        GamePeriod::PreSuddenDeath => ("PRE-SUDDEN DEATH", "PRE SD"),
+        GamePeriod::SuddenDeath => ("SUDDEN DEATH", "SDN DEATH"),
+    };
+    if element.fits(full) {
+        full
     } else {
-        // 3/4 Size Right Score (Double Digit - Centered on Score Panel)
+        short
+    }
+}
+
+/// The team's code, or `color` if it doesn't have one
+pub(crate) fn team_name<'a>(code: Option<&'a TeamCode>, color: &'a str) -> &'a str {
+    code.map_or(color, TeamCode::as_str)
+}
+
+/// The label of a ref timeout, abbreviated if it is too wide for `element`
+pub(crate) fn ref_timeout_text(element: &TextElement) -> &'static str {
+    if element.fits("REF TIMEOUT") {
+        "REF TIMEOUT"
+    } else {
+        "REF T/O"
+    }
+}
+
+/// The name of `period` drawn beside the clock during a timeout, on two lines
+pub(crate) fn timeout_period_text(period: GamePeriod) -> &'static str {
+    match period {
+        GamePeriod::BetweenGames => "NEXT\nGAME",
+        GamePeriod::FirstHalf => "1ST\nHALF",
+        GamePeriod::HalfTime => "HALF\nTIME",
+        GamePeriod::SecondHalf => "2ND\nHALF",
+        GamePeriod::PreOvertime => "PRE OT\nBREAK",
+        GamePeriod::OvertimeFirstHalf => "OT 1ST\nHALF",
+        GamePeriod::OvertimeHalfTime => "OT HLF\nTIME",
+        GamePeriod::OvertimeSecondHalf => "OT 2ND\nHALF",
+        GamePeriod::PreSuddenDeath => "PRE SD\nBREAK",
+        GamePeriod::SuddenDeath => "SUDDEN\nDEATH",
+    }
+}
+
+pub(crate) fn draw_text<D: DrawTarget<Color = Rgb888>>(
+    display: &mut D,
+    text: &str,
+    element: &TextElement,
+    color: Rgb888,
+) -> Result<(), D::Error> {
+    element_text(text, element, color).draw(display)?;
+    Ok(())
+}
+
+/// How many characters a message scrolls by each second
+const MESSAGE_SCROLL_CHARS_PER_SEC: u32 = 4;
+
+/// How long a message that scrolls is held still when it is first shown
+const MESSAGE_PAUSE_MILLIS: u32 = 1_000;
+
+/// Draws `message` in `element` if it fits. Otherwise it starts at the left of the
+/// region and scrolls left after a pause, followed by another copy half the region's width behind
+/// it. Nothing is drawn outside of the region
+fn draw_message<D: DrawTarget<Color = Rgb888>>(
+    display: &mut D,
+    message: &str,
+    element: &TextElement,
+    color: Rgb888,
+    millis: u32,
+) -> Result<(), D::Error> {
+    if element.fits(message) {
+        return draw_text(display, message, element, color);
+    }
+
+    let mut clipped = display.clipped(&element.region);
+    for text in message_text(message, element, color, millis) {
+        text.draw(&mut clipped)?;
+    }
+    Ok(())
+}
+
+/// The two copies of a scrolling `message` as they are drawn `millis` after it was first shown
+pub(crate) fn message_text<'a>(
+    message: &'a str,
+    element: &TextElement,
+    color: Rgb888,
+    millis: u32,
+) -> [Text<'a, PanelTextStyle>; 2] {
+    // Messages scroll at the same speed in any font of the same size
+    let spacing = element.font.character_spacing();
+    let char_width = element.font.character_size().width + spacing;
+    let cycle = element.font.text_width(message) + spacing + element.region.size.width / 2;
+    let scrolled = (millis.saturating_sub(MESSAGE_PAUSE_MILLIS) as u64
+        * (MESSAGE_SCROLL_CHARS_PER_SEC * char_width) as u64
+        / 1_000) as u32
+        % cycle;
+
+    let start = element.region.top_left - Point::new(scrolled as i32, 0);
+    [start, start + Point::new(cycle as i32, 0)].map(|position| {
         Text::with_text_style(
-            &right_score_string,
-            Point::new(223, 2),
-            MonoTextStyle::new(&FONT_20X46, right_color),
-            CENTERED,
+            message,
+            position,
+            PanelTextStyle::new(element.font, color),
+            text_style(Alignment::Left),
         )
-        .draw(display)?;
-    };
+    })
+}
 
-    // Define layout for Penalties
-    let mut draw_penalty =
-        |x_pos: i32, y_pos: i32, color, penalty: &PenaltySnapshot| -> Result<(), D::Error> {
-            let mut penalty_string = ArrayString::<3>::new();
-            write!(&mut penalty_string, "#{}", penalty.player_number).unwrap();
-            Text::with_text_style(
-                &penalty_string,
-                Point::new(x_pos, y_pos),
-                MonoTextStyle::new(&FONT_5X8, color),
-                CENTERED,
-            )
-            .draw(display)?;
-            let time: ArrayString<4> = match penalty.time {
-                PenaltyTime::Seconds(secs) => {
-                    ArrayString::from(secs_to_time_string(secs).trim()).unwrap()
-                }
-                PenaltyTime::TotalDismissal => ArrayString::from("DSMS").unwrap(),
-            };
-            Text::with_text_style(
-                &time,
-                Point::new(x_pos, y_pos + 8),
-                MonoTextStyle::new(&FONT_5X8, RED),
-                CENTERED,
-            )
-            .draw(display)?;
-            Ok(())
-        };
+/// `text` as it is drawn in `element`
+pub(crate) fn element_text<'a>(
+    text: &'a str,
+    element: &TextElement,
+    color: Rgb888,
+) -> Text<'a, PanelTextStyle> {
+    Text::with_text_style(
+        text,
+        element.anchor(),
+        PanelTextStyle::new(element.font, color),
+        text_style(element.alignment),
+    )
+}
+
+/// Draws text in either kind of `PanelFont`
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum PanelTextStyle {
+    Mono(MonoTextStyle<'static, Rgb888>),
+    Proportional(ProportionalTextStyle<'static, Rgb888>),
+}
 
-    // Penalties on Left Score Panel
-    if left_score < 10 {
-        // Vertical Penalties (Up to 3) - Justified Left/Outside/Away from Time Panels
-        // Penalties "Fall-Off" the Bottom as they run out
-        for (i, penalty) in [0i32, 1, 2].into_iter().zip(left_penalties.iter()) {
-            let x_pos = 15;
-            let y_pos = 47 - i * 22;
-            draw_penalty(x_pos, y_pos, left_color, penalty)?;
+impl PanelTextStyle {
+    fn new(font: PanelFont, color: Rgb888) -> Self {
+        match font {
+            PanelFont::Mono(font) => Self::Mono(MonoTextStyle::new(font, color)),
+            PanelFont::Proportional(font) => {
+                Self::Proportional(ProportionalTextStyle::new(font, color))
+            }
         }
-    } else {
-        // Horizontal Penalties (Up to 2) - Justified Left/Outside/Away from Time Panels
-        // Penalties "Fall-Off" the Left Side as they run out
-        for (i, penalty) in [0i32, 1].into_iter().zip(left_penalties.iter()) {
-            let x_pos = 17 + i * 29;
-            let y_pos = 47;
-            draw_penalty(x_pos, y_pos, left_color, penalty)?;
+    }
+}
+
+impl TextRenderer for PanelTextStyle {
+    type Color = Rgb888;
+
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        match self {
+            Self::Mono(style) => style.draw_string(text, position, baseline, target),
+            Self::Proportional(style) => style.draw_string(text, position, baseline, target),
         }
     }
 
-    // Penalties on Right Score Panel
-    if right_score < 10 {
-        // Vertical Penalties (Up to 3) - Justified Right/Outside/Away from Time Panels
-        // Penalties "Fall-Off" the Bottom as they run out
-        for (i, penalty) in [0i32, 1, 2].into_iter().zip(right_penalties.iter()) {
-            let x_pos = 240;
-            let y_pos = 47i32 - i * 22i32;
-            draw_penalty(x_pos, y_pos, right_color, penalty)?;
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        match self {
+            Self::Mono(style) => style.draw_whitespace(width, position, baseline, target),
+            Self::Proportional(style) => style.draw_whitespace(width, position, baseline, target),
         }
-    } else {
-        // Horizontal Penalties (Up to 2) - Justified Right/Outside/Away from Time Panels
-        // Penalties "Fall-Off" the Right Side as they run out
-        for (i, penalty) in [0i32, 1].into_iter().zip(right_penalties.iter()) {
-            let x_pos = 238 - i * 29;
-            let y_pos = 47;
-            draw_penalty(x_pos, y_pos, right_color, penalty)?;
+    }
+
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        match self {
+            Self::Mono(style) => style.measure_string(text, position, baseline),
+            Self::Proportional(style) => style.measure_string(text, position, baseline),
+        }
+    }
+
+    fn line_height(&self) -> u32 {
+        match self {
+            Self::Mono(style) => style.line_height(),
+            Self::Proportional(style) => style.line_height(),
         }
     }
+}
+
+/// Draws the player number with the time remaining below it in `time_color`, centered on
+/// `position`
+fn draw_penalty<D: DrawTarget<Color = Rgb888>>(
+    display: &mut D,
+    position: Point,
+    font: PanelFont,
+    color: Rgb888,
+    time_color: Rgb888,
+    penalty: &PenaltySnapshot,
+) -> Result<(), D::Error> {
+    let mut penalty_string = ArrayString::<3>::new();
+    write!(&mut penalty_string, "#{}", penalty.player_number).unwrap();
+    let time: ArrayString<4> = match penalty.time {
+        PenaltyTime::Seconds(secs) => ArrayString::from(secs_to_time_string(secs).trim()).unwrap(),
+        PenaltyTime::TotalDismissal => ArrayString::from("DSMS").unwrap(),
+    };
+    for text in penalty_text(&penalty_string, &time, position, font, color, time_color) {
+        text.draw(display)?;
+    }
     Ok(())
 }
 
+/// The player number and time of a penalty as they are drawn at `position`
+pub(crate) fn penalty_text<'a>(
+    number: &'a str,
+    time: &'a str,
+    position: Point,
+    font: PanelFont,
+    color: Rgb888,
+    time_color: Rgb888,
+) -> [Text<'a, PanelTextStyle>; 2] {
+    [
+        Text::with_text_style(
+            number,
+            position,
+            PanelTextStyle::new(font, color),
+            text_style(Alignment::Center),
+        ),
+        Text::with_text_style(
+            time,
+            position + Point::new(0, font.character_size().height as i32),
+            PanelTextStyle::new(font, time_color),
+            text_style(Alignment::Center),
+        ),
+    ]
+}
+
+fn text_style(alignment: Alignment) -> TextStyle {
+    TextStyleBuilder::new()
+        .alignment(alignment)
+        .baseline(Baseline::Top)
+        .line_height(LineHeight::Percent(100))
+        .build()
+}
+
 pub fn secs_to_time_string<T>(secs: T) -> ArrayString<5>
 where
     T: Div<T> + Rem<T> + From<u16> + Copy + Ord + Debug,
@@ -400,6 +583,18 @@ where
     time_string
 }
 
+/// Seconds and tenths of a second, e.g. `59.3`
+pub fn secs_to_tenths_string<T>(secs: T, tenths: u8) -> ArrayString<4>
+where
+    T: From<u8> + Ord + Copy + Display + Debug,
+{
+    assert_le!(secs, T::from(99u8));
+    assert_le!(tenths, 9);
+    let mut time_string = ArrayString::new();
+    write!(&mut time_string, "{:2}.{}", secs, tenths).unwrap();
+    time_string
+}
+
 pub fn secs_to_short_time_string<T>(secs: T) -> ArrayString<3>
 where
     T: From<u8> + Ord + Copy + Display + Debug,
@@ -409,3 +604,214 @@ where
     write!(&mut time_string, ":{:02}", secs).unwrap();
     time_string
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FrameBuffer;
+    use arrayvec::ArrayVec;
+
+    /// FNV-1a over the position and color of every lit pixel, in row order
+    fn hash(frame: &FrameBuffer) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let width = frame.size().width as usize;
+        for (i, color) in frame.pixels().iter().enumerate() {
+            if *color == Rgb888::BLACK {
+                continue;
+            }
+            let (x, y) = ((i % width) as u16, (i / width) as u16);
+            for byte in x.to_le_bytes().into_iter().chain(y.to_le_bytes()).chain([
+                color.r(),
+                color.g(),
+                color.b(),
+            ]) {
+                hash = (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+            }
+        }
+        hash
+    }
+
+    fn render(state: GameSnapshotNoHeap, white_on_right: bool, flash: bool) -> FrameBuffer {
+        let mut frame = FrameBuffer::new(Size::new(256, 64));
+        draw_panels(&mut frame, state, white_on_right, flash).unwrap();
+        frame
+    }
+
+    fn penalties(list: &[(u8, PenaltyTime)]) -> ArrayVec<PenaltySnapshot, 3> {
+        list.iter()
+            .map(|&(player_number, time)| PenaltySnapshot {
+                player_number,
+                time,
+                infraction: Infraction::Unknown,
+            })
+            .collect()
+    }
+
+    fn snapshot(period: GamePeriod, timeout: TimeoutSnapshot) -> GameSnapshotNoHeap {
+        GameSnapshotNoHeap {
+            current_period: period,
+            secs_in_period: 754,
+            timeout,
+            b_score: 3,
+            w_score: 5,
+            b_penalties: ArrayVec::new(),
+            w_penalties: ArrayVec::new(),
+            is_old_game: false,
+            clock_stopped_by_rule: false,
+            b_team_code: None,
+            w_team_code: None,
+            message: None,
+            tenths_in_period: None,
+            panel_animation: None,
+        }
+    }
+
+    /// A spread of states, each paired with `white_on_right`
+    fn cases() -> Vec<(GameSnapshotNoHeap, bool)> {
+        let mut cases = Vec::new();
+        for (i, period) in [
+            GamePeriod::BetweenGames,
+            GamePeriod::FirstHalf,
+            GamePeriod::HalfTime,
+            GamePeriod::SecondHalf,
+            GamePeriod::PreOvertime,
+            GamePeriod::OvertimeFirstHalf,
+            GamePeriod::OvertimeHalfTime,
+            GamePeriod::OvertimeSecondHalf,
+            GamePeriod::PreSuddenDeath,
+            GamePeriod::SuddenDeath,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            cases.push((snapshot(period, TimeoutSnapshot::None), i % 2 == 0));
+            cases.push((snapshot(period, TimeoutSnapshot::White(45)), i % 2 == 1));
+        }
+
+        for timeout in [
+            TimeoutSnapshot::Black(9),
+            TimeoutSnapshot::Ref(120),
+            TimeoutSnapshot::PenaltyShot(0),
+        ] {
+            cases.push((snapshot(GamePeriod::SecondHalf, timeout), false));
+        }
+
+        let single_digits = GameSnapshotNoHeap {
+            b_penalties: penalties(&[
+                (7, PenaltyTime::Seconds(25)),
+                (12, PenaltyTime::Seconds(119)),
+                (99, PenaltyTime::TotalDismissal),
+            ]),
+            w_penalties: penalties(&[(1, PenaltyTime::Seconds(300))]),
+            ..snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::None)
+        };
+        let double_digits = GameSnapshotNoHeap {
+            b_score: 10,
+            w_score: 99,
+            b_penalties: penalties(&[
+                (4, PenaltyTime::Seconds(5)),
+                (15, PenaltyTime::TotalDismissal),
+            ]),
+            w_penalties: penalties(&[
+                (3, PenaltyTime::Seconds(60)),
+                (22, PenaltyTime::Seconds(240)),
+                (8, PenaltyTime::Seconds(599)),
+            ]),
+            ..snapshot(GamePeriod::SecondHalf, TimeoutSnapshot::Ref(30))
+        };
+        let mixed = GameSnapshotNoHeap {
+            b_score: 12,
+            w_score: 0,
+            secs_in_period: 5999,
+            ..single_digits.clone()
+        };
+        let no_penalties = GameSnapshotNoHeap {
+            b_score: 10,
+            w_score: 8,
+            ..snapshot(GamePeriod::OvertimeSecondHalf, TimeoutSnapshot::None)
+        };
+        for state in [single_digits, double_digits, mixed, no_penalties] {
+            cases.push((state.clone(), false));
+            cases.push((state, true));
+        }
+
+        cases
+    }
+
+    /// Hashes of `cases` as drawn before `draw_panels` was driven by `PanelLayout`, apart from the
+    /// cases with penalties (23 to 28), which changed when penalties moved to proportional fonts
+    const BASELINE: [u64; 31] = [
+        0xe279_3aa9_93f9_6b53,
+        0x877c_a775_a693_f33d,
+        0x4e9f_a1d1_df92_8c35,
+        0x4f4b_f873_9736_7135,
+        0xb22f_4144_e961_61e5,
+        0x2b36_1129_0f83_6f12,
+        0xc9ef_d8ca_01f9_1228,
+        0xb8d6_f6bf_aec7_8e34,
+        0x8172_8133_e3d5_c5c7,
+        0x349d_4ea6_ba27_5b49,
+        0xbaf0_1cd0_5de5_7996,
+        0x1996_6a55_6891_0216,
+        0x424a_7651_91f8_89be,
+        0x6bc4_7420_f0ca_6093,
+        0x776d_650e_3ddb_d79d,
+        0x3a99_a222_1e9b_f0f9,
+        0xb8c2_21fb_4ce3_8e76,
+        0x2dcb_d0f0_315a_6e95,
+        0x42dc_055b_1f7a_a4d3,
+        0x807b_f7d5_46b2_04f2,
+        0xb871_6421_33b5_75c8,
+        0xf433_32e2_ce77_4e42,
+        0x8ba6_bdbf_63a1_cf93,
+        0xaa05_b2a5_e56c_5f0d,
+        0x9a10_a6b7_3fe0_b179,
+        0x9e38_840b_f4ef_c2b3,
+        0x4fe9_f867_db2f_2c50,
+        0xbd9e_e4af_e4b5_51fc,
+        0x6c47_2d34_30fd_5049,
+        0x6963_2481_5cd3_6106,
+        0xeb13_0bec_967e_5f2c,
+    ];
+
+    #[test]
+    fn test_matches_baseline() {
+        let hashes: Vec<u64> = cases()
+            .into_iter()
+            .map(|(state, white_on_right)| hash(&render(state, white_on_right, false)))
+            .collect();
+        for (i, (hash, expected)) in hashes.iter().zip(BASELINE).enumerate() {
+            assert_eq!(*hash, expected, "case {i}: {:?}", cases()[i]);
+        }
+        assert_eq!(hashes.len(), BASELINE.len(), "{hashes:#x?}");
+    }
+
+    #[test]
+    fn test_game_clock_string() {
+        let state = |secs_in_period, tenths_in_period| GameSnapshotNoHeap {
+            secs_in_period,
+            tenths_in_period,
+            ..snapshot(GamePeriod::SecondHalf, TimeoutSnapshot::None)
+        };
+        assert_eq!(game_clock_string(&state(59, Some(3))).as_str(), "59.3");
+        assert_eq!(game_clock_string(&state(5, Some(0))).as_str(), " 5.0");
+        assert_eq!(game_clock_string(&state(59, None)).as_str(), " 0:59");
+        // Too long to show the tenths
+        assert_eq!(game_clock_string(&state(100, Some(9))).as_str(), " 1:40");
+    }
+
+    #[test]
+    fn test_flash_fills_display() {
+        // The baseline filled a 255 pixel wide rectangle, leaving the last column dark. The whole
+        // layout is lit now
+        let frame = render(
+            snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::None),
+            false,
+            true,
+        );
+        assert!(frame
+            .pixels()
+            .iter()
+            .all(|&color| color == Rgb888::new(0, 200, 200)));
+    }
+}
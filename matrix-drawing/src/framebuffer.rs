@@ -0,0 +1,196 @@
+use core::convert::Infallible;
+use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+use std::io::{self, Write};
+
+/// A display in memory, for drawing the panels without any hardware. Pixels that haven't been
+/// drawn are black
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameBuffer {
+    size: Size,
+    pixels: Vec<Rgb888>,
+}
+
+impl FrameBuffer {
+    pub fn new(size: Size) -> Self {
+        Self {
+            size,
+            pixels: vec![Rgb888::BLACK; (size.width * size.height) as usize],
+        }
+    }
+
+    /// The color of the pixel at `point`, or `None` if it is outside the buffer
+    pub fn pixel(&self, point: Point) -> Option<Rgb888> {
+        self.index(point).map(|i| self.pixels[i])
+    }
+
+    /// Every pixel, a row at a time from the top left
+    pub fn pixels(&self) -> &[Rgb888] {
+        &self.pixels
+    }
+
+    /// A copy with each pixel drawn as a `scale` by `scale` square, for looking at
+    pub fn scaled(&self, scale: u32) -> Self {
+        let mut scaled = Self::new(self.size * scale);
+        for (i, pixel) in scaled.pixels.iter_mut().enumerate() {
+            let x = i as u32 % scaled.size.width / scale;
+            let y = i as u32 / scaled.size.width / scale;
+            *pixel = self.pixels[(y * self.size.width + x) as usize];
+        }
+        scaled
+    }
+
+    /// The pixels as 8 bit RGB triples, a row at a time from the top left
+    pub fn rgb_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|color| [color.r(), color.g(), color.b()])
+            .collect()
+    }
+
+    /// Writes the buffer as a binary PPM (P6) image
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(
+            writer,
+            "P6\n{} {}\n255\n",
+            self.size.width, self.size.height
+        )?;
+        writer.write_all(&self.rgb_bytes())
+    }
+
+    /// Writes the buffer as an 8 bit RGB PNG image
+    #[cfg(feature = "png")]
+    pub fn write_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.size.width, self.size.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.rgb_bytes())?;
+        Ok(writer.finish()?)
+    }
+
+    /// Reads an 8 bit RGB PNG image, like those from `write_png`
+    #[cfg(feature = "png")]
+    pub fn read_png<R: io::Read>(reader: R) -> io::Result<Self> {
+        let mut reader = png::Decoder::new(reader).read_info()?;
+        let mut bytes = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut bytes)?;
+        if info.color_type != png::ColorType::Rgb || info.bit_depth != png::BitDepth::Eight {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "expected an 8 bit RGB image",
+            ));
+        }
+
+        let pixels = bytes[..info.buffer_size()]
+            .chunks_exact(3)
+            .map(|rgb| Rgb888::new(rgb[0], rgb[1], rgb[2]))
+            .collect();
+        Ok(Self {
+            size: Size::new(info.width, info.height),
+            pixels,
+        })
+    }
+
+    fn index(&self, point: Point) -> Option<usize> {
+        let Point { x, y } = point;
+        if x < 0 || y < 0 || x as u32 >= self.size.width || y as u32 >= self.size.height {
+            return None;
+        }
+        Some((y as u32 * self.size.width + x as u32) as usize)
+    }
+}
+
+impl OriginDimensions for FrameBuffer {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl DrawTarget for FrameBuffer {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let Some(i) = self.index(point) {
+                self.pixels[i] = color;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+    fn sample() -> FrameBuffer {
+        let mut buffer = FrameBuffer::new(Size::new(4, 3));
+        Rectangle::new(Point::new(1, 1), Size::new(2, 1))
+            .into_styled(PrimitiveStyle::with_fill(Rgb888::RED))
+            .draw(&mut buffer)
+            .unwrap();
+        // Off the buffer, must be ignored
+        Pixel(Point::new(4, 0), Rgb888::GREEN)
+            .draw(&mut buffer)
+            .unwrap();
+        Pixel(Point::new(-1, 2), Rgb888::GREEN)
+            .draw(&mut buffer)
+            .unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_draw() {
+        let buffer = sample();
+        assert_eq!(buffer.pixel(Point::new(0, 0)), Some(Rgb888::BLACK));
+        assert_eq!(buffer.pixel(Point::new(1, 1)), Some(Rgb888::RED));
+        assert_eq!(buffer.pixel(Point::new(2, 1)), Some(Rgb888::RED));
+        assert_eq!(buffer.pixel(Point::new(3, 1)), Some(Rgb888::BLACK));
+        assert_eq!(buffer.pixel(Point::new(4, 0)), None);
+        assert_eq!(
+            buffer
+                .pixels()
+                .iter()
+                .filter(|&&color| color != Rgb888::BLACK)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_scaled() {
+        let scaled = sample().scaled(3);
+        assert_eq!(scaled.size(), Size::new(12, 9));
+        assert_eq!(scaled.pixel(Point::new(2, 2)), Some(Rgb888::BLACK));
+        assert_eq!(scaled.pixel(Point::new(3, 3)), Some(Rgb888::RED));
+        assert_eq!(scaled.pixel(Point::new(8, 5)), Some(Rgb888::RED));
+        assert_eq!(scaled.pixel(Point::new(9, 5)), Some(Rgb888::BLACK));
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let mut ppm = Vec::new();
+        sample().write_ppm(&mut ppm).unwrap();
+        let header = b"P6\n4 3\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 4 * 3 * 3);
+        assert_eq!(
+            &ppm[header.len() + 15..header.len() + 21],
+            &[255, 0, 0, 255, 0, 0]
+        );
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_png_round_trip() {
+        let buffer = sample();
+        let mut png = Vec::new();
+        buffer.write_png(&mut png).unwrap();
+        assert_eq!(FrameBuffer::read_png(png.as_slice()).unwrap(), buffer);
+    }
+}
@@ -0,0 +1,444 @@
+//! Golden image tests of `draw_panels_frame` and `draw_animation_frame`. Each case is drawn with
+//! every built in layout and compared with `golden/<layout>/<case>.png`. To accept a change to what
+//! is drawn, run the tests with `UPDATE_GOLDEN=1` and review the changed images before committing
+//! them. When an image doesn't match, what was drawn is written to the system temp directory for
+//! comparison.
+
+use crate::{
+    draw_animation_frame, draw_panels_frame, panel_colors, FrameBuffer, PanelBrightness,
+    PanelColors, PanelLayout, PanelTheme,
+};
+use arrayvec::ArrayVec;
+use embedded_graphics::geometry::OriginDimensions;
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::PathBuf,
+};
+use uwh_common::game_snapshot::{
+    Color, GamePeriod, GameSnapshotNoHeap, Infraction, PanelAnimation, PanelMessage,
+    PenaltySnapshot, PenaltyTime, TeamCode, TimeoutSnapshot,
+};
+
+struct Case {
+    name: &'static str,
+    state: GameSnapshotNoHeap,
+    white_on_right: bool,
+    flash: bool,
+    colors: PanelColors,
+    message_millis: u32,
+    /// How far into `state.panel_animation` the frame is drawn, instead of drawing the game
+    animation_millis: Option<u32>,
+}
+
+fn snapshot(current_period: GamePeriod, timeout: TimeoutSnapshot) -> GameSnapshotNoHeap {
+    GameSnapshotNoHeap {
+        current_period,
+        secs_in_period: 421,
+        timeout,
+        b_score: 2,
+        w_score: 4,
+        b_penalties: ArrayVec::new(),
+        w_penalties: ArrayVec::new(),
+        is_old_game: false,
+        clock_stopped_by_rule: false,
+        b_team_code: None,
+        w_team_code: None,
+        message: None,
+        tenths_in_period: None,
+        panel_animation: None,
+    }
+}
+
+fn penalties(list: &[(u8, PenaltyTime)]) -> ArrayVec<PenaltySnapshot, 3> {
+    list.iter()
+        .map(|&(player_number, time)| PenaltySnapshot {
+            player_number,
+            time,
+            infraction: Infraction::Unknown,
+        })
+        .collect()
+}
+
+fn cases() -> Vec<Case> {
+    let case = |name, state| Case {
+        name,
+        state,
+        white_on_right: false,
+        flash: false,
+        colors: PanelColors::STANDARD,
+        message_millis: 0,
+        animation_millis: None,
+    };
+
+    let mut cases: Vec<Case> = [
+        ("between_games", GamePeriod::BetweenGames),
+        ("first_half", GamePeriod::FirstHalf),
+        ("half_time", GamePeriod::HalfTime),
+        ("second_half", GamePeriod::SecondHalf),
+        ("pre_overtime", GamePeriod::PreOvertime),
+        ("overtime_first_half", GamePeriod::OvertimeFirstHalf),
+        ("overtime_half_time", GamePeriod::OvertimeHalfTime),
+        ("overtime_second_half", GamePeriod::OvertimeSecondHalf),
+        ("pre_sudden_death", GamePeriod::PreSuddenDeath),
+        ("sudden_death", GamePeriod::SuddenDeath),
+    ]
+    .into_iter()
+    .map(|(name, period)| case(name, snapshot(period, TimeoutSnapshot::None)))
+    .collect();
+
+    cases.push(case(
+        "clock_stopped",
+        GameSnapshotNoHeap {
+            clock_stopped_by_rule: true,
+            ..snapshot(GamePeriod::SecondHalf, TimeoutSnapshot::None)
+        },
+    ));
+    for (name, timeout) in [
+        ("timeout_white", TimeoutSnapshot::White(54)),
+        ("timeout_black", TimeoutSnapshot::Black(7)),
+        ("timeout_ref", TimeoutSnapshot::Ref(95)),
+        ("timeout_penalty_shot", TimeoutSnapshot::PenaltyShot(12)),
+    ] {
+        cases.push(case(name, snapshot(GamePeriod::FirstHalf, timeout)));
+    }
+    cases.push(case(
+        "timeout_overtime",
+        snapshot(GamePeriod::OvertimeHalfTime, TimeoutSnapshot::Black(60)),
+    ));
+
+    let single_digits = GameSnapshotNoHeap {
+        b_penalties: penalties(&[
+            (3, PenaltyTime::Seconds(15)),
+            (11, PenaltyTime::Seconds(104)),
+            (87, PenaltyTime::Seconds(290)),
+        ]),
+        w_penalties: penalties(&[(6, PenaltyTime::Seconds(59))]),
+        ..snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::None)
+    };
+    let double_digits = GameSnapshotNoHeap {
+        b_score: 14,
+        w_score: 10,
+        b_penalties: penalties(&[
+            (9, PenaltyTime::Seconds(31)),
+            (21, PenaltyTime::Seconds(240)),
+        ]),
+        w_penalties: penalties(&[
+            (1, PenaltyTime::Seconds(2)),
+            (99, PenaltyTime::Seconds(599)),
+            (45, PenaltyTime::Seconds(120)),
+        ]),
+        ..snapshot(GamePeriod::SecondHalf, TimeoutSnapshot::None)
+    };
+    let dismissals = GameSnapshotNoHeap {
+        b_score: 10,
+        w_score: 3,
+        b_penalties: penalties(&[(13, PenaltyTime::TotalDismissal)]),
+        w_penalties: penalties(&[
+            (2, PenaltyTime::Seconds(45)),
+            (8, PenaltyTime::TotalDismissal),
+            (50, PenaltyTime::TotalDismissal),
+        ]),
+        ..snapshot(GamePeriod::SecondHalf, TimeoutSnapshot::Ref(30))
+    };
+    let two_digit_scores = GameSnapshotNoHeap {
+        b_score: 99,
+        w_score: 10,
+        ..snapshot(GamePeriod::SuddenDeath, TimeoutSnapshot::None)
+    };
+
+    for (name, state) in [
+        ("penalties_single_digit", single_digits.clone()),
+        ("penalties_double_digit", double_digits.clone()),
+        ("total_dismissals", dismissals),
+        ("two_digit_scores", two_digit_scores),
+    ] {
+        cases.push(case(name, state));
+    }
+
+    let with_teams = |state: GameSnapshotNoHeap| GameSnapshotNoHeap {
+        b_team_code: TeamCode::from_name("NZL"),
+        w_team_code: TeamCode::from_name("AUS 2"),
+        ..state
+    };
+    for (name, state) in [
+        (
+            "team_codes",
+            with_teams(snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::None)),
+        ),
+        (
+            "team_code_one_side",
+            GameSnapshotNoHeap {
+                w_team_code: TeamCode::from_name("GBR"),
+                ..snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::None)
+            },
+        ),
+        (
+            "team_codes_timeout",
+            with_teams(snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::Black(54))),
+        ),
+        ("team_codes_single_digit", with_teams(single_digits.clone())),
+        ("team_codes_double_digit", with_teams(double_digits.clone())),
+        (
+            "team_codes_latin1",
+            GameSnapshotNoHeap {
+                b_team_code: TeamCode::from_name("Köln"),
+                w_team_code: TeamCode::from_name("Åland"),
+                ..snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::None)
+            },
+        ),
+    ] {
+        cases.push(case(name, state));
+    }
+
+    cases.push(case(
+        "tenths",
+        GameSnapshotNoHeap {
+            secs_in_period: 9,
+            tenths_in_period: Some(4),
+            ..snapshot(GamePeriod::SecondHalf, TimeoutSnapshot::None)
+        },
+    ));
+    cases.push(case(
+        "tenths_timeout",
+        GameSnapshotNoHeap {
+            secs_in_period: 42,
+            tenths_in_period: Some(7),
+            ..snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::White(25))
+        },
+    ));
+
+    let with_message = |text| GameSnapshotNoHeap {
+        message: PanelMessage::from_text(text),
+        ..snapshot(GamePeriod::BetweenGames, TimeoutSnapshot::None)
+    };
+    cases.push(case("message_short", with_message("FINAL")));
+    cases.push(case("message_latin1", with_message("Jeu à 14h")));
+    cases.push(case(
+        "message_team_codes",
+        GameSnapshotNoHeap {
+            message: PanelMessage::from_text("COURT 2"),
+            ..with_teams(snapshot(GamePeriod::BetweenGames, TimeoutSnapshot::None))
+        },
+    ));
+    let long_message = with_message("RESULTS AT UWHSCORES COM");
+    for (name, message_millis) in [
+        ("message_scroll_start", 0),
+        ("message_scroll_middle", 3_500),
+        ("message_scroll_wrapped", 8_000),
+    ] {
+        cases.push(Case {
+            message_millis,
+            ..case(name, long_message.clone())
+        });
+    }
+    cases.push(case(
+        "message_timeout",
+        GameSnapshotNoHeap {
+            timeout: TimeoutSnapshot::Ref(30),
+            ..long_message
+        },
+    ));
+
+    for (name, theme, brightness, state) in [
+        (
+            "color_blind",
+            PanelTheme::ColorBlind,
+            PanelBrightness::Full,
+            single_digits.clone(),
+        ),
+        (
+            "color_blind_sudden_death",
+            PanelTheme::ColorBlind,
+            PanelBrightness::Full,
+            snapshot(GamePeriod::SuddenDeath, TimeoutSnapshot::Black(30)),
+        ),
+        (
+            "night",
+            PanelTheme::Standard,
+            PanelBrightness::Night,
+            single_digits.clone(),
+        ),
+    ] {
+        cases.push(Case {
+            colors: panel_colors(theme, brightness),
+            ..case(name, state)
+        });
+    }
+
+    for (name, state) in [
+        ("white_on_right_single_digit", single_digits),
+        ("white_on_right_double_digit", double_digits),
+    ] {
+        cases.push(Case {
+            white_on_right: true,
+            ..case(name, state)
+        });
+    }
+
+    let animation = |animation, state| GameSnapshotNoHeap {
+        panel_animation: Some(animation),
+        ..state
+    };
+    let goal = animation(
+        PanelAnimation::Goal(Color::White, 7),
+        snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::None),
+    );
+    let period_end = animation(
+        PanelAnimation::PeriodEnd,
+        snapshot(GamePeriod::HalfTime, TimeoutSnapshot::None),
+    );
+    for (name, state, millis) in [
+        ("goal_opening", goal.clone(), 200),
+        ("goal_banner", goal.clone(), 500),
+        ("goal_banner_swapped", goal, 900),
+        (
+            "goal_team_code",
+            animation(
+                PanelAnimation::Goal(Color::Black, 12),
+                with_teams(snapshot(GamePeriod::SecondHalf, TimeoutSnapshot::None)),
+            ),
+            500,
+        ),
+        (
+            "goal_no_player_number",
+            animation(
+                PanelAnimation::Goal(Color::Black, 0),
+                snapshot(GamePeriod::SecondHalf, TimeoutSnapshot::None),
+            ),
+            500,
+        ),
+        (
+            "timeout_dropping",
+            animation(
+                PanelAnimation::Timeout,
+                snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::Black(60)),
+            ),
+            250,
+        ),
+        (
+            "timeout_banner",
+            animation(
+                PanelAnimation::Timeout,
+                snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::PenaltyShot(0)),
+            ),
+            1_000,
+        ),
+        ("period_end", period_end.clone(), 0),
+        ("period_end_blink", period_end, 300),
+        (
+            "game_over",
+            animation(
+                PanelAnimation::PeriodEnd,
+                snapshot(GamePeriod::BetweenGames, TimeoutSnapshot::None),
+            ),
+            0,
+        ),
+    ] {
+        cases.push(Case {
+            animation_millis: Some(millis),
+            ..case(name, state)
+        });
+    }
+
+    cases.push(Case {
+        flash: true,
+        ..case(
+            "flash",
+            snapshot(GamePeriod::FirstHalf, TimeoutSnapshot::None),
+        )
+    });
+
+    cases
+}
+
+fn golden_path(layout: &PanelLayout, case: &Case) -> PathBuf {
+    [
+        env!("CARGO_MANIFEST_DIR"),
+        "golden",
+        layout.name,
+        &format!("{}.png", case.name),
+    ]
+    .iter()
+    .collect()
+}
+
+fn write_png(frame: &FrameBuffer, path: &PathBuf) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    frame
+        .write_png(BufWriter::new(File::create(path).unwrap()))
+        .unwrap();
+}
+
+#[test]
+fn test_golden_images() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let actual_dir = std::env::temp_dir().join("matrix-drawing-golden");
+
+    let mut failures: Vec<String> = Vec::new();
+    for layout in PanelLayout::ALL {
+        for case in cases() {
+            let mut frame = FrameBuffer::new(layout.size);
+            match (case.state.panel_animation, case.animation_millis) {
+                (Some(animation), Some(millis)) => draw_animation_frame(
+                    &mut frame,
+                    &case.state,
+                    animation,
+                    layout,
+                    &case.colors,
+                    millis,
+                ),
+                _ => draw_panels_frame(
+                    &mut frame,
+                    case.state.clone(),
+                    case.white_on_right,
+                    case.flash,
+                    layout,
+                    &case.colors,
+                    case.message_millis,
+                ),
+            }
+            .unwrap();
+
+            let path = golden_path(layout, &case);
+            if update {
+                write_png(&frame, &path);
+                continue;
+            }
+
+            let expected =
+                File::open(&path).and_then(|file| FrameBuffer::read_png(BufReader::new(file)));
+            let differences = match expected {
+                Ok(expected) if expected.size() == frame.size() => expected
+                    .pixels()
+                    .iter()
+                    .zip(frame.pixels())
+                    .filter(|(expected, actual)| expected != actual)
+                    .count(),
+                Ok(_) => frame.pixels().len(),
+                Err(e) => {
+                    failures.push(format!("{}: {e}", path.display()));
+                    continue;
+                }
+            };
+            if differences > 0 {
+                let actual = actual_dir
+                    .join(layout.name)
+                    .join(format!("{}.png", case.name));
+                write_png(&frame, &actual);
+                failures.push(format!(
+                    "{}: {differences} pixels differ, drew {}",
+                    path.display(),
+                    actual.display()
+                ));
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "Golden images don't match, rerun with UPDATE_GOLDEN=1 if the changes are \
+         intended:\n{}",
+        failures.join("\n")
+    );
+}
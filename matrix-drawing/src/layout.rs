@@ -0,0 +1,851 @@
+use embedded_graphics::{
+    geometry::{Point, Size},
+    mono_font::MonoFont,
+    primitives::Rectangle,
+    text::Alignment,
+};
+use fonts::{
+    fonts::{
+        FONT_10X25, FONT_14X31, FONT_20X46, FONT_28X64, FONT_5X8, FONT_7X15, PROPORTIONAL_10X25,
+        PROPORTIONAL_5X8, PROPORTIONAL_7X15,
+    },
+    ProportionalFont,
+};
+
+/// The font a piece of text is drawn in. Proportional fonts are used where fitting more text in
+/// matters more than lining the characters up, like names, messages and penalties
+#[derive(Debug, Clone, Copy)]
+pub enum PanelFont {
+    Mono(&'static MonoFont<'static>),
+    Proportional(&'static ProportionalFont<'static>),
+}
+
+impl PanelFont {
+    /// The size of the cell each glyph is drawn from. Text is this high
+    pub const fn character_size(&self) -> Size {
+        match self {
+            Self::Mono(font) => font.character_size,
+            Self::Proportional(font) => font.font.character_size,
+        }
+    }
+
+    /// The gap between characters
+    pub const fn character_spacing(&self) -> u32 {
+        match self {
+            Self::Mono(font) => font.character_spacing,
+            Self::Proportional(font) => font.font.character_spacing,
+        }
+    }
+
+    /// The width of a single line of `text`
+    pub fn text_width(&self, text: &str) -> u32 {
+        match self {
+            Self::Mono(font) => (text.chars().count() as u32
+                * (font.character_size.width + font.character_spacing))
+                .saturating_sub(font.character_spacing),
+            Self::Proportional(font) => font.text_width(text),
+        }
+    }
+}
+
+/// Where and how a piece of text is drawn on the panels
+#[derive(Debug, Clone, Copy)]
+pub struct TextElement {
+    /// The area the text is drawn in. Text is drawn from the top of the region, and lines that
+    /// are too long are not cropped
+    pub region: Rectangle,
+    pub font: PanelFont,
+    pub alignment: Alignment,
+}
+
+impl TextElement {
+    pub const fn new(
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        font: &'static MonoFont<'static>,
+        alignment: Alignment,
+    ) -> Self {
+        Self {
+            region: Rectangle::new(Point::new(x, y), Size::new(width, height)),
+            font: PanelFont::Mono(font),
+            alignment,
+        }
+    }
+
+    /// The same as `new`, with the text drawn in a proportional font
+    pub const fn proportional(
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        font: &'static ProportionalFont<'static>,
+        alignment: Alignment,
+    ) -> Self {
+        Self {
+            region: Rectangle::new(Point::new(x, y), Size::new(width, height)),
+            font: PanelFont::Proportional(font),
+            alignment,
+        }
+    }
+
+    /// The point to draw the text from, on the edge or center of the region given by the
+    /// alignment
+    pub fn anchor(&self) -> Point {
+        let Rectangle { top_left, size } = self.region;
+        let x = match self.alignment {
+            Alignment::Left => top_left.x,
+            Alignment::Center => top_left.x + (size.width as i32 - 1) / 2,
+            Alignment::Right => top_left.x + size.width as i32 - 1,
+        };
+        Point::new(x, top_left.y)
+    }
+
+    /// Whether a single line of `text` is no wider than the region
+    pub fn fits(&self, text: &str) -> bool {
+        self.font.text_width(text) <= self.region.size.width
+    }
+
+    /// The same element reflected left to right on a display `width` pixels wide
+    pub const fn mirrored(&self, width: u32) -> Self {
+        let Rectangle { top_left, size } = self.region;
+        Self {
+            region: Rectangle::new(
+                Point::new(width as i32 - top_left.x - size.width as i32, top_left.y),
+                size,
+            ),
+            font: self.font,
+            alignment: match self.alignment {
+                Alignment::Left => Alignment::Right,
+                Alignment::Center => Alignment::Center,
+                Alignment::Right => Alignment::Left,
+            },
+        }
+    }
+}
+
+/// A row or column of penalties. Each penalty is the player number with the time remaining below
+/// it, centered on its position.
+#[derive(Debug, Clone, Copy)]
+pub struct PenaltyList {
+    /// The top center of the first penalty, which has the least time remaining
+    pub first: Point,
+    /// The offset from one penalty to the next
+    pub step: Point,
+    /// The most penalties shown, any more are not drawn
+    pub count: usize,
+    pub font: PanelFont,
+}
+
+impl PenaltyList {
+    /// The top center of each penalty in the list
+    pub fn positions(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.count as i32).map(|i| self.first + self.step * i)
+    }
+
+    /// The same list reflected left to right on a display `width` pixels wide
+    pub const fn mirrored(&self, width: u32) -> Self {
+        Self {
+            first: Point::new(width as i32 - 1 - self.first.x, self.first.y),
+            step: Point::new(-self.step.x, self.step.y),
+            count: self.count,
+            font: self.font,
+        }
+    }
+}
+
+/// The score and penalties of one team
+#[derive(Debug, Clone, Copy)]
+pub struct ScoreLayout {
+    /// A single digit score when the team has no penalties
+    pub alone: TextElement,
+    /// A double digit score when the team has no penalties
+    pub alone_two_digits: TextElement,
+    /// A single digit score drawn beside `penalties_beside`
+    pub beside_penalties: TextElement,
+    /// A double digit score drawn above `penalties_below`
+    pub above_penalties: TextElement,
+    pub penalties_beside: PenaltyList,
+    pub penalties_below: PenaltyList,
+}
+
+impl ScoreLayout {
+    /// The same layout reflected left to right on a display `width` pixels wide
+    pub const fn mirrored(&self, width: u32) -> Self {
+        Self {
+            alone: self.alone.mirrored(width),
+            alone_two_digits: self.alone_two_digits.mirrored(width),
+            beside_penalties: self.beside_penalties.mirrored(width),
+            above_penalties: self.above_penalties.mirrored(width),
+            penalties_beside: self.penalties_beside.mirrored(width),
+            penalties_below: self.penalties_below.mirrored(width),
+        }
+    }
+}
+
+/// One team's code, with its score and penalties below it
+#[derive(Debug, Clone, Copy)]
+pub struct TeamScoreLayout {
+    pub team: TextElement,
+    pub score: ScoreLayout,
+}
+
+impl TeamScoreLayout {
+    /// The same layout reflected left to right on a display `width` pixels wide
+    pub const fn mirrored(&self, width: u32) -> Self {
+        Self {
+            team: self.team.mirrored(width),
+            score: self.score.mirrored(width),
+        }
+    }
+}
+
+/// Where everything is drawn on a display of a particular size
+#[derive(Debug, Clone, Copy)]
+pub struct PanelLayout {
+    pub name: &'static str,
+    pub size: Size,
+    /// The game clock when there is no timeout
+    pub clock: TextElement,
+    /// The name of the period when there is no timeout. Names wider than the region are
+    /// abbreviated
+    pub period: TextElement,
+    /// A message drawn instead of the period. It is kept clear of the team names, and messages
+    /// wider than the region scroll through it
+    pub message: TextElement,
+    /// The game clock during a timeout
+    pub timeout_clock: TextElement,
+    /// The name of the period during a timeout, on two lines
+    pub timeout_period: TextElement,
+    /// The team that called a team timeout, on two lines
+    pub team_timeout_label: TextElement,
+    /// The time left in a team timeout
+    pub team_timeout_time: TextElement,
+    /// Ref timeouts and invalid timeouts, abbreviated if too wide for the region
+    pub ref_timeout: TextElement,
+    /// The two words of "PENALTY SHOT"
+    pub penalty_shot: [TextElement; 2],
+    pub left: ScoreLayout,
+    pub right: ScoreLayout,
+    /// Used instead of `left` and `right` when the team codes are drawn
+    pub left_with_team: TeamScoreLayout,
+    pub right_with_team: TeamScoreLayout,
+}
+
+/// The left side's layout and its reflection for the right side
+const fn with_mirror(left: ScoreLayout, width: u32) -> (ScoreLayout, ScoreLayout) {
+    (left, left.mirrored(width))
+}
+
+/// The left side's layout and its reflection for the right side
+const fn with_team_mirror(left: TeamScoreLayout, width: u32) -> (TeamScoreLayout, TeamScoreLayout) {
+    (left, left.mirrored(width))
+}
+
+const LEFT_256X64: ScoreLayout = ScoreLayout {
+    alone: TextElement::new(0, 2, 64, 62, &FONT_28X64, Alignment::Center),
+    alone_two_digits: TextElement::new(0, 2, 64, 62, &FONT_28X64, Alignment::Center),
+    beside_penalties: TextElement::new(0, 2, 62, 62, &FONT_28X64, Alignment::Right),
+    above_penalties: TextElement::new(0, 2, 64, 46, &FONT_20X46, Alignment::Center),
+    penalties_beside: PenaltyList {
+        first: Point::new(15, 47),
+        step: Point::new(0, -22),
+        count: 3,
+        font: PanelFont::Proportional(&PROPORTIONAL_5X8),
+    },
+    penalties_below: PenaltyList {
+        first: Point::new(17, 47),
+        step: Point::new(29, 0),
+        count: 2,
+        font: PanelFont::Proportional(&PROPORTIONAL_5X8),
+    },
+};
+const SCORES_256X64: (ScoreLayout, ScoreLayout) = with_mirror(LEFT_256X64, 256);
+
+const LEFT_WITH_TEAM_256X64: TeamScoreLayout = TeamScoreLayout {
+    team: TextElement::proportional(0, 0, 64, 15, &PROPORTIONAL_7X15, Alignment::Center),
+    score: ScoreLayout {
+        alone: TextElement::new(0, 17, 64, 46, &FONT_20X46, Alignment::Center),
+        alone_two_digits: TextElement::new(0, 17, 64, 46, &FONT_20X46, Alignment::Center),
+        beside_penalties: TextElement::new(0, 17, 62, 46, &FONT_20X46, Alignment::Right),
+        above_penalties: TextElement::new(0, 16, 64, 31, &FONT_14X31, Alignment::Center),
+        penalties_beside: PenaltyList {
+            first: Point::new(15, 48),
+            step: Point::new(0, -16),
+            count: 3,
+            font: PanelFont::Proportional(&PROPORTIONAL_5X8),
+        },
+        penalties_below: LEFT_256X64.penalties_below,
+    },
+};
+const TEAM_SCORES_256X64: (TeamScoreLayout, TeamScoreLayout) =
+    with_team_mirror(LEFT_WITH_TEAM_256X64, 256);
+
+const LEFT_128X32: ScoreLayout = ScoreLayout {
+    alone: TextElement::new(0, 0, 32, 32, &FONT_14X31, Alignment::Center),
+    alone_two_digits: TextElement::new(0, 0, 32, 32, &FONT_14X31, Alignment::Center),
+    beside_penalties: TextElement::new(0, 8, 32, 15, &FONT_7X15, Alignment::Right),
+    above_penalties: TextElement::new(0, 0, 32, 15, &FONT_7X15, Alignment::Center),
+    penalties_beside: PenaltyList {
+        first: Point::new(11, 16),
+        step: Point::new(0, -16),
+        count: 2,
+        font: PanelFont::Proportional(&PROPORTIONAL_5X8),
+    },
+    penalties_below: PenaltyList {
+        first: Point::new(15, 16),
+        step: Point::new(0, 0),
+        count: 1,
+        font: PanelFont::Proportional(&PROPORTIONAL_5X8),
+    },
+};
+const SCORES_128X32: (ScoreLayout, ScoreLayout) = with_mirror(LEFT_128X32, 128);
+
+const LEFT_WITH_TEAM_128X32: TeamScoreLayout = TeamScoreLayout {
+    team: TextElement::proportional(0, 0, 32, 8, &PROPORTIONAL_5X8, Alignment::Center),
+    score: ScoreLayout {
+        alone: TextElement::new(0, 7, 32, 25, &FONT_10X25, Alignment::Center),
+        alone_two_digits: TextElement::new(0, 7, 32, 25, &FONT_10X25, Alignment::Center),
+        beside_penalties: LEFT_128X32.beside_penalties,
+        above_penalties: TextElement::new(0, 8, 32, 8, &FONT_5X8, Alignment::Center),
+        penalties_beside: PenaltyList {
+            count: 1,
+            ..LEFT_128X32.penalties_beside
+        },
+        penalties_below: LEFT_128X32.penalties_below,
+    },
+};
+const TEAM_SCORES_128X32: (TeamScoreLayout, TeamScoreLayout) =
+    with_team_mirror(LEFT_WITH_TEAM_128X32, 128);
+
+const LEFT_192X64: ScoreLayout = ScoreLayout {
+    alone: TextElement::new(0, 0, 48, 64, &FONT_28X64, Alignment::Center),
+    alone_two_digits: TextElement::new(0, 9, 48, 46, &FONT_20X46, Alignment::Center),
+    beside_penalties: TextElement::new(0, 9, 47, 46, &FONT_20X46, Alignment::Right),
+    above_penalties: TextElement::new(0, 2, 48, 46, &FONT_20X46, Alignment::Center),
+    penalties_beside: PenaltyList {
+        first: Point::new(11, 47),
+        step: Point::new(0, -22),
+        count: 3,
+        font: PanelFont::Proportional(&PROPORTIONAL_5X8),
+    },
+    penalties_below: PenaltyList {
+        first: Point::new(11, 47),
+        step: Point::new(24, 0),
+        count: 2,
+        font: PanelFont::Proportional(&PROPORTIONAL_5X8),
+    },
+};
+const SCORES_192X64: (ScoreLayout, ScoreLayout) = with_mirror(LEFT_192X64, 192);
+
+const LEFT_WITH_TEAM_192X64: TeamScoreLayout = TeamScoreLayout {
+    team: TextElement::proportional(0, 0, 48, 15, &PROPORTIONAL_7X15, Alignment::Center),
+    score: ScoreLayout {
+        alone: TextElement::new(0, 17, 48, 46, &FONT_20X46, Alignment::Center),
+        alone_two_digits: TextElement::new(0, 17, 48, 46, &FONT_20X46, Alignment::Center),
+        beside_penalties: TextElement::new(0, 17, 47, 46, &FONT_20X46, Alignment::Right),
+        above_penalties: TextElement::new(0, 16, 48, 31, &FONT_14X31, Alignment::Center),
+        penalties_beside: PenaltyList {
+            first: Point::new(11, 48),
+            step: Point::new(0, -16),
+            count: 3,
+            font: PanelFont::Proportional(&PROPORTIONAL_5X8),
+        },
+        penalties_below: LEFT_192X64.penalties_below,
+    },
+};
+const TEAM_SCORES_192X64: (TeamScoreLayout, TeamScoreLayout) =
+    with_team_mirror(LEFT_WITH_TEAM_192X64, 192);
+
+const LEFT_384X96: ScoreLayout = ScoreLayout {
+    alone: TextElement::new(0, 16, 96, 64, &FONT_28X64, Alignment::Center),
+    alone_two_digits: TextElement::new(0, 16, 96, 64, &FONT_28X64, Alignment::Center),
+    beside_penalties: TextElement::new(0, 2, 94, 64, &FONT_28X64, Alignment::Right),
+    above_penalties: TextElement::new(0, 0, 96, 64, &FONT_28X64, Alignment::Center),
+    penalties_beside: PenaltyList {
+        first: Point::new(22, 66),
+        step: Point::new(0, -31),
+        count: 3,
+        font: PanelFont::Proportional(&PROPORTIONAL_7X15),
+    },
+    penalties_below: PenaltyList {
+        first: Point::new(22, 64),
+        step: Point::new(46, 0),
+        count: 2,
+        font: PanelFont::Proportional(&PROPORTIONAL_7X15),
+    },
+};
+const SCORES_384X96: (ScoreLayout, ScoreLayout) = with_mirror(LEFT_384X96, 384);
+
+const LEFT_WITH_TEAM_384X96: TeamScoreLayout = TeamScoreLayout {
+    team: TextElement::proportional(0, 1, 96, 15, &PROPORTIONAL_7X15, Alignment::Center),
+    score: ScoreLayout {
+        alone: TextElement::new(0, 18, 96, 64, &FONT_28X64, Alignment::Center),
+        alone_two_digits: TextElement::new(0, 18, 96, 64, &FONT_28X64, Alignment::Center),
+        beside_penalties: TextElement::new(0, 18, 94, 64, &FONT_28X64, Alignment::Right),
+        above_penalties: TextElement::new(0, 17, 96, 46, &FONT_20X46, Alignment::Center),
+        penalties_beside: PenaltyList {
+            count: 2,
+            ..LEFT_384X96.penalties_beside
+        },
+        penalties_below: LEFT_384X96.penalties_below,
+    },
+};
+const TEAM_SCORES_384X96: (TeamScoreLayout, TeamScoreLayout) =
+    with_team_mirror(LEFT_WITH_TEAM_384X96, 384);
+
+impl PanelLayout {
+    /// Four 64x64 panels: a score panel either side of two time panels
+    pub const STANDARD_256X64: Self = Self {
+        name: "256x64",
+        size: Size::new(256, 64),
+        clock: TextElement::new(0, 18, 256, 46, &FONT_20X46, Alignment::Center),
+        period: TextElement::new(0, 2, 256, 15, &FONT_7X15, Alignment::Center),
+        message: TextElement::proportional(64, 2, 128, 15, &PROPORTIONAL_7X15, Alignment::Center),
+        timeout_clock: TextElement::new(113, 33, 79, 31, &FONT_14X31, Alignment::Center),
+        timeout_period: TextElement::new(64, 33, 47, 30, &FONT_7X15, Alignment::Center),
+        team_timeout_label: TextElement::new(64, 2, 71, 30, &FONT_7X15, Alignment::Center),
+        team_timeout_time: TextElement::new(138, 2, 54, 31, &FONT_14X31, Alignment::Left),
+        ref_timeout: TextElement::new(64, 3, 127, 25, &FONT_10X25, Alignment::Center),
+        penalty_shot: [
+            TextElement::new(64, 3, 85, 25, &FONT_10X25, Alignment::Left),
+            TextElement::new(149, 3, 43, 25, &FONT_10X25, Alignment::Left),
+        ],
+        left: SCORES_256X64.0,
+        right: SCORES_256X64.1,
+        left_with_team: TEAM_SCORES_256X64.0,
+        right_with_team: TEAM_SCORES_256X64.1,
+    };
+
+    /// Four 32x32 panels, laid out like `STANDARD_256X64`
+    pub const SMALL_128X32: Self = Self {
+        name: "128x32",
+        size: Size::new(128, 32),
+        clock: TextElement::new(0, 7, 128, 25, &FONT_10X25, Alignment::Center),
+        period: TextElement::new(32, 0, 64, 8, &FONT_5X8, Alignment::Center),
+        message: TextElement::proportional(32, 0, 64, 8, &PROPORTIONAL_5X8, Alignment::Center),
+        timeout_clock: TextElement::new(67, 20, 29, 8, &FONT_5X8, Alignment::Left),
+        timeout_period: TextElement::new(32, 16, 35, 16, &FONT_5X8, Alignment::Center),
+        team_timeout_label: TextElement::new(32, 0, 41, 16, &FONT_5X8, Alignment::Center),
+        team_timeout_time: TextElement::new(73, 0, 23, 15, &FONT_7X15, Alignment::Left),
+        ref_timeout: TextElement::new(32, 4, 64, 8, &FONT_5X8, Alignment::Center),
+        penalty_shot: [
+            TextElement::new(32, 4, 41, 8, &FONT_5X8, Alignment::Left),
+            TextElement::new(73, 4, 23, 8, &FONT_5X8, Alignment::Left),
+        ],
+        left: SCORES_128X32.0,
+        right: SCORES_128X32.1,
+        left_with_team: TEAM_SCORES_128X32.0,
+        right_with_team: TEAM_SCORES_128X32.1,
+    };
+
+    /// Three 64x64 panels, with narrower score areas than `STANDARD_256X64`
+    pub const NARROW_192X64: Self = Self {
+        name: "192x64",
+        size: Size::new(192, 64),
+        clock: TextElement::new(0, 20, 192, 31, &FONT_14X31, Alignment::Center),
+        period: TextElement::new(48, 2, 96, 15, &FONT_7X15, Alignment::Center),
+        message: TextElement::proportional(48, 2, 96, 15, &PROPORTIONAL_7X15, Alignment::Center),
+        timeout_clock: TextElement::new(100, 41, 41, 15, &FONT_7X15, Alignment::Center),
+        timeout_period: TextElement::new(48, 33, 49, 30, &FONT_7X15, Alignment::Center),
+        team_timeout_label: TextElement::new(48, 2, 57, 30, &FONT_7X15, Alignment::Center),
+        team_timeout_time: TextElement::new(108, 4, 36, 25, &FONT_10X25, Alignment::Left),
+        ref_timeout: TextElement::new(0, 8, 192, 15, &FONT_7X15, Alignment::Center),
+        penalty_shot: [
+            TextElement::new(48, 8, 64, 15, &FONT_7X15, Alignment::Left),
+            TextElement::new(112, 8, 32, 15, &FONT_7X15, Alignment::Left),
+        ],
+        left: SCORES_192X64.0,
+        right: SCORES_192X64.1,
+        left_with_team: TEAM_SCORES_192X64.0,
+        right_with_team: TEAM_SCORES_192X64.1,
+    };
+
+    /// A 384x96 wall, laid out like `STANDARD_256X64` with larger text
+    pub const WALL_384X96: Self = Self {
+        name: "384x96",
+        size: Size::new(384, 96),
+        clock: TextElement::new(0, 26, 384, 64, &FONT_28X64, Alignment::Center),
+        period: TextElement::new(0, 1, 384, 25, &FONT_10X25, Alignment::Center),
+        message: TextElement::proportional(96, 1, 192, 25, &PROPORTIONAL_10X25, Alignment::Center),
+        timeout_clock: TextElement::new(172, 49, 109, 46, &FONT_20X46, Alignment::Center),
+        timeout_period: TextElement::new(96, 57, 89, 30, &FONT_7X15, Alignment::Center),
+        team_timeout_label: TextElement::new(96, 9, 101, 30, &FONT_7X15, Alignment::Center),
+        team_timeout_time: TextElement::new(180, 1, 66, 46, &FONT_20X46, Alignment::Left),
+        ref_timeout: TextElement::new(0, 8, 384, 31, &FONT_14X31, Alignment::Center),
+        penalty_shot: [
+            TextElement::new(96, 8, 128, 31, &FONT_14X31, Alignment::Left),
+            TextElement::new(224, 8, 64, 31, &FONT_14X31, Alignment::Left),
+        ],
+        left: SCORES_384X96.0,
+        right: SCORES_384X96.1,
+        left_with_team: TEAM_SCORES_384X96.0,
+        right_with_team: TEAM_SCORES_384X96.1,
+    };
+
+    pub const ALL: [&'static Self; 4] = [
+        &Self::STANDARD_256X64,
+        &Self::SMALL_128X32,
+        &Self::NARROW_192X64,
+        &Self::WALL_384X96,
+    ];
+
+    /// The built in layout called `name`, e.g. `"256x64"`
+    pub fn by_name(name: &str) -> Option<&'static Self> {
+        Self::ALL
+            .into_iter()
+            .find(|layout| layout.name.eq_ignore_ascii_case(name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::drawing::{
+        element_text, message_text, penalty_text, period_text, ref_timeout_text,
+        timeout_period_text,
+    };
+    use core::convert::Infallible;
+    use embedded_graphics::{
+        geometry::OriginDimensions,
+        pixelcolor::{Rgb888, RgbColor},
+        prelude::{DrawTarget, DrawTargetExt, Drawable, Pixel},
+    };
+    use std::{string::String, vec, vec::Vec};
+    use uwh_common::game_snapshot::GamePeriod;
+
+    const PERIODS: [GamePeriod; 10] = [
+        GamePeriod::BetweenGames,
+        GamePeriod::FirstHalf,
+        GamePeriod::HalfTime,
+        GamePeriod::SecondHalf,
+        GamePeriod::PreOvertime,
+        GamePeriod::OvertimeFirstHalf,
+        GamePeriod::OvertimeHalfTime,
+        GamePeriod::OvertimeSecondHalf,
+        GamePeriod::PreSuddenDeath,
+        GamePeriod::SuddenDeath,
+    ];
+
+    /// Collects the bounds of the pixels drawn on it, wherever they are
+    #[derive(Default)]
+    struct Bounds(Option<(Point, Point)>);
+
+    impl OriginDimensions for Bounds {
+        fn size(&self) -> Size {
+            Size::new(u16::MAX as u32, u16::MAX as u32)
+        }
+    }
+
+    impl DrawTarget for Bounds {
+        type Color = Rgb888;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, _) in pixels {
+                self.0 = Some(match self.0 {
+                    Some((min, max)) => (min.component_min(point), max.component_max(point)),
+                    None => (point, point),
+                });
+            }
+            Ok(())
+        }
+    }
+
+    /// The area of the pixels lit by drawing `text`
+    fn lit_box(text: impl Drawable<Color = Rgb888>) -> Rectangle {
+        let mut bounds = Bounds::default();
+        text.draw(&mut bounds).unwrap();
+        let (min, max) = bounds.0.unwrap();
+        Rectangle::with_corners(min, max)
+    }
+
+    /// The area of the pixels lit by `text` when it is drawn in `element`
+    fn text_box(text: &str, element: &TextElement) -> Rectangle {
+        lit_box(element_text(text, element, Rgb888::WHITE))
+    }
+
+    /// The area of the pixels lit by the longest message that fits in `layout.message`, and by a
+    /// message that scrolls at a few points as it goes by
+    fn message_boxes(layout: &PanelLayout) -> Vec<Rectangle> {
+        let element = &layout.message;
+        let mut fitting = String::from("W");
+        while element.fits(&(fitting.clone() + "W")) {
+            fitting.push('W');
+        }
+        let scrolling = fitting.clone() + "WWW";
+
+        let mut boxes = vec![text_box(&fitting, element)];
+        for millis in [0, 1_500, 4_000, 10_000] {
+            let mut bounds = Bounds::default();
+            for text in message_text(&scrolling, element, Rgb888::WHITE, millis) {
+                text.draw(&mut bounds.clipped(&element.region)).unwrap();
+            }
+            let (min, max) = bounds.0.unwrap();
+            boxes.push(Rectangle::with_corners(min, max));
+        }
+        boxes
+    }
+
+    /// Every combination of the texts drawn in the middle of the panels, as the area each one
+    /// covers. The widest text of each element is used
+    fn center_boxes(layout: &PanelLayout) -> Vec<Vec<(&'static str, Rectangle)>> {
+        let mut sets = vec![];
+        for message in message_boxes(layout) {
+            sets.push(vec![
+                ("clock", text_box("88:88", &layout.clock)),
+                ("message", message),
+            ]);
+        }
+        for period in PERIODS {
+            for clock_stopped in [false, true] {
+                sets.push(vec![
+                    ("clock", text_box("88:88", &layout.clock)),
+                    (
+                        "period",
+                        text_box(
+                            period_text(period, clock_stopped, &layout.period),
+                            &layout.period,
+                        ),
+                    ),
+                ]);
+            }
+
+            let timeout = [
+                ("timeout_clock", text_box("88:88", &layout.timeout_clock)),
+                (
+                    "timeout_period",
+                    text_box(timeout_period_text(period), &layout.timeout_period),
+                ),
+            ];
+            let team = [
+                (
+                    "team_timeout_label",
+                    text_box("WHITE\nTIMEOUT", &layout.team_timeout_label),
+                ),
+                (
+                    "team_timeout_time",
+                    text_box(":88", &layout.team_timeout_time),
+                ),
+            ];
+            let penalty_shot = [
+                (
+                    "penalty_shot[0]",
+                    text_box("PENALTY", &layout.penalty_shot[0]),
+                ),
+                ("penalty_shot[1]", text_box("SHOT", &layout.penalty_shot[1])),
+            ];
+            sets.push(timeout.into_iter().chain(team).collect());
+            sets.push(timeout.into_iter().chain(penalty_shot).collect());
+            for text in [ref_timeout_text(&layout.ref_timeout), "T/O ERROR"] {
+                sets.push(
+                    timeout
+                        .into_iter()
+                        .chain([("ref_timeout", text_box(text, &layout.ref_timeout))])
+                        .collect(),
+                );
+            }
+        }
+        sets
+    }
+
+    /// The areas covered by one team's score and a full list of penalties, for each way the score
+    /// can be drawn
+    fn score_boxes(score: &ScoreLayout) -> [Vec<(&'static str, Rectangle)>; 4] {
+        let penalties = |list: &PenaltyList| -> Vec<(&'static str, Rectangle)> {
+            list.positions()
+                .flat_map(|position| {
+                    penalty_text("#88", "DSMS", position, list.font, Rgb888::RED, Rgb888::RED)
+                })
+                .map(|text| ("penalty", lit_box(text)))
+                .collect()
+        };
+        [
+            vec![("alone", text_box("8", &score.alone))],
+            vec![("alone_two_digits", text_box("88", &score.alone_two_digits))],
+            [("beside_penalties", text_box("8", &score.beside_penalties))]
+                .into_iter()
+                .chain(penalties(&score.penalties_beside))
+                .collect(),
+            [("above_penalties", text_box("88", &score.above_penalties))]
+                .into_iter()
+                .chain(penalties(&score.penalties_below))
+                .collect(),
+        ]
+    }
+
+    /// The same as `score_boxes`, with the widest team name above the score
+    fn team_score_boxes(team: &TeamScoreLayout) -> [Vec<(&'static str, Rectangle)>; 4] {
+        score_boxes(&team.score).map(|boxes| {
+            [("team", text_box("WHITE", &team.team))]
+                .into_iter()
+                .chain(boxes)
+                .collect()
+        })
+    }
+
+    fn elements(layout: &PanelLayout) -> impl Iterator<Item = TextElement> + '_ {
+        [
+            layout.clock,
+            layout.period,
+            layout.message,
+            layout.timeout_clock,
+            layout.timeout_period,
+            layout.team_timeout_label,
+            layout.team_timeout_time,
+            layout.ref_timeout,
+            layout.penalty_shot[0],
+            layout.penalty_shot[1],
+        ]
+        .into_iter()
+        .chain(
+            [
+                layout.left,
+                layout.right,
+                layout.left_with_team.score,
+                layout.right_with_team.score,
+            ]
+            .into_iter()
+            .flat_map(|score| [score.alone, score.beside_penalties, score.above_penalties]),
+        )
+        .chain([layout.left_with_team.team, layout.right_with_team.team])
+    }
+
+    #[test]
+    fn test_anchor() {
+        let element = TextElement::new(10, 5, 21, 8, &FONT_5X8, Alignment::Left);
+        assert_eq!(element.anchor(), Point::new(10, 5));
+        let element = TextElement {
+            alignment: Alignment::Center,
+            ..element
+        };
+        assert_eq!(element.anchor(), Point::new(20, 5));
+        let element = TextElement {
+            alignment: Alignment::Right,
+            ..element
+        };
+        assert_eq!(element.anchor(), Point::new(30, 5));
+    }
+
+    #[test]
+    fn test_mirrored() {
+        let element = TextElement::new(0, 2, 62, 62, &FONT_28X64, Alignment::Right);
+        let mirrored = element.mirrored(256);
+        assert_eq!(mirrored.region.top_left, Point::new(194, 2));
+        assert_eq!(mirrored.alignment, Alignment::Left);
+        assert_eq!(mirrored.anchor(), Point::new(194, 2));
+
+        let right = PanelLayout::STANDARD_256X64.right;
+        assert_eq!(right.alone.anchor(), Point::new(223, 2));
+        assert_eq!(
+            right.penalties_beside.positions().collect::<Vec<_>>(),
+            vec![Point::new(240, 47), Point::new(240, 25), Point::new(240, 3)]
+        );
+        assert_eq!(
+            right.penalties_below.positions().collect::<Vec<_>>(),
+            vec![Point::new(238, 47), Point::new(209, 47)]
+        );
+    }
+
+    #[test]
+    fn test_layouts_fit() {
+        for layout in PanelLayout::ALL {
+            let display = Rectangle::new(Point::zero(), layout.size);
+            for element in elements(layout) {
+                assert!(
+                    display.contains(element.region.top_left)
+                        && display.contains(element.region.bottom_right().unwrap()),
+                    "{} element {:?} is off the display",
+                    layout.name,
+                    element.region
+                );
+            }
+
+            for penalties in [
+                layout.left,
+                layout.right,
+                layout.left_with_team.score,
+                layout.right_with_team.score,
+            ]
+            .iter()
+            .flat_map(|score| [score.penalties_beside, score.penalties_below])
+            {
+                for position in penalties.positions() {
+                    let bottom = position.y + 2 * penalties.font.character_size().height as i32;
+                    assert!(
+                        display.contains(position) && bottom <= layout.size.height as i32,
+                        "{} penalty at {position:?} is off the display",
+                        layout.name
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_layouts_dont_overlap() {
+        for layout in PanelLayout::ALL {
+            let display = Rectangle::new(Point::zero(), layout.size);
+            let sides = [
+                (score_boxes(&layout.left), score_boxes(&layout.right)),
+                (
+                    team_score_boxes(&layout.left_with_team),
+                    team_score_boxes(&layout.right_with_team),
+                ),
+            ];
+            for center in center_boxes(layout) {
+                for (left, right) in sides.iter().flat_map(|(left, right)| {
+                    left.iter()
+                        .flat_map(move |l| right.iter().map(move |r| (l, r)))
+                }) {
+                    let boxes: Vec<_> = center.iter().chain(left).chain(right).collect();
+                    for (i, (name, area)) in boxes.iter().enumerate() {
+                        assert!(
+                            display.contains(area.top_left)
+                                && display.contains(area.bottom_right().unwrap()),
+                            "{} {name} {area:?} is off the display",
+                            layout.name
+                        );
+                        for (other_name, other) in &boxes[i + 1..] {
+                            assert!(
+                                area.intersection(other).is_zero_sized(),
+                                "{} {name} {area:?} overlaps {other_name} {other:?}",
+                                layout.name
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fits() {
+        let element = TextElement::new(0, 0, 127, 15, &FONT_7X15, Alignment::Center);
+        assert!(element.fits("1234567890123456"));
+        assert!(!element.fits("12345678901234567"));
+        assert!(element.fits(""));
+
+        // The same message is too wide for the monospaced font
+        let message = "1ST GAME AT 11:10";
+        assert!(!element.fits(message));
+        assert!(PanelLayout::STANDARD_256X64.message.fits(message));
+    }
+
+    #[test]
+    fn test_by_name() {
+        assert_eq!(PanelLayout::by_name("128X32").unwrap().name, "128x32");
+        assert_eq!(
+            PanelLayout::by_name("384x96").unwrap().size,
+            Size::new(384, 96)
+        );
+        assert!(PanelLayout::by_name("64x64").is_none());
+        for layout in PanelLayout::ALL {
+            assert_eq!(PanelLayout::by_name(layout.name).unwrap().size, layout.size);
+        }
+    }
+}
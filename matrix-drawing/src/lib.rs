@@ -1,6 +1,18 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+mod animation;
+pub use animation::*;
 mod drawing;
 pub use drawing::*;
+#[cfg(feature = "std")]
+mod framebuffer;
+#[cfg(feature = "std")]
+pub use framebuffer::*;
+#[cfg(all(test, feature = "png"))]
+mod golden;
+mod layout;
+pub use layout::*;
+mod theme;
+pub use theme::*;
 
 pub mod transmitted_data;
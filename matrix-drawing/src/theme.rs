@@ -0,0 +1,179 @@
+use core::fmt::{self, Display};
+use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+use serde_derive::{Deserialize, Serialize};
+use uwh_common::game_snapshot::DecodingError;
+
+/// The colors everything on the panels is drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PanelColors {
+    /// The clock and period while the game is being played
+    pub play: Rgb888,
+    /// The clock and period during breaks, and ref timeouts
+    pub pause: Rgb888,
+    /// Sudden death, penalty shots and the time left on penalties
+    pub alert: Rgb888,
+    pub black_team: Rgb888,
+    pub white_team: Rgb888,
+    /// Fills the panels when they flash
+    pub flash: Rgb888,
+}
+
+impl PanelColors {
+    /// The colors the panels have always been drawn in
+    pub const STANDARD: Self = Self {
+        play: Rgb888::GREEN,
+        pause: Rgb888::YELLOW,
+        alert: Rgb888::RED,
+        black_team: Rgb888::new(64, 128, 255), //purple (225, 0, 255)
+        white_team: Rgb888::WHITE,
+        flash: Rgb888::new(0, 200, 200),
+    };
+
+    /// Colors from the Okabe-Ito palette, so that play and sudden death can be told apart without
+    /// seeing the difference between red and green
+    pub const COLOR_BLIND: Self = Self {
+        play: Rgb888::new(86, 180, 233),
+        pause: Rgb888::new(240, 228, 66),
+        alert: Rgb888::new(230, 159, 0),
+        black_team: Rgb888::new(204, 121, 167),
+        white_team: Rgb888::WHITE,
+        flash: Rgb888::new(0, 158, 115),
+    };
+
+    /// Every color scaled down to `percent` of its brightness
+    pub fn dimmed(self, percent: u8) -> Self {
+        let dim = |color: Rgb888| {
+            let scale = |channel: u8| (channel as u16 * percent.min(100) as u16 / 100) as u8;
+            Rgb888::new(scale(color.r()), scale(color.g()), scale(color.b()))
+        };
+        Self {
+            play: dim(self.play),
+            pause: dim(self.pause),
+            alert: dim(self.alert),
+            black_team: dim(self.black_team),
+            white_team: dim(self.white_team),
+            flash: dim(self.flash),
+        }
+    }
+}
+
+/// The set of colors the panels are drawn in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PanelTheme {
+    #[default]
+    Standard,
+    ColorBlind,
+}
+
+impl PanelTheme {
+    pub const ALL: [Self; 2] = [Self::Standard, Self::ColorBlind];
+
+    pub const fn colors(self) -> PanelColors {
+        match self {
+            Self::Standard => PanelColors::STANDARD,
+            Self::ColorBlind => PanelColors::COLOR_BLIND,
+        }
+    }
+
+    pub(crate) const fn encode(self) -> u8 {
+        self as u8
+    }
+
+    pub(crate) fn decode(val: u8) -> Result<Self, DecodingError> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.encode() == val)
+            .ok_or(DecodingError::InvalidPanelTheme(val))
+    }
+}
+
+impl Display for PanelTheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Standard => write!(f, "Standard"),
+            Self::ColorBlind => write!(f, "Color Blind"),
+        }
+    }
+}
+
+/// How bright the panels are drawn. Full is as bright as the panels can be, for outdoor pools in
+/// daylight, and the others are dimmer for indoor pools and evening sessions
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PanelBrightness {
+    #[default]
+    Full,
+    Indoor,
+    Night,
+}
+
+impl PanelBrightness {
+    pub const ALL: [Self; 3] = [Self::Full, Self::Indoor, Self::Night];
+
+    /// The percentage of full brightness that colors are scaled to
+    pub const fn percent(self) -> u8 {
+        match self {
+            Self::Full => 100,
+            Self::Indoor => 60,
+            Self::Night => 25,
+        }
+    }
+
+    pub(crate) const fn encode(self) -> u8 {
+        self as u8
+    }
+
+    pub(crate) fn decode(val: u8) -> Result<Self, DecodingError> {
+        Self::ALL
+            .into_iter()
+            .find(|brightness| brightness.encode() == val)
+            .ok_or(DecodingError::InvalidPanelBrightness(val))
+    }
+}
+
+impl Display for PanelBrightness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full => write!(f, "Full"),
+            Self::Indoor => write!(f, "Indoor"),
+            Self::Night => write!(f, "Night"),
+        }
+    }
+}
+
+/// The colors of `theme`, at `brightness`
+pub fn panel_colors(theme: PanelTheme, brightness: PanelBrightness) -> PanelColors {
+    theme.colors().dimmed(brightness.percent())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dimmed() {
+        assert_eq!(PanelColors::STANDARD.dimmed(100), PanelColors::STANDARD);
+        let night = panel_colors(PanelTheme::Standard, PanelBrightness::Night);
+        assert_eq!(night.play, Rgb888::new(0, 63, 0));
+        assert_eq!(night.black_team, Rgb888::new(16, 32, 63));
+        assert_eq!(night.flash, Rgb888::new(0, 50, 50));
+        assert_eq!(PanelColors::STANDARD.dimmed(0).white_team, Rgb888::BLACK);
+    }
+
+    #[test]
+    fn test_encoding() {
+        for theme in PanelTheme::ALL {
+            assert_eq!(PanelTheme::decode(theme.encode()), Ok(theme));
+        }
+        for brightness in PanelBrightness::ALL {
+            assert_eq!(PanelBrightness::decode(brightness.encode()), Ok(brightness));
+        }
+        assert_eq!(
+            PanelTheme::decode(2),
+            Err(DecodingError::InvalidPanelTheme(2))
+        );
+        assert_eq!(
+            PanelBrightness::decode(3),
+            Err(DecodingError::InvalidPanelBrightness(3))
+        );
+    }
+}
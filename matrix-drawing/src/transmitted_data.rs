@@ -1,33 +1,100 @@
+use crate::{panel_colors, PanelBrightness, PanelColors, PanelTheme};
 use arrayref::array_ref;
 use serde_derive::{Deserialize, Serialize};
-use uwh_common::game_snapshot::{DecodingError, EncodingError, GameSnapshotNoHeap};
+use uwh_common::game_snapshot::{
+    DecodingError, EncodingError, GameSnapshotNoHeap, PanelAnimation, PanelMessage, TeamCode,
+};
 
+/// Everything the panels are sent. See `wire_format.md` in `uwh-common` for the encoding
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct TransmittedData {
     pub white_on_right: bool,
     pub flash: bool,
     pub snapshot: GameSnapshotNoHeap,
+    #[serde(default)]
+    pub theme: PanelTheme,
+    #[serde(default)]
+    pub brightness: PanelBrightness,
 }
 
+const TEAM_CODES_START: usize = GameSnapshotNoHeap::ENCODED_LEN + 1;
+const MESSAGE_START: usize = TEAM_CODES_START + 2 * TeamCode::ENCODED_LEN;
+const APPEARANCE_START: usize = MESSAGE_START + PanelMessage::ENCODED_LEN;
+const ANIMATION_START: usize = APPEARANCE_START + 1;
+
 impl TransmittedData {
-    pub const ENCODED_LEN: usize = GameSnapshotNoHeap::ENCODED_LEN + 1;
+    pub const ENCODED_LEN: usize = ANIMATION_START + PanelAnimation::ENCODED_LEN;
+
+    /// The colors the panels should be drawn in
+    pub fn colors(&self) -> PanelColors {
+        panel_colors(self.theme, self.brightness)
+    }
 
     pub fn encode(&self) -> Result<[u8; Self::ENCODED_LEN], EncodingError> {
         let mut val = [0u8; Self::ENCODED_LEN];
-        val[0] = ((self.flash as u8) << 1) | self.white_on_right as u8;
-        val[1..].copy_from_slice(&self.snapshot.encode()?);
+        // Older panels only read the two lowest bits of the first byte, so new flags go above them
+        let tenths = match self.snapshot.tenths_in_period {
+            Some(tenths @ 0..=9) => tenths + 1,
+            Some(tenths) => return Err(EncodingError::TenthsTooLarge(tenths)),
+            None => 0,
+        };
+        val[0] = (tenths << 3)
+            | ((self.snapshot.clock_stopped_by_rule as u8) << 2)
+            | ((self.flash as u8) << 1)
+            | self.white_on_right as u8;
+        val[1..TEAM_CODES_START].copy_from_slice(&self.snapshot.encode()?);
+
+        let encode_code =
+            |code: Option<&TeamCode>| code.map_or_else(TeamCode::encode_none, TeamCode::encode);
+        val[TEAM_CODES_START..TEAM_CODES_START + TeamCode::ENCODED_LEN]
+            .copy_from_slice(&encode_code(self.snapshot.b_team_code.as_ref()));
+        val[TEAM_CODES_START + TeamCode::ENCODED_LEN..MESSAGE_START]
+            .copy_from_slice(&encode_code(self.snapshot.w_team_code.as_ref()));
+        val[MESSAGE_START..APPEARANCE_START].copy_from_slice(
+            &self
+                .snapshot
+                .message
+                .map_or_else(PanelMessage::encode_none, |message| message.encode()),
+        );
+        val[APPEARANCE_START] = (self.brightness.encode() << 4) | self.theme.encode();
+        val[ANIMATION_START..].copy_from_slice(
+            &self
+                .snapshot
+                .panel_animation
+                .map_or_else(|| Ok(PanelAnimation::encode_none()), |a| a.encode())?,
+        );
         Ok(val)
     }
 
     pub fn decode(bytes: &[u8; Self::ENCODED_LEN]) -> Result<Self, DecodingError> {
+        let mut snapshot =
+            GameSnapshotNoHeap::decode(array_ref![bytes, 1, GameSnapshotNoHeap::ENCODED_LEN])?;
+        snapshot.clock_stopped_by_rule = bytes[0] & 0x04 != 0;
+        snapshot.tenths_in_period = match (bytes[0] >> 3) & 0x0f {
+            0 => None,
+            tenths @ 1..=10 => Some(tenths - 1),
+            tenths => return Err(DecodingError::InvalidTenths(tenths)),
+        };
+        snapshot.b_team_code =
+            TeamCode::decode(array_ref![bytes, TEAM_CODES_START, TeamCode::ENCODED_LEN])?;
+        snapshot.w_team_code = TeamCode::decode(array_ref![
+            bytes,
+            TEAM_CODES_START + TeamCode::ENCODED_LEN,
+            TeamCode::ENCODED_LEN
+        ])?;
+        snapshot.message =
+            PanelMessage::decode(array_ref![bytes, MESSAGE_START, PanelMessage::ENCODED_LEN])?;
+        snapshot.panel_animation = PanelAnimation::decode(array_ref![
+            bytes,
+            ANIMATION_START,
+            PanelAnimation::ENCODED_LEN
+        ])?;
         Ok(Self {
             white_on_right: bytes[0] & 0x01 != 0,
             flash: bytes[0] & 0x02 != 0,
-            snapshot: GameSnapshotNoHeap::decode(array_ref![
-                bytes,
-                1,
-                GameSnapshotNoHeap::ENCODED_LEN
-            ])?,
+            snapshot,
+            theme: PanelTheme::decode(bytes[APPEARANCE_START] & 0x0f)?,
+            brightness: PanelBrightness::decode(bytes[APPEARANCE_START] >> 4)?,
         })
     }
 }
@@ -37,7 +104,7 @@ mod test {
     use super::*;
     use arrayvec::ArrayVec;
     use uwh_common::game_snapshot::{
-        GamePeriod, Infraction, PenaltySnapshot, PenaltyTime, TimeoutSnapshot,
+        Color, GamePeriod, Infraction, PenaltySnapshot, PenaltyTime, TimeoutSnapshot,
     };
 
     #[test]
@@ -51,12 +118,20 @@ mod test {
             b_penalties: ArrayVec::new(),
             w_penalties: ArrayVec::new(),
             is_old_game: true,
+            clock_stopped_by_rule: false,
+            b_team_code: None,
+            w_team_code: None,
+            message: None,
+            tenths_in_period: None,
+            panel_animation: None,
         };
 
         let mut data = TransmittedData {
             white_on_right: true,
             flash: true,
             snapshot: state,
+            theme: PanelTheme::Standard,
+            brightness: PanelBrightness::Full,
         };
 
         let test_data = |data: &mut TransmittedData| -> Result<(), Box<dyn std::error::Error>> {
@@ -99,6 +174,95 @@ mod test {
 
         test_data(&mut data)?;
 
+        data.snapshot.clock_stopped_by_rule = true;
+
+        test_data(&mut data)?;
+
+        data.snapshot.b_team_code = TeamCode::from_name("NZL");
+
+        test_data(&mut data)?;
+
+        data.snapshot.w_team_code = TeamCode::from_name("AUS 2");
+
+        test_data(&mut data)?;
+
+        // Decoders that only know about `white_on_right` and `flash` still see a valid period
+        let encoded = data.encode()?;
+        assert_eq!(encoded[0] & !0x07, 0);
+        assert_eq!(encoded[1] & 0x7f, data.snapshot.current_period.encode());
+        assert_eq!(&encoded[20..30], b"NZL\0\0AUS 2");
+        assert!(encoded[30..].iter().all(|&b| b == 0));
+
+        let mut invalid = encoded;
+        invalid[21] = b'z';
+        assert_eq!(
+            TransmittedData::decode(&invalid),
+            Err(DecodingError::InvalidTeamCode('z'))
+        );
+
+        data.snapshot.message = PanelMessage::from_text("Final at 14:00");
+
+        test_data(&mut data)?;
+
+        for tenths in [0, 3, 9] {
+            data.snapshot.tenths_in_period = Some(tenths);
+            test_data(&mut data)?;
+            assert_eq!(data.encode()?[0] & 0x78, (tenths + 1) << 3);
+        }
+
+        data.snapshot.tenths_in_period = Some(10);
+        assert_eq!(data.encode(), Err(EncodingError::TenthsTooLarge(10)));
+        data.snapshot.tenths_in_period = None;
+
+        let mut invalid = data.encode()?;
+        invalid[0] |= 0x58;
+        assert_eq!(
+            TransmittedData::decode(&invalid),
+            Err(DecodingError::InvalidTenths(11))
+        );
+
+        let encoded = data.encode()?;
+        assert_eq!(&encoded[30..44], b"FINAL AT 14:00");
+        assert!(encoded[44..].iter().all(|&b| b == 0));
+
+        data.theme = PanelTheme::ColorBlind;
+        data.brightness = PanelBrightness::Night;
+
+        test_data(&mut data)?;
+        assert_eq!(data.encode()?[62], 0x21);
+
+        let mut invalid = data.encode()?;
+        invalid[62] = 0x02;
+        assert_eq!(
+            TransmittedData::decode(&invalid),
+            Err(DecodingError::InvalidPanelTheme(2))
+        );
+        invalid[62] = 0x30;
+        assert_eq!(
+            TransmittedData::decode(&invalid),
+            Err(DecodingError::InvalidPanelBrightness(3))
+        );
+
+        for animation in [
+            PanelAnimation::Goal(Color::White, 12),
+            PanelAnimation::Timeout,
+            PanelAnimation::PeriodEnd,
+        ] {
+            data.snapshot.panel_animation = Some(animation);
+            test_data(&mut data)?;
+        }
+        assert_eq!(&data.encode()?[63..], [4, 0]);
+
+        data.snapshot.panel_animation = Some(PanelAnimation::Goal(Color::Black, 100));
+        assert_eq!(data.encode(), Err(EncodingError::PlayerNumTooLarge(100)));
+
+        let mut invalid = encoded;
+        invalid[31] = 0x7f;
+        assert_eq!(
+            TransmittedData::decode(&invalid),
+            Err(DecodingError::InvalidPanelMessage('\u{7f}'))
+        );
+
         Ok(())
     }
 }
@@ -0,0 +1,236 @@
+use log::{info, warn};
+use reqwest::{
+    header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    Client, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// What is stored alongside a cached response to revalidate it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct CacheMeta {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// Responses to GET requests stored on disk, keyed by URL, so that images and team data
+/// survive restarts and can still be used when the internet connection drops
+#[derive(Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+/// 64 bit FNV-1a, which unlike `DefaultHasher` is stable between builds
+fn url_key(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+fn header_string(
+    response: &reqwest::Response,
+    name: reqwest::header::HeaderName,
+) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+impl DiskCache {
+    pub fn new(dir: &Path) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        info!("Using cache directory {dir:?}");
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = url_key(url);
+        (
+            self.dir.join(format!("{key}.json")),
+            self.dir.join(format!("{key}.body")),
+        )
+    }
+
+    async fn load(&self, url: &str) -> Option<(CacheMeta, Vec<u8>)> {
+        let (meta_path, body_path) = self.paths(url);
+        let meta: CacheMeta =
+            serde_json::from_slice(&tokio::fs::read(meta_path).await.ok()?).ok()?;
+        // Guard against hash collisions
+        if meta.url != url {
+            return None;
+        }
+        let body = tokio::fs::read(body_path).await.ok()?;
+        Some((meta, body))
+    }
+
+    async fn store(&self, meta: &CacheMeta, body: &[u8]) {
+        let (meta_path, body_path) = self.paths(&meta.url);
+
+        // Write to temporary files first so that a partly written entry is never read
+        let result = async {
+            let meta_tmp = meta_path.with_extension("json.tmp");
+            let body_tmp = body_path.with_extension("body.tmp");
+            tokio::fs::write(&body_tmp, body).await?;
+            tokio::fs::write(&meta_tmp, serde_json::to_vec(meta)?).await?;
+            tokio::fs::rename(body_tmp, body_path).await?;
+            tokio::fs::rename(meta_tmp, meta_path).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            warn!("Couldn't cache {}: {e}", meta.url);
+        }
+    }
+
+    /// Fetches `url`, revalidating any cached copy with the server. The cached copy is used if
+    /// it is still valid or the server can't be reached.
+    pub async fn fetch(&self, client: &Client, url: &str) -> Result<Vec<u8>, reqwest::Error> {
+        let cached = self.load(url).await;
+
+        let mut request = client.get(url);
+        if let Some((ref meta, _)) = cached {
+            if let Some(ref etag) = meta.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(ref last_modified) = meta.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                return match cached {
+                    Some((_, body)) => {
+                        warn!("Couldn't fetch {url}, using cached copy: {e}");
+                        Ok(body)
+                    }
+                    None => Err(e),
+                };
+            }
+        };
+
+        let status = response.status();
+        match cached {
+            Some((_, body)) if status == StatusCode::NOT_MODIFIED => return Ok(body),
+            Some((_, body)) if !status.is_success() => {
+                warn!("Fetching {url} returned {status}, using cached copy");
+                return Ok(body);
+            }
+            _ => {}
+        }
+
+        let meta = CacheMeta {
+            url: url.to_string(),
+            etag: header_string(&response, ETAG),
+            last_modified: header_string(&response, LAST_MODIFIED),
+        };
+        let body = response.bytes().await?.to_vec();
+        if status.is_success() {
+            self.store(&meta, &body).await;
+        }
+        Ok(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    /// Serves `body` with an ETag of `"v1"`, answering conditional requests with 304. Returns
+    /// the base URL and the count of full responses sent.
+    fn serve(body: &'static str, requests: usize) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let full_responses = Arc::new(AtomicUsize::new(0));
+        let counter = full_responses.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut revalidating = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.to_lowercase().starts_with("if-none-match: \"v1\"") {
+                        revalidating = true;
+                    }
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+
+                let response = if revalidating {
+                    "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\n\
+                         Connection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url, full_responses)
+    }
+
+    #[tokio::test]
+    async fn test_fetch() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::new(dir.path()).unwrap();
+        let client = Client::new();
+
+        let (base, full_responses) = serve("team data", 2);
+        let url = format!("{base}/team");
+
+        assert_eq!(cache.fetch(&client, &url).await.unwrap(), b"team data");
+        assert_eq!(full_responses.load(Ordering::SeqCst), 1);
+
+        // Revalidated rather than downloaded again
+        assert_eq!(cache.fetch(&client, &url).await.unwrap(), b"team data");
+        assert_eq!(full_responses.load(Ordering::SeqCst), 1);
+
+        // The server has stopped, so the cached copy is used
+        assert_eq!(cache.fetch(&client, &url).await.unwrap(), b"team data");
+
+        // Survives being reopened
+        let cache = DiskCache::new(dir.path()).unwrap();
+        assert_eq!(cache.fetch(&client, &url).await.unwrap(), b"team data");
+
+        assert!(cache
+            .fetch(&client, &format!("{base}/other"))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_url_key() {
+        assert_eq!(url_key(""), "cbf29ce484222325");
+        assert_eq!(url_key("a"), "af63dc4c8601ec8c");
+        assert_ne!(
+            url_key("https://example.com/a.png"),
+            url_key("https://example.com/b.png")
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use crate::schedule::Page;
+use crossbeam_channel::Sender;
+use log::{error, info, warn};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    str::FromStr,
+};
+
+/// A command sent by the operator over the control port, one per line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// `force <page>`: show `page` until told otherwise
+    Force(Page),
+    /// `auto`: go back to the configured timeline
+    Auto,
+}
+
+impl FromStr for ControlCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (Some(command), Some(page), None) if command.eq_ignore_ascii_case("force") => {
+                Ok(Self::Force(page.parse()?))
+            }
+            (Some(command), None, None) if command.eq_ignore_ascii_case("auto") => Ok(Self::Auto),
+            _ => Err(format!(
+                "Unknown command {:?}, expected \"force <page>\" or \"auto\"",
+                s.trim()
+            )),
+        }
+    }
+}
+
+impl ControlCommand {
+    fn forced_page(&self) -> Option<Page> {
+        match self {
+            Self::Force(page) => Some(*page),
+            Self::Auto => None,
+        }
+    }
+}
+
+/// Listens for operator commands on `port` of the local machine, sending the page to force (or
+/// `None` to stop forcing) to the render loop
+pub fn control_thread(tx: Sender<Option<Page>>, port: u16) {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Could not open the control port {port}: {e}");
+            return;
+        }
+    };
+    info!("Listening for control commands on port {port}");
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let tx = tx.clone();
+                std::thread::spawn(move || handle_connection(stream, tx));
+            }
+            Err(e) => warn!("Failed to accept control connection: {e}"),
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, tx: Sender<Option<Page>>) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => {
+            warn!("Failed to set up control connection: {e}");
+            return;
+        }
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match line.parse::<ControlCommand>() {
+            Ok(command) => {
+                info!("Received control command {command:?}");
+                if tx.send(command.forced_page()).is_err() {
+                    break;
+                }
+                "OK".to_string()
+            }
+            Err(e) => {
+                warn!("{e}");
+                format!("ERR {e}")
+            }
+        };
+
+        if writeln!(writer, "{reply}").is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            "force roster".parse(),
+            Ok(ControlCommand::Force(Page::Roster))
+        );
+        assert_eq!(
+            " FORCE final_scores \r".parse(),
+            Ok(ControlCommand::Force(Page::FinalScores))
+        );
+        assert_eq!("auto".parse(), Ok(ControlCommand::Auto));
+
+        assert!("force".parse::<ControlCommand>().is_err());
+        assert!("force scores".parse::<ControlCommand>().is_err());
+        assert!("force roster now".parse::<ControlCommand>().is_err());
+        assert!("auto roster".parse::<ControlCommand>().is_err());
+        assert!("reset".parse::<ControlCommand>().is_err());
+    }
+}
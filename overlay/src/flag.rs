@@ -4,6 +4,7 @@
 
 use crate::load_images::Texture;
 use crate::pages::draw_text_both_ex;
+use crate::pages::draw_text_ex;
 use crate::pages::draw_texture_both;
 use crate::pages::fit_text;
 use crate::pages::Interpolate;
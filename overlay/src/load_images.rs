@@ -11,6 +11,7 @@ macro_rules! asset_load {
                 include_bytes!(concat!("../assets/alpha/1080/", $file)),
                 None,
             ),
+            scale: 1.0,
         }
     };
 }
@@ -20,6 +21,42 @@ pub(crate) use asset_load;
 pub struct Texture {
     pub alpha: Texture2D,
     pub color: Texture2D,
+    /// Pixels of the images to each pixel of the 1080p layout, more than 1 for theme art made for
+    /// a higher resolution
+    pub scale: f32,
+}
+
+impl Texture {
+    /// The size the texture is drawn at in the layout
+    pub fn size(&self) -> Vec2 {
+        self.color.size() / self.scale
+    }
+
+    pub fn width(&self) -> f32 {
+        self.size().x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.size().y
+    }
+
+    /// `params` for drawing the texture at its size in the layout, with `source` given in layout
+    /// pixels rather than pixels of the images
+    pub fn draw_params(&self, params: DrawTextureParams) -> DrawTextureParams {
+        let dest_size = params
+            .dest_size
+            .unwrap_or_else(|| params.source.map_or(self.size(), |source| source.size()));
+        DrawTextureParams {
+            dest_size: Some(dest_size),
+            source: params.source.map(|source| Rect {
+                x: source.x * self.scale,
+                y: source.y * self.scale,
+                w: source.w * self.scale,
+                h: source.h * self.scale,
+            }),
+            ..params
+        }
+    }
 }
 
 pub struct RpdTextures {
@@ -97,3 +134,80 @@ impl Default for Textures {
         }
     }
 }
+
+impl Textures {
+    /// Names of the textures that a theme pack can replace
+    pub const SLOTS: [&'static str; 32] = [
+        "atlantis_logo",
+        "bottom",
+        "team_information",
+        "team_black_banner",
+        "team_white_banner",
+        "team_bar",
+        "time_and_game_state",
+        "final_score",
+        "penalty",
+        "white_timeout",
+        "black_timeout",
+        "referee_timeout",
+        "number_background",
+        "picture_frame",
+        "black_team_name",
+        "black_single_line_name",
+        "black_double_line_name",
+        "black_triple_line_name",
+        "black_member_role",
+        "black_picture_background",
+        "white_team_name",
+        "white_single_line_name",
+        "white_double_line_name",
+        "white_triple_line_name",
+        "white_member_role",
+        "white_picture_background",
+        "red_team_name",
+        "red_single_line_name",
+        "red_double_line_name",
+        "red_triple_line_name",
+        "red_member_role",
+        "red_picture_background",
+    ];
+
+    /// The texture in the slot called `name`, see `SLOTS`
+    pub fn slot_mut(&mut self, name: &str) -> Option<&mut Texture> {
+        Some(match name {
+            "atlantis_logo" => &mut self.atlantis_logo,
+            "bottom" => &mut self.bottom,
+            "team_information" => &mut self.team_information,
+            "team_black_banner" => &mut self.team_black_banner,
+            "team_white_banner" => &mut self.team_white_banner,
+            "team_bar" => &mut self.team_bar,
+            "time_and_game_state" => &mut self.time_and_game_state,
+            "final_score" => &mut self.final_score,
+            "penalty" => &mut self.penalty,
+            "white_timeout" => &mut self.white_timout,
+            "black_timeout" => &mut self.black_timout,
+            "referee_timeout" => &mut self.referee_timout,
+            "number_background" => &mut self.number_bg_rpd,
+            "picture_frame" => &mut self.frame_rpd,
+            "black_team_name" => &mut self.black_rpd.team_name_bg,
+            "black_single_line_name" => &mut self.black_rpd.single_line_name_bg,
+            "black_double_line_name" => &mut self.black_rpd.double_line_name_bg,
+            "black_triple_line_name" => &mut self.black_rpd.triple_line_name_bg,
+            "black_member_role" => &mut self.black_rpd.team_member_role_bg,
+            "black_picture_background" => &mut self.black_rpd.frame_bg,
+            "white_team_name" => &mut self.white_rpd.team_name_bg,
+            "white_single_line_name" => &mut self.white_rpd.single_line_name_bg,
+            "white_double_line_name" => &mut self.white_rpd.double_line_name_bg,
+            "white_triple_line_name" => &mut self.white_rpd.triple_line_name_bg,
+            "white_member_role" => &mut self.white_rpd.team_member_role_bg,
+            "white_picture_background" => &mut self.white_rpd.frame_bg,
+            "red_team_name" => &mut self.red_rpd.team_name_bg,
+            "red_single_line_name" => &mut self.red_rpd.single_line_name_bg,
+            "red_double_line_name" => &mut self.red_rpd.double_line_name_bg,
+            "red_triple_line_name" => &mut self.red_rpd.triple_line_name_bg,
+            "red_member_role" => &mut self.red_rpd.team_member_role_bg,
+            "red_picture_background" => &mut self.red_rpd.frame_bg,
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,352 @@
+//! Team, roster and referee data read from a local directory, for running the overlay without
+//! access to uwhportal and uwhscores. The directory contains:
+//!
+//! - `teams.json`: an object mapping team ids to teams:
+//!   `{"12": {"name": "Team A", "flag": "flags/a.png", "members": [{"name": "Jo Bloggs",
+//!   "number": 4, "role": "Captain", "picture": "photos/jo.jpg", "dark_gear_picture": "...",
+//!   "light_gear_picture": "..."}]}}`. Members without a `number` are support staff.
+//! - `games.json`: an object mapping game numbers to games:
+//!   `{"1": {"black": 12, "white": 13, "pool": "A", "start_time": "09:30", "referees":
+//!   [{"name": "Sam Smith", "role": "Chief", "picture": "...", "geared_picture": "..."}]}}`
+//! - `tournament.json` (optional): `{"logo": "logo.png", "sponsor_logo": "sponsors.png"}`
+//!
+//! Image paths are relative to the directory. Everything except names and team ids is optional.
+
+use crate::network::{decode_image, GameData, Image, MemberRaw, TeamInfoRaw, TournamentLogos};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use uwh_common::game_snapshot::Color;
+
+const TEAMS_FILE: &str = "teams.json";
+const GAMES_FILE: &str = "games.json";
+const TOURNAMENT_FILE: &str = "tournament.json";
+
+/// Where the overlay gets team and roster data from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DataSource {
+    #[default]
+    Portal,
+    Local,
+    /// Use the portal, but fall back to the local data for anything that can't be fetched
+    PortalWithLocalFallback,
+}
+
+#[derive(Debug, Error)]
+pub enum LocalDataError {
+    #[error("Could not read {0:?}: {1}")]
+    Io(PathBuf, io::Error),
+    #[error("Could not parse {0:?}: {1}")]
+    Json(PathBuf, serde_json::Error),
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+struct LocalMember {
+    name: String,
+    #[serde(default)]
+    number: Option<u8>,
+    #[serde(default)]
+    role: Option<String>,
+    #[serde(default)]
+    picture: Option<PathBuf>,
+    #[serde(default)]
+    dark_gear_picture: Option<PathBuf>,
+    #[serde(default)]
+    light_gear_picture: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+struct LocalTeam {
+    name: String,
+    #[serde(default)]
+    flag: Option<PathBuf>,
+    #[serde(default)]
+    members: Vec<LocalMember>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+struct LocalReferee {
+    name: String,
+    #[serde(default)]
+    role: Option<String>,
+    #[serde(default)]
+    picture: Option<PathBuf>,
+    #[serde(default)]
+    geared_picture: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+struct LocalGame {
+    black: u64,
+    white: u64,
+    #[serde(default)]
+    pool: Option<String>,
+    #[serde(default)]
+    start_time: Option<String>,
+    #[serde(default)]
+    referees: Vec<LocalReferee>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+struct LocalTournament {
+    #[serde(default)]
+    logo: Option<PathBuf>,
+    #[serde(default)]
+    sponsor_logo: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct LocalData {
+    dir: PathBuf,
+    teams: HashMap<u64, LocalTeam>,
+    games: HashMap<u32, LocalGame>,
+    tournament: LocalTournament,
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, LocalDataError> {
+    let file = File::open(path).map_err(|e| LocalDataError::Io(path.to_path_buf(), e))?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| LocalDataError::Json(path.to_path_buf(), e))
+}
+
+impl LocalData {
+    pub fn load(dir: &Path) -> Result<Self, LocalDataError> {
+        let teams: HashMap<u64, LocalTeam> = read_json(&dir.join(TEAMS_FILE))?;
+        let games: HashMap<u32, LocalGame> = read_json(&dir.join(GAMES_FILE))?;
+
+        let tournament_path = dir.join(TOURNAMENT_FILE);
+        let tournament = if tournament_path.exists() {
+            read_json(&tournament_path)?
+        } else {
+            LocalTournament::default()
+        };
+
+        info!(
+            "Loaded local data for {} teams and {} games from {dir:?}",
+            teams.len(),
+            games.len()
+        );
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            teams,
+            games,
+            tournament,
+        })
+    }
+
+    fn image(&self, path: &Option<PathBuf>) -> Option<Image> {
+        let path = self.dir.join(path.as_ref()?);
+        match std::fs::read(&path) {
+            Ok(bytes) => decode_image(&bytes).or_else(|| {
+                warn!("Couldn't decode image {path:?}");
+                None
+            }),
+            Err(e) => {
+                warn!("Couldn't read image {path:?}: {e}");
+                None
+            }
+        }
+    }
+
+    /// The team with `team_id`, with the gear pictures for `team_color`
+    pub fn team(&self, team_id: u64, team_color: Color) -> Option<TeamInfoRaw> {
+        let team = self.teams.get(&team_id)?;
+
+        let members = team
+            .members
+            .iter()
+            .map(|member| MemberRaw {
+                name: member.name.trim().to_string(),
+                role: member.role.clone(),
+                number: member.number,
+                picture: self.image(&member.picture),
+                geared_picture: self.image(match team_color {
+                    Color::Black => &member.dark_gear_picture,
+                    Color::White => &member.light_gear_picture,
+                }),
+            })
+            .collect();
+
+        Some(TeamInfoRaw {
+            team_name: team.name.trim().to_uppercase(),
+            members,
+            flag: self.image(&team.flag),
+        })
+    }
+
+    /// The referees assigned to game `game_id`
+    pub fn referees(&self, game_id: u32) -> Option<Vec<MemberRaw>> {
+        let game = self.games.get(&game_id)?;
+
+        Some(
+            game.referees
+                .iter()
+                .map(|referee| MemberRaw {
+                    name: referee.name.trim().to_string(),
+                    role: referee.role.clone(),
+                    number: None,
+                    picture: self.image(&referee.picture),
+                    geared_picture: self.image(&referee.geared_picture),
+                })
+                .collect(),
+        )
+    }
+
+    /// The data for game `game_id`. Teams that aren't in the local data are named after their
+    /// colour.
+    pub fn game_data(&self, tournament_id: u32, game_id: u32) -> Option<GameData> {
+        let game = self.games.get(&game_id)?;
+
+        Some(GameData {
+            pool: game
+                .pool
+                .as_ref()
+                .map(|pool| format!("POOL: {pool}"))
+                .unwrap_or_default(),
+            start_time: game
+                .start_time
+                .as_ref()
+                .map(|time| format!("START: {time}"))
+                .unwrap_or_default(),
+            referees: self.referees(game_id).unwrap_or_default(),
+            black: self
+                .team(game.black, Color::Black)
+                .unwrap_or_else(|| TeamInfoRaw::from_color(Color::Black)),
+            white: self
+                .team(game.white, Color::White)
+                .unwrap_or_else(|| TeamInfoRaw::from_color(Color::White)),
+            sponsor_logo: None,
+            game_id,
+            tournament_id,
+        })
+    }
+
+    pub fn tournament_logos(&self) -> TournamentLogos {
+        TournamentLogos {
+            tournament_logo: self.image(&self.tournament.logo),
+            sponsors: self.image(&self.tournament.sponsor_logo),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_example(dir: &Path) {
+        std::fs::write(
+            dir.join(TEAMS_FILE),
+            r#"{
+                "12": {
+                    "name": " Team A ",
+                    "flag": "flag.png",
+                    "members": [
+                        {
+                            "name": "Jo Bloggs",
+                            "number": 4,
+                            "role": "Captain",
+                            "picture": "jo.png",
+                            "dark_gear_picture": "jo-dark.png",
+                            "light_gear_picture": "missing.png"
+                        },
+                        { "name": "Coach", "role": "Coach" }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join(GAMES_FILE),
+            r#"{
+                "1": {
+                    "black": 12,
+                    "white": 13,
+                    "pool": "A",
+                    "start_time": "09:30",
+                    "referees": [{ "name": "Sam Smith", "role": "Chief" }]
+                },
+                "2": { "black": 13, "white": 12 }
+            }"#,
+        )
+        .unwrap();
+
+        for (name, width) in [("flag.png", 3), ("jo.png", 2), ("jo-dark.png", 1)] {
+            image::RgbaImage::new(width, 1)
+                .save(dir.join(name))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_game_data() {
+        let dir = tempfile::tempdir().unwrap();
+        write_example(dir.path());
+        let data = LocalData::load(dir.path()).unwrap();
+
+        assert!(data.game_data(7, 3).is_none());
+        assert!(data.referees(3).is_none());
+        assert!(data.team(13, Color::White).is_none());
+
+        let game = data.game_data(7, 1).unwrap();
+        assert_eq!(game.tournament_id, 7);
+        assert_eq!(game.game_id, 1);
+        assert_eq!(game.pool, "POOL: A");
+        assert_eq!(game.start_time, "START: 09:30");
+        assert_eq!(game.referees.len(), 1);
+        assert_eq!(game.referees[0].name, "Sam Smith");
+        assert_eq!(game.referees[0].role.as_deref(), Some("Chief"));
+
+        assert_eq!(game.black.team_name, "TEAM A");
+        assert_eq!(game.black.flag.as_ref().map(|f| f.0), Some(3));
+        assert_eq!(game.black.members.len(), 2);
+        let jo = &game.black.members[0];
+        assert_eq!(jo.number, Some(4));
+        assert_eq!(jo.role.as_deref(), Some("Captain"));
+        assert_eq!(jo.picture.as_ref().map(|p| p.0), Some(2));
+        assert_eq!(jo.geared_picture.as_ref().map(|p| p.0), Some(1));
+        assert_eq!(game.black.members[1].number, None);
+
+        // Team 13 isn't in the local data
+        assert_eq!(game.white.team_name, "White");
+        assert!(game.white.members.is_empty());
+
+        // A missing image leaves the member without a picture
+        let game = data.game_data(7, 2).unwrap();
+        assert_eq!(game.pool, "");
+        assert_eq!(game.black.team_name, "Black");
+        let jo = &game.white.members[0];
+        assert_eq!(jo.picture.as_ref().map(|p| p.0), Some(2));
+        assert!(jo.geared_picture.is_none());
+
+        let logos = data.tournament_logos();
+        assert!(logos.tournament_logo.is_none() && logos.sponsors.is_none());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            LocalData::load(dir.path()),
+            Err(LocalDataError::Io(path, _)) if path.ends_with(TEAMS_FILE)
+        ));
+
+        write_example(dir.path());
+        std::fs::write(dir.path().join(GAMES_FILE), r#"{"1": {"black": 12}}"#).unwrap();
+        assert!(matches!(
+            LocalData::load(dir.path()),
+            Err(LocalDataError::Json(path, _)) if path.ends_with(GAMES_FILE)
+        ));
+
+        write_example(dir.path());
+        std::fs::write(dir.path().join(TOURNAMENT_FILE), r#"{"logo": "flag.png"}"#).unwrap();
+        let logos = LocalData::load(dir.path()).unwrap().tournament_logos();
+        assert_eq!(logos.tournament_logo.map(|l| l.0), Some(3));
+    }
+}
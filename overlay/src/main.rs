@@ -1,6 +1,7 @@
 use clap::Parser;
 use coarsetime::Instant;
 use crossbeam_channel::bounded;
+use local_data::DataSource;
 use log::{info, warn, LevelFilter};
 #[cfg(debug_assertions)]
 use log4rs::append::console::{ConsoleAppender, Target};
@@ -16,14 +17,20 @@ use log4rs::{
 };
 use macroquad::prelude::*;
 use network::{GameData, StatePacket, TeamInfoRaw};
+use schedule::{Page, PageScheduler, PageTimeline};
 use std::{cmp::Ordering, str::FromStr};
 use std::{net::IpAddr, path::PathBuf};
 use uwh_common::game_snapshot::{GamePeriod, GameSnapshot, TimeoutSnapshot};
 
+mod cache;
+mod control;
 mod flag;
 mod load_images;
+mod local_data;
 mod network;
 mod pages;
+mod schedule;
+mod theme;
 
 use load_images::Texture;
 
@@ -35,6 +42,26 @@ pub struct AppConfig {
     refbox_port: u64,
     uwhscores_url: String,
     uwhportal_url: String,
+    /// Port on the local machine that accepts commands to force a page, `None` to disable it
+    #[serde(default = "default_control_port")]
+    control_port: Option<u16>,
+    #[serde(default)]
+    page_timeline: PageTimeline,
+    #[serde(default)]
+    data_source: DataSource,
+    /// Directory of team and roster data to use instead of, or as a fallback to, the portal
+    #[serde(default)]
+    local_data_dir: Option<PathBuf>,
+    /// Directory where downloaded images and team data are kept, default is platform dependent
+    #[serde(default)]
+    cache_dir: Option<PathBuf>,
+    /// Directory of a theme pack to use instead of the built in graphics, see `theme`
+    #[serde(default)]
+    theme_dir: Option<PathBuf>,
+}
+
+fn default_control_port() -> Option<u16> {
+    Some(8010)
 }
 
 impl Default for AppConfig {
@@ -44,6 +71,12 @@ impl Default for AppConfig {
             refbox_port: 8000,
             uwhscores_url: String::from("https://api.uwhscores.com"),
             uwhportal_url: String::from("https://api.uwhportal.com"),
+            control_port: default_control_port(),
+            page_timeline: PageTimeline::default(),
+            data_source: DataSource::default(),
+            local_data_dir: None,
+            cache_dir: None,
+            theme_dir: None,
         }
     }
 }
@@ -78,6 +111,7 @@ fn texture_from_image_result(image: network::Image) -> Result<Texture, Box<dyn s
     Ok(Texture {
         color: Texture2D::from_rgba8(image.0, image.1, &image.2),
         alpha: Texture2D::from_rgba8(image.0, image.1, &alpha_image),
+        scale: 1.0,
     })
 }
 
@@ -199,11 +233,15 @@ struct Cli {
     #[clap(long, default_value = "3")]
     /// Number of archived logs to keep
     num_old_logs: u32,
+
+    #[clap(long, value_name = "TOURNAMENT_ID")]
+    /// Download the data and images for every game of a tournament into the cache, then exit
+    prefetch: Option<u32>,
 }
 
-#[macroquad::main(window_conf())]
-async fn main() {
-    init_logging();
+fn main() {
+    let args = Cli::parse();
+    init_logging(&args);
 
     let config: AppConfig = match confy::load(APP_NAME, None) {
         Ok(config) => config,
@@ -215,13 +253,30 @@ async fn main() {
         }
     };
 
+    if let Some(tournament_id) = args.prefetch {
+        network::prefetch(config, tournament_id);
+        return;
+    }
+
+    theme::load_resolution(config.theme_dir.as_deref());
+    macroquad::Window::from_config(window_conf(), run(config));
+}
+
+async fn run(config: AppConfig) {
     let (tx, rx) = bounded::<StatePacket>(3);
 
+    let mut scheduler = PageScheduler::new(config.page_timeline.clone());
+    let (control_tx, control_rx) = bounded::<Option<Page>>(3);
+    if let Some(port) = config.control_port {
+        std::thread::spawn(move || control::control_thread(control_tx, port));
+    }
+
+    let theme_dir = config.theme_dir.clone();
     let net_worker = std::thread::spawn(|| {
         network::networking_thread(tx, config);
     });
 
-    let assets = load_images::Textures::default();
+    let assets = theme::load_textures(theme_dir.as_deref());
 
     let mut local_state: State = State {
         snapshot: GameSnapshot {
@@ -257,6 +312,7 @@ async fn main() {
     loop {
         assert!(!net_worker.is_finished(), "Networking thread panikd!");
         clear_background(BLACK);
+        set_camera(&theme::layout_camera());
 
         if let Ok(recieved_state) = rx.try_recv() {
             local_state.update_state(recieved_state);
@@ -264,67 +320,54 @@ async fn main() {
             flag_renderer.synchronize_flags(&local_state);
         }
 
-        match local_state.snapshot.current_period {
-            GamePeriod::BetweenGames => {
-                flag_renderer.reset();
-                if let Some(duration) = local_state.snapshot.next_period_len_secs {
-                    local_state.half_play_duration = Some(duration);
-                }
-                match local_state.snapshot.secs_in_period {
-                    182..=u32::MAX => {
-                        // If an old game just finished, display its scores
-                        if local_state.snapshot.is_old_game {
-                            renderer.final_scores(&local_state);
-                        } else {
-                            renderer.next_game(&local_state);
-                        }
-                    }
-                    30..=181 => {
-                        if local_state.snapshot.is_old_game {
-                            renderer.final_scores(&local_state);
-                        } else {
-                            renderer.roster(&local_state);
-                        }
-                    }
-                    _ => {
-                        if local_state.snapshot.is_old_game
-                            && local_state.snapshot.secs_in_period > 5
-                        {
-                            renderer.final_scores(&local_state);
-                        } else {
-                            renderer.pre_game_display(&local_state);
-                        }
-                    }
-                }
+        if let Ok(page) = control_rx.try_recv() {
+            match page {
+                Some(page) => info!("Forcing the {page} page"),
+                None => info!("Returning to the page timeline"),
             }
-            GamePeriod::FirstHalf | GamePeriod::SecondHalf | GamePeriod::HalfTime => {
-                renderer.in_game_display(&local_state);
-                flag_renderer.draw();
+            scheduler.force(page);
+        }
+
+        if local_state.snapshot.current_period == GamePeriod::BetweenGames {
+            flag_renderer.reset();
+            if let Some(duration) = local_state.snapshot.next_period_len_secs {
+                local_state.half_play_duration = Some(duration);
             }
-            GamePeriod::OvertimeFirstHalf
-            | GamePeriod::OvertimeHalfTime
-            | GamePeriod::OvertimeSecondHalf
-            | GamePeriod::PreOvertime
-            | GamePeriod::PreSuddenDeath
-            | GamePeriod::SuddenDeath => {
-                renderer.overtime_and_sudden_death_display(&local_state);
-                flag_renderer.draw();
+        }
+
+        let page = scheduler.update(&local_state.snapshot);
+        match page {
+            Page::FinalScores => renderer.final_scores(&local_state),
+            Page::NextGame => renderer.next_game(&local_state),
+            Page::Roster => {
+                renderer.roster(&local_state, scheduler.secs_on_page(&local_state.snapshot))
             }
+            Page::PreGame => renderer.pre_game_display(
+                &local_state,
+                Some(scheduler.secs_on_page(&local_state.snapshot)),
+            ),
+            Page::InGame => renderer.in_game_display(&local_state),
+            Page::OvertimeAndSuddenDeath => {
+                renderer.overtime_and_sudden_death_display(&local_state)
+            }
+            Page::Stats => renderer.stats(&local_state),
+            Page::Blank => {}
+        }
+        if page.shows_flags() {
+            flag_renderer.draw();
         }
         next_frame().await;
     }
 }
 
-fn init_logging() {
-    let args = Cli::parse();
-
+fn init_logging(args: &Cli) {
     let log_level = match args.verbose {
         0 => LevelFilter::Info,
         1 => LevelFilter::Debug,
         _ => LevelFilter::Trace,
     };
 
-    let log_base_path = args.log_location.unwrap_or_else(|| {
+    let log_base_path = args.log_location.clone().unwrap_or_else(|| {
         let mut path = directories::BaseDirs::new()
             .expect("Could not find a directory to store logs")
             .data_local_dir()
@@ -393,8 +436,8 @@ fn init_logging() {
 fn window_conf() -> Conf {
     Conf {
         window_title: String::from("Overlay Program"),
-        window_width: 3840,
-        window_height: 1080,
+        window_width: theme::window_size().0,
+        window_height: theme::window_size().1,
         window_resizable: false,
         platform: miniquad::conf::Platform {
             linux_backend: miniquad::conf::LinuxBackend::X11WithWaylandFallback,
@@ -1,50 +1,82 @@
+use crate::{
+    cache::DiskCache,
+    local_data::{DataSource, LocalData},
+};
 use log::{error, info, warn};
-use reqwest::{Client, ClientBuilder, IntoUrl};
+use reqwest::{Client, ClientBuilder};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::net::TcpStream;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 use std::{io::Read, time::Duration};
+use thiserror::Error;
 use uwh_common::game_snapshot::{Color, GamePeriod, GameSnapshot};
 
 static CLIENT_CELL: OnceLock<Client> = OnceLock::new();
+static CACHE_CELL: OnceLock<Option<DiskCache>> = OnceLock::new();
 
 pub type Image = (u16, u16, Vec<u8>);
 
-async fn get_image_from_opt_url<T: IntoUrl>(url: Option<T>) -> Option<Image> {
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Sets up the HTTP client and the disk cache, must be called before anything is fetched
+fn init_http(config: &crate::AppConfig) {
+    CLIENT_CELL
+        .set(
+            ClientBuilder::new()
+                .connect_timeout(Duration::from_secs(20))
+                .build()
+                .expect("Couldn't create HTTP client!"),
+        )
+        .unwrap();
+
+    let cache_dir = config.cache_dir.clone().or_else(|| {
+        directories::BaseDirs::new().map(|dirs| dirs.cache_dir().join("uwh-overlay-cache"))
+    });
+    let cache = cache_dir.and_then(|dir| {
+        DiskCache::new(&dir)
+            .map_err(|e| error!("Couldn't open the cache directory {dir:?}: {e}"))
+            .ok()
+    });
+    CACHE_CELL.set(cache).unwrap();
+}
+
+/// Fetches `url` through the disk cache if there is one
+async fn fetch_bytes(url: &str) -> Result<Vec<u8>, reqwest::Error> {
     let client = CLIENT_CELL.get().unwrap();
-    let img_bytes = match url {
-        None => None,
-        Some(url) => Some(
-            client
-                .get(url)
-                .send()
-                .await
-                .map_err(|e| {
-                    warn!("Couldn't get image from network: {e}");
-                    e
-                })
-                .ok()?
-                .bytes()
-                .await
-                .map_err(|e| {
-                    warn!("Couldn't get image body: {e}");
-                    e
-                })
-                .ok()?
-                .to_vec(),
-        ),
-    };
+    match CACHE_CELL.get().and_then(Option::as_ref) {
+        Some(cache) => cache.fetch(client, url).await,
+        None => Ok(client.get(url).send().await?.bytes().await?.to_vec()),
+    }
+}
 
-    img_bytes
-        .and_then(|bytes| image::load_from_memory(&bytes).ok())
-        .map(|img| {
-            (
-                img.width() as u16,
-                img.height() as u16,
-                img.into_rgba8().into_raw(),
-            )
-        })
+async fn fetch_json(url: &str) -> Result<Value, FetchError> {
+    Ok(serde_json::from_slice(&fetch_bytes(url).await?)?)
+}
+
+async fn get_image_from_opt_url<T: AsRef<str>>(url: Option<T>) -> Option<Image> {
+    let bytes = fetch_bytes(url?.as_ref())
+        .await
+        .map_err(|e| warn!("Couldn't get image from network: {e}"))
+        .ok()?;
+
+    decode_image(&bytes)
+}
+
+pub fn decode_image(bytes: &[u8]) -> Option<Image> {
+    image::load_from_memory(bytes).ok().map(|img| {
+        (
+            img.width() as u16,
+            img.height() as u16,
+            img.into_rgba8().into_raw(),
+        )
+    })
 }
 
 /// Contains data of each individual in the roster. pictures are raw unprocessed bytes that are
@@ -67,27 +99,28 @@ pub struct TeamInfoRaw {
 }
 
 impl TeamInfoRaw {
+    /// A team with no information other than its colour
+    pub fn from_color(team_color: Color) -> Self {
+        Self {
+            team_name: match team_color {
+                Color::Black => String::from("Black"),
+                Color::White => String::from("White"),
+            },
+            ..Default::default()
+        }
+    }
+
     pub async fn new(
         uwhportal_url: &str,
         tournament_id: u32,
         team_id: u64,
         team_color: Color,
-    ) -> Self {
-        let client = CLIENT_CELL.get().unwrap();
+    ) -> Result<Self, FetchError> {
         info!("Requesting UWH API for team information for team: {team_id} of tournament: {tournament_id}");
-        let data: Value = serde_json::from_str(
-            &client
-                .get(format!(
-                    "{uwhportal_url}/api/admin/get-event-team?legacyEventId={tournament_id}&legacyTeamId={team_id}"
-                ))
-                .send()
-                .await
-                .expect("Coudn't request team data")
-                .text()
-                .await
-                .expect("Coudn't get team data body"),
-        )
-        .unwrap();
+        let data = fetch_json(&format!(
+            "{uwhportal_url}/api/admin/get-event-team?legacyEventId={tournament_id}&legacyTeamId={team_id}"
+        ))
+        .await?;
 
         let (members, flag) = tokio::join!(
             futures::future::join_all(
@@ -128,43 +161,30 @@ impl TeamInfoRaw {
         );
         let members = members.into_iter().collect();
 
-        Self {
-            team_name: data["name"].as_str().map_or(
-                match team_color {
-                    Color::Black => String::from("Black"),
-                    Color::White => String::from("White"),
-                },
+        Ok(Self {
+            team_name: data["name"].as_str().map_or_else(
+                || Self::from_color(team_color).team_name,
                 |s| s.trim().to_uppercase(),
             ),
             members,
             flag,
-        }
+        })
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct TournamentLogos {
     pub tournament_logo: Option<Image>,
     pub sponsors: Option<Image>,
 }
 
 impl TournamentLogos {
-    pub async fn new(uwhportal_url: &str, tournament_id: u32) -> Self {
-        let client = CLIENT_CELL.get().unwrap();
+    pub async fn new(uwhportal_url: &str, tournament_id: u32) -> Result<Self, FetchError> {
         info!("Requesting Portal API for overlay images for tournament: {tournament_id}");
-        let data: Value = serde_json::from_str(
-            &client
-                .get(format!(
-                    "{uwhportal_url}/api/admin/events/overlay-attachments?legacyEventId={tournament_id}"
-                ))
-                .send()
-                .await
-                .expect("Coudn't request tournament images")
-                .text()
-                .await
-                .expect("Coudn't get tournament images body"),
-        )
-        .unwrap();
+        let data = fetch_json(&format!(
+            "{uwhportal_url}/api/admin/events/overlay-attachments?legacyEventId={tournament_id}"
+        ))
+        .await?;
 
         let mut tournament_logo_url = None;
         let mut sponsors_url = None;
@@ -186,10 +206,10 @@ impl TournamentLogos {
             get_image_from_opt_url(sponsors_url)
         );
 
-        Self {
+        Ok(Self {
             tournament_logo,
             sponsors,
-        }
+        })
     }
 }
 
@@ -232,22 +252,14 @@ async fn fetch_game_referees(
     uwhportal_url: &str,
     tournament_id: u32,
     game_id: u32,
-) -> Result<Vec<MemberRaw>, reqwest::Error> {
-    let client = CLIENT_CELL.get().unwrap();
+) -> Result<Vec<MemberRaw>, FetchError> {
     info!(
         "Requesting Portal API for referees for (tournament, game): ({tournament_id}, {game_id})"
     );
-    let data: Value =
-        client
-            .get(format!(
-                "{uwhportal_url}/api/admin/events/game-referees?legacyEventId={tournament_id}&gameNumber={game_id}"
-            ))
-            .send()
-            .await
-            ?
-            .json()
-            .await
-            ?;
+    let data = fetch_json(&format!(
+        "{uwhportal_url}/api/admin/events/game-referees?legacyEventId={tournament_id}&gameNumber={game_id}"
+    ))
+    .await?;
 
     Ok(futures::future::join_all(
         data.get("referees")
@@ -311,84 +323,265 @@ async fn fetch_game_referees(
     .collect())
 }
 
+/// Where the networking thread gets game data from
+pub struct DataSources {
+    pub uwhscores_url: String,
+    pub uwhportal_url: String,
+    pub mode: DataSource,
+    pub local: Option<LocalData>,
+}
+
+impl DataSources {
+    fn new(config: &crate::AppConfig) -> Self {
+        let local = config
+            .local_data_dir
+            .as_ref()
+            .and_then(|dir| match LocalData::load(dir) {
+                Ok(data) => Some(data),
+                Err(e) => {
+                    error!("Couldn't load local data: {e}");
+                    None
+                }
+            });
+        if config.data_source != DataSource::Portal && local.is_none() {
+            warn!("No local data is available, teams will be named after their colours");
+        }
+
+        Self {
+            uwhscores_url: config.uwhscores_url.clone(),
+            uwhportal_url: config.uwhportal_url.clone(),
+            mode: config.data_source,
+            local,
+        }
+    }
+
+    /// The local data, if it should be used in place of data that couldn't be fetched
+    fn fallback(&self) -> Option<&LocalData> {
+        match self.mode {
+            DataSource::Portal => None,
+            DataSource::Local | DataSource::PortalWithLocalFallback => self.local.as_ref(),
+        }
+    }
+
+    fn fallback_team(&self, team_id: u64, team_color: Color) -> TeamInfoRaw {
+        self.fallback()
+            .and_then(|local| local.team(team_id, team_color))
+            .unwrap_or_else(|| TeamInfoRaw::from_color(team_color))
+    }
+
+    fn fallback_game_data(&self, tournament_id: u32, game_id: u32) -> Option<GameData> {
+        let data = self
+            .fallback()
+            .and_then(|local| local.game_data(tournament_id, game_id));
+        if data.is_some() {
+            info!("Using local game data for tid:{tournament_id}, gid:{game_id}");
+        }
+        data
+    }
+
+    async fn tournament_logos(&self, tournament_id: u32) -> TournamentLogos {
+        if self.mode == DataSource::Local {
+            return self
+                .local
+                .as_ref()
+                .map(LocalData::tournament_logos)
+                .unwrap_or_default();
+        }
+
+        match TournamentLogos::new(&self.uwhportal_url, tournament_id).await {
+            Ok(logos) => logos,
+            Err(e) => {
+                warn!("Couldn't fetch tournament logos for tid:{tournament_id}: {e}");
+                self.fallback()
+                    .map(LocalData::tournament_logos)
+                    .unwrap_or_default()
+            }
+        }
+    }
+}
+
 async fn fetch_game_data(
     tr: crossbeam_channel::Sender<(GameData, bool)>,
-    uwhscores_url: &str,
-    uwhportal_url: &str,
+    sources: Arc<DataSources>,
     tournament_id: u32,
     game_id: u32,
     is_current_game: bool,
 ) {
-    let client = CLIENT_CELL.get().unwrap();
+    let data = if sources.mode == DataSource::Local {
+        Some(
+            sources
+                .fallback_game_data(tournament_id, game_id)
+                .unwrap_or_else(|| {
+                    warn!("No local data for tid:{tournament_id}, gid:{game_id}");
+                    GameData {
+                        black: TeamInfoRaw::from_color(Color::Black),
+                        white: TeamInfoRaw::from_color(Color::White),
+                        ..GameData::default(game_id, tournament_id)
+                    }
+                }),
+        )
+    } else {
+        fetch_portal_game_data(&sources, tournament_id, game_id).await
+    };
+
+    if let Some(data) = data {
+        tr.send((data, is_current_game))
+            .map_err(|e| error!("Couldn't send data: {e}"))
+            .unwrap();
+    }
+}
+
+/// Fetches game data from the portal, retrying until it succeeds unless local data can be used
+async fn fetch_portal_game_data(
+    sources: &DataSources,
+    tournament_id: u32,
+    game_id: u32,
+) -> Option<GameData> {
     // retry periodically if no connection
     loop {
-        if let Ok(data) = client
-            .get(format!(
-                "{uwhscores_url}/api/v1/tournaments/{tournament_id}/games/{game_id}"
-            ))
-            .send()
-            .await
-        {
-            let text = data
-                .text()
-                .await
-                .expect("Response body could not be recieved!");
-            let data: Value = match serde_json::from_str(text.as_str()) {
-                Ok(d) => d,
-                _ => {
-                    error!("Aborting game data fetch! Server did not return valid JSON for tournament ID: {tournament_id}, game ID: {game_id}!: {text}");
-                    return;
+        match fetch_portal_game_data_once(sources, tournament_id, game_id).await {
+            Ok(data) => return Some(data),
+            Err(FetchError::Json(e)) => {
+                error!("Aborting game data fetch! Server did not return valid JSON for tournament ID: {tournament_id}, game ID: {game_id}!: {e}");
+                return sources.fallback_game_data(tournament_id, game_id);
+            }
+            Err(FetchError::Http(e)) => {
+                if let Some(data) = sources.fallback_game_data(tournament_id, game_id) {
+                    return Some(data);
                 }
-            };
-            info!("Got game data for tid:{tournament_id}, gid:{game_id} from UWH API");
-            let team_id_black = data["game"]["black_id"].as_u64().unwrap_or(0);
-            let team_id_white = data["game"]["white_id"].as_u64().unwrap_or(0);
+                warn!("Game data request for tid:{tournament_id}, gid:{game_id} failed. Trying again in 5 seconds.: {e}");
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            }
+        }
+    }
+}
 
-            let pool = data["game"]["pool"]
-                .as_str()
-                .map(|s| format!("POOL: {s}"))
-                .unwrap_or_default();
-            let start_time = data["game"]["start_time"]
-                .as_str()
-                .map(|s| String::from("START: ") + s.split_at(11).1.split_at(5).0)
-                .unwrap_or_default();
-            let (referees, black, white) = tokio::join!(
-                fetch_game_referees(uwhportal_url, tournament_id, game_id),
-                TeamInfoRaw::new(uwhportal_url, tournament_id, team_id_black, Color::Black,),
-                TeamInfoRaw::new(uwhportal_url, tournament_id, team_id_white, Color::White,)
-            );
-
-            let referees = match referees {
-                Ok(r) => {
-                    info!("Fetched referees for game {game_id}, there are {}", r.len());
-                    r
-                }
-                Err(e) => {
-                    warn!("Couldn't fetch referees for tid:{tournament_id}, gid:{game_id}!: {e}");
-                    Vec::new()
-                }
-            };
+async fn fetch_portal_game_data_once(
+    sources: &DataSources,
+    tournament_id: u32,
+    game_id: u32,
+) -> Result<GameData, FetchError> {
+    let DataSources {
+        uwhscores_url,
+        uwhportal_url,
+        ..
+    } = sources;
+
+    let data = fetch_json(&format!(
+        "{uwhscores_url}/api/v1/tournaments/{tournament_id}/games/{game_id}"
+    ))
+    .await?;
+    info!("Got game data for tid:{tournament_id}, gid:{game_id} from UWH API");
+    let team_id_black = data["game"]["black_id"].as_u64().unwrap_or(0);
+    let team_id_white = data["game"]["white_id"].as_u64().unwrap_or(0);
+
+    let pool = data["game"]["pool"]
+        .as_str()
+        .map(|s| format!("POOL: {s}"))
+        .unwrap_or_default();
+    let start_time = data["game"]["start_time"]
+        .as_str()
+        .map(|s| String::from("START: ") + s.split_at(11).1.split_at(5).0)
+        .unwrap_or_default();
+    let (referees, black, white) = tokio::join!(
+        fetch_game_referees(uwhportal_url, tournament_id, game_id),
+        TeamInfoRaw::new(uwhportal_url, tournament_id, team_id_black, Color::Black,),
+        TeamInfoRaw::new(uwhportal_url, tournament_id, team_id_white, Color::White,)
+    );
 
-            tr.send((
-                GameData {
-                    pool,
-                    start_time,
-                    referees,
-                    black,
-                    white,
-                    sponsor_logo: None,
-                    tournament_id,
-                    game_id,
-                },
-                is_current_game,
-            ))
-            .map_err(|e| error!("Couldn't send data: {e}"))
-            .unwrap();
+    let referees = match referees {
+        Ok(r) => {
+            info!("Fetched referees for game {game_id}, there are {}", r.len());
+            r
+        }
+        Err(e) => {
+            warn!("Couldn't fetch referees for tid:{tournament_id}, gid:{game_id}!: {e}");
+            sources
+                .fallback()
+                .and_then(|local| local.referees(game_id))
+                .unwrap_or_default()
+        }
+    };
+
+    let [black, white] = [
+        (black, team_id_black, Color::Black),
+        (white, team_id_white, Color::White),
+    ]
+    .map(|(team, team_id, color)| {
+        team.unwrap_or_else(|e| {
+            warn!("Couldn't fetch team {team_id} for tid:{tournament_id}!: {e}");
+            sources.fallback_team(team_id, color)
+        })
+    });
+
+    Ok(GameData {
+        pool,
+        start_time,
+        referees,
+        black,
+        white,
+        sponsor_logo: None,
+        tournament_id,
+        game_id,
+    })
+}
+
+/// Downloads the data and images for every game of a tournament into the cache, so that they are
+/// available even if the internet connection drops during the event
+#[tokio::main]
+pub async fn prefetch(config: crate::AppConfig, tournament_id: u32) {
+    init_http(&config);
+    if CACHE_CELL.get().unwrap().is_none() {
+        println!("There is no cache directory to prefetch into");
+        return;
+    }
+    let sources = DataSources::new(&config);
+
+    let url = format!(
+        "{}/api/v1/tournaments/{tournament_id}/games",
+        sources.uwhscores_url
+    );
+    let game_ids: Vec<u32> = match fetch_json(&url).await {
+        Ok(data) => data["games"]
+            .as_array()
+            .map(|games| {
+                games
+                    .iter()
+                    .filter_map(|game| game["gid"].as_u64())
+                    .map(|gid| gid as u32)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        Err(e) => {
+            println!("Couldn't get the list of games for tournament {tournament_id}: {e}");
             return;
         }
-        warn!("Game data request for tid:{tournament_id}, gid:{game_id} failed. Trying again in 5 seconds.");
-        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    };
+    println!(
+        "Prefetching {} games of tournament {tournament_id}",
+        game_ids.len()
+    );
+
+    if let Err(e) = TournamentLogos::new(&sources.uwhportal_url, tournament_id).await {
+        println!("Couldn't fetch the tournament logos: {e}");
     }
+
+    let mut failed = 0;
+    for (i, game_id) in game_ids.iter().enumerate() {
+        match fetch_portal_game_data_once(&sources, tournament_id, *game_id).await {
+            Ok(_) => println!("Fetched game {game_id} ({}/{})", i + 1, game_ids.len()),
+            Err(e) => {
+                failed += 1;
+                println!("Couldn't fetch game {game_id}: {e}");
+            }
+        }
+    }
+
+    println!(
+        "Done, {} of {} games fetched. Check the log for any missing teams or images",
+        game_ids.len() - failed,
+        game_ids.len()
+    );
 }
 
 #[tokio::main]
@@ -396,14 +589,8 @@ pub async fn networking_thread(
     tx: crossbeam_channel::Sender<StatePacket>,
     config: crate::AppConfig,
 ) {
-    CLIENT_CELL
-        .set(
-            ClientBuilder::new()
-                .connect_timeout(Duration::from_secs(20))
-                .build()
-                .expect("Couldn't create HTTP client!"),
-        )
-        .unwrap();
+    init_http(&config);
+    let sources = Arc::new(DataSources::new(&config));
 
     info!("Attempting refbox connection!");
     let mut stream = loop {
@@ -447,33 +634,23 @@ pub async fn networking_thread(
             let next_gid = snapshot.next_game_number;
 
             let tr_ = tr.clone();
-            let uwhscores_url = config.uwhscores_url.clone();
-            let uwhportal_url = config.uwhportal_url.clone();
+            let sources_ = sources.clone();
 
             // initial case when no data is initialised
             if game_id.is_none() {
                 let tr_ = tr.clone();
-                let uwhscores_url = config.uwhscores_url.clone();
-                let uwhportal_url = config.uwhportal_url.clone();
+                let sources_ = sources.clone();
                 game_id = Some(game_id_new);
                 tournament_id = Some(tournament_id_new);
                 info!("Fetching intial game data for tid: {tournament_id_new}, gid: {game_id_new}");
                 tokio::spawn(async move {
-                    fetch_game_data(
-                        tr_,
-                        &uwhscores_url,
-                        &uwhportal_url,
-                        tournament_id_new,
-                        game_id_new,
-                        true,
-                    )
-                    .await;
+                    fetch_game_data(tr_, sources_, tournament_id_new, game_id_new, true).await;
                 });
 
                 let tt_ = tt.clone();
-                let uwhportal_url = config.uwhportal_url.clone();
+                let sources_ = sources.clone();
                 tokio::spawn(async move {
-                    tt_.send(TournamentLogos::new(&uwhportal_url, tournament_id_new).await)
+                    tt_.send(sources_.tournament_logos(tournament_id_new).await)
                         .map_err(|e| error!("Couldn't send tournament logos: {e}"))
                         .unwrap();
                 });
@@ -483,15 +660,12 @@ pub async fn networking_thread(
             if let (Some(game_id_old), Some(tournament_id_old)) =
                 (game_id.as_mut(), tournament_id.as_mut())
             {
-                let tr_ = tr.clone();
-                let uwhscores_url = config.uwhscores_url.clone();
-                let uwhportal_url = config.uwhportal_url.clone();
                 if *game_id_old != game_id_new || *tournament_id_old != tournament_id_new {
                     if *tournament_id_old != tournament_id_new {
                         let tt_ = tt.clone();
-                        let uwhportal_url = config.uwhportal_url.clone();
+                        let sources_ = sources.clone();
                         tokio::spawn(async move {
-                            tt_.send(TournamentLogos::new(&uwhportal_url, tournament_id_new).await)
+                            tt_.send(sources_.tournament_logos(tournament_id_new).await)
                                 .map_err(|e| error!("Couldn't send tournament logos: {e}"))
                                 .unwrap();
                         });
@@ -518,18 +692,10 @@ pub async fn networking_thread(
                         info!(
                             "Fetching game data for tid: {tournament_id_new}, gid: {game_id_new}. Cache is empty or invalid!"
                         );
-                        let (uwhscores_url_, uwhportal_url_, tr__) =
-                            (uwhscores_url.clone(), uwhportal_url.clone(), tr_.clone());
+                        let (sources_, tr_) = (sources.clone(), tr.clone());
                         tokio::spawn(async move {
-                            fetch_game_data(
-                                tr__,
-                                &uwhscores_url_,
-                                &uwhportal_url_,
-                                tournament_id_new,
-                                game_id_new,
-                                true,
-                            )
-                            .await;
+                            fetch_game_data(tr_, sources_, tournament_id_new, game_id_new, true)
+                                .await;
                         });
                     }
                     continue;
@@ -543,15 +709,7 @@ pub async fn networking_thread(
             {
                 info!("Fetching game data to cache for tid: {tournament_id_new}, gid: {next_gid}");
                 tokio::spawn(async move {
-                    fetch_game_data(
-                        tr_,
-                        &uwhscores_url,
-                        &uwhportal_url,
-                        tournament_id_new,
-                        next_gid,
-                        false,
-                    )
-                    .await;
+                    fetch_game_data(tr_, sources_, tournament_id_new, next_gid, false).await;
                 });
                 next_game_data = Some(GameData::default(next_gid, tournament_id_new));
             }
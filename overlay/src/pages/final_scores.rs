@@ -3,6 +3,7 @@ use super::fit_text;
 use super::PageRenderer;
 use crate::pages::draw_text_both;
 use crate::pages::draw_text_both_ex;
+use crate::pages::draw_text_ex;
 use crate::pages::draw_texture_both_ex;
 use crate::pages::Justify;
 use crate::State;
@@ -17,8 +18,8 @@ impl PageRenderer {
         draw_texture_both!(self.assets.final_score, 314f32, 347f32, WHITE);
 
         if let Some(logo) = state.tournament_logo.as_ref() {
-            let x = (1920f32 - logo.color.width()) / 2f32;
-            let y = 675f32 - logo.color.height();
+            let x = (1920f32 - logo.width()) / 2f32;
+            let y = 675f32 - logo.height();
             draw_texture_both!(logo, x, y, WHITE);
         }
 
@@ -1,6 +1,6 @@
-use super::{draw_texture_both, fit_text, Interpolate, PageRenderer};
+use super::{draw_texture_both, fit_text, game_clock_text, Interpolate, PageRenderer};
 use crate::{
-    pages::{draw_text_both, draw_text_both_ex, draw_texture_both_ex, Justify},
+    pages::{draw_text_both, draw_text_both_ex, draw_text_ex, draw_texture_both_ex, Justify},
     State,
 };
 use coarsetime::Instant;
@@ -371,21 +371,7 @@ impl PageRenderer {
             }
         );
         draw_texture_both!(self.assets.time_and_game_state, 367f32, 18f32, WHITE);
-        let min = state.snapshot.secs_in_period / 60;
-        let secs = state.snapshot.secs_in_period % 60;
-        let text = format!(
-            "{}:{}",
-            if min < 10 {
-                format!("0{min}")
-            } else {
-                format!("{min}")
-            },
-            if secs < 10 {
-                format!("0{secs}")
-            } else {
-                format!("{secs}")
-            }
-        );
+        let text = game_clock_text(&state.snapshot);
         let (x_off, text) = fit_text(180f32, &text, 50, &self.assets.font, Justify::Center);
         draw_text_ex(
             text.as_str(),
@@ -467,7 +453,7 @@ impl PageRenderer {
         );
 
         if let Some(logo) = state.tournament_logo.as_ref() {
-            let x = 1900f32 - logo.color.width();
+            let x = 1900f32 - logo.width();
             draw_texture_both!(logo, x, 20f32, WHITE);
         }
 
@@ -495,7 +481,7 @@ impl PageRenderer {
 
         if state.snapshot.current_period == GamePeriod::HalfTime {
             if let Some(sponsor_logo) = &state.sponsor_logo {
-                let x = (1920f32 - sponsor_logo.color.width()) / 2f32;
+                let x = (1920f32 - sponsor_logo.width()) / 2f32;
                 if sponsor_alpha > 0f32 {
                     draw_texture_both!(
                         sponsor_logo,
@@ -8,6 +8,7 @@ mod next_game;
 mod overtime_and_sudden_death;
 mod pre_game;
 pub mod roster;
+mod stats;
 
 pub trait Interpolate {
     /// `value` must be a `float` normally varying from `0f32` to `1f32`
@@ -127,37 +128,72 @@ pub fn fit_text(
 
 macro_rules! draw_texture_both {
     ($texture: expr, $x: expr, $y: expr, $color: expr) => {
-        draw_texture(&$texture.color, $x, $y, $color);
-        draw_texture(&$texture.alpha, $x + 1920f32, $y, $color);
+        $crate::pages::draw_texture_both_ex!($texture, $x, $y, $color, Default::default());
     };
 }
 pub(crate) use draw_texture_both;
 
+/// Textures are drawn at their size in the layout, see `Texture::draw_params`
 macro_rules! draw_texture_both_ex {
-    ($texture: expr, $x: expr, $y: expr, $color: expr, $params: expr) => {
-        draw_texture_ex(&$texture.color, $x, $y, $color, $params);
-        draw_texture_ex(&$texture.alpha, $x + 1920f32, $y, $color, $params);
-    };
+    ($texture: expr, $x: expr, $y: expr, $color: expr, $params: expr) => {{
+        let params = $texture.draw_params($params);
+        draw_texture_ex(&$texture.color, $x, $y, $color, params.clone());
+        draw_texture_ex(&$texture.alpha, $x + 1920f32, $y, $color, params);
+    }};
 }
 pub(crate) use draw_texture_both_ex;
 
+/// Text colors on the color side are swapped for those of the theme, see `ThemeColors::recolor`
 macro_rules! draw_text_both {
     ($text: expr, $x: expr, $y: expr, $params: expr) => {
-        draw_text_ex($text, $x, $y, $params);
-        draw_text_ex($text, $x + 1920f32, $y, $params);
+        $crate::pages::draw_text_ex($text, $x, $y, $crate::pages::themed($params));
+        $crate::pages::draw_text_ex($text, $x + 1920f32, $y, $params);
     };
 }
 pub(crate) use draw_text_both;
 
 macro_rules! draw_text_both_ex {
     ($text: expr, $x: expr, $y: expr, $params_color: expr, $params_alpha: expr) => {
-        draw_text_ex($text, $x, $y, $params_color);
-        draw_text_ex($text, $x + 1920f32, $y, $params_alpha);
+        $crate::pages::draw_text_ex($text, $x, $y, $crate::pages::themed($params_color));
+        $crate::pages::draw_text_ex($text, $x + 1920f32, $y, $params_alpha);
     };
 }
 pub(crate) use draw_text_both_ex;
 
-use uwh_common::game_snapshot::TimeoutSnapshot;
+/// Used instead of macroquad's `draw_text_ex`. The text is laid out in the 1080p layout as usual,
+/// but its glyphs are rasterised at the resolution the overlay renders at so that they stay sharp
+pub fn draw_text_ex(text: &str, x: f32, y: f32, params: TextParams) -> TextDimensions {
+    let scale = crate::theme::render_scale();
+    macroquad::text::draw_text_ex(
+        text,
+        x,
+        y,
+        TextParams {
+            font_size: (params.font_size as f32 * scale).round() as u16,
+            font_scale: params.font_scale / scale,
+            ..params
+        },
+    )
+}
+
+use uwh_common::game_snapshot::{GameSnapshot, TimeoutSnapshot};
+
+/// The game clock as `MM:SS`, or as seconds and tenths (`SS.T`) when the snapshot has the tenths
+pub fn game_clock_text(snapshot: &GameSnapshot) -> String {
+    let secs = snapshot.secs_in_period;
+    match snapshot.tenths_in_period {
+        Some(tenths) => format!("{secs:02}.{tenths}"),
+        None => format!("{:02}:{:02}", secs / 60, secs % 60),
+    }
+}
+
+/// `params` with the text color swapped for the theme's
+pub fn themed(params: TextParams) -> TextParams {
+    TextParams {
+        color: crate::theme::colors().recolor(params.color),
+        ..params
+    }
+}
 
 #[allow(dead_code)]
 /// Utility function used to place overlay elements quickly through user input without recompiling
@@ -183,3 +219,21 @@ pub struct PageRenderer {
     /// We need to keep track of the last timeout snapshot in order to display information during the fade out
     pub last_snapshot_timeout: TimeoutSnapshot,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_game_clock_text() {
+        let snapshot = |secs_in_period, tenths_in_period| GameSnapshot {
+            secs_in_period,
+            tenths_in_period,
+            ..Default::default()
+        };
+        assert_eq!(game_clock_text(&snapshot(754, None)), "12:34");
+        assert_eq!(game_clock_text(&snapshot(5, None)), "00:05");
+        assert_eq!(game_clock_text(&snapshot(59, Some(3))), "59.3");
+        assert_eq!(game_clock_text(&snapshot(5, Some(0))), "05.0");
+    }
+}
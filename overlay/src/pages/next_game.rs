@@ -3,6 +3,7 @@ use super::fit_text;
 use super::PageRenderer;
 use crate::pages::draw_text_both;
 use crate::pages::draw_text_both_ex;
+use crate::pages::draw_text_ex;
 use crate::pages::draw_texture_both_ex;
 use crate::pages::Justify;
 use crate::State;
@@ -18,12 +19,12 @@ impl PageRenderer {
         draw_texture_both!(self.assets.team_information, 130f32, 710f32, WHITE);
 
         if let Some(logo) = &state.tournament_logo {
-            let x = 1900f32 - logo.color.width();
+            let x = 1900f32 - logo.width();
             draw_texture_both!(logo, x, 20f32, WHITE);
         }
 
         if let Some(sponsor_logo) = &state.sponsor_logo {
-            let x = (1920f32 - sponsor_logo.color.width()) / 2f32;
+            let x = (1920f32 - sponsor_logo.width()) / 2f32;
             draw_texture_both!(sponsor_logo, x, 200f32, WHITE);
         }
 
@@ -1,7 +1,7 @@
-use super::{draw_texture_both, fit_text, Interpolate, PageRenderer};
+use super::{draw_texture_both, fit_text, game_clock_text, Interpolate, PageRenderer};
 use crate::{
     draw_timeout_flag,
-    pages::{draw_text_both, draw_text_both_ex, draw_texture_both_ex, Justify},
+    pages::{draw_text_both, draw_text_both_ex, draw_text_ex, draw_texture_both_ex, Justify},
     State,
 };
 use coarsetime::Instant;
@@ -215,21 +215,7 @@ impl PageRenderer {
         );
 
         draw_texture_both!(self.assets.time_and_game_state, 367f32, 18f32, WHITE);
-        let min = state.snapshot.secs_in_period / 60;
-        let secs = state.snapshot.secs_in_period % 60;
-        let text = format!(
-            "{}:{}",
-            if min < 10 {
-                format!("0{min}")
-            } else {
-                format!("{min}")
-            },
-            if secs < 10 {
-                format!("0{secs}")
-            } else {
-                format!("{secs}")
-            }
-        );
+        let text = game_clock_text(&state.snapshot);
         let (x_off, text) = fit_text(180f32, &text, 50, &self.assets.font, Justify::Center);
         draw_text_ex(
             text.as_str(),
@@ -4,55 +4,52 @@ use super::Interpolate;
 use super::PageRenderer;
 use crate::pages::draw_text_both;
 use crate::pages::draw_text_both_ex;
+use crate::pages::draw_text_ex;
 use crate::pages::draw_texture_both_ex;
 use crate::{pages::Justify, State};
 use coarsetime::Instant;
 use macroquad::prelude::*;
 
+/// Seconds into the page when the bottom graphic fades out
+const MIDFADE_SECS: u32 = 14;
+
 impl PageRenderer {
-    /// Displayed from 30 seconds before a game begins.
-    pub fn pre_game_display(&mut self, state: &State) {
-        let (sponsor_alpha, midfade_alpha, tandg_alpha) = match state.snapshot.secs_in_period {
-            31.. => {
+    /// Displayed for the last part of the break before a game. `secs_on_page` is how long the
+    /// page has been up, or `None` when it is drawn after the rosters have finished, before the
+    /// page itself starts. The sponsor logo fades out as the page starts, and the bottom graphic
+    /// fades out `MIDFADE_SECS` later.
+    pub fn pre_game_display(&mut self, state: &State, secs_on_page: Option<u32>) {
+        // Fades take a second, and hold once done in case the page started without the register
+        // being reset just before
+        let fade =
+            |register: Instant| (Instant::now().duration_since(register).as_f64() as f32).min(1f32);
+        let (sponsor_alpha, midfade_alpha, tandg_alpha) = match secs_on_page {
+            None => {
                 self.animation_register1 = Instant::now();
                 (1f32, 1f32, 1f32)
             }
-            30 => (
-                (1f32, 0f32).interpolate_linear(
-                    Instant::now()
-                        .duration_since(self.animation_register1)
-                        .as_f64() as f32,
-                ),
-                (0f32, 1f32).interpolate_linear(
-                    Instant::now()
-                        .duration_since(self.animation_register1)
-                        .as_f64() as f32,
-                ),
+            Some(0) => (
+                (1f32, 0f32).interpolate_linear(fade(self.animation_register1)),
+                (0f32, 1f32).interpolate_linear(fade(self.animation_register1)),
                 1f32,
             ),
-            16.. => {
+            Some(secs) if secs < MIDFADE_SECS => {
                 self.animation_register1 = Instant::now();
                 (0f32, 1f32, 1f32)
             }
-            15 => {
-                (
-                    0f32, // animate a fade on the fifteenth second
-                    (1f32, 0f32).interpolate_linear(
-                        Instant::now()
-                            .duration_since(self.animation_register1)
-                            .as_f64() as f32,
-                    ),
-                    1f32,
-                )
-            }
-            _ => {
+            Some(MIDFADE_SECS) => (
+                0f32,
+                (1f32, 0f32).interpolate_linear(fade(self.animation_register1)),
+                1f32,
+            ),
+            Some(_) => {
                 self.animation_register1 = Instant::now();
                 (0f32, 0f32, 1f32)
             }
         };
 
         if let Some(sponsor_logo) = &state.sponsor_logo {
-            let x = (1920f32 - sponsor_logo.color.width()) / 2f32;
+            let x = (1920f32 - sponsor_logo.width()) / 2f32;
             if sponsor_alpha > 0f32 {
                 draw_texture_both!(
                     sponsor_logo,
@@ -309,7 +306,7 @@ impl PageRenderer {
         }
 
         if let Some(logo) = state.tournament_logo.as_ref() {
-            let x = 1900f32 - logo.color.width();
+            let x = 1900f32 - logo.width();
             draw_texture_both!(logo, x, 20f32, WHITE);
         }
     }
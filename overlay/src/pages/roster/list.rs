@@ -1,6 +1,6 @@
-use super::{draw_texture_both, fit_text, Interpolate, Justify, PageRenderer};
+use super::{draw_texture_both, fit_text, Interpolate, Justify, PageRenderer, LIST_SECS};
 use crate::{
-    pages::{draw_text_both, draw_text_both_ex, draw_texture_both_ex},
+    pages::{draw_text_both, draw_text_both_ex, draw_text_ex, draw_texture_both_ex},
     State,
 };
 use coarsetime::Instant;
@@ -11,20 +11,20 @@ const LIST_NUMBER_BG_WIDTH: f32 = 85f32;
 const PLAYER_ROW_HEIGHT: f32 = 58f32;
 const TEAM_BANNER_ROSTER_OFFSET: f32 = -650f32;
 
-pub fn draw(renderer: &mut PageRenderer, state: &State) {
-    let offset = if state.snapshot.secs_in_period == 181 {
+pub fn draw(renderer: &mut PageRenderer, state: &State, secs_on_page: u32) {
+    let offset = if secs_on_page == 0 {
         (0f32, TEAM_BANNER_ROSTER_OFFSET).interpolate_linear(
             Instant::now()
                 .duration_since(renderer.animation_register1)
                 .as_f64() as f32,
         )
     } else {
-        if state.snapshot.secs_in_period != 169 {
+        if secs_on_page != LIST_SECS {
             renderer.animation_register1 = Instant::now();
         }
         (0f32, TEAM_BANNER_ROSTER_OFFSET).interpolate_linear(1f32)
     };
-    let timeout_alpha_offset = if state.snapshot.secs_in_period == 169 {
+    let timeout_alpha_offset = if secs_on_page == LIST_SECS {
         renderer.animation_register2 = Instant::now();
         (1f32, 0f32).interpolate_linear(
             2f32 * (Instant::now()
@@ -36,8 +36,8 @@ pub fn draw(renderer: &mut PageRenderer, state: &State) {
     };
     if let Some(logo) = state.tournament_logo.as_ref() {
         if offset <= -210f32 {
-            let x = (1920f32 - logo.color.width()) / 2f32;
-            let y = 675f32 - logo.color.height();
+            let x = (1920f32 - logo.width()) / 2f32;
+            let y = 675f32 - logo.height();
             draw_texture_both!(
                 logo,
                 x,
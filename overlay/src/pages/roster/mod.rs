@@ -3,11 +3,15 @@ use crate::State;
 mod list;
 mod picture;
 
+/// Number of seconds the list is shown for before the pictures
+const LIST_SECS: u32 = 12;
+
 impl PageRenderer {
-    pub fn roster(&mut self, state: &State) {
-        if state.snapshot.secs_in_period >= 169 {
-            list::draw(self, state);
-        } else if state.snapshot.secs_in_period < 169 {
+    /// `secs_on_page` is the number of seconds since the rosters were first shown
+    pub fn roster(&mut self, state: &State, secs_on_page: u32) {
+        if secs_on_page <= LIST_SECS {
+            list::draw(self, state, secs_on_page);
+        } else {
             picture::draw(self, state);
         }
     }
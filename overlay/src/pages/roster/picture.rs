@@ -1,6 +1,6 @@
 use super::{draw_texture_both, fit_text, Justify, PageRenderer};
 use crate::{
-    pages::{draw_text_both_ex, draw_texture_both_ex, multilinify},
+    pages::{draw_text_both_ex, draw_text_ex, draw_texture_both_ex, multilinify},
     Member, State,
 };
 use coarsetime::Instant;
@@ -122,7 +122,7 @@ pub fn draw(renderer: &mut PageRenderer, state: &State) {
                 )
             }
             _ => {
-                renderer.pre_game_display(state);
+                renderer.pre_game_display(state, None);
                 return;
             } // time after rpd display
         };
@@ -150,7 +150,7 @@ pub fn draw(renderer: &mut PageRenderer, state: &State) {
         }
     );
     if let Some(flag) = team_flag {
-        let f_width = flag.color.width() * (170f32 / flag.color.height());
+        let f_width = flag.width() * (170f32 / flag.height());
         draw_texture_both_ex!(
             flag,
             484f32 - f_width / 2f32,
@@ -383,10 +383,9 @@ pub fn draw(renderer: &mut PageRenderer, state: &State) {
                     }
                 );
                 let text_height = measure_text("Q", Some(&renderer.assets.font), 33, 1.0).height;
-                let v_margin = text_height.mul_add(
-                    -(lines.len().min(3) as f32),
-                    text_box_texture.color.height(),
-                ) / (lines.len().min(3) as f32 + 1f32);
+                let v_margin = text_height
+                    .mul_add(-(lines.len().min(3) as f32), text_box_texture.height())
+                    / (lines.len().min(3) as f32 + 1f32);
                 for (j, line) in lines.iter().take(3).enumerate() {
                     let (x_off, text) =
                         fit_text(400f32, line, 33, &renderer.assets.font, Justify::Center);
@@ -0,0 +1,277 @@
+use super::{draw_text_both, fit_text, Justify, PageRenderer};
+use crate::State;
+use macroquad::prelude::*;
+use uwh_common::game_snapshot::{
+    Color as UwhColor, GamePeriod, GameSnapshot, GoalSnapshot, PenaltySnapshot, PenaltyTime,
+};
+
+const PANEL_WIDTH: f32 = 800f32;
+const PANEL_TOP: f32 = 190f32;
+const PANEL_HEIGHT: f32 = 780f32;
+const ROW_HEIGHT: f32 = 42f32;
+/// Rows available to each of the goal and penalty lists
+const MAX_ROWS: usize = 6;
+
+/// The stats for one team, as shown on the stats page
+#[derive(Debug, PartialEq, Eq)]
+struct TeamStats {
+    score: u8,
+    timeouts: u16,
+    fouls: usize,
+    warnings: usize,
+    goals: Vec<String>,
+    penalties: Vec<String>,
+}
+
+fn period_name(period: GamePeriod) -> &'static str {
+    match period {
+        GamePeriod::FirstHalf => "1ST HALF",
+        GamePeriod::SecondHalf => "2ND HALF",
+        GamePeriod::OvertimeFirstHalf => "OT 1ST HALF",
+        GamePeriod::OvertimeSecondHalf => "OT 2ND HALF",
+        GamePeriod::SuddenDeath => "SUDDEN DEATH",
+        _ => "",
+    }
+}
+
+fn goal_line(goal: &GoalSnapshot) -> String {
+    format!(
+        "#{:<3} {:>2}:{:02}  {}",
+        goal.player_number,
+        goal.secs_in_period / 60,
+        goal.secs_in_period % 60,
+        period_name(goal.period)
+    )
+}
+
+fn penalty_line(penalty: &PenaltySnapshot) -> String {
+    let length = match penalty.time {
+        PenaltyTime::Seconds(secs) if secs % 60 == 0 => format!("{} MIN", secs / 60),
+        PenaltyTime::Seconds(secs) => format!("{secs} SEC"),
+        PenaltyTime::TotalDismissal => "DISMISSED".to_string(),
+    };
+    format!(
+        "#{:<3} {}  {length}",
+        penalty.player_number,
+        penalty.infraction.short_name().to_uppercase()
+    )
+}
+
+/// Cuts `lines` down to `MAX_ROWS`, replacing the last row with a count of those left out
+fn limit_rows(mut lines: Vec<String>) -> Vec<String> {
+    if lines.len() > MAX_ROWS {
+        let more = lines.len() - MAX_ROWS + 1;
+        lines.truncate(MAX_ROWS - 1);
+        lines.push(format!("+{more} MORE"));
+    }
+    lines
+}
+
+impl TeamStats {
+    fn new(snapshot: &GameSnapshot, color: UwhColor) -> Self {
+        let (score, timeouts, fouls, warnings, goals, penalties) = match color {
+            UwhColor::Black => (
+                snapshot.b_score,
+                snapshot.b_timeouts_used,
+                &snapshot.b_fouls,
+                &snapshot.b_warnings,
+                &snapshot.b_goals,
+                &snapshot.b_game_penalties,
+            ),
+            UwhColor::White => (
+                snapshot.w_score,
+                snapshot.w_timeouts_used,
+                &snapshot.w_fouls,
+                &snapshot.w_warnings,
+                &snapshot.w_goals,
+                &snapshot.w_game_penalties,
+            ),
+        };
+
+        // Penalties are grouped by player, in the order they were given
+        let mut penalties: Vec<&PenaltySnapshot> = penalties.iter().collect();
+        penalties.sort_by_key(|penalty| penalty.player_number);
+
+        Self {
+            score,
+            timeouts,
+            fouls: fouls.len(),
+            warnings: warnings.len(),
+            goals: limit_rows(goals.iter().map(goal_line).collect()),
+            penalties: limit_rows(penalties.into_iter().map(penalty_line).collect()),
+        }
+    }
+}
+
+impl PageRenderer {
+    /// Display goal scorers, penalties and other stats at half time and after the game
+    pub fn stats(&mut self, state: &State) {
+        let title = if state.snapshot.current_period == GamePeriod::HalfTime {
+            "HALF TIME STATS"
+        } else {
+            "FINAL STATS"
+        };
+        let (x_off, text) = fit_text(1920f32, title, 60, &self.assets.font, Justify::Center);
+        draw_text_both!(
+            text.as_str(),
+            x_off,
+            140f32,
+            TextParams {
+                font: Some(&self.assets.font),
+                font_size: 60,
+                ..Default::default()
+            }
+        );
+
+        // White on the left, as on the final scores
+        self.team_stats_panel(
+            110f32,
+            &state.white.team_name,
+            &TeamStats::new(&state.snapshot, UwhColor::White),
+        );
+        self.team_stats_panel(
+            1010f32,
+            &state.black.team_name,
+            &TeamStats::new(&state.snapshot, UwhColor::Black),
+        );
+    }
+
+    fn team_stats_panel(&self, x: f32, team_name: &str, stats: &TeamStats) {
+        draw_rectangle(
+            x,
+            PANEL_TOP,
+            PANEL_WIDTH,
+            PANEL_HEIGHT,
+            Color::from_rgba(0, 0, 0, 200),
+        );
+        draw_rectangle(
+            x + 1920f32,
+            PANEL_TOP,
+            PANEL_WIDTH,
+            PANEL_HEIGHT,
+            Color::from_rgba(200, 200, 200, 255),
+        );
+
+        let text = |text: &str, y: f32, font_size: u16, justify: Justify| {
+            let (x_off, text) = fit_text(
+                PANEL_WIDTH - 60f32,
+                text,
+                font_size,
+                &self.assets.font,
+                justify,
+            );
+            draw_text_both!(
+                text.as_str(),
+                x + 30f32 + x_off,
+                y,
+                TextParams {
+                    font: Some(&self.assets.font),
+                    font_size,
+                    ..Default::default()
+                }
+            );
+        };
+
+        text(team_name, PANEL_TOP + 65f32, 45, Justify::Center);
+        text(
+            &format!(
+                "GOALS {}   TIMEOUTS {}   FOULS {}   WARNINGS {}",
+                stats.score, stats.timeouts, stats.fouls, stats.warnings
+            ),
+            PANEL_TOP + 125f32,
+            28,
+            Justify::Center,
+        );
+
+        let mut y = PANEL_TOP + 195f32;
+        for (heading, lines) in [("SCORERS", &stats.goals), ("PENALTIES", &stats.penalties)] {
+            text(heading, y, 32, Justify::Left);
+            y += ROW_HEIGHT;
+            if lines.is_empty() {
+                text("NONE", y, 28, Justify::Left);
+                y += ROW_HEIGHT;
+            }
+            for line in lines {
+                text(line, y, 28, Justify::Left);
+                y += ROW_HEIGHT;
+            }
+            y += ROW_HEIGHT / 2f32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uwh_common::game_snapshot::{Infraction, InfractionSnapshot};
+
+    #[test]
+    fn test_team_stats() {
+        let penalty = |player_number, time, infraction| PenaltySnapshot {
+            player_number,
+            time,
+            infraction,
+        };
+        let snapshot = GameSnapshot {
+            b_score: 2,
+            b_timeouts_used: 1,
+            b_goals: vec![
+                GoalSnapshot {
+                    player_number: 4,
+                    period: GamePeriod::FirstHalf,
+                    secs_in_period: 512,
+                },
+                GoalSnapshot {
+                    player_number: 12,
+                    period: GamePeriod::SuddenDeath,
+                    secs_in_period: 5,
+                },
+            ],
+            b_game_penalties: vec![
+                penalty(9, PenaltyTime::Seconds(60), Infraction::FreeArm),
+                penalty(3, PenaltyTime::Seconds(30), Infraction::DelayOfGame),
+                penalty(9, PenaltyTime::TotalDismissal, Infraction::Obstruction),
+            ],
+            b_fouls: vec![InfractionSnapshot {
+                player_number: None,
+                infraction: Infraction::Unknown,
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            TeamStats::new(&snapshot, UwhColor::Black),
+            TeamStats {
+                score: 2,
+                timeouts: 1,
+                fouls: 1,
+                warnings: 0,
+                goals: vec![
+                    "#4    8:32  1ST HALF".to_string(),
+                    "#12   0:05  SUDDEN DEATH".to_string(),
+                ],
+                penalties: vec![
+                    "#3   DELAY OF GAME  30 SEC".to_string(),
+                    "#9   FREE ARM  1 MIN".to_string(),
+                    "#9   OBSTRUCTION  DISMISSED".to_string(),
+                ],
+            }
+        );
+
+        let white = TeamStats::new(&snapshot, UwhColor::White);
+        assert_eq!(white.score, 0);
+        assert!(white.goals.is_empty() && white.penalties.is_empty());
+    }
+
+    #[test]
+    fn test_limit_rows() {
+        let lines: Vec<String> = (0..MAX_ROWS).map(|i| i.to_string()).collect();
+        assert_eq!(limit_rows(lines.clone()), lines);
+
+        let lines: Vec<String> = (0..MAX_ROWS + 3).map(|i| i.to_string()).collect();
+        let limited = limit_rows(lines);
+        assert_eq!(limited.len(), MAX_ROWS);
+        assert_eq!(limited[MAX_ROWS - 2], (MAX_ROWS - 2).to_string());
+        assert_eq!(limited[MAX_ROWS - 1], "+4 MORE");
+    }
+}
@@ -0,0 +1,413 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+use uwh_common::game_snapshot::{GamePeriod, GameSnapshot};
+
+/// The pages the overlay can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+    FinalScores,
+    NextGame,
+    Roster,
+    PreGame,
+    InGame,
+    OvertimeAndSuddenDeath,
+    /// Goal scorers, penalties and other stats for the game so far
+    Stats,
+    /// Nothing is drawn
+    Blank,
+}
+
+impl Page {
+    const ALL: [Self; 8] = [
+        Self::FinalScores,
+        Self::NextGame,
+        Self::Roster,
+        Self::PreGame,
+        Self::InGame,
+        Self::OvertimeAndSuddenDeath,
+        Self::Stats,
+        Self::Blank,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::FinalScores => "final_scores",
+            Self::NextGame => "next_game",
+            Self::Roster => "roster",
+            Self::PreGame => "pre_game",
+            Self::InGame => "in_game",
+            Self::OvertimeAndSuddenDeath => "overtime",
+            Self::Stats => "stats",
+            Self::Blank => "blank",
+        }
+    }
+
+    /// Whether the penalty flags are drawn on top of this page
+    pub fn shows_flags(&self) -> bool {
+        matches!(self, Self::InGame | Self::OvertimeAndSuddenDeath)
+    }
+}
+
+impl Display for Page {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Page {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|page| page.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown page {s:?}"))
+    }
+}
+
+/// When each page is shown between games. All times are in seconds.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct PageTimeline {
+    /// How long the final scores stay up after a game ends. `None` keeps them up until the next
+    /// game is loaded, after which the scheduled pages for that game are shown
+    pub final_scores_duration: Option<u32>,
+    /// The final scores always come down once the break has this much time left
+    pub final_scores_end: u32,
+    /// How long the final scores are up before the stats for the game replace them. `None` never
+    /// shows the stats after the game
+    pub final_stats_after: Option<u32>,
+    /// The stats are shown at half time until it has this much time left. `None` never shows
+    /// them at half time
+    pub half_time_stats_end: Option<u32>,
+    /// Whether the next game page is shown before the rosters. If not, nothing is shown
+    pub show_next_game: bool,
+    /// Time left in the break when the rosters of the next game start
+    pub roster_start: u32,
+    /// Time left in the break when the pre game page starts
+    pub pre_game_start: u32,
+}
+
+impl Default for PageTimeline {
+    fn default() -> Self {
+        Self {
+            final_scores_duration: None,
+            final_scores_end: 5,
+            final_stats_after: Some(30),
+            half_time_stats_end: Some(15),
+            show_next_game: true,
+            roster_start: 181,
+            pre_game_start: 29,
+        }
+    }
+}
+
+/// Picks the page to show for each snapshot received from the refbox
+#[derive(Debug)]
+pub struct PageScheduler {
+    timeline: PageTimeline,
+    forced: Option<Page>,
+    /// Time left in the break when the final scores were first shown
+    final_scores_start: Option<u32>,
+    page: Page,
+    /// `secs_in_period` when `page` was first shown
+    page_start: u32,
+}
+
+impl PageScheduler {
+    pub fn new(timeline: PageTimeline) -> Self {
+        Self {
+            timeline,
+            forced: None,
+            final_scores_start: None,
+            page: Page::Blank,
+            page_start: 0,
+        }
+    }
+
+    /// Shows `page` regardless of the game state, or returns to the timeline if `None`
+    pub fn force(&mut self, page: Option<Page>) {
+        self.forced = page;
+    }
+
+    /// Updates the state with a new snapshot and returns the page to show
+    pub fn update(&mut self, snapshot: &GameSnapshot) -> Page {
+        let scheduled = self.scheduled_page(snapshot);
+        let page = self.forced.unwrap_or(scheduled);
+
+        if page != self.page {
+            self.page = page;
+            self.page_start = snapshot.secs_in_period;
+        }
+
+        page
+    }
+
+    /// Number of seconds the current page has been up, as counted by the game clock
+    pub fn secs_on_page(&self, snapshot: &GameSnapshot) -> u32 {
+        self.page_start.abs_diff(snapshot.secs_in_period)
+    }
+
+    fn scheduled_page(&mut self, snapshot: &GameSnapshot) -> Page {
+        let secs = snapshot.secs_in_period;
+        let timeline = &self.timeline;
+
+        match snapshot.current_period {
+            GamePeriod::BetweenGames if snapshot.is_old_game => {
+                let start = *self.final_scores_start.get_or_insert(secs);
+                let expired = timeline
+                    .final_scores_duration
+                    .is_some_and(|duration| start.saturating_sub(secs) >= duration);
+
+                if secs <= timeline.final_scores_end {
+                    Page::PreGame
+                } else if expired {
+                    Page::Blank
+                } else if timeline
+                    .final_stats_after
+                    .is_some_and(|after| start.saturating_sub(secs) >= after)
+                {
+                    Page::Stats
+                } else {
+                    Page::FinalScores
+                }
+            }
+            GamePeriod::BetweenGames => {
+                self.final_scores_start = None;
+
+                if secs <= timeline.pre_game_start {
+                    Page::PreGame
+                } else if secs <= timeline.roster_start {
+                    Page::Roster
+                } else if timeline.show_next_game {
+                    Page::NextGame
+                } else {
+                    Page::Blank
+                }
+            }
+            GamePeriod::HalfTime if timeline.half_time_stats_end.is_some_and(|end| secs > end) => {
+                self.final_scores_start = None;
+                Page::Stats
+            }
+            GamePeriod::FirstHalf | GamePeriod::SecondHalf | GamePeriod::HalfTime => {
+                self.final_scores_start = None;
+                Page::InGame
+            }
+            GamePeriod::OvertimeFirstHalf
+            | GamePeriod::OvertimeHalfTime
+            | GamePeriod::OvertimeSecondHalf
+            | GamePeriod::PreOvertime
+            | GamePeriod::PreSuddenDeath
+            | GamePeriod::SuddenDeath => {
+                self.final_scores_start = None;
+                Page::OvertimeAndSuddenDeath
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn between_games(secs_in_period: u32, is_old_game: bool) -> GameSnapshot {
+        GameSnapshot {
+            current_period: GamePeriod::BetweenGames,
+            secs_in_period,
+            is_old_game,
+            ..Default::default()
+        }
+    }
+
+    /// Counts a break down from `from` to `to`, returning the page shown at each second
+    fn run_break(
+        scheduler: &mut PageScheduler,
+        from: u32,
+        to: u32,
+        is_old_game: impl Fn(u32) -> bool,
+    ) -> Vec<(u32, Page)> {
+        (to..=from)
+            .rev()
+            .map(|secs| {
+                (
+                    secs,
+                    scheduler.update(&between_games(secs, is_old_game(secs))),
+                )
+            })
+            .collect()
+    }
+
+    /// The seconds at which the page changed, and the page changed to
+    fn changes(pages: &[(u32, Page)]) -> Vec<(u32, Page)> {
+        let mut changes: Vec<(u32, Page)> = vec![];
+        for &(secs, page) in pages {
+            if changes.last().map(|(_, last)| *last) != Some(page) {
+                changes.push((secs, page));
+            }
+        }
+        changes
+    }
+
+    #[test]
+    fn test_default_timeline() {
+        let mut scheduler = PageScheduler::new(PageTimeline::default());
+
+        // The next game is loaded half way through the break
+        let pages = run_break(&mut scheduler, 300, 0, |secs| secs > 200);
+        assert_eq!(
+            changes(&pages),
+            vec![
+                (300, Page::FinalScores),
+                (270, Page::Stats),
+                (200, Page::NextGame),
+                (181, Page::Roster),
+                (29, Page::PreGame),
+            ]
+        );
+
+        // The next game is loaded late
+        let pages = run_break(&mut scheduler, 300, 0, |secs| secs > 20);
+        assert_eq!(
+            changes(&pages),
+            vec![
+                (300, Page::FinalScores),
+                (270, Page::Stats),
+                (20, Page::PreGame)
+            ]
+        );
+
+        // The next game is never loaded
+        let pages = run_break(&mut scheduler, 300, 0, |_| true);
+        assert_eq!(
+            changes(&pages),
+            vec![
+                (300, Page::FinalScores),
+                (270, Page::Stats),
+                (5, Page::PreGame)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_configured_timeline() {
+        let mut scheduler = PageScheduler::new(PageTimeline {
+            final_scores_duration: Some(60),
+            final_scores_end: 5,
+            final_stats_after: None,
+            half_time_stats_end: None,
+            show_next_game: false,
+            roster_start: 120,
+            pre_game_start: 15,
+        });
+
+        let pages = run_break(&mut scheduler, 300, 0, |secs| secs > 200);
+        assert_eq!(
+            changes(&pages),
+            vec![
+                (300, Page::FinalScores),
+                (240, Page::Blank),
+                (120, Page::Roster),
+                (15, Page::PreGame),
+            ]
+        );
+
+        // The final scores time starts again after each game
+        scheduler.update(&GameSnapshot {
+            current_period: GamePeriod::SecondHalf,
+            ..Default::default()
+        });
+        let pages = run_break(&mut scheduler, 100, 0, |_| true);
+        assert_eq!(
+            changes(&pages),
+            vec![
+                (100, Page::FinalScores),
+                (40, Page::Blank),
+                (5, Page::PreGame)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stats_pages() {
+        let mut scheduler = PageScheduler::new(PageTimeline {
+            final_scores_duration: Some(60),
+            final_stats_after: Some(10),
+            half_time_stats_end: Some(15),
+            ..Default::default()
+        });
+
+        let pages = run_break(&mut scheduler, 300, 0, |_| true);
+        assert_eq!(
+            changes(&pages),
+            vec![
+                (300, Page::FinalScores),
+                (290, Page::Stats),
+                (240, Page::Blank),
+                (5, Page::PreGame)
+            ]
+        );
+
+        let half_time = |secs_in_period| GameSnapshot {
+            current_period: GamePeriod::HalfTime,
+            secs_in_period,
+            ..Default::default()
+        };
+        assert_eq!(scheduler.update(&half_time(180)), Page::Stats);
+        assert_eq!(scheduler.update(&half_time(16)), Page::Stats);
+        assert_eq!(scheduler.update(&half_time(15)), Page::InGame);
+    }
+
+    #[test]
+    fn test_game_pages() {
+        let mut scheduler = PageScheduler::new(PageTimeline::default());
+
+        for (period, page) in [
+            (GamePeriod::FirstHalf, Page::InGame),
+            (GamePeriod::HalfTime, Page::InGame),
+            (GamePeriod::SecondHalf, Page::InGame),
+            (GamePeriod::PreOvertime, Page::OvertimeAndSuddenDeath),
+            (GamePeriod::OvertimeSecondHalf, Page::OvertimeAndSuddenDeath),
+            (GamePeriod::SuddenDeath, Page::OvertimeAndSuddenDeath),
+        ] {
+            let snapshot = GameSnapshot {
+                current_period: period,
+                ..Default::default()
+            };
+            assert_eq!(scheduler.update(&snapshot), page);
+        }
+    }
+
+    #[test]
+    fn test_force() {
+        let mut scheduler = PageScheduler::new(PageTimeline::default());
+
+        assert_eq!(scheduler.update(&between_games(400, false)), Page::NextGame);
+        scheduler.force(Some(Page::Blank));
+        assert_eq!(scheduler.update(&between_games(399, false)), Page::Blank);
+        assert_eq!(scheduler.update(&between_games(100, false)), Page::Blank);
+        scheduler.force(None);
+        assert_eq!(scheduler.update(&between_games(99, false)), Page::Roster);
+    }
+
+    #[test]
+    fn test_secs_on_page() {
+        let mut scheduler = PageScheduler::new(PageTimeline::default());
+
+        scheduler.update(&between_games(190, false));
+        let snapshot = between_games(181, false);
+        assert_eq!(scheduler.update(&snapshot), Page::Roster);
+        assert_eq!(scheduler.secs_on_page(&snapshot), 0);
+        let snapshot = between_games(169, false);
+        scheduler.update(&snapshot);
+        assert_eq!(scheduler.secs_on_page(&snapshot), 12);
+    }
+
+    #[test]
+    fn test_page_names() {
+        for page in Page::ALL {
+            assert_eq!(page.to_string().parse(), Ok(page));
+        }
+        assert_eq!("Roster".parse(), Ok(Page::Roster));
+        assert!("scores".parse::<Page>().is_err());
+    }
+}
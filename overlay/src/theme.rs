@@ -0,0 +1,478 @@
+//! Theme packs, which replace the built in graphics, text colours and font with an event's own
+//! branding. A pack is a directory containing `theme.json`:
+//!
+//! ```json
+//! {
+//!     "resolution": 2160,
+//!     "font": "MyFont.ttf",
+//!     "colors": { "text": "#FFFFFF", "dark_text": "#1A2B3C" },
+//!     "textures": {
+//!         "team_bar": "team_bar.png",
+//!         "final_score": { "color": "color/final_score.png", "alpha": "alpha/final_score.png" }
+//!     }
+//! }
+//! ```
+//!
+//! Every field is optional, and anything not given falls back to the built in assets. Textures
+//! given as a single image have their alpha image generated from it. Paths are relative to the
+//! pack directory.
+//!
+//! The overlay renders at the pack's `resolution` (720, 1080 or 2160 lines), which is the screen
+//! height its artwork was exported for. The 1080p layout is scaled to fit, while the pack's
+//! artwork and all text are drawn at full resolution. Built in artwork that the pack doesn't
+//! replace is scaled from 1080p.
+
+use crate::load_images::{Texture, Textures};
+use image::RgbaImage;
+use log::{error, info, warn};
+use macroquad::prelude::*;
+use serde::{Deserialize, Deserializer};
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use thiserror::Error;
+
+const MANIFEST_FILE: &str = "theme.json";
+/// The screen height the overlay's layout is drawn for
+const LAYOUT_RESOLUTION: u32 = 1080;
+/// The width of the layout, with the color and alpha frames side by side
+const LAYOUT_WIDTH: u32 = 3840;
+const RESOLUTIONS: [u32; 3] = [720, 1080, 2160];
+
+static COLORS: OnceLock<ThemeColors> = OnceLock::new();
+static RESOLUTION: OnceLock<u32> = OnceLock::new();
+
+#[derive(Debug, Error)]
+pub enum ThemeError {
+    #[error("Could not read {0:?}: {1}")]
+    Io(PathBuf, io::Error),
+    #[error("Could not parse {0:?}: {1}")]
+    Json(PathBuf, serde_json::Error),
+    #[error("Unsupported theme resolution {0}, expected one of {RESOLUTIONS:?}")]
+    Resolution(u32),
+    #[error("Unknown texture slot {0:?}")]
+    UnknownSlot(String),
+    #[error("Could not decode {0:?}: {1}")]
+    Image(PathBuf, image::ImageError),
+    #[error("The color and alpha images of slot {0:?} are different sizes")]
+    SizeMismatch(String),
+    #[error("Could not generate the alpha image for {0:?}: {1}")]
+    Alpha(PathBuf, String),
+    #[error("Could not load font {0:?}: {1}")]
+    Font(PathBuf, String),
+}
+
+/// Colours of text drawn on top of the theme's graphics
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ThemeColors {
+    /// Used wherever the built in theme draws white text
+    #[serde(deserialize_with = "deserialize_color")]
+    pub text: Color,
+    /// Used wherever the built in theme draws black text
+    #[serde(deserialize_with = "deserialize_color")]
+    pub dark_text: Color,
+}
+
+impl Default for ThemeColors {
+    fn default() -> Self {
+        Self {
+            text: WHITE,
+            dark_text: BLACK,
+        }
+    }
+}
+
+impl ThemeColors {
+    /// Swaps the built in white and black for the theme's colours, keeping the alpha of `color`
+    pub fn recolor(&self, color: Color) -> Color {
+        let replacement = if (color.r, color.g, color.b) == (WHITE.r, WHITE.g, WHITE.b) {
+            self.text
+        } else if (color.r, color.g, color.b) == (BLACK.r, BLACK.g, BLACK.b) {
+            self.dark_text
+        } else {
+            return color;
+        };
+        Color {
+            a: color.a * replacement.a,
+            ..replacement
+        }
+    }
+}
+
+/// The colours of the loaded theme
+pub fn colors() -> &'static ThemeColors {
+    COLORS.get_or_init(ThemeColors::default)
+}
+
+/// Reads the resolution of the theme pack in `dir`, which the overlay renders at. This has to be
+/// known before the window is opened, so before `load_textures`, which reports any problems with
+/// the pack
+pub fn load_resolution(dir: Option<&Path>) -> u32 {
+    let resolution = dir
+        .and_then(|dir| ThemeManifest::load(dir).ok())
+        .map_or(LAYOUT_RESOLUTION, |manifest| manifest.resolution);
+    if RESOLUTION.set(resolution).is_err() {
+        warn!("Theme resolution was already set, ignoring the one in {dir:?}");
+    }
+    resolution
+}
+
+/// The screen height the overlay renders at, see `load_resolution`
+fn resolution() -> u32 {
+    *RESOLUTION.get_or_init(|| LAYOUT_RESOLUTION)
+}
+
+/// Pixels on the screen to each pixel of the 1080p layout
+pub fn render_scale() -> f32 {
+    resolution() as f32 / LAYOUT_RESOLUTION as f32
+}
+
+/// The size of the window for the resolution the overlay renders at
+pub fn window_size() -> (i32, i32) {
+    let width = LAYOUT_WIDTH * resolution() / LAYOUT_RESOLUTION;
+    (width as i32, resolution() as i32)
+}
+
+/// A camera that maps the 1080p layout onto the whole window
+pub fn layout_camera() -> Camera2D {
+    let size = vec2(LAYOUT_WIDTH as f32, LAYOUT_RESOLUTION as f32);
+    Camera2D {
+        target: size / 2.0,
+        zoom: 2.0 / size,
+        ..Default::default()
+    }
+}
+
+/// Parses `#RRGGBB` or `#RRGGBBAA`
+fn parse_hex_color(s: &str) -> Option<Color> {
+    let hex = s.strip_prefix('#')?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .map_or(Some(255), |c| u8::from_str_radix(c, 16).ok())
+    };
+    Some(Color::from_rgba(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        channel(6)?,
+    ))
+}
+
+fn deserialize_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_hex_color(&s).ok_or_else(|| {
+        serde::de::Error::custom(format!(
+            "invalid color {s:?}, expected \"#RRGGBB\" or \"#RRGGBBAA\""
+        ))
+    })
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+enum TextureSource {
+    /// The alpha image is generated from the color image
+    Single(PathBuf),
+    Pair {
+        color: PathBuf,
+        alpha: PathBuf,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct ThemeManifest {
+    /// Screen height the images were exported for, which the overlay renders at
+    resolution: u32,
+    font: Option<PathBuf>,
+    colors: ThemeColors,
+    textures: BTreeMap<String, TextureSource>,
+}
+
+impl Default for ThemeManifest {
+    fn default() -> Self {
+        Self {
+            resolution: LAYOUT_RESOLUTION,
+            font: None,
+            colors: ThemeColors::default(),
+            textures: BTreeMap::new(),
+        }
+    }
+}
+
+impl ThemeManifest {
+    fn load(dir: &Path) -> Result<Self, ThemeError> {
+        let path = dir.join(MANIFEST_FILE);
+        let bytes = std::fs::read(&path).map_err(|e| ThemeError::Io(path.clone(), e))?;
+        let manifest: Self =
+            serde_json::from_slice(&bytes).map_err(|e| ThemeError::Json(path, e))?;
+
+        if !RESOLUTIONS.contains(&manifest.resolution) {
+            return Err(ThemeError::Resolution(manifest.resolution));
+        }
+        if let Some(slot) = manifest
+            .textures
+            .keys()
+            .find(|slot| !Textures::SLOTS.contains(&slot.as_str()))
+        {
+            return Err(ThemeError::UnknownSlot(slot.clone()));
+        }
+
+        Ok(manifest)
+    }
+}
+
+fn load_image(path: &Path) -> Result<RgbaImage, ThemeError> {
+    Ok(image::open(path)
+        .map_err(|e| ThemeError::Image(path.to_path_buf(), e))?
+        .into_rgba8())
+}
+
+/// The color and alpha images for `slot`
+fn load_slot_images(
+    dir: &Path,
+    slot: &str,
+    source: &TextureSource,
+) -> Result<(RgbaImage, RgbaImage), ThemeError> {
+    match source {
+        TextureSource::Single(path) => {
+            let path = dir.join(path);
+            let color = load_image(&path)?;
+            let alpha = alphagen::make_white_alpha_raw_rgba8(
+                color.width() as u16,
+                color.height() as u16,
+                color.as_raw().clone(),
+            )
+            .map_err(|e| ThemeError::Alpha(path, e.to_string()))?;
+            let alpha = RgbaImage::from_raw(color.width(), color.height(), alpha)
+                .ok_or_else(|| ThemeError::SizeMismatch(slot.to_string()))?;
+            Ok((color, alpha))
+        }
+        TextureSource::Pair { color, alpha } => {
+            let color = load_image(&dir.join(color))?;
+            let alpha = load_image(&dir.join(alpha))?;
+            if color.dimensions() != alpha.dimensions() {
+                return Err(ThemeError::SizeMismatch(slot.to_string()));
+            }
+            Ok((color, alpha))
+        }
+    }
+}
+
+fn texture_from_rgba(image: &RgbaImage) -> Texture2D {
+    Texture2D::from_rgba8(image.width() as u16, image.height() as u16, image.as_raw())
+}
+
+fn load_font(path: &Path) -> Result<Font, ThemeError> {
+    let bytes = std::fs::read(path).map_err(|e| ThemeError::Io(path.to_path_buf(), e))?;
+    load_ttf_font_from_bytes(&bytes)
+        .map_err(|e| ThemeError::Font(path.to_path_buf(), e.to_string()))
+}
+
+/// Loads the built in assets, replacing any that are provided by the theme pack in `dir`. Any
+/// part of the theme that can't be loaded is logged and left as built in.
+pub fn load_textures(dir: Option<&Path>) -> Textures {
+    let mut textures = Textures::default();
+    let Some(dir) = dir else {
+        return textures;
+    };
+
+    let manifest = match ThemeManifest::load(dir) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            error!("Could not load the theme in {dir:?}, using the built in theme: {e}");
+            return textures;
+        }
+    };
+
+    if manifest.resolution != resolution() {
+        // Only if the manifest changed since `load_resolution`, the art would be the wrong size
+        error!(
+            "The theme in {dir:?} is for {}p, but the overlay is rendering at {}p, using the \
+             built in theme",
+            manifest.resolution,
+            resolution()
+        );
+        return textures;
+    }
+    let scale = manifest.resolution as f32 / LAYOUT_RESOLUTION as f32;
+
+    for (slot, source) in manifest.textures.iter() {
+        match load_slot_images(dir, slot, source) {
+            Ok((color, alpha)) => {
+                // `ThemeManifest::load` has already checked that the slot exists
+                if let Some(texture) = textures.slot_mut(slot) {
+                    *texture = Texture {
+                        color: texture_from_rgba(&color),
+                        alpha: texture_from_rgba(&alpha),
+                        scale,
+                    };
+                }
+            }
+            Err(e) => warn!("Using the built in texture for {slot}: {e}"),
+        }
+    }
+
+    if let Some(font) = manifest.font.as_ref() {
+        match load_font(&dir.join(font)) {
+            Ok(font) => textures.font = font,
+            Err(e) => warn!("Using the built in font: {e}"),
+        }
+    }
+
+    if COLORS.set(manifest.colors).is_err() {
+        warn!("Theme colors were already set, ignoring those in {dir:?}");
+    }
+
+    info!(
+        "Loaded theme from {dir:?} with {} textures, rendering at {}p",
+        manifest.textures.len(),
+        manifest.resolution
+    );
+    textures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(
+            parse_hex_color("#FF8000"),
+            Some(Color::from_rgba(255, 128, 0, 255))
+        );
+        assert_eq!(
+            parse_hex_color("#1a2b3c80"),
+            Some(Color::from_rgba(0x1a, 0x2b, 0x3c, 0x80))
+        );
+        assert_eq!(parse_hex_color("FF8000"), None);
+        assert_eq!(parse_hex_color("#FF80"), None);
+        assert_eq!(parse_hex_color("#GG8000"), None);
+        assert_eq!(parse_hex_color("#FF80€"), None);
+    }
+
+    #[test]
+    fn test_recolor() {
+        let colors = ThemeColors {
+            text: Color::from_rgba(255, 255, 0, 255),
+            dark_text: Color::from_rgba(0, 0, 255, 128),
+        };
+        assert_eq!(colors.recolor(WHITE), colors.text);
+        assert_eq!(
+            colors.recolor(Color { a: 0.5, ..WHITE }),
+            Color {
+                a: 0.5,
+                ..colors.text
+            }
+        );
+        assert_eq!(
+            colors.recolor(Color { a: 0.5, ..BLACK }),
+            Color {
+                a: 0.5 * colors.dark_text.a,
+                ..colors.dark_text
+            }
+        );
+        assert_eq!(colors.recolor(RED), RED);
+        assert_eq!(ThemeColors::default().recolor(BLACK), BLACK);
+    }
+
+    #[test]
+    fn test_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |json: &str| std::fs::write(dir.path().join(MANIFEST_FILE), json).unwrap();
+
+        write(
+            r##"{
+                "resolution": 720,
+                "font": "font.ttf",
+                "colors": { "dark_text": "#102030" },
+                "textures": {
+                    "team_bar": "bar.png",
+                    "black_team_name": { "color": "c.png", "alpha": "a.png" }
+                }
+            }"##,
+        );
+        let manifest = ThemeManifest::load(dir.path()).unwrap();
+        assert_eq!(manifest.resolution, 720);
+        assert_eq!(manifest.font, Some(PathBuf::from("font.ttf")));
+        assert_eq!(manifest.colors.text, WHITE);
+        assert_eq!(
+            manifest.colors.dark_text,
+            Color::from_rgba(0x10, 0x20, 0x30, 255)
+        );
+        assert_eq!(
+            manifest.textures["team_bar"],
+            TextureSource::Single("bar.png".into())
+        );
+        assert_eq!(
+            manifest.textures["black_team_name"],
+            TextureSource::Pair {
+                color: "c.png".into(),
+                alpha: "a.png".into()
+            }
+        );
+
+        write("{}");
+        assert_eq!(
+            ThemeManifest::load(dir.path()).unwrap(),
+            ThemeManifest::default()
+        );
+
+        write(r#"{"resolution": 1440}"#);
+        assert!(matches!(
+            ThemeManifest::load(dir.path()),
+            Err(ThemeError::Resolution(1440))
+        ));
+
+        write(r#"{"textures": {"scoreboard": "a.png"}}"#);
+        assert!(matches!(
+            ThemeManifest::load(dir.path()),
+            Err(ThemeError::UnknownSlot(slot)) if slot == "scoreboard"
+        ));
+
+        write(r#"{"colors": {"text": "white"}}"#);
+        assert!(matches!(
+            ThemeManifest::load(dir.path()),
+            Err(ThemeError::Json(..))
+        ));
+    }
+
+    #[test]
+    fn test_slot_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut image = RgbaImage::new(300, 60);
+        image.put_pixel(0, 0, image::Rgba([255, 255, 255, 255]));
+        image.save(dir.path().join("bar.png")).unwrap();
+        RgbaImage::new(300, 30)
+            .save(dir.path().join("short.png"))
+            .unwrap();
+
+        let single = TextureSource::Single("bar.png".into());
+        // Art is kept at the resolution it was exported for
+        let (color, alpha) = load_slot_images(dir.path(), "team_bar", &single).unwrap();
+        assert_eq!(color.dimensions(), (300, 60));
+        assert_eq!(alpha.dimensions(), (300, 60));
+
+        let pair = TextureSource::Pair {
+            color: "bar.png".into(),
+            alpha: "short.png".into(),
+        };
+        assert!(matches!(
+            load_slot_images(dir.path(), "team_bar", &pair),
+            Err(ThemeError::SizeMismatch(slot)) if slot == "team_bar"
+        ));
+
+        let missing = TextureSource::Single("missing.png".into());
+        assert!(matches!(
+            load_slot_images(dir.path(), "team_bar", &missing),
+            Err(ThemeError::Image(..))
+        ));
+    }
+}
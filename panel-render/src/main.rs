@@ -0,0 +1,271 @@
+use clap::{Parser, ValueEnum};
+use matrix_drawing::{
+    draw_animation_frame, draw_panels_frame, transmitted_data::TransmittedData, FrameBuffer,
+    PanelLayout,
+};
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufWriter, Read},
+    path::{Path, PathBuf},
+};
+use uwh_common::game_snapshot::GameSnapshot;
+
+#[derive(ValueEnum, Default, Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Picked from the input's extension: `.json`, `.hex` or `.txt`, otherwise binary
+    #[default]
+    Auto,
+    /// An encoded `TransmittedData`, as sent to the panels
+    Binary,
+    /// An encoded `TransmittedData` written as hex digits, whitespace is ignored
+    Hex,
+    /// A `TransmittedData`, or a `GameSnapshot` as sent on the refbox's JSON port
+    Json,
+}
+
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    /// File holding the state to draw, `-` reads stdin
+    input: PathBuf,
+
+    /// Image to write, PNG or PPM depending on the extension
+    output: PathBuf,
+
+    #[clap(long, short, value_enum, default_value = "auto")]
+    format: Format,
+
+    #[clap(long, short, default_value = "256x64", value_parser = parse_panel_layout)]
+    /// Layout of the panels, one of 256x64, 128x32, 192x64 or 384x96
+    layout: &'static PanelLayout,
+
+    #[clap(long, short, default_value = "1")]
+    /// Size of each panel pixel in the image
+    scale: u32,
+
+    #[clap(long)]
+    /// Put the white team on the right, for a `GameSnapshot` input from a refbox that doesn't send
+    /// the side
+    white_on_right: bool,
+
+    #[clap(long)]
+    /// Draw the flash, for a `GameSnapshot` input
+    flash: bool,
+
+    #[clap(long, default_value = "0")]
+    /// How long the message has been shown, in milliseconds, to draw a scrolling message part way
+    /// through
+    message_millis: u32,
+
+    #[clap(long)]
+    /// Draw the input's panel animation this many milliseconds after it started, instead of the
+    /// game
+    animation_millis: Option<u32>,
+}
+
+fn parse_panel_layout(name: &str) -> Result<&'static PanelLayout, String> {
+    PanelLayout::by_name(name).ok_or_else(|| {
+        let names: Vec<_> = PanelLayout::ALL.iter().map(|layout| layout.name).collect();
+        format!("unknown layout, expected one of {}", names.join(", "))
+    })
+}
+
+fn decode(bytes: &[u8]) -> Result<TransmittedData, Box<dyn Error>> {
+    let bytes: &[u8; TransmittedData::ENCODED_LEN] = bytes.try_into().map_err(|_| {
+        format!(
+            "expected {} bytes, found {}",
+            TransmittedData::ENCODED_LEN,
+            bytes.len()
+        )
+    })?;
+    Ok(TransmittedData::decode(bytes)?)
+}
+
+fn parse_hex(text: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let digits: Vec<u8> = text
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err("odd number of hex digits".into());
+    }
+    digits
+        .chunks(2)
+        .map(|pair| Ok(u8::from_str_radix(std::str::from_utf8(pair)?, 16)?))
+        .collect()
+}
+
+/// Reads the state to draw from `input`. `white_on_right` and `flash` are used for snapshots,
+/// which don't carry the flash, and only carry the side if the refbox is new enough
+fn parse_input(
+    input: &[u8],
+    format: Format,
+    white_on_right: bool,
+    flash: bool,
+) -> Result<TransmittedData, Box<dyn Error>> {
+    match format {
+        Format::Auto => unreachable!("the format is resolved before parsing"),
+        Format::Binary => decode(input),
+        Format::Hex => decode(&parse_hex(std::str::from_utf8(input)?)?),
+        Format::Json => {
+            if let Ok(data) = serde_json::from_slice::<TransmittedData>(input) {
+                return Ok(data);
+            }
+            let snapshot: GameSnapshot = serde_json::from_slice(input)?;
+            Ok(TransmittedData {
+                white_on_right: white_on_right || snapshot.white_on_right,
+                flash,
+                snapshot: snapshot.into(),
+                theme: Default::default(),
+                brightness: Default::default(),
+            })
+        }
+    }
+}
+
+fn resolve_format(format: Format, input: &Path) -> Format {
+    if format != Format::Auto {
+        return format;
+    }
+    match input.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("json") => Format::Json,
+        Some(ext) if ext.eq_ignore_ascii_case("hex") || ext.eq_ignore_ascii_case("txt") => {
+            Format::Hex
+        }
+        _ => Format::Binary,
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    let mut input = Vec::new();
+    if args.input == Path::new("-") {
+        std::io::stdin().read_to_end(&mut input)?;
+    } else {
+        File::open(&args.input)?.read_to_end(&mut input)?;
+    }
+    let format = resolve_format(args.format, &args.input);
+    let data = parse_input(&input, format, args.white_on_right, args.flash)?;
+
+    let mut frame = FrameBuffer::new(args.layout.size);
+    let colors = data.colors();
+    match (data.snapshot.panel_animation, args.animation_millis) {
+        (Some(animation), Some(millis)) if !data.flash => draw_animation_frame(
+            &mut frame,
+            &data.snapshot,
+            animation,
+            args.layout,
+            &colors,
+            millis,
+        )?,
+        _ => draw_panels_frame(
+            &mut frame,
+            data.snapshot,
+            data.white_on_right,
+            data.flash,
+            args.layout,
+            &colors,
+            args.message_millis,
+        )?,
+    }
+    let frame = frame.scaled(args.scale.max(1));
+
+    let writer = BufWriter::new(File::create(&args.output)?);
+    match args.output.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("ppm") => frame.write_ppm(writer)?,
+        Some(ext) if ext.eq_ignore_ascii_case("png") => frame.write_png(writer)?,
+        _ => return Err("the output must be a .png or .ppm file".into()),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use uwh_common::game_snapshot::{GamePeriod, TimeoutSnapshot};
+
+    fn snapshot() -> GameSnapshot {
+        GameSnapshot {
+            current_period: GamePeriod::SecondHalf,
+            secs_in_period: 300,
+            timeout: TimeoutSnapshot::White(20),
+            b_score: 3,
+            w_score: 1,
+            ..Default::default()
+        }
+    }
+
+    fn data() -> TransmittedData {
+        TransmittedData {
+            white_on_right: true,
+            flash: false,
+            snapshot: snapshot().into(),
+            theme: Default::default(),
+            brightness: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_format() {
+        assert_eq!(
+            resolve_format(Format::Auto, Path::new("a.JSON")),
+            Format::Json
+        );
+        assert_eq!(
+            resolve_format(Format::Auto, Path::new("a.hex")),
+            Format::Hex
+        );
+        assert_eq!(
+            resolve_format(Format::Auto, Path::new("a.bin")),
+            Format::Binary
+        );
+        assert_eq!(resolve_format(Format::Auto, Path::new("-")), Format::Binary);
+        assert_eq!(
+            resolve_format(Format::Hex, Path::new("a.json")),
+            Format::Hex
+        );
+    }
+
+    #[test]
+    fn test_parse_input() {
+        let data = data();
+        let encoded = data.encode().unwrap();
+        assert_eq!(
+            parse_input(&encoded, Format::Binary, false, false).unwrap(),
+            data
+        );
+        assert!(parse_input(&encoded[1..], Format::Binary, false, false).is_err());
+
+        let hex: String = encoded
+            .chunks(4)
+            .map(|chunk| chunk.iter().map(|b| format!("{b:02x}")).collect::<String>() + " ")
+            .collect();
+        assert_eq!(
+            parse_input(hex.as_bytes(), Format::Hex, false, false).unwrap(),
+            data
+        );
+        assert!(parse_input(b"0g", Format::Hex, false, false).is_err());
+        assert!(parse_input(b"012", Format::Hex, false, false).is_err());
+
+        let json = serde_json::to_vec(&data).unwrap();
+        assert_eq!(
+            parse_input(&json, Format::Json, false, false).unwrap(),
+            data
+        );
+
+        let json = serde_json::to_vec(&snapshot()).unwrap();
+        assert_eq!(parse_input(&json, Format::Json, true, false).unwrap(), data);
+        let json = serde_json::to_vec(&GameSnapshot {
+            white_on_right: true,
+            ..snapshot()
+        })
+        .unwrap();
+        assert_eq!(
+            parse_input(&json, Format::Json, false, false).unwrap(),
+            data
+        );
+    }
+}
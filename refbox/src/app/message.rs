@@ -1,4 +1,7 @@
-use crate::tournament_manager::penalty::PenaltyKind;
+use crate::{
+    sound_controller::LogicalChannel,
+    tournament_manager::{goal::AnnulmentReason, penalty::PenaltyKind},
+};
 use tokio::time::Duration;
 use uwh_common::{
     game_snapshot::{Color as GameColor, GameSnapshot, Infraction},
@@ -19,6 +22,7 @@ pub enum Message {
         canceled: bool,
     },
     StartPlayNow,
+    SwitchSides,
     EditScores,
     AddNewScore(GameColor),
     ChangeScore {
@@ -28,6 +32,12 @@ pub enum Message {
     ScoreEditComplete {
         canceled: bool,
     },
+    GoalList,
+    SelectGoal(usize),
+    ChangeAnnulmentReason(AnnulmentReason),
+    GoalAnnulmentComplete {
+        canceled: bool,
+    },
     PenaltyOverview,
     WarningOverview,
     FoulOverview,
@@ -86,6 +96,12 @@ pub enum Message {
     RequestRemoteId,
     GotRemoteId(u32),
     DeleteRemote(usize),
+    AddCue,
+    DeleteCue(usize),
+    AddPanelText,
+    DeletePanelText(usize),
+    PreviewBuzzerSound,
+    PlayTestTone(LogicalChannel),
     ConfirmationSelected(ConfirmationOption),
     TeamTimeout(GameColor, bool),
     RefTimeout(bool),
@@ -125,9 +141,14 @@ impl Message {
             | Self::EditTime
             | Self::TimeEditComplete { .. }
             | Self::StartPlayNow
+            | Self::SwitchSides
             | Self::EditScores
             | Self::AddNewScore(_)
             | Self::ScoreEditComplete { .. }
+            | Self::GoalList
+            | Self::SelectGoal(_)
+            | Self::ChangeAnnulmentReason(_)
+            | Self::GoalAnnulmentComplete { .. }
             | Self::PenaltyOverview
             | Self::WarningOverview
             | Self::FoulOverview
@@ -156,6 +177,12 @@ impl Message {
             | Self::RequestRemoteId
             | Self::GotRemoteId(_)
             | Self::DeleteRemote(_)
+            | Self::AddCue
+            | Self::DeleteCue(_)
+            | Self::AddPanelText
+            | Self::DeletePanelText(_)
+            | Self::PreviewBuzzerSound
+            | Self::PlayTestTone(_)
             | Self::ConfirmationSelected(_)
             | Self::TeamTimeout(_, _)
             | Self::RefTimeout(_)
@@ -179,6 +206,9 @@ pub enum ConfigPage {
     App,
     Credentials,
     Remotes(usize, bool),
+    SoundOutputs,
+    Cues(usize),
+    PanelMessages(usize),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -191,6 +221,7 @@ pub enum LengthParameter {
     OvertimeHalf,
     OvertimeHalfTime,
     PreSuddenDeath,
+    StoppedClockFinal,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -204,7 +235,9 @@ pub enum ListableParameter {
 pub enum BoolGameParameter {
     OvertimeAllowed,
     SuddenDeathAllowed,
+    StopClockOnGoal,
     WhiteOnRight,
+    AutoSwitchSides,
     UsingUwhScores,
     SoundEnabled,
     RefAlertEnabled,
@@ -224,7 +257,15 @@ pub enum CyclingParameter {
     AlertVolume,
     AboveWaterVol,
     UnderWaterVol,
+    OutputDevice(LogicalChannel),
+    OutputChannel(LogicalChannel),
+    CuePeriod(usize),
+    CueSound(usize),
+    CueChannels(usize),
     Mode,
+    TenthsThreshold,
+    PanelTheme,
+    PanelBrightness,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -232,6 +273,9 @@ pub enum TextParameter {
     UwhscoresEmail,
     UwhscoresPassword,
     UwhportalToken,
+    BlackTeamCode,
+    WhiteTeamCode,
+    PanelMessage(usize),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -240,6 +284,7 @@ pub enum ScrollOption {
     White,
     Equal,
     GameParameter,
+    Goals,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -253,6 +298,7 @@ pub enum KeypadPage {
     ),
     GameNumber,
     TeamTimeouts(Duration, bool),
+    CueTime(usize),
     FoulAdd {
         origin: Option<(Option<GameColor>, usize)>,
         color: Option<GameColor>,
@@ -276,7 +322,7 @@ impl KeypadPage {
             | Self::FoulAdd { .. }
             | Self::WarningAdd { .. } => 99,
             Self::GameNumber => 9999,
-            Self::TeamTimeouts(_, _) => 999,
+            Self::TeamTimeouts(_, _) | Self::CueTime(_) => 999,
         }
     }
 
@@ -289,6 +335,7 @@ impl KeypadPage {
             Self::GameNumber => "GAME\nNUMBER:",
             Self::TeamTimeouts(_, true) => "NUM T/Os\nPER HALF:",
             Self::TeamTimeouts(_, false) => "NUM T/Os\nPER GAME:",
+            Self::CueTime(_) => "SECONDS\nLEFT:",
         }
     }
 }
@@ -2,10 +2,10 @@ use self::infraction::InfractionDetails;
 
 use super::APP_NAME;
 use crate::{
-    config::{Config, Mode},
+    config::{next_tenths_threshold, Config, Mode},
     penalty_editor::*,
     sound_controller::*,
-    tournament_manager::{penalty::*, *},
+    tournament_manager::{goal::*, penalty::*, *},
 };
 use iced::{executor, widget::column, Application, Command, Subscription};
 use iced_futures::{
@@ -19,6 +19,7 @@ use std::{
     borrow::Cow,
     cmp::min,
     collections::BTreeMap,
+    path::PathBuf,
     pin::Pin,
     process::Child,
     sync::{Arc, Mutex},
@@ -32,7 +33,7 @@ use tokio_serial::SerialPortBuilder;
 use uwh_common::{
     config::Game as GameConfig,
     drawing_support::*,
-    game_snapshot::{Color, GamePeriod, GameSnapshot, Infraction, TimeoutSnapshot},
+    game_snapshot::{Color, GamePeriod, GameSnapshot, Infraction, TeamCode},
     uwhportal::UwhPortalClient,
     uwhscores::*,
 };
@@ -43,6 +44,9 @@ use view_builders::*;
 mod message;
 use message::*;
 
+mod panel_messages;
+use panel_messages::*;
+
 pub mod style;
 use style::{PADDING, SPACING};
 
@@ -78,8 +82,11 @@ pub struct RefBoxApp {
     games: Option<BTreeMap<u32, GameInfo>>,
     current_tid: Option<u32>,
     current_pool: Option<String>,
+    /// Team codes typed by the ref, by game number, used instead of the ones made from the
+    /// uwhscores team names for that game only
+    team_codes: BTreeMap<u32, BlackWhiteBundle<String>>,
     sound: SoundController,
-    sim_child: Option<Child>,
+    sim_children: Vec<Child>,
     fullscreen: bool,
     list_all_tournaments: bool,
     touchscreen: bool,
@@ -91,11 +98,13 @@ pub struct RefBoxAppFlags {
     pub serial_ports: Vec<SerialPortBuilder>,
     pub binary_port: u16,
     pub json_port: u16,
-    pub sim_child: Option<Child>,
+    pub sim_children: Vec<Child>,
     pub require_https: bool,
     pub fullscreen: bool,
     pub list_all_tournaments: bool,
     pub touchscreen: bool,
+    pub remote_sources: Vec<RemoteInputSource>,
+    pub sound_pack: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone)]
@@ -106,6 +115,8 @@ enum AppState {
         scores: BlackWhiteBundle<u8>,
         is_confirmation: bool,
     },
+    GoalList(usize),
+    GoalAnnulment(usize, AnnulmentReason),
     PenaltyOverview(BlackWhiteBundle<usize>),
     WarningOverview(BlackWhiteBundle<usize>),
     FoulOverview(OptColorBundle<usize>),
@@ -139,74 +150,51 @@ impl RefBoxApp {
         if let Some(tid) = self.current_tid {
             new_snapshot.tournament_id = tid;
         }
+        let game_number = if new_snapshot.current_period == GamePeriod::BetweenGames
+            && !new_snapshot.is_old_game
+        {
+            new_snapshot.next_game_number
+        } else {
+            new_snapshot.game_number
+        };
+        let team_codes = self.team_codes(game_number);
+        new_snapshot.b_team_code = team_codes.black;
+        new_snapshot.w_team_code = team_codes.white;
+        new_snapshot.message = scheduled_message(&self.config.panel_messages, &new_snapshot);
         self.maybe_play_sound(&new_snapshot);
         self.update_sender
-            .send_snapshot(new_snapshot.clone(), self.config.hardware.white_on_right)
+            .send_snapshot(new_snapshot.clone(), new_snapshot.white_on_right)
             .unwrap();
         self.snapshot = new_snapshot;
     }
 
-    fn maybe_play_sound(&self, new_snapshot: &GameSnapshot) {
-        let (play_whistle, play_buzzer) = match new_snapshot.timeout {
-            TimeoutSnapshot::Black(time) | TimeoutSnapshot::White(time) => {
-                match self.snapshot.timeout {
-                    TimeoutSnapshot::Black(old_time) | TimeoutSnapshot::White(old_time) => (
-                        time != old_time && time == 15,
-                        time != old_time && time == 0,
-                    ),
-                    _ => (false, false),
-                }
-            }
-            TimeoutSnapshot::Ref(_) | TimeoutSnapshot::PenaltyShot(_) => (false, false),
-            TimeoutSnapshot::None => {
-                let prereqs = new_snapshot.current_period != GamePeriod::SuddenDeath
-                    && new_snapshot.secs_in_period != self.snapshot.secs_in_period;
-
-                let is_whistle_period = match new_snapshot.current_period {
-                    GamePeriod::BetweenGames
-                    | GamePeriod::HalfTime
-                    | GamePeriod::PreOvertime
-                    | GamePeriod::OvertimeHalfTime
-                    | GamePeriod::PreSuddenDeath => true,
-                    GamePeriod::FirstHalf
-                    | GamePeriod::SecondHalf
-                    | GamePeriod::OvertimeFirstHalf
-                    | GamePeriod::OvertimeSecondHalf
-                    | GamePeriod::SuddenDeath => false,
-                };
-
-                let (end_starts_play, end_stops_play) = match new_snapshot.current_period {
-                    GamePeriod::FirstHalf
-                    | GamePeriod::SecondHalf
-                    | GamePeriod::OvertimeFirstHalf
-                    | GamePeriod::OvertimeSecondHalf => (false, true),
-                    GamePeriod::BetweenGames
-                    | GamePeriod::HalfTime
-                    | GamePeriod::PreOvertime
-                    | GamePeriod::OvertimeHalfTime
-                    | GamePeriod::PreSuddenDeath => (true, false),
-                    GamePeriod::SuddenDeath => (false, false),
-                };
-
-                let is_buzz_period = end_starts_play && self.config.sound.auto_sound_start_play
-                    || end_stops_play && self.config.sound.auto_sound_stop_play;
-
-                (
-                    prereqs && is_whistle_period && new_snapshot.secs_in_period == 30,
-                    prereqs && is_buzz_period && new_snapshot.secs_in_period == 0,
-                )
-            }
+    /// The codes of the teams playing game `game_number`. Codes typed by the ref for that game are
+    /// used first, then ones made from the team names on uwhscores
+    fn team_codes(&self, game_number: u32) -> BlackWhiteBundle<Option<TeamCode>> {
+        let game = if self.using_uwhscores {
+            self.games
+                .as_ref()
+                .and_then(|games| games.get(&game_number))
+        } else {
+            None
         };
-
-        if play_whistle {
-            info!("Triggering whistle");
-            self.sound.trigger_whistle();
-        } else if play_buzzer {
-            info!("Triggering buzzer");
-            self.sound.trigger_buzzer();
+        let typed = self.team_codes.get(&game_number);
+        let code = |typed: Option<&String>, name: Option<&String>| {
+            typed
+                .and_then(|typed| TeamCode::from_name(typed))
+                .or_else(|| TeamCode::from_name(name?))
+        };
+        BlackWhiteBundle {
+            black: code(typed.map(|t| &t.black), game.map(|game| &game.black)),
+            white: code(typed.map(|t| &t.white), game.map(|game| &game.white)),
         }
     }
 
+    fn maybe_play_sound(&self, new_snapshot: &GameSnapshot) {
+        self.sound
+            .play_cues(&self.config.sound, &self.snapshot, new_snapshot);
+    }
+
     fn do_get_request<T, F>(
         &self,
         url: String,
@@ -664,6 +652,7 @@ impl RefBoxApp {
 
         let EditableSettings {
             white_on_right,
+            auto_switch_sides,
             using_uwhscores,
             current_tid,
             current_pool,
@@ -672,24 +661,55 @@ impl RefBoxApp {
             mode,
             collect_scorer_cap_num,
             hide_time,
+            tenths_threshold_secs,
+            panel_theme,
+            panel_brightness,
+            black_team_code,
+            white_team_code,
+            panel_messages,
             config: _config,
-            game_number: _game_number,
+            game_number,
             track_fouls_and_warnings,
             uwhscores_email: _,
             uwhscores_password: _,
             uwhportal_token: _,
         } = edited_settings;
 
-        self.config.hardware.white_on_right = white_on_right;
+        if self.config.hardware.white_on_right != white_on_right {
+            self.config.hardware.white_on_right = white_on_right;
+            self.tm.lock().unwrap().set_white_on_right(white_on_right);
+        }
+        self.config.hardware.auto_switch_sides = auto_switch_sides;
+        self.tm
+            .lock()
+            .unwrap()
+            .set_auto_switch_sides(auto_switch_sides);
         self.using_uwhscores = using_uwhscores;
         self.current_tid = current_tid;
         self.current_pool = current_pool;
         self.games = games;
+        if black_team_code.is_empty() && white_team_code.is_empty() {
+            self.team_codes.remove(&game_number);
+        } else {
+            self.team_codes.insert(
+                game_number,
+                BlackWhiteBundle {
+                    black: black_team_code,
+                    white: white_team_code,
+                },
+            );
+        }
         self.config.sound = sound;
         self.sound.update_settings(self.config.sound.clone());
         self.config.mode = mode;
         self.config.collect_scorer_cap_num = collect_scorer_cap_num;
         self.config.track_fouls_and_warnings = track_fouls_and_warnings;
+        self.config.panel_messages = panel_messages;
+        self.config.tenths_threshold_secs = tenths_threshold_secs;
+        self.tm
+            .lock()
+            .unwrap()
+            .set_tenths_threshold(Duration::from_secs(tenths_threshold_secs.into()));
 
         if self.config.hide_time != hide_time {
             self.config.hide_time = hide_time;
@@ -697,13 +717,27 @@ impl RefBoxApp {
                 .set_hide_time(self.config.hide_time)
                 .unwrap();
         }
+
+        if self.config.panel_theme != panel_theme
+            || self.config.panel_brightness != panel_brightness
+        {
+            self.config.panel_theme = panel_theme;
+            self.config.panel_brightness = panel_brightness;
+            self.update_sender
+                .set_panel_theme(panel_theme, panel_brightness)
+                .unwrap();
+        }
     }
 }
 
 impl Drop for RefBoxApp {
     fn drop(&mut self) {
-        if let Some(mut child) = self.sim_child.take() {
-            info!("Waiting for child");
+        for mut child in self.sim_children.drain(..) {
+            // The simulator keeps trying to reconnect, so it won't stop on its own
+            info!("Stopping child");
+            if let Err(e) = child.kill() {
+                warn!("Failed to stop child: {e:?}");
+            }
             child.wait().unwrap();
         }
     }
@@ -721,11 +755,13 @@ impl Application for RefBoxApp {
             serial_ports,
             binary_port,
             json_port,
-            sim_child,
+            sim_children,
             require_https,
             fullscreen,
             list_all_tournaments,
             touchscreen,
+            remote_sources,
+            sound_pack,
         } = flags;
 
         let (msg_tx, rx) = mpsc::unbounded_channel();
@@ -736,6 +772,9 @@ impl Application for RefBoxApp {
 
         let mut tm = TournamentManager::new(config.game.clone());
         tm.set_timezone(config.uwhscores.timezone);
+        tm.set_white_on_right(config.hardware.white_on_right);
+        tm.set_auto_switch_sides(config.hardware.auto_switch_sides);
+        tm.set_tenths_threshold(Duration::from_secs(config.tenths_threshold_secs.into()));
         tm.start_clock(Instant::now());
 
         let client = match Client::builder()
@@ -774,9 +813,16 @@ impl Application for RefBoxApp {
 
         let update_sender =
             UpdateSender::new(serial_ports, binary_port, json_port, config.hide_time);
+        update_sender
+            .set_panel_theme(config.panel_theme, config.panel_brightness)
+            .unwrap();
 
-        let sound =
-            SoundController::new(config.sound.clone(), update_sender.get_trigger_flash_fn());
+        let sound = SoundController::new(
+            config.sound.clone(),
+            &remote_sources,
+            sound_pack.as_deref(),
+            update_sender.get_trigger_flash_fn(),
+        );
 
         let snapshot = Default::default();
 
@@ -808,8 +854,9 @@ impl Application for RefBoxApp {
                 games: None,
                 current_tid: None,
                 current_pool: None,
+                team_codes: Default::default(),
                 sound,
-                sim_child,
+                sim_children,
                 fullscreen,
                 list_all_tournaments,
                 touchscreen,
@@ -946,6 +993,14 @@ impl Application for RefBoxApp {
                 std::mem::drop(tm);
                 self.apply_snapshot(snapshot);
             }
+            Message::SwitchSides => {
+                let mut tm = self.tm.lock().unwrap();
+                let now = Instant::now();
+                tm.switch_sides(now);
+                let snapshot = tm.generate_snapshot(now).unwrap();
+                std::mem::drop(tm);
+                self.apply_snapshot(snapshot);
+            }
             Message::EditScores => {
                 let tm = self.tm.lock().unwrap();
                 self.app_state = AppState::ScoreEdit {
@@ -1028,6 +1083,40 @@ impl Application for RefBoxApp {
 
                 trace!("AppState changed to {:?}", self.app_state);
             }
+            Message::GoalList => {
+                self.app_state = AppState::GoalList(0);
+                trace!("AppState changed to {:?}", self.app_state);
+            }
+            Message::SelectGoal(index) => {
+                self.app_state = AppState::GoalAnnulment(index, Default::default());
+                trace!("AppState changed to {:?}", self.app_state);
+            }
+            Message::ChangeAnnulmentReason(new_reason) => {
+                if let AppState::GoalAnnulment(_, ref mut reason) = self.app_state {
+                    *reason = new_reason;
+                } else {
+                    unreachable!()
+                }
+                trace!("AppState changed to {:?}", self.app_state);
+            }
+            Message::GoalAnnulmentComplete { canceled } => {
+                if let AppState::GoalAnnulment(index, reason) = self.app_state {
+                    if !canceled {
+                        let mut tm = self.tm.lock().unwrap();
+                        let now = Instant::now();
+                        match tm.annul_goal(index, reason, now) {
+                            Ok(()) => {
+                                let snapshot = tm.generate_snapshot(now).unwrap();
+                                std::mem::drop(tm);
+                                self.apply_snapshot(snapshot);
+                            }
+                            Err(e) => error!("Failed to annul goal: {e}"),
+                        }
+                    }
+                }
+                self.app_state = AppState::MainPage;
+                trace!("AppState changed to {:?}", self.app_state);
+            }
             Message::PenaltyOverview => {
                 if let Err(e) = self.pen_edit.start_session() {
                     warn!("Failed to start penalty edit session: {e}");
@@ -1066,7 +1155,9 @@ impl Application for RefBoxApp {
                         let idx = match which {
                             ScrollOption::Black => &mut indices.black,
                             ScrollOption::White => &mut indices.white,
-                            ScrollOption::GameParameter | ScrollOption::Equal => unreachable!(),
+                            ScrollOption::GameParameter
+                            | ScrollOption::Equal
+                            | ScrollOption::Goals => unreachable!(),
                         };
                         if up {
                             *idx = idx.saturating_sub(1);
@@ -1079,7 +1170,7 @@ impl Application for RefBoxApp {
                             ScrollOption::Black => &mut indices.black,
                             ScrollOption::Equal => &mut indices.equal,
                             ScrollOption::White => &mut indices.white,
-                            ScrollOption::GameParameter => unreachable!(),
+                            ScrollOption::GameParameter | ScrollOption::Goals => unreachable!(),
                         };
                         if up {
                             *idx = idx.saturating_sub(1);
@@ -1095,6 +1186,23 @@ impl Application for RefBoxApp {
                             *idx = idx.saturating_add(1);
                         }
                     }
+                    AppState::EditGameConfig(ConfigPage::Cues(ref mut idx))
+                    | AppState::EditGameConfig(ConfigPage::PanelMessages(ref mut idx)) => {
+                        debug_assert_eq!(which, ScrollOption::GameParameter);
+                        if up {
+                            *idx = idx.saturating_sub(1);
+                        } else {
+                            *idx = idx.saturating_add(1);
+                        }
+                    }
+                    AppState::GoalList(ref mut idx) => {
+                        debug_assert_eq!(which, ScrollOption::Goals);
+                        if up {
+                            *idx = idx.saturating_sub(1);
+                        } else {
+                            *idx = idx.saturating_add(1);
+                        }
+                    }
                     _ => {
                         unreachable!();
                     }
@@ -1386,6 +1494,11 @@ impl Application for RefBoxApp {
                         .map(|n| n.into())
                         .unwrap_or(0),
                     KeypadPage::TeamTimeouts(_, _) => self.config.game.num_team_timeouts_allowed,
+                    KeypadPage::CueTime(index) => self.edited_settings.as_ref().unwrap().sound.cues
+                        [index]
+                        .secs_remaining
+                        .try_into()
+                        .unwrap_or(u16::MAX),
                     KeypadPage::GameNumber => self
                         .edited_settings
                         .as_ref()
@@ -1477,14 +1590,17 @@ impl Application for RefBoxApp {
                 trace!("AppState changed to {:?}", self.app_state);
             }
             Message::EditGameConfig => {
+                let game_number = if self.snapshot.current_period == GamePeriod::BetweenGames {
+                    self.snapshot.next_game_number
+                } else {
+                    self.snapshot.game_number
+                };
+                let typed_codes = self.team_codes.get(&game_number).cloned();
                 let edited_settings = EditableSettings {
                     config: self.tm.lock().unwrap().config().clone(),
-                    game_number: if self.snapshot.current_period == GamePeriod::BetweenGames {
-                        self.snapshot.next_game_number
-                    } else {
-                        self.snapshot.game_number
-                    },
+                    game_number,
                     white_on_right: self.config.hardware.white_on_right,
+                    auto_switch_sides: self.config.hardware.auto_switch_sides,
                     using_uwhscores: self.using_uwhscores,
                     uwhscores_email: self.config.uwhscores.email.clone(),
                     uwhscores_password: self.config.uwhscores.password.clone(),
@@ -1495,8 +1611,17 @@ impl Application for RefBoxApp {
                     sound: self.config.sound.clone(),
                     mode: self.config.mode,
                     hide_time: self.config.hide_time,
+                    tenths_threshold_secs: self.config.tenths_threshold_secs,
+                    panel_theme: self.config.panel_theme,
+                    panel_brightness: self.config.panel_brightness,
                     collect_scorer_cap_num: self.config.collect_scorer_cap_num,
                     track_fouls_and_warnings: self.config.track_fouls_and_warnings,
+                    black_team_code: typed_codes
+                        .as_ref()
+                        .map(|codes| codes.black.clone())
+                        .unwrap_or_default(),
+                    white_team_code: typed_codes.map(|codes| codes.white).unwrap_or_default(),
+                    panel_messages: self.config.panel_messages.clone(),
                 };
 
                 self.edited_settings = Some(edited_settings);
@@ -1653,6 +1778,9 @@ impl Application for RefBoxApp {
                         LengthParameter::PreSuddenDeath => {
                             self.config.game.pre_sudden_death_duration
                         }
+                        LengthParameter::StoppedClockFinal => {
+                            self.config.game.stopped_clock_final_duration
+                        }
                     },
                 );
                 trace!("AppState changed to {:?}", self.app_state);
@@ -1727,6 +1855,9 @@ impl Application for RefBoxApp {
                             LengthParameter::PreSuddenDeath => {
                                 edited_settings.config.pre_sudden_death_duration = dur
                             }
+                            LengthParameter::StoppedClockFinal => {
+                                edited_settings.config.stopped_clock_final_duration = dur
+                            }
                         },
                         AppState::KeypadPage(KeypadPage::GameNumber, num) => {
                             edited_settings.game_number = num.into();
@@ -1736,16 +1867,23 @@ impl Application for RefBoxApp {
                             edited_settings.config.num_team_timeouts_allowed = num;
                             edited_settings.config.timeouts_counted_per_half = per_half;
                         }
+                        AppState::KeypadPage(KeypadPage::CueTime(index), secs) => {
+                            edited_settings.sound.cues[index].secs_remaining = secs.into();
+                        }
                         _ => unreachable!(),
                     }
                 }
 
                 let next_page = match self.app_state {
+                    AppState::ParameterEditor(LengthParameter::StoppedClockFinal, _) => {
+                        ConfigPage::App
+                    }
                     AppState::ParameterEditor(_, _) => ConfigPage::Tournament,
                     AppState::KeypadPage(KeypadPage::GameNumber, _) => ConfigPage::Main,
                     AppState::KeypadPage(KeypadPage::TeamTimeouts(_, _), _) => {
                         ConfigPage::Tournament
                     }
+                    AppState::KeypadPage(KeypadPage::CueTime(index), _) => ConfigPage::Cues(index),
                     AppState::ParameterList(param, _) => match param {
                         ListableParameter::Game => ConfigPage::Main,
                         ListableParameter::Tournament | ListableParameter::Pool => {
@@ -1829,7 +1967,13 @@ impl Application for RefBoxApp {
                         BoolGameParameter::SuddenDeathAllowed => {
                             edited_settings.config.sudden_death_allowed ^= true
                         }
+                        BoolGameParameter::StopClockOnGoal => {
+                            edited_settings.config.stop_clock_on_goal ^= true
+                        }
                         BoolGameParameter::WhiteOnRight => edited_settings.white_on_right ^= true,
+                        BoolGameParameter::AutoSwitchSides => {
+                            edited_settings.auto_switch_sides ^= true
+                        }
                         BoolGameParameter::UsingUwhScores => {
                             edited_settings.using_uwhscores ^= true
                         }
@@ -1862,14 +2006,39 @@ impl Application for RefBoxApp {
             Message::CycleParameter(param) => {
                 let settings = &mut self.edited_settings.as_mut().unwrap();
                 match param {
-                    CyclingParameter::BuzzerSound => settings.sound.buzzer_sound.cycle(),
+                    CyclingParameter::BuzzerSound => {
+                        settings.sound.buzzer_sound =
+                            settings.sound.buzzer_sound.next(self.sound.custom_sounds())
+                    }
                     CyclingParameter::RemoteBuzzerSound(idx) => {
-                        settings.sound.remotes[idx].sound.cycle()
+                        let sound = &mut settings.sound.remotes[idx].sound;
+                        *sound =
+                            BuzzerSound::next_optional(sound.as_ref(), self.sound.custom_sounds())
                     }
                     CyclingParameter::AlertVolume => settings.sound.whistle_vol.cycle(),
                     CyclingParameter::AboveWaterVol => settings.sound.above_water_vol.cycle(),
                     CyclingParameter::UnderWaterVol => settings.sound.under_water_vol.cycle(),
+                    CyclingParameter::OutputDevice(channel) => settings
+                        .sound
+                        .channel_map
+                        .get_mut(channel)
+                        .next_device(self.sound.output_devices()),
+                    CyclingParameter::OutputChannel(channel) => {
+                        settings.sound.channel_map.get_mut(channel).next_channel()
+                    }
+                    CyclingParameter::CuePeriod(idx) => settings.sound.cues[idx].period.cycle(),
+                    CyclingParameter::CueSound(idx) => {
+                        let sound = &mut settings.sound.cues[idx].sound;
+                        *sound = sound.next(self.sound.custom_sounds())
+                    }
+                    CyclingParameter::CueChannels(idx) => settings.sound.cues[idx].channels.cycle(),
                     CyclingParameter::Mode => settings.mode.cycle(),
+                    CyclingParameter::PanelTheme => settings.panel_theme.cycle(),
+                    CyclingParameter::PanelBrightness => settings.panel_brightness.cycle(),
+                    CyclingParameter::TenthsThreshold => {
+                        settings.tenths_threshold_secs =
+                            next_tenths_threshold(settings.tenths_threshold_secs)
+                    }
                 }
             }
             Message::TextParameterChanged(param, val) => {
@@ -1878,6 +2047,9 @@ impl Application for RefBoxApp {
                     TextParameter::UwhscoresEmail => settings.uwhscores_email = val,
                     TextParameter::UwhscoresPassword => settings.uwhscores_password = val,
                     TextParameter::UwhportalToken => settings.uwhportal_token = val,
+                    TextParameter::BlackTeamCode => settings.black_team_code = val,
+                    TextParameter::WhiteTeamCode => settings.white_team_code = val,
+                    TextParameter::PanelMessage(idx) => settings.panel_messages[idx] = val,
                 }
             }
             Message::ApplyAuthChanges => {
@@ -1930,6 +2102,58 @@ impl Application for RefBoxApp {
                     unreachable!()
                 }
             }
+            Message::AddCue => {
+                if let AppState::EditGameConfig(ConfigPage::Cues(ref mut index)) = self.app_state {
+                    let cues = &mut self.edited_settings.as_mut().unwrap().sound.cues;
+                    cues.push(SoundCue::default());
+                    // Scroll to the new cue
+                    *index = cues.len() - 1;
+                } else {
+                    unreachable!()
+                }
+                trace!("AppState changed to {:?}", self.app_state);
+            }
+            Message::AddPanelText => {
+                if let AppState::EditGameConfig(ConfigPage::PanelMessages(ref mut index)) =
+                    self.app_state
+                {
+                    let messages = &mut self.edited_settings.as_mut().unwrap().panel_messages;
+                    messages.push(String::new());
+                    // Scroll to the new message
+                    *index = messages.len() - 1;
+                } else {
+                    unreachable!()
+                }
+                trace!("AppState changed to {:?}", self.app_state);
+            }
+            Message::DeletePanelText(index) => {
+                if let Some(ref mut settings) = self.edited_settings {
+                    settings.panel_messages.remove(index);
+                } else {
+                    unreachable!()
+                }
+            }
+            Message::DeleteCue(index) => {
+                if let Some(ref mut settings) = self.edited_settings {
+                    settings.sound.cues.remove(index);
+                } else {
+                    unreachable!()
+                }
+            }
+            Message::PreviewBuzzerSound => {
+                if let Some(ref settings) = self.edited_settings {
+                    self.sound.preview_buzzer(settings.sound.clone());
+                } else {
+                    unreachable!()
+                }
+            }
+            Message::PlayTestTone(channel) => {
+                if let Some(ref settings) = self.edited_settings {
+                    self.sound.play_test_tone(channel, settings.sound.clone());
+                } else {
+                    unreachable!()
+                }
+            }
             Message::ConfirmationSelected(selection) => {
                 let new_config = if let AppState::ConfirmationPage(
                     ConfirmationKind::GameConfigChanged(ref config),
@@ -2240,6 +2464,20 @@ impl Application for RefBoxApp {
                 self.config.mode,
                 clock_running,
             ),
+            AppState::GoalList(index) => build_goal_list_page(
+                &self.snapshot,
+                self.tm.lock().unwrap().get_goals(),
+                index,
+                self.config.mode,
+                clock_running,
+            ),
+            AppState::GoalAnnulment(index, reason) => build_goal_annulment_page(
+                &self.snapshot,
+                self.tm.lock().unwrap().get_goals().get(index).cloned(),
+                reason,
+                self.config.mode,
+                clock_running,
+            ),
             AppState::PenaltyOverview(indices) => build_penalty_overview_page(
                 &self.snapshot,
                 self.pen_edit.get_printable_lists(Instant::now()).unwrap(),
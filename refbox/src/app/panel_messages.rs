@@ -0,0 +1,95 @@
+use uwh_common::game_snapshot::{GamePeriod, GameSnapshot, PanelMessage, TimeoutSnapshot};
+
+/// How long each message is shown before the next one
+const MESSAGE_SECS: u32 = 15;
+
+/// The panels go back to showing "NEXT GAME IN" for the end of the break, so that the teams know
+/// when to get ready
+const NO_MESSAGE_SECS: u32 = 30;
+
+/// The message the panels should show for `snapshot`, taking turns through `messages` in order
+/// between games. Messages that have nothing the panels can draw are skipped
+pub(super) fn scheduled_message(
+    messages: &[String],
+    snapshot: &GameSnapshot,
+) -> Option<PanelMessage> {
+    if snapshot.current_period != GamePeriod::BetweenGames
+        || snapshot.timeout != TimeoutSnapshot::None
+        || snapshot.secs_in_period <= NO_MESSAGE_SECS
+    {
+        return None;
+    }
+
+    let messages: Vec<_> = messages
+        .iter()
+        .filter_map(|text| PanelMessage::from_text(text))
+        .collect();
+    if messages.is_empty() {
+        return None;
+    }
+
+    // The clock counts down between games, so the slots are counted back from the end of the
+    // break to show the messages in order
+    let slot = (snapshot.secs_in_period - NO_MESSAGE_SECS - 1) / MESSAGE_SECS;
+    let index = messages.len() - 1 - slot as usize % messages.len();
+    Some(messages[index])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn between_games(secs_in_period: u32) -> GameSnapshot {
+        GameSnapshot {
+            current_period: GamePeriod::BetweenGames,
+            secs_in_period,
+            ..Default::default()
+        }
+    }
+
+    fn scheduled(messages: &[&str], snapshot: &GameSnapshot) -> Option<String> {
+        let messages: Vec<_> = messages.iter().map(|text| text.to_string()).collect();
+        scheduled_message(&messages, snapshot).map(|message| message.to_string())
+    }
+
+    #[test]
+    fn test_scheduled_message_order() {
+        let messages = ["first", "second", "\u{2603}", "third"];
+        let shown: Vec<_> = [120, 106, 105, 91, 90, 76, 75, 61, 60, 46, 45, 31]
+            .into_iter()
+            .map(|secs| scheduled(&messages, &between_games(secs)).unwrap())
+            .collect();
+        assert_eq!(
+            shown,
+            [
+                "FIRST", "FIRST", "SECOND", "SECOND", "THIRD", "THIRD", "FIRST", "FIRST", "SECOND",
+                "SECOND", "THIRD", "THIRD"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scheduled_message_hidden() {
+        let messages = ["final at 2pm"];
+        assert_eq!(
+            scheduled(&messages, &between_games(31)),
+            Some("FINAL AT 2PM".to_string())
+        );
+        assert_eq!(scheduled(&messages, &between_games(30)), None);
+        assert_eq!(scheduled(&messages, &between_games(0)), None);
+        assert_eq!(scheduled(&[], &between_games(300)), None);
+        assert_eq!(scheduled(&["\u{2603}", ""], &between_games(300)), None);
+
+        let playing = GameSnapshot {
+            current_period: GamePeriod::FirstHalf,
+            ..between_games(300)
+        };
+        assert_eq!(scheduled(&messages, &playing), None);
+
+        let timeout = GameSnapshot {
+            timeout: TimeoutSnapshot::Ref(10),
+            ..between_games(300)
+        };
+        assert_eq!(scheduled(&messages, &timeout), None);
+    }
+}
@@ -1,6 +1,6 @@
 use futures_lite::future::FutureExt;
 use log::*;
-use matrix_drawing::transmitted_data::TransmittedData;
+use matrix_drawing::{transmitted_data::TransmittedData, PanelBrightness, PanelTheme};
 use std::{
     collections::HashMap,
     fmt::Debug,
@@ -100,6 +100,24 @@ impl UpdateSender {
                 _ => unreachable!(),
             })
     }
+
+    pub fn set_panel_theme(
+        &self,
+        theme: PanelTheme,
+        brightness: PanelBrightness,
+    ) -> Result<(), TrySendError<(PanelTheme, PanelBrightness)>> {
+        self.tx
+            .try_send(ServerMessage::SetPanelTheme(theme, brightness))
+            .map_err(|e| match e {
+                TrySendError::Full(ServerMessage::SetPanelTheme(theme, brightness)) => {
+                    TrySendError::Full((theme, brightness))
+                }
+                TrySendError::Closed(ServerMessage::SetPanelTheme(theme, brightness)) => {
+                    TrySendError::Closed((theme, brightness))
+                }
+                _ => unreachable!(),
+            })
+    }
 }
 
 impl Drop for UpdateSender {
@@ -145,7 +163,7 @@ async fn worker_loop<T: AsyncWrite + Debug + Unpin + Send>(
 
 #[derive(Debug)]
 enum SerialWorkerMessage {
-    NewSnapshot(GameSnapshotNoHeap, bool),
+    NewSnapshot(GameSnapshotNoHeap, bool, PanelTheme, PanelBrightness),
     TriggerFlash,
 }
 
@@ -154,8 +172,10 @@ async fn serial_worker_loop(
     mut write: SerialStream,
 ) -> Result<(), WorkerError> {
     let msg = rx.recv().await.ok_or(WorkerError::ChannelClosed)?;
-    let (snapshot, white_on_right) = match msg {
-        SerialWorkerMessage::NewSnapshot(snapshot, white_on_right) => (snapshot, white_on_right),
+    let (snapshot, white_on_right, theme, brightness) = match msg {
+        SerialWorkerMessage::NewSnapshot(snapshot, white_on_right, theme, brightness) => {
+            (snapshot, white_on_right, theme, brightness)
+        }
         SerialWorkerMessage::TriggerFlash => {
             return Err(WorkerError::IllegalMessage);
         }
@@ -165,6 +185,8 @@ async fn serial_worker_loop(
         snapshot,
         flash: false,
         white_on_right,
+        theme,
+        brightness,
     };
     let mut bytes = data.encode()?;
 
@@ -192,9 +214,11 @@ async fn serial_worker_loop(
             }
             recv = rx.recv() => {
                 match recv {
-                    Some(SerialWorkerMessage::NewSnapshot(snapshot, white_on_right)) => {
+                    Some(SerialWorkerMessage::NewSnapshot(snapshot, white_on_right, theme, brightness)) => {
                         data.snapshot = snapshot;
                         data.white_on_right = white_on_right;
+                        data.theme = theme;
+                        data.brightness = brightness;
                         bytes = data.encode()?;
                     }
                     Some(SerialWorkerMessage::TriggerFlash) => {
@@ -270,6 +294,8 @@ impl WorkerHandle {
         json: &[u8],
         snapshot: &GameSnapshotNoHeap,
         white_on_right: bool,
+        theme: PanelTheme,
+        brightness: PanelBrightness,
     ) -> Result<(), TrySendError<String>> {
         match self.tx {
             WorkerTx::Binary(ref tx) => tx.try_send(Vec::from(binary)).map_err(error_formatter),
@@ -278,6 +304,8 @@ impl WorkerHandle {
                 .try_send(SerialWorkerMessage::NewSnapshot(
                     snapshot.clone(),
                     white_on_right,
+                    theme,
+                    brightness,
                 ))
                 .map_err(error_formatter),
         }
@@ -298,6 +326,7 @@ pub enum ServerMessage {
     TriggerFlash,
     Stop,
     SetHideTime(bool),
+    SetPanelTheme(PanelTheme, PanelBrightness),
 }
 
 #[derive(Debug)]
@@ -313,6 +342,8 @@ struct Server {
     binary: Vec<u8>,
     json: Vec<u8>,
     hide_time: bool,
+    theme: PanelTheme,
+    brightness: PanelBrightness,
 }
 
 impl Server {
@@ -333,6 +364,8 @@ impl Server {
             binary: Vec::new(),
             json: Vec::new(),
             hide_time,
+            theme: Default::default(),
+            brightness: Default::default(),
         };
 
         for stream in initial {
@@ -425,6 +458,8 @@ impl Server {
                     white_on_right: self.white_on_right,
                     flash: self.flash,
                     snapshot: self.snapshot.clone(),
+                    theme: self.theme,
+                    brightness: self.brightness,
                 }
                 .encode()
                 .unwrap(),
@@ -450,6 +485,8 @@ impl Server {
                 &self.json,
                 &self.snapshot,
                 self.white_on_right,
+                self.theme,
+                self.brightness,
             ) {
                 if matches!(e, TrySendError::Closed(_)) {
                     info!("Worker channel closed");
@@ -508,6 +545,10 @@ impl Server {
                         Some(ServerMessage::SetHideTime(hide_time)) => {
                             self.hide_time = hide_time
                         }
+                        Some(ServerMessage::SetPanelTheme(theme, brightness)) => {
+                            self.theme = theme;
+                            self.brightness = brightness;
+                        }
                         None => {
                             break;
                         }
@@ -625,7 +666,8 @@ mod test {
     use std::io::ErrorKind;
     use tokio::io::AsyncReadExt;
     use uwh_common::game_snapshot::{
-        GamePeriod, Infraction, InfractionSnapshot, PenaltySnapshot, PenaltyTime, TimeoutSnapshot,
+        GamePeriod, GoalSnapshot, Infraction, InfractionSnapshot, PenaltySnapshot, PenaltyTime,
+        TeamCode, TimeoutSnapshot,
     };
 
     const BINARY_PORT: u16 = 12345;
@@ -781,6 +823,27 @@ mod test {
             tournament_id: 1,
             recent_goal: None,
             next_period_len_secs: Some(180),
+            white_on_right,
+            clock_stopped_by_rule: false,
+            b_goals: vec![GoalSnapshot {
+                player_number: 7,
+                period: GamePeriod::FirstHalf,
+                secs_in_period: 512,
+            }],
+            w_goals: vec![],
+            b_game_penalties: vec![],
+            w_game_penalties: vec![PenaltySnapshot {
+                time: PenaltyTime::Seconds(300),
+                player_number: 12,
+                infraction: Infraction::FalseStart,
+            }],
+            b_timeouts_used: 1,
+            w_timeouts_used: 0,
+            b_team_code: TeamCode::from_name("NZL"),
+            w_team_code: TeamCode::from_name("AUS"),
+            message: None,
+            tenths_in_period: None,
+            panel_animation: None,
         };
 
         let json_expected = serde_json::to_string(&snapshot).unwrap().into_bytes();
@@ -790,11 +853,16 @@ mod test {
                 white_on_right,
                 flash,
                 snapshot: snapshot.clone().into(),
+                theme: PanelTheme::ColorBlind,
+                brightness: PanelBrightness::Night,
             }
             .encode()
             .unwrap(),
         );
 
+        update_sender
+            .set_panel_theme(PanelTheme::ColorBlind, PanelBrightness::Night)
+            .unwrap();
         update_sender
             .send_snapshot(snapshot, white_on_right)
             .unwrap();
@@ -14,10 +14,14 @@ use iced::{
     widget::{button, column, container, horizontal_space, row, text, vertical_space, TextInput},
     Alignment, Length,
 };
+use matrix_drawing::{PanelBrightness, PanelTheme};
 use std::collections::BTreeMap;
 use tokio::time::Duration;
 use uwh_common::{
-    config::Game as GameConfig, game_snapshot::GameSnapshot, uwhportal::TokenValidity, uwhscores::*,
+    config::Game as GameConfig,
+    game_snapshot::{GameSnapshot, TeamCode},
+    uwhportal::TokenValidity,
+    uwhscores::*,
 };
 
 const NO_SELECTION_TXT: &str = "None Selected";
@@ -28,6 +32,7 @@ pub(in super::super) struct EditableSettings {
     pub config: GameConfig,
     pub game_number: u32,
     pub white_on_right: bool,
+    pub auto_switch_sides: bool,
     pub using_uwhscores: bool,
     pub uwhscores_email: String,
     pub uwhscores_password: String,
@@ -38,8 +43,14 @@ pub(in super::super) struct EditableSettings {
     pub sound: SoundSettings,
     pub mode: Mode,
     pub hide_time: bool,
+    pub tenths_threshold_secs: u32,
+    pub panel_theme: PanelTheme,
+    pub panel_brightness: PanelBrightness,
     pub collect_scorer_cap_num: bool,
     pub track_fouls_and_warnings: bool,
+    pub black_team_code: String,
+    pub white_team_code: String,
+    pub panel_messages: Vec<String>,
 }
 
 pub(in super::super) trait Cyclable
@@ -53,39 +64,52 @@ where
     }
 }
 
-impl Cyclable for BuzzerSound {
+impl Cyclable for Volume {
     fn next(&self) -> Self {
         match self {
-            Self::Buzz => Self::Whoop,
-            Self::Whoop => Self::Crazy,
-            Self::Crazy => Self::DeDeDu,
-            Self::DeDeDu => Self::TwoTone,
-            Self::TwoTone => Self::Buzz,
+            Self::Off => Self::Low,
+            Self::Low => Self::Medium,
+            Self::Medium => Self::High,
+            Self::High => Self::Max,
+            Self::Max => Self::Off,
         }
     }
 }
 
-impl Cyclable for Option<BuzzerSound> {
+impl Cyclable for CuePeriod {
+    fn next(&self) -> Self {
+        match Self::CHOICES.iter().position(|choice| choice == self) {
+            Some(i) => Self::CHOICES[(i + 1) % Self::CHOICES.len()],
+            None => Self::CHOICES[0],
+        }
+    }
+}
+
+impl Cyclable for CueChannels {
     fn next(&self) -> Self {
         match self {
-            Some(BuzzerSound::Buzz) => Some(BuzzerSound::Whoop),
-            Some(BuzzerSound::Whoop) => Some(BuzzerSound::Crazy),
-            Some(BuzzerSound::Crazy) => Some(BuzzerSound::DeDeDu),
-            Some(BuzzerSound::DeDeDu) => Some(BuzzerSound::TwoTone),
-            Some(BuzzerSound::TwoTone) => None,
-            None => Some(BuzzerSound::Buzz),
+            Self::AboveWater => Self::UnderWater,
+            Self::UnderWater => Self::Both,
+            Self::Both => Self::AboveWater,
         }
     }
 }
 
-impl Cyclable for Volume {
+impl Cyclable for PanelTheme {
     fn next(&self) -> Self {
         match self {
-            Self::Off => Self::Low,
-            Self::Low => Self::Medium,
-            Self::Medium => Self::High,
-            Self::High => Self::Max,
-            Self::Max => Self::Off,
+            Self::Standard => Self::ColorBlind,
+            Self::ColorBlind => Self::Standard,
+        }
+    }
+}
+
+impl Cyclable for PanelBrightness {
+    fn next(&self) -> Self {
+        match self {
+            Self::Full => Self::Indoor,
+            Self::Indoor => Self::Night,
+            Self::Night => Self::Full,
         }
     }
 }
@@ -133,6 +157,15 @@ pub(in super::super) fn build_game_config_edit_page<'a>(
         ConfigPage::Remotes(index, listening) => {
             make_remote_config_page(snapshot, settings, index, listening, mode, clock_running)
         }
+        ConfigPage::SoundOutputs => {
+            make_sound_outputs_config_page(snapshot, settings, mode, clock_running)
+        }
+        ConfigPage::Cues(index) => {
+            make_cues_config_page(snapshot, settings, index, mode, clock_running)
+        }
+        ConfigPage::PanelMessages(index) => {
+            make_panel_messages_config_page(snapshot, settings, index, mode, clock_running)
+        }
     }
 }
 
@@ -570,6 +603,7 @@ fn make_app_config_page<'a>(
     clock_running: bool,
 ) -> Element<'a, Message> {
     let EditableSettings {
+        config,
         collect_scorer_cap_num,
         track_fouls_and_warnings,
         ..
@@ -604,12 +638,29 @@ fn make_app_config_page<'a>(
                     BoolGameParameter::FoulsAndWarnings,
                 )),
             ),
+            make_value_button(
+                "STOP CLOCK\nON GOAL",
+                bool_string(config.stop_clock_on_goal),
+                (false, true),
+                Some(Message::ToggleBoolParameter(
+                    BoolGameParameter::StopClockOnGoal,
+                )),
+            ),
+        ]
+        .spacing(SPACING)
+        .height(Length::Fill),
+        row![
+            make_value_button(
+                "STOPPED CLOCK\nAT END OF GAME",
+                time_string(config.stopped_clock_final_duration),
+                (false, true),
+                Some(Message::EditParameter(LengthParameter::StoppedClockFinal)),
+            ),
             horizontal_space(Length::Fill),
         ]
         .spacing(SPACING)
         .height(Length::Fill),
         vertical_space(Length::Fill),
-        vertical_space(Length::Fill),
         row![
             horizontal_space(Length::Fill),
             horizontal_space(Length::Fill),
@@ -634,10 +685,45 @@ fn make_display_config_page<'a>(
 ) -> Element<'a, Message> {
     let EditableSettings {
         white_on_right,
+        auto_switch_sides,
         hide_time,
+        tenths_threshold_secs,
+        panel_theme,
+        panel_brightness,
+        black_team_code,
+        white_team_code,
+        using_uwhscores,
+        games,
+        game_number,
         ..
     } = settings;
 
+    // The typed codes are only used for this game. Without one, the panels show a code made from
+    // the uwhscores team name if there is one
+    let game = if *using_uwhscores {
+        games.as_ref().and_then(|games| games.get(game_number))
+    } else {
+        None
+    };
+    let team_code_input = |label: String, typed: &String, name: Option<&String>, param| {
+        let placeholder = name
+            .and_then(|name| TeamCode::from_name(name))
+            .map(|code| code.to_string())
+            .unwrap_or_default();
+        row![
+            text(label)
+                .size(MEDIUM_TEXT)
+                .line_height(LINE_HEIGHT)
+                .vertical_alignment(Vertical::Center)
+                .height(Length::Fill),
+            TextInput::new(&placeholder, typed)
+                .on_input(move |s| Message::TextParameterChanged(param, s))
+                .width(Length::Fill)
+        ]
+        .spacing(SPACING)
+        .width(Length::Fill)
+    };
+
     let white = container("WHITE")
         .center_x()
         .center_y()
@@ -680,16 +766,71 @@ fn make_display_config_page<'a>(
     column![
         make_game_time_button(snapshot, false, false, mode, clock_running),
         row![sides_btn].spacing(SPACING),
-        row![make_value_button(
-            "HIDE TIME FOR\nLAST 15 SECONDS",
-            bool_string(*hide_time),
-            (false, true),
-            Some(Message::ToggleBoolParameter(BoolGameParameter::HideTime))
-        )]
+        row![
+            make_value_button(
+                "SWITCH SIDES AT\nHALF TIME",
+                bool_string(*auto_switch_sides),
+                (false, true),
+                Some(Message::ToggleBoolParameter(
+                    BoolGameParameter::AutoSwitchSides
+                ))
+            ),
+            make_value_button(
+                "HIDE TIME FOR\nLAST 15 SECONDS",
+                bool_string(*hide_time),
+                (false, true),
+                Some(Message::ToggleBoolParameter(BoolGameParameter::HideTime))
+            ),
+            make_value_button(
+                "SHOW TENTHS IN\nLAST",
+                if *tenths_threshold_secs == 0 {
+                    "OFF".to_string()
+                } else {
+                    format!("{tenths_threshold_secs} SECONDS")
+                },
+                (false, true),
+                Some(Message::CycleParameter(CyclingParameter::TenthsThreshold))
+            ),
+        ]
         .spacing(SPACING),
+        row![
+            make_value_button(
+                "PANEL\nCOLORS",
+                panel_theme.to_string().to_uppercase(),
+                (false, true),
+                Some(Message::CycleParameter(CyclingParameter::PanelTheme))
+            ),
+            make_value_button(
+                "PANEL\nBRIGHTNESS",
+                panel_brightness.to_string().to_uppercase(),
+                (false, true),
+                Some(Message::CycleParameter(CyclingParameter::PanelBrightness))
+            ),
+        ]
+        .spacing(SPACING),
+        row![
+            team_code_input(
+                format!("BLACK TEAM CODE\nFOR GAME {game_number}:"),
+                black_team_code,
+                game.map(|game| &game.black),
+                TextParameter::BlackTeamCode
+            ),
+            team_code_input(
+                format!("WHITE TEAM CODE\nFOR GAME {game_number}:"),
+                white_team_code,
+                game.map(|game| &game.white),
+                TextParameter::WhiteTeamCode
+            ),
+        ]
+        .spacing(SPACING)
+        .height(Length::Fixed(MIN_BUTTON_SIZE)),
         vertical_space(Length::Fill),
         row![
-            horizontal_space(Length::Fill),
+            make_message_button(
+                "PANEL MESSAGES",
+                Some(Message::ChangeConfigPage(ConfigPage::PanelMessages(0))),
+            )
+            .style(ButtonStyle::LightGray),
             horizontal_space(Length::Fill),
             make_button("DONE")
                 .style(ButtonStyle::Green)
@@ -703,6 +844,97 @@ fn make_display_config_page<'a>(
     .into()
 }
 
+fn make_panel_messages_config_page<'a>(
+    snapshot: &GameSnapshot,
+    settings: &EditableSettings,
+    index: usize,
+    mode: Mode,
+    clock_running: bool,
+) -> Element<'a, Message> {
+    const MESSAGES_LIST_LEN: usize = 4;
+
+    let messages = &settings.panel_messages;
+    // Deleting messages can leave the scroll position past the end of the list
+    let index = index.min(messages.len().saturating_sub(MESSAGES_LIST_LEN));
+
+    let title = text("MESSAGES SHOWN BETWEEN GAMES")
+        .line_height(LINE_HEIGHT)
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .horizontal_alignment(Horizontal::Center)
+        .vertical_alignment(Vertical::Center);
+
+    let buttons: CollectArrayResult<_, MESSAGES_LIST_LEN> = messages
+        .iter()
+        .enumerate()
+        .skip(index)
+        .map(Some)
+        .chain([None].into_iter().cycle())
+        .take(MESSAGES_LIST_LEN)
+        .map(|message| {
+            if let Some((idx, message)) = message {
+                container(
+                    row![
+                        TextInput::new("TYPE A MESSAGE", message)
+                            .on_input(move |s| {
+                                Message::TextParameterChanged(TextParameter::PanelMessage(idx), s)
+                            })
+                            .width(Length::FillPortion(8)),
+                        make_message_button("DELETE", Some(Message::DeletePanelText(idx)))
+                            .width(Length::FillPortion(2))
+                            .height(Length::Fixed(MIN_BUTTON_SIZE - (2.0 * PADDING)))
+                            .style(ButtonStyle::Red),
+                    ]
+                    .align_items(Alignment::Center)
+                    .padding(PADDING)
+                    .spacing(SPACING),
+                )
+                .width(Length::Fill)
+                .height(Length::Fixed(MIN_BUTTON_SIZE))
+                .style(ContainerStyle::Gray)
+                .into()
+            } else {
+                container(horizontal_space(Length::Fill))
+                    .width(Length::Fill)
+                    .height(Length::Fixed(MIN_BUTTON_SIZE))
+                    .style(ContainerStyle::Disabled)
+                    .into()
+            }
+        })
+        .collect();
+
+    column![
+        make_game_time_button(snapshot, false, false, mode, clock_running),
+        row![
+            make_scroll_list(
+                buttons.unwrap(),
+                messages.len(),
+                index,
+                title,
+                ScrollOption::GameParameter,
+                ContainerStyle::LightGray,
+            )
+            .height(Length::Fill)
+            .width(Length::FillPortion(5)),
+            column![
+                vertical_space(Length::Fill),
+                make_message_button("ADD", Some(Message::AddPanelText)).style(ButtonStyle::Orange),
+                make_message_button("DONE", Some(Message::ChangeConfigPage(ConfigPage::Display)))
+                    .style(ButtonStyle::Green),
+            ]
+            .spacing(SPACING)
+            .height(Length::Fill)
+            .width(Length::Fill),
+        ]
+        .spacing(SPACING)
+        .height(Length::Fill)
+        .width(Length::Fill),
+    ]
+    .spacing(SPACING)
+    .height(Length::Fill)
+    .into()
+}
+
 fn make_sound_config_page<'a>(
     snapshot: &GameSnapshot,
     settings: &EditableSettings,
@@ -812,13 +1044,108 @@ fn make_sound_config_page<'a>(
         ]
         .spacing(SPACING),
         vertical_space(Length::Fill),
+        row![
+            make_message_button(
+                "PREVIEW BUZZER",
+                if sound.sound_enabled {
+                    Some(Message::PreviewBuzzerSound)
+                } else {
+                    None
+                },
+            )
+            .style(ButtonStyle::Orange),
+            make_message_button(
+                "AUDIO OUTPUTS",
+                Some(Message::ChangeConfigPage(ConfigPage::SoundOutputs)),
+            )
+            .style(ButtonStyle::LightGray),
+            make_message_button(
+                "SOUND CUES",
+                Some(Message::ChangeConfigPage(ConfigPage::Cues(0))),
+            )
+            .style(ButtonStyle::LightGray),
+            make_button("DONE")
+                .style(ButtonStyle::Green)
+                .width(Length::Fill)
+                .on_press(Message::ChangeConfigPage(ConfigPage::Main)),
+        ]
+        .spacing(SPACING),
+    ]
+    .spacing(SPACING)
+    .height(Length::Fill)
+    .into()
+}
+
+fn make_sound_outputs_config_page<'a>(
+    snapshot: &GameSnapshot,
+    settings: &EditableSettings,
+    mode: Mode,
+    clock_running: bool,
+) -> Element<'a, Message> {
+    const MAX_DEVICE_LABEL_LEN: usize = 16;
+
+    let EditableSettings { sound, .. } = settings;
+
+    let make_output_row = |name: &str, channel: LogicalChannel, output: &ChannelOutput| {
+        let device = match output.device {
+            Some(ref label) if label.chars().count() > MAX_DEVICE_LABEL_LEN => {
+                let short: String = label.chars().take(MAX_DEVICE_LABEL_LEN - 3).collect();
+                format!("{short}...")
+            }
+            Some(ref label) => label.clone(),
+            None => "DEFAULT".to_string(),
+        };
+
+        row![
+            make_value_button(
+                format!("{name}\nDEVICE:"),
+                device.to_uppercase(),
+                (false, false),
+                Some(Message::CycleParameter(CyclingParameter::OutputDevice(
+                    channel
+                ))),
+            ),
+            make_value_button(
+                format!("{name}\nCHANNEL:"),
+                output.channel + 1,
+                (false, true),
+                Some(Message::CycleParameter(CyclingParameter::OutputChannel(
+                    channel
+                ))),
+            ),
+            make_message_button(
+                format!("TEST {name}"),
+                if sound.sound_enabled {
+                    Some(Message::PlayTestTone(channel))
+                } else {
+                    None
+                },
+            )
+            .style(ButtonStyle::Orange),
+        ]
+        .spacing(SPACING)
+    };
+
+    column![
+        make_game_time_button(snapshot, false, true, mode, clock_running),
+        make_output_row(
+            "ABOVE WATER",
+            LogicalChannel::AboveWater,
+            &sound.channel_map.above_water
+        ),
+        make_output_row(
+            "UNDER WATER",
+            LogicalChannel::UnderWater,
+            &sound.channel_map.under_water
+        ),
+        vertical_space(Length::Fill),
         row![
             horizontal_space(Length::Fill),
             horizontal_space(Length::Fill),
             make_button("DONE")
                 .style(ButtonStyle::Green)
                 .width(Length::Fill)
-                .on_press(Message::ChangeConfigPage(ConfigPage::Main)),
+                .on_press(Message::ChangeConfigPage(ConfigPage::Sound)),
         ]
         .spacing(SPACING),
     ]
@@ -827,6 +1154,116 @@ fn make_sound_config_page<'a>(
     .into()
 }
 
+fn make_cues_config_page<'a>(
+    snapshot: &GameSnapshot,
+    settings: &EditableSettings,
+    index: usize,
+    mode: Mode,
+    clock_running: bool,
+) -> Element<'a, Message> {
+    const CUES_LIST_LEN: usize = 4;
+
+    let cues = &settings.sound.cues;
+    // Deleting cues can leave the scroll position past the end of the list
+    let index = index.min(cues.len().saturating_sub(CUES_LIST_LEN));
+
+    let title = text("SOUND CUES")
+        .line_height(LINE_HEIGHT)
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .horizontal_alignment(Horizontal::Center)
+        .vertical_alignment(Vertical::Center);
+
+    let make_cue_button = |label: String, message: Message| {
+        button(centered_text(label).size(SMALL_TEXT))
+            .padding(PADDING)
+            .width(Length::FillPortion(2))
+            .height(Length::Fixed(MIN_BUTTON_SIZE - (2.0 * PADDING)))
+            .style(ButtonStyle::Yellow)
+            .on_press(message)
+    };
+
+    let buttons: CollectArrayResult<_, CUES_LIST_LEN> = cues
+        .iter()
+        .enumerate()
+        .skip(index)
+        .map(Some)
+        .chain([None].into_iter().cycle())
+        .take(CUES_LIST_LEN)
+        .map(|cue| {
+            if let Some((idx, cue)) = cue {
+                container(
+                    row![
+                        make_cue_button(
+                            cue.period.to_string().to_uppercase(),
+                            Message::CycleParameter(CyclingParameter::CuePeriod(idx)),
+                        ),
+                        make_cue_button(
+                            time_string(Duration::from_secs(cue.secs_remaining.into())),
+                            Message::KeypadPage(KeypadPage::CueTime(idx)),
+                        ),
+                        make_cue_button(
+                            cue.sound.to_string().to_uppercase(),
+                            Message::CycleParameter(CyclingParameter::CueSound(idx)),
+                        ),
+                        make_cue_button(
+                            cue.channels.to_string().to_uppercase(),
+                            Message::CycleParameter(CyclingParameter::CueChannels(idx)),
+                        ),
+                        make_message_button("DELETE", Some(Message::DeleteCue(idx)))
+                            .width(Length::FillPortion(2))
+                            .height(Length::Fixed(MIN_BUTTON_SIZE - (2.0 * PADDING)))
+                            .style(ButtonStyle::Red),
+                    ]
+                    .padding(PADDING)
+                    .spacing(SPACING),
+                )
+                .width(Length::Fill)
+                .height(Length::Fixed(MIN_BUTTON_SIZE))
+                .style(ContainerStyle::Gray)
+                .into()
+            } else {
+                container(horizontal_space(Length::Fill))
+                    .width(Length::Fill)
+                    .height(Length::Fixed(MIN_BUTTON_SIZE))
+                    .style(ContainerStyle::Disabled)
+                    .into()
+            }
+        })
+        .collect();
+
+    column![
+        make_game_time_button(snapshot, false, false, mode, clock_running),
+        row![
+            make_scroll_list(
+                buttons.unwrap(),
+                cues.len(),
+                index,
+                title,
+                ScrollOption::GameParameter,
+                ContainerStyle::LightGray,
+            )
+            .height(Length::Fill)
+            .width(Length::FillPortion(5)),
+            column![
+                vertical_space(Length::Fill),
+                make_message_button("ADD", Some(Message::AddCue)).style(ButtonStyle::Orange),
+                make_message_button("DONE", Some(Message::ChangeConfigPage(ConfigPage::Sound)))
+                    .style(ButtonStyle::Green),
+            ]
+            .spacing(SPACING)
+            .height(Length::Fill)
+            .width(Length::Fill),
+        ]
+        .spacing(SPACING)
+        .height(Length::Fill)
+        .width(Length::Fill),
+    ]
+    .spacing(SPACING)
+    .height(Length::Fill)
+    .into()
+}
+
 fn make_remote_config_page<'a>(
     snapshot: &GameSnapshot,
     settings: &EditableSettings,
@@ -855,7 +1292,7 @@ fn make_remote_config_page<'a>(
         .take(REMOTES_LIST_LEN)
         .map(|rem| {
             if let Some((idx, rem_info)) = rem {
-                let sound_text = if let Some(sound) = rem_info.sound {
+                let sound_text = if let Some(ref sound) = rem_info.sound {
                     sound.to_string().to_uppercase()
                 } else {
                     "DEFAULT".to_owned()
@@ -1052,6 +1489,12 @@ pub(in super::super) fn build_game_parameter_editor<'a>(
             "PRE SD BREAK",
             "The length of the break between the preceeding play period and Sudden Death",
         ),
+        LengthParameter::StoppedClockFinal => (
+            "STOPPED CLK",
+            "During this much time at the end of the Second Half, the game clock will stop \
+            automatically whenever a goal, penalty, or foul is recorded. The ref restarts the \
+            clock when play resumes. Set to 0:00 to keep the clock running.",
+        ),
     };
 
     column![
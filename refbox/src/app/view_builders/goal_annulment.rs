@@ -0,0 +1,188 @@
+use super::{
+    style::{ButtonStyle, ContainerStyle, Element, LINE_HEIGHT, MIN_BUTTON_SIZE, PADDING, SPACING},
+    *,
+};
+use crate::tournament_manager::goal::{AnnulmentReason, RecordedGoal};
+use collect_array::CollectArrayResult;
+use enum_iterator::all;
+use iced::{
+    alignment::{Horizontal, Vertical},
+    widget::{button, column, container, horizontal_space, row, text, vertical_space},
+    Alignment, Length,
+};
+
+use uwh_common::game_snapshot::GameSnapshot;
+
+pub(in super::super) fn build_goal_list_page<'a>(
+    snapshot: &GameSnapshot,
+    goals: Vec<RecordedGoal>,
+    index: usize,
+    mode: Mode,
+    clock_running: bool,
+) -> Element<'a, Message> {
+    const LIST_LEN: usize = 4;
+
+    let title = text("SELECT GOAL TO ANNUL")
+        .line_height(LINE_HEIGHT)
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .horizontal_alignment(Horizontal::Center)
+        .vertical_alignment(Vertical::Center);
+
+    let num_goals = goals.len();
+
+    let buttons: CollectArrayResult<_, LIST_LEN> = goals
+        .into_iter()
+        .enumerate()
+        .skip(index)
+        .map(Some)
+        .chain([None].into_iter().cycle())
+        .take(LIST_LEN)
+        .map(|goal| {
+            if let Some((i, goal)) = goal {
+                let text = text(goal_string(&goal))
+                    .line_height(LINE_HEIGHT)
+                    .vertical_alignment(Vertical::Center)
+                    .horizontal_alignment(Horizontal::Left)
+                    .width(Length::Fill);
+
+                button(text)
+                    .padding(PADDING)
+                    .height(Length::Fixed(MIN_BUTTON_SIZE))
+                    .width(Length::Fill)
+                    .style(ButtonStyle::Gray)
+                    .on_press(Message::SelectGoal(i))
+                    .into()
+            } else {
+                button(horizontal_space(Length::Shrink))
+                    .height(Length::Fixed(MIN_BUTTON_SIZE))
+                    .width(Length::Fill)
+                    .style(ButtonStyle::Gray)
+                    .into()
+            }
+        })
+        .collect();
+
+    let scroll_list = make_scroll_list(
+        buttons.unwrap(),
+        num_goals,
+        index,
+        title,
+        ScrollOption::Goals,
+        ContainerStyle::LightGray,
+    )
+    .width(Length::FillPortion(4));
+
+    column![
+        make_game_time_button(snapshot, false, false, mode, clock_running),
+        row![
+            scroll_list,
+            column![
+                vertical_space(Length::Fill),
+                make_button("CANCEL")
+                    .style(ButtonStyle::Red)
+                    .width(Length::Fill)
+                    .height(Length::Fixed(MIN_BUTTON_SIZE))
+                    .on_press(Message::GoalAnnulmentComplete { canceled: true }),
+            ]
+            .width(Length::Fill),
+        ]
+        .spacing(SPACING)
+        .height(Length::Fill)
+        .width(Length::Fill),
+    ]
+    .spacing(SPACING)
+    .height(Length::Fill)
+    .into()
+}
+
+pub(in super::super) fn build_goal_annulment_page<'a>(
+    snapshot: &GameSnapshot,
+    goal: Option<RecordedGoal>,
+    reason: AnnulmentReason,
+    mode: Mode,
+    clock_running: bool,
+) -> Element<'a, Message> {
+    let header = match goal {
+        Some(ref goal) => format!("Annul this goal?\n\n{}", goal_string(goal)),
+        None => "This goal no longer exists".to_string(),
+    };
+
+    let mut reason_col = column![].spacing(SPACING).width(Length::Fill);
+    for option in all::<AnnulmentReason>() {
+        let style = if option == reason {
+            ButtonStyle::LightGraySelected
+        } else {
+            ButtonStyle::LightGray
+        };
+        reason_col = reason_col.push(
+            make_smaller_button(option.to_string().to_uppercase())
+                .style(style)
+                .width(Length::Fill)
+                .on_press(Message::ChangeAnnulmentReason(option)),
+        );
+    }
+
+    let annul_msg = goal
+        .as_ref()
+        .map(|_| Message::GoalAnnulmentComplete { canceled: false });
+
+    column![
+        make_game_time_button(snapshot, false, false, mode, clock_running),
+        row![
+            container(
+                text(header)
+                    .line_height(LINE_HEIGHT)
+                    .horizontal_alignment(Horizontal::Center),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(ContainerStyle::LightGray)
+            .padding(PADDING),
+            column![text("REASON:").line_height(LINE_HEIGHT), reason_col]
+                .spacing(SPACING)
+                .width(Length::Fill)
+                .align_items(Alignment::Center),
+        ]
+        .spacing(SPACING)
+        .height(Length::Fill),
+        row![
+            make_button("CANCEL")
+                .style(ButtonStyle::Red)
+                .width(Length::Fill)
+                .on_press(Message::GoalAnnulmentComplete { canceled: true }),
+            horizontal_space(Length::Fill),
+            make_message_button("ANNUL GOAL", annul_msg)
+                .style(ButtonStyle::Orange)
+                .width(Length::Fill),
+        ]
+        .spacing(SPACING),
+    ]
+    .spacing(SPACING)
+    .height(Length::Fill)
+    .into()
+}
+
+fn goal_string(goal: &RecordedGoal) -> String {
+    let period = match goal.period {
+        GamePeriod::FirstHalf => "FIRST HALF",
+        GamePeriod::SecondHalf => "SECOND HALF",
+        GamePeriod::OvertimeFirstHalf => "OT FIRST HALF",
+        GamePeriod::OvertimeSecondHalf => "OT SECOND HALF",
+        GamePeriod::SuddenDeath => "SUDDEN DEATH",
+        GamePeriod::BetweenGames
+        | GamePeriod::HalfTime
+        | GamePeriod::PreOvertime
+        | GamePeriod::OvertimeHalfTime
+        | GamePeriod::PreSuddenDeath => "BREAK",
+    };
+    format!(
+        "{} #{} - {} {}",
+        goal.color.to_string().to_uppercase(),
+        goal.player_number,
+        period,
+        time_string(goal.time)
+    )
+}
@@ -84,7 +84,8 @@ pub(in super::super) fn build_keypad_page<'a>(
         }
         KeypadPage::GameNumber
         | KeypadPage::Penalty(_, _, _, _)
-        | KeypadPage::TeamTimeouts(_, _) => player_num.to_string(),
+        | KeypadPage::TeamTimeouts(_, _)
+        | KeypadPage::CueTime(_) => player_num.to_string(),
     };
 
     column![
@@ -200,7 +201,7 @@ pub(in super::super) fn build_keypad_page<'a>(
                 KeypadPage::Penalty(origin, color, kind, foul) => {
                     make_penalty_edit_page(origin, color, kind, config, foul)
                 }
-                KeypadPage::GameNumber => make_game_number_edit_page(),
+                KeypadPage::GameNumber | KeypadPage::CueTime(_) => make_game_number_edit_page(),
                 KeypadPage::TeamTimeouts(dur, per_half) =>
                     make_team_timeout_edit_page(dur, per_half),
                 KeypadPage::FoulAdd {
@@ -77,10 +77,16 @@ pub(in super::super) fn build_main_view<'a>(
                 | GamePeriod::PreOvertime
                 | GamePeriod::OvertimeHalfTime
                 | GamePeriod::PreSuddenDeath => {
-                    let mut start_warning_row = row![make_button("START NOW")
-                        .style(ButtonStyle::Green)
-                        .width(Length::Fill)
-                        .on_press(Message::StartPlayNow)]
+                    let mut start_warning_row = row![
+                        make_button("START NOW")
+                            .style(ButtonStyle::Green)
+                            .width(Length::Fill)
+                            .on_press(Message::StartPlayNow),
+                        make_multi_label_button(("SWITCH", "SIDES"))
+                            .style(ButtonStyle::LightGray)
+                            .width(Length::Fill)
+                            .on_press(Message::SwitchSides),
+                    ]
                     .spacing(SPACING);
 
                     if config.track_fouls_and_warnings {
@@ -12,6 +12,9 @@ pub(super) use confirmation::*;
 pub mod game_info;
 pub(super) use game_info::*;
 
+pub mod goal_annulment;
+pub(super) use goal_annulment::*;
+
 pub mod warnings_fouls_summary;
 pub(super) use warnings_fouls_summary::*;
 
@@ -24,6 +24,12 @@ pub(in super::super) fn build_score_edit_view<'a>(
         Some(Message::ScoreEditComplete { canceled: true })
     };
 
+    let annul_btn_msg = if is_confirmation {
+        None
+    } else {
+        Some(Message::GoalList)
+    };
+
     let black_edit = container(
         row![
             column![
@@ -126,6 +132,8 @@ pub(in super::super) fn build_score_edit_view<'a>(
             row![
                 make_message_button("CANCEL", cancel_btn_msg).style(ButtonStyle::Red),
                 horizontal_space(Length::Fill),
+                make_message_button("ANNUL GOAL", annul_btn_msg).style(ButtonStyle::Orange),
+                horizontal_space(Length::Fill),
                 make_button("DONE")
                     .style(ButtonStyle::Green)
                     .on_press(Message::ScoreEditComplete { canceled: false }),
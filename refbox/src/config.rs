@@ -2,6 +2,7 @@ use crate::sound_controller::SoundSettings;
 use derivative::Derivative;
 use enum_derive_2018::{EnumDisplay, EnumFromStr};
 use macro_attr_2018::macro_attr;
+use matrix_drawing::{PanelBrightness, PanelTheme};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use time::UtcOffset;
@@ -13,6 +14,7 @@ pub struct Hardware {
     pub screen_x: i32,
     pub screen_y: i32,
     pub white_on_right: bool,
+    pub auto_switch_sides: bool,
 }
 
 impl Default for Hardware {
@@ -21,6 +23,7 @@ impl Default for Hardware {
             screen_x: 945,
             screen_y: 691,
             white_on_right: false,
+            auto_switch_sides: false,
         }
     }
 }
@@ -31,16 +34,19 @@ impl Hardware {
             mut screen_x,
             mut screen_y,
             mut white_on_right,
+            mut auto_switch_sides,
         } = Default::default();
 
         get_integer_value(old, "screen_x", &mut screen_x);
         get_integer_value(old, "screen_y", &mut screen_y);
         get_boolean_value(old, "white_on_right", &mut white_on_right);
+        get_boolean_value(old, "auto_switch_sides", &mut auto_switch_sides);
 
         Self {
             screen_x,
             screen_y,
             white_on_right,
+            auto_switch_sides,
         }
     }
 }
@@ -110,15 +116,34 @@ impl UwhPortal {
     }
 }
 
+/// The choices for `Config::tenths_threshold_secs` offered on the display settings page
+const TENTHS_THRESHOLD_CHOICES: [u32; 4] = [0, 10, 30, 60];
+
+/// The next choice of tenths threshold after `secs`, going back to the first after the last
+pub fn next_tenths_threshold(secs: u32) -> u32 {
+    TENTHS_THRESHOLD_CHOICES
+        .into_iter()
+        .find(|&choice| choice > secs)
+        .unwrap_or(TENTHS_THRESHOLD_CHOICES[0])
+}
+
 #[derive(Derivative, Serialize, Deserialize)]
 #[derivative(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Config {
     pub mode: Mode,
     pub hide_time: bool,
+    /// The panels show tenths of a second once the game clock is below this many seconds. Zero
+    /// turns the tenths off
+    #[derivative(Default(value = "60"))]
+    pub tenths_threshold_secs: u32,
+    pub panel_theme: PanelTheme,
+    pub panel_brightness: PanelBrightness,
     #[derivative(Default(value = "true"))]
     pub collect_scorer_cap_num: bool,
     #[derivative(Default(value = "true"))]
     pub track_fouls_and_warnings: bool,
+    /// Messages shown on the panels in turn between games
+    pub panel_messages: Vec<String>,
     pub game: Game,
     pub hardware: Hardware,
     pub uwhscores: UwhScores,
@@ -131,8 +156,12 @@ impl Config {
         let Self {
             mut mode,
             mut hide_time,
+            mut tenths_threshold_secs,
+            mut panel_theme,
+            mut panel_brightness,
             mut collect_scorer_cap_num,
             mut track_fouls_and_warnings,
+            mut panel_messages,
             mut game,
             mut hardware,
             mut uwhscores,
@@ -148,12 +177,28 @@ impl Config {
             }
         }
         get_boolean_value(old, "hide_time", &mut hide_time);
+        get_integer_value(old, "tenths_threshold_secs", &mut tenths_threshold_secs);
         get_boolean_value(old, "collect_scorer_cap_num", &mut collect_scorer_cap_num);
         get_boolean_value(
             old,
             "track_fouls_and_warnings",
             &mut track_fouls_and_warnings,
         );
+        if let Some(old_theme) = old.get("panel_theme") {
+            if let Ok(old_theme) = old_theme.clone().try_into() {
+                panel_theme = old_theme;
+            }
+        }
+        if let Some(old_brightness) = old.get("panel_brightness") {
+            if let Ok(old_brightness) = old_brightness.clone().try_into() {
+                panel_brightness = old_brightness;
+            }
+        }
+        if let Some(old_messages) = old.get("panel_messages") {
+            if let Ok(old_messages) = old_messages.clone().try_into() {
+                panel_messages = old_messages;
+            }
+        }
         if let Some(old_game) = old.get("game") {
             if let Some(old_game) = old_game.as_table() {
                 game = Game::migrate(old_game);
@@ -183,8 +228,12 @@ impl Config {
         Self {
             mode,
             hide_time,
+            tenths_threshold_secs,
+            panel_theme,
+            panel_brightness,
             collect_scorer_cap_num,
             track_fouls_and_warnings,
+            panel_messages,
             game,
             hardware,
             uwhscores,
@@ -276,10 +325,12 @@ mod test {
         old.insert("screen_x".to_string(), toml::Value::Integer(123));
         old.insert("screen_y".to_string(), toml::Value::Integer(456));
         old.insert("white_on_right".to_string(), toml::Value::Boolean(true));
+        old.insert("auto_switch_sides".to_string(), toml::Value::Boolean(true));
         let hw = Hardware::migrate(&old);
         assert_eq!(hw.screen_x, 123);
         assert_eq!(hw.screen_y, 456);
         assert_eq!(hw.white_on_right, true);
+        assert!(hw.auto_switch_sides);
     }
 
     #[test]
@@ -319,11 +370,33 @@ mod test {
         assert_eq!(u.token, "token");
     }
 
+    #[test]
+    fn test_next_tenths_threshold() {
+        assert_eq!(next_tenths_threshold(0), 10);
+        assert_eq!(next_tenths_threshold(10), 30);
+        assert_eq!(next_tenths_threshold(30), 60);
+        assert_eq!(next_tenths_threshold(60), 0);
+        assert_eq!(next_tenths_threshold(45), 60);
+        assert_eq!(next_tenths_threshold(120), 0);
+    }
+
     #[test]
     fn test_migrate_config() {
         let mut old: Table = Default::default();
         old.insert("mode".to_string(), toml::Value::String("Rugby".to_string()));
         old.insert("hide_time".to_string(), toml::Value::Boolean(true));
+        old.insert(
+            "tenths_threshold_secs".to_string(),
+            toml::Value::Integer(30),
+        );
+        old.insert(
+            "panel_theme".to_string(),
+            toml::Value::String("ColorBlind".to_string()),
+        );
+        old.insert(
+            "panel_brightness".to_string(),
+            toml::Value::String("Night".to_string()),
+        );
         old.insert(
             "collect_scorer_cap_num".to_string(),
             toml::Value::Boolean(true),
@@ -371,9 +444,16 @@ mod test {
             toml::Value::String("Max".to_string()),
         );
         old.insert("sound".to_string(), toml::Value::Table(sound));
+        old.insert(
+            "panel_messages".to_string(),
+            toml::Value::Array(vec![toml::Value::String("Final at 2pm".to_string())]),
+        );
         let config = Config::migrate(&old);
         assert_eq!(config.mode, Mode::Rugby);
         assert_eq!(config.hide_time, true);
+        assert_eq!(config.tenths_threshold_secs, 30);
+        assert_eq!(config.panel_theme, PanelTheme::ColorBlind);
+        assert_eq!(config.panel_brightness, PanelBrightness::Night);
         assert_eq!(config.collect_scorer_cap_num, true);
         assert_eq!(config.game.half_play_duration, Duration::from_secs(123));
         assert_eq!(config.hardware.screen_x, 123);
@@ -387,5 +467,6 @@ mod test {
         assert_eq!(config.uwhportal.token, "token");
         assert_eq!(config.sound.sound_enabled, false);
         assert_eq!(config.sound.whistle_vol, Volume::Max);
+        assert_eq!(config.panel_messages, vec!["Final at 2pm".to_string()]);
     }
 }
@@ -16,9 +16,11 @@ use log4rs::{
     config::{Appender, Config as LogConfig, Logger, Root},
     encode::pattern::PatternEncoder,
 };
+use matrix_drawing::PanelLayout;
 use std::{
     path::PathBuf,
     process::{Command, Stdio},
+    time::Duration,
 };
 use tokio_serial::{DataBits, FlowControl, Parity, StopBits};
 
@@ -27,12 +29,16 @@ mod app_icon;
 mod penalty_editor;
 mod sim_app;
 mod sound_controller;
+use sound_controller::{RemoteInputSource, SoundController};
 mod tournament_manager;
 
 mod config;
 use config::Config;
 
 const APP_NAME: &str = "refbox";
+/// Longest game rendered by `--render-sounds`, in case it never ends (e.g. a scoreless sudden
+/// death)
+const MAX_RENDER_LEN: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -53,6 +59,12 @@ struct Cli {
     /// Spacing between pixels in the panel the simulator
     spacing: Option<f32>,
 
+    #[clap(long, default_value = "256x64", value_parser = parse_panel_layout)]
+    /// Layout of the panels shown in the simulator, one of 256x64, 128x32, 192x64 or 384x96.
+    /// Give it more than once to open a simulator for each layout. Serial panels choose their own
+    /// layout in their firmware
+    panel_layout: Vec<&'static PanelLayout>,
+
     #[clap(long, short)]
     /// Make the app fullscreen
     fullscreen: bool,
@@ -97,10 +109,55 @@ struct Cli {
     /// Number of archived logs to keep
     num_old_logs: u32,
 
-    #[clap(long, hide = true)]
+    #[cfg(target_os = "linux")]
+    #[clap(long)]
+    /// Keyboard-like input device (in /dev/input) whose keys sound the buzzer, e.g. a USB buzzer
+    remote_keyboard: Option<PathBuf>,
+
+    #[clap(long)]
+    /// File of recorded remote edge timings to replay, for testing without the hardware
+    remote_sim: Option<PathBuf>,
+
+    #[clap(long)]
+    /// Directory of WAV files to offer as extra buzzer sounds, default is a `sounds` directory
+    /// next to the config file
+    sound_pack: Option<PathBuf>,
+
+    #[clap(long)]
+    /// Render the sounds of a game played with the saved settings to this WAV file and exit,
+    /// instead of starting the refbox
+    render_sounds: Option<PathBuf>,
+
+    #[clap(long)]
+    /// A panel for the simulator to show: `HOST:PORT` for the binary port of a refbox,
+    /// `udp:PORT` to listen for one frame in each datagram, or `serial:PATH` to read the frames
+    /// sent to serial panels at `--baud-rate`. End it with `/swapped` to show the panel at the
+    /// other end of the court. Give it more than once to show several panels, default is this
+    /// refbox's binary port
+    sim_source: Vec<sim_app::PanelSource>,
+
+    #[clap(long)]
+    /// Only run the simulator, e.g. to watch the panels of other refboxes given with
+    /// `--sim-source`
     is_simulator: bool,
 }
 
+fn parse_panel_layout(name: &str) -> Result<&'static PanelLayout, String> {
+    PanelLayout::by_name(name).ok_or_else(|| {
+        let names: Vec<_> = PanelLayout::ALL.iter().map(|layout| layout.name).collect();
+        format!("unknown layout, expected one of {}", names.join(", "))
+    })
+}
+
+/// The sound pack given on the command line, or the `sounds` directory next to the config file
+fn sound_pack_path(arg: Option<PathBuf>) -> Option<PathBuf> {
+    arg.or_else(|| {
+        let mut path = confy::get_configuration_file_path(APP_NAME, None).ok()?;
+        path.set_file_name("sounds");
+        path.is_dir().then_some(path)
+    })
+}
+
 fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     let args = Cli::parse();
 
@@ -190,12 +247,21 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         icon::from_rgba(Vec::from(app_icon::DATA), app_icon::WIDTH, app_icon::HEIGHT).unwrap();
 
     if args.is_simulator {
+        let sources = if args.sim_source.is_empty() {
+            vec![sim_app::PanelSource::local(args.binary_port)]
+        } else {
+            args.sim_source
+        };
+        let window_size =
+            sim_app::window_size(args.panel_layout[0], sources.len(), args.scale, spacing);
         let flags = sim_app::SimRefBoxAppFlags {
-            tcp_port: args.binary_port,
+            sources,
+            baud_rate: args.baud_rate,
+            layout: args.panel_layout[0],
         };
 
         let mut settings = Settings::with_flags(flags);
-        settings.window.size = sim_app::window_size(args.scale, spacing);
+        settings.window.size = window_size;
         settings.window.resizable = true;
         settings.window.icon = Some(icon);
         info!("Starting Simulator UI");
@@ -206,8 +272,30 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         info!("Starting RefBox App");
     }
 
-    let child = if args.no_simulate {
-        None
+    if let Some(path) = args.render_sounds {
+        let config: Config = confy::load(APP_NAME, None)?;
+        let custom_sounds = match sound_pack_path(args.sound_pack) {
+            Some(dir) => {
+                let (sounds, errors) = sound_controller::load_sound_pack(&dir)?;
+                for e in errors {
+                    error!("Skipping custom buzzer sound: {e}");
+                }
+                sounds
+            }
+            None => vec![],
+        };
+        let (messages, len) =
+            SoundController::game_cues(config.game, &config.sound, MAX_RENDER_LEN);
+        info!(
+            "Rendering {} sounds over {len:?} to {path:?}",
+            messages.len()
+        );
+        SoundController::render_offline(&config.sound, &custom_sounds, messages, len, &path)?;
+        return Ok(());
+    }
+
+    let children = if args.no_simulate {
+        vec![]
     } else {
         let bin_name = std::env::current_exe()?.into_os_string();
         info!("Current bin_name: {bin_name:?}");
@@ -219,6 +307,8 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         let log_location = log_base_path.to_str().unwrap().to_string();
         let log_max_file_size = args.log_max_file_size.to_string();
         let num_old_logs = args.num_old_logs.to_string();
+        let baud_rate = args.baud_rate.to_string();
+        let sim_sources: Vec<_> = args.sim_source.iter().map(|s| s.to_string()).collect();
 
         let mut child_args = vec![
             "--is-simulator",
@@ -236,19 +326,34 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
             &log_max_file_size,
             "--num-old-logs",
             &num_old_logs,
+            "--baud-rate",
+            &baud_rate,
         ];
+        for source in sim_sources.iter() {
+            child_args.extend(["--sim-source", source]);
+        }
 
         child_args.resize(child_args.len() + args.verbose as usize, "--verbose");
 
-        debug!("Child args: {child_args:?}");
+        let mut children = vec![];
+        for layout in args.panel_layout.iter() {
+            let mut child_args = child_args.clone();
+            child_args.extend(["--panel-layout", layout.name]);
 
-        info!("Starting child with birany port {binary_port}");
-        let child = Command::new(bin_name)
-            .args(child_args)
-            .stdin(Stdio::null())
-            .spawn()?;
+            debug!("Child args: {child_args:?}");
 
-        Some(child)
+            info!(
+                "Starting child with birany port {binary_port} and layout {}",
+                layout.name
+            );
+            let child = Command::new(&bin_name)
+                .args(child_args)
+                .stdin(Stdio::null())
+                .spawn()?;
+            children.push(child);
+        }
+
+        children
     };
 
     let serial_ports = if let Some(port) = args.serial_port {
@@ -313,16 +418,33 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         config.hardware.screen_y as u32,
     );
 
+    #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
+    let mut remote_sources = vec![];
+    #[cfg(target_os = "linux")]
+    {
+        remote_sources.push(RemoteInputSource::Gpio);
+        if let Some(path) = args.remote_keyboard {
+            remote_sources.push(RemoteInputSource::Keyboard(path));
+        }
+    }
+    if let Some(path) = args.remote_sim {
+        remote_sources.push(RemoteInputSource::Simulated(path));
+    }
+
+    let sound_pack = sound_pack_path(args.sound_pack);
+
     let flags = app::RefBoxAppFlags {
         config,
         serial_ports,
         binary_port: args.binary_port,
         json_port: args.json_port,
-        sim_child: child,
+        sim_children: children,
         require_https: !args.allow_http,
         fullscreen: args.fullscreen,
         list_all_tournaments: args.all_tournaments,
         touchscreen: args.touchscreen,
+        remote_sources,
+        sound_pack,
     };
 
     let mut settings = Settings::with_flags(flags);
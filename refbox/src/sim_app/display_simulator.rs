@@ -7,37 +7,41 @@ use skip_error::SkipError;
 use std::ops::{Deref, DerefMut};
 
 #[derive(Debug)]
-pub struct DisplayBuffer<const WIDTH: usize, const HEIGHT: usize>([[Option<Color>; WIDTH]; HEIGHT]);
+pub struct DisplayBuffer {
+    size: Size,
+    rows: Vec<Vec<Option<Color>>>,
+}
+
+impl DisplayBuffer {
+    pub fn new(size: Size) -> Self {
+        Self {
+            size,
+            rows: vec![vec![None; size.width as usize]; size.height as usize],
+        }
+    }
 
-impl<const WIDTH: usize, const HEIGHT: usize> Default for DisplayBuffer<WIDTH, HEIGHT> {
-    fn default() -> Self {
-        Self([[None; WIDTH]; HEIGHT])
+    pub fn clear_buffer(&mut self) {
+        for pix in self.iter_mut().flatten() {
+            *pix = None;
+        }
     }
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> Deref for DisplayBuffer<WIDTH, HEIGHT> {
-    type Target = [[Option<Color>; WIDTH]; HEIGHT];
+impl Deref for DisplayBuffer {
+    type Target = [Vec<Option<Color>>];
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.rows
     }
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> DerefMut for DisplayBuffer<WIDTH, HEIGHT> {
+impl DerefMut for DisplayBuffer {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
-    }
-}
-
-impl<const WIDTH: usize, const HEIGHT: usize> DisplayBuffer<WIDTH, HEIGHT> {
-    pub fn clear_buffer(&mut self) {
-        for pix in self.iter_mut().flatten() {
-            *pix = None;
-        }
+        &mut self.rows
     }
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> DrawTarget for DisplayBuffer<WIDTH, HEIGHT> {
+impl DrawTarget for DisplayBuffer {
     type Color = Rgb888;
     type Error = core::convert::Infallible;
 
@@ -57,7 +61,7 @@ impl<const WIDTH: usize, const HEIGHT: usize> DrawTarget for DisplayBuffer<WIDTH
             .skip_error()
         {
             // Check if the pixel coordinates are not out of bounds
-            if (x < WIDTH) & (y < HEIGHT) {
+            if (x < self.size.width as usize) & (y < self.size.height as usize) {
                 let color = Color::from_rgb8(color.r(), color.g(), color.b());
                 self[y][x] = Some(color);
             }
@@ -67,8 +71,8 @@ impl<const WIDTH: usize, const HEIGHT: usize> DrawTarget for DisplayBuffer<WIDTH
     }
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> OriginDimensions for DisplayBuffer<WIDTH, HEIGHT> {
+impl OriginDimensions for DisplayBuffer {
     fn size(&self) -> Size {
-        Size::new(WIDTH as u32, HEIGHT as u32)
+        self.size
     }
 }
@@ -0,0 +1,383 @@
+use super::Message;
+use arrayref::array_ref;
+use iced_futures::{
+    futures::stream::{self, BoxStream},
+    subscription::{EventStream, Recipe},
+};
+use log::*;
+use matrix_drawing::transmitted_data::TransmittedData;
+use socket2::{SockRef, TcpKeepalive};
+use std::{fmt, io, str::FromStr};
+use tokio::{
+    io::AsyncReadExt,
+    net::{TcpStream, UdpSocket},
+    time::{self, Duration},
+};
+use tokio_serial::{DataBits, FlowControl, Parity, SerialPortBuilderExt, SerialStream, StopBits};
+
+/// How long to wait before the first attempt to reconnect
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+/// The longest wait between attempts to reconnect
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// A pause in the bytes from a serial port that ends a frame. The refbox sends a frame every
+/// 100ms, so a pause this long means that any bytes before it were only part of a frame
+const SERIAL_FRAME_GAP: Duration = Duration::from_millis(20);
+/// How long a UDP or serial source can go without sending a frame before it is treated as lost.
+/// The refbox sends a frame to serial panels every 100ms
+const FRAME_TIMEOUT: Duration = Duration::from_secs(1);
+/// How long a TCP connection can be idle before keepalive probes are sent, and how often they
+/// are sent after that. A refbox that drops off the network is noticed after a few probes
+const KEEPALIVE_TIME: Duration = Duration::from_secs(5);
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where the simulator gets the frames for its panels from
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FrameSource {
+    /// The binary port of a refbox, as `host:port`
+    Tcp(String),
+    /// A UDP port to listen on, with one frame in each datagram
+    Udp(u16),
+    /// A serial port carrying the frames the refbox sends to the panels
+    Serial(String),
+}
+
+impl fmt::Display for FrameSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(address) => write!(f, "{address}"),
+            Self::Udp(port) => write!(f, "udp:{port}"),
+            Self::Serial(path) => write!(f, "serial:{path}"),
+        }
+    }
+}
+
+/// One panel shown by the simulator
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PanelSource {
+    pub frames: FrameSource,
+    /// Show the panel at the other end of the court, with the teams on the opposite sides
+    pub swap_sides: bool,
+}
+
+impl PanelSource {
+    pub fn local(port: u16) -> Self {
+        Self {
+            frames: FrameSource::Tcp(format!("localhost:{port}")),
+            swap_sides: false,
+        }
+    }
+}
+
+impl fmt::Display for PanelSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.frames)?;
+        if self.swap_sides {
+            write!(f, "/swapped")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PanelSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (s, swap_sides) = match s.strip_suffix("/swapped") {
+            Some(s) => (s, true),
+            None => (s, false),
+        };
+
+        let frames = if let Some(port) = s.strip_prefix("udp:") {
+            FrameSource::Udp(
+                port.parse()
+                    .map_err(|_| format!("invalid UDP port `{port}`"))?,
+            )
+        } else if let Some(path) = s.strip_prefix("serial:") {
+            if path.is_empty() {
+                return Err("missing serial port".to_string());
+            }
+            FrameSource::Serial(path.to_string())
+        } else {
+            let (host, port) = s.rsplit_once(':').ok_or_else(|| {
+                format!("expected `host:port`, `udp:port` or `serial:path`, got `{s}`")
+            })?;
+            if host.is_empty() {
+                return Err("missing host".to_string());
+            }
+            port.parse::<u16>()
+                .map_err(|_| format!("invalid TCP port `{port}`"))?;
+            FrameSource::Tcp(s.to_string())
+        };
+
+        Ok(Self { frames, swap_sides })
+    }
+}
+
+#[derive(Debug)]
+enum Connection {
+    Tcp(TcpStream),
+    Udp(UdpSocket),
+    Serial(SerialStream),
+}
+
+impl Connection {
+    async fn open(source: &FrameSource, baud_rate: u32) -> io::Result<Self> {
+        Ok(match source {
+            FrameSource::Tcp(address) => {
+                let stream = TcpStream::connect(address.as_str()).await?;
+                // A refbox that drops off the network doesn't close the connection, so without
+                // keepalive the read would never return
+                SockRef::from(&stream).set_tcp_keepalive(
+                    &TcpKeepalive::new()
+                        .with_time(KEEPALIVE_TIME)
+                        .with_interval(KEEPALIVE_INTERVAL),
+                )?;
+                Self::Tcp(stream)
+            }
+            FrameSource::Udp(port) => Self::Udp(UdpSocket::bind(("0.0.0.0", *port)).await?),
+            // The same settings the refbox sends to the panels with
+            FrameSource::Serial(path) => Self::Serial(
+                tokio_serial::new(path, baud_rate)
+                    .flow_control(FlowControl::None)
+                    .data_bits(DataBits::Eight)
+                    .parity(Parity::Even)
+                    .stop_bits(StopBits::One)
+                    .open_native_async()?,
+            ),
+        })
+    }
+
+    /// Waits for the next frame. Returns `None` if something was received that isn't a frame
+    async fn read_frame(&mut self) -> io::Result<Option<[u8; TransmittedData::ENCODED_LEN]>> {
+        let mut frame = [0u8; TransmittedData::ENCODED_LEN];
+
+        match self {
+            Self::Tcp(stream) => {
+                stream.read_exact(&mut frame).await?;
+            }
+            Self::Udp(socket) => {
+                // Make the buffer longer than needed so that we can detect messages that are too
+                // long
+                let mut buffer = [0u8; TransmittedData::ENCODED_LEN + 1];
+                let len = time::timeout(FRAME_TIMEOUT, socket.recv(&mut buffer)).await??;
+                if len != TransmittedData::ENCODED_LEN {
+                    warn!("Sim: Received message of wrong length: {len}");
+                    return Ok(None);
+                }
+                frame = *array_ref![buffer, 0, TransmittedData::ENCODED_LEN];
+            }
+            Self::Serial(port) => {
+                time::timeout(FRAME_TIMEOUT, Self::read_serial_frame(port, &mut frame)).await??;
+            }
+        }
+
+        Ok(Some(frame))
+    }
+
+    /// Frames aren't delimited on a serial port, so a pause is used to find the start of one
+    async fn read_serial_frame(
+        port: &mut SerialStream,
+        frame: &mut [u8; TransmittedData::ENCODED_LEN],
+    ) -> io::Result<()> {
+        let mut len = 0;
+        while len < frame.len() {
+            match time::timeout(SERIAL_FRAME_GAP, port.read(&mut frame[len..])).await {
+                Ok(Ok(0)) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(read) => len += read?,
+                Err(_) if len > 0 => {
+                    debug!("Sim: Dropping {len} bytes that weren't a whole frame");
+                    len = 0;
+                }
+                Err(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Receives frames from one source, reconnecting whenever the connection fails
+#[derive(Clone, Debug)]
+pub(super) struct SnapshotListener {
+    /// The index of the source, sent with each message
+    pub index: usize,
+    pub source: FrameSource,
+    pub baud_rate: u32,
+}
+
+impl Recipe for SnapshotListener {
+    type Output = Message;
+
+    fn hash(&self, state: &mut iced_core::Hasher) {
+        use std::hash::Hash;
+
+        "SnapshotListener".hash(state);
+        self.index.hash(state);
+        self.source.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: EventStream) -> BoxStream<'static, Self::Output> {
+        info!("Sim: starting listener for {}", self.source);
+
+        #[derive(Debug)]
+        struct State {
+            connection: Option<Connection>,
+            retry_delay: Duration,
+            /// Whether a frame has been received since the source was last lost
+            receiving: bool,
+        }
+
+        let state = State {
+            connection: None,
+            retry_delay: RECONNECT_DELAY,
+            receiving: false,
+        };
+
+        let Self {
+            index,
+            source,
+            baud_rate,
+        } = *self;
+
+        Box::pin(stream::unfold(state, move |mut state| {
+            let source = source.clone();
+            async move {
+                let connection = match state.connection.as_mut() {
+                    Some(connection) => connection,
+                    None => match Connection::open(&source, baud_rate).await {
+                        Ok(connection) => {
+                            info!("Sim: Connected to {source}");
+                            state.retry_delay = RECONNECT_DELAY;
+                            state.connection.insert(connection)
+                        }
+                        Err(e) => {
+                            // Only log the first failure, the source may be gone for a long time
+                            if state.retry_delay == RECONNECT_DELAY {
+                                warn!("Sim: Failed to connect to {source}, retrying: {e:?}");
+                            } else {
+                                debug!("Sim: Failed to connect to {source}: {e:?}");
+                            }
+                            time::sleep(state.retry_delay).await;
+                            state.retry_delay = (state.retry_delay * 2).min(MAX_RECONNECT_DELAY);
+                            return Some((Message::NoAction, state));
+                        }
+                    },
+                };
+
+                let bytes = match connection.read_frame().await {
+                    Ok(Some(bytes)) => bytes,
+                    Ok(None) => return Some((Message::NoAction, state)),
+                    Err(e) => {
+                        state.connection = None;
+                        // Only log the first loss, a UDP or serial source times out again every
+                        // time it is reopened until it sends a frame
+                        if !state.receiving {
+                            debug!("Sim: Nothing received from {source}, reconnecting: {e:?}");
+                            return Some((Message::NoAction, state));
+                        }
+                        warn!("Sim: Lost connection to {source}, reconnecting: {e:?}");
+                        state.receiving = false;
+                        return Some((Message::Disconnected(index), state));
+                    }
+                };
+
+                match TransmittedData::decode(&bytes) {
+                    Ok(data) => {
+                        state.receiving = true;
+                        Some((Message::NewSnapshot(index, data), state))
+                    }
+                    Err(e) => {
+                        warn!("Sim: Decoding error: {e:?}");
+                        Some((Message::NoAction, state))
+                    }
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_panel_source() {
+        let source = |frames, swap_sides| Ok(PanelSource { frames, swap_sides });
+
+        assert_eq!(
+            "localhost:8001".parse(),
+            source(FrameSource::Tcp("localhost:8001".to_string()), false)
+        );
+        assert_eq!(
+            "10.0.0.12:8001/swapped".parse(),
+            source(FrameSource::Tcp("10.0.0.12:8001".to_string()), true)
+        );
+        assert_eq!(
+            "[::1]:8001".parse(),
+            source(FrameSource::Tcp("[::1]:8001".to_string()), false)
+        );
+        assert_eq!("udp:9000".parse(), source(FrameSource::Udp(9000), false));
+        assert_eq!(
+            "serial:/dev/ttyUSB0/swapped".parse(),
+            source(FrameSource::Serial("/dev/ttyUSB0".to_string()), true)
+        );
+
+        assert!("localhost".parse::<PanelSource>().is_err());
+        assert!(":8001".parse::<PanelSource>().is_err());
+        assert!("localhost:http".parse::<PanelSource>().is_err());
+        assert!("udp:70000".parse::<PanelSource>().is_err());
+        assert!("serial:".parse::<PanelSource>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_frames_stay_aligned() {
+        use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+        let listener = TcpListener::bind("localhost:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let frames: Vec<_> = (0..3u8)
+            .map(|i| [i; TransmittedData::ENCODED_LEN])
+            .collect();
+
+        let sent = frames.clone();
+        let sender = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            // One frame split in two, then two frames at once
+            stream.write_all(&sent[0][..10]).await.unwrap();
+            stream.flush().await.unwrap();
+            time::sleep(Duration::from_millis(10)).await;
+            stream.write_all(&sent[0][10..]).await.unwrap();
+            stream
+                .write_all(&[sent[1], sent[2]].concat())
+                .await
+                .unwrap();
+        });
+
+        let mut connection = Connection::open(&FrameSource::Tcp(address), 0)
+            .await
+            .unwrap();
+        for frame in frames {
+            assert_eq!(connection.read_frame().await.unwrap(), Some(frame));
+        }
+        sender.await.unwrap();
+        assert!(connection.read_frame().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_udp_times_out() {
+        let mut connection = Connection::Udp(UdpSocket::bind("localhost:0").await.unwrap());
+        let e = connection.read_frame().await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn test_panel_source_round_trip() {
+        for s in [
+            "court2.local:8001",
+            "court2.local:8001/swapped",
+            "udp:9000",
+            "serial:COM3/swapped",
+        ] {
+            assert_eq!(s.parse::<PanelSource>().unwrap().to_string(), s);
+        }
+    }
+}
@@ -1,56 +1,84 @@
-use arrayref::array_ref;
 use iced::{
     application, executor,
     mouse::Cursor,
     widget::canvas::{Cache, Canvas, Fill, Geometry, Program},
-    Application, Color, Command, Length, Point, Rectangle, Size, Subscription,
+    Application, Color, Command, Length, Point, Rectangle, Size, Subscription, Vector,
 };
-use iced_futures::{
-    futures::{
-        future::{pending, Pending},
-        stream::{self, BoxStream},
-    },
-    subscription::{EventStream, Recipe},
-};
-use iced_runtime::{command, window};
 use log::*;
-use matrix_drawing::{draw_panels, transmitted_data::TransmittedData};
-use std::{rc::Rc, sync::Mutex};
-use tokio::{
-    net::TcpStream,
-    time::{self, Duration},
+use matrix_drawing::{
+    draw_animation_frame, draw_panels_frame, transmitted_data::TransmittedData, PanelLayout,
 };
+use std::{rc::Rc, sync::Mutex, time::Instant};
+use tokio::time::Duration;
 
 mod display_simulator;
 use display_simulator::*;
 
-const WIDTH: usize = 256;
-const HEIGHT: usize = 64;
+mod listener;
+pub use listener::PanelSource;
+use listener::*;
+
+/// How often a scrolling message or an animation is redrawn
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+/// The gap between panels stacked in one window, in pixels of the panels
+const PANEL_GAP: u32 = 8;
+
+/// The height of `count` panels stacked in one window, in pixels of the panels
+fn stack_height(layout: &PanelLayout, count: usize) -> u32 {
+    let count = count.max(1) as u32;
+    count * layout.size.height + (count - 1) * PANEL_GAP
+}
 
-pub fn window_size(scale: f32, spacing: f32) -> (u32, u32) {
+pub fn window_size(layout: &PanelLayout, panels: usize, scale: f32, spacing: f32) -> (u32, u32) {
+    let width = layout.size.width as f32;
+    let height = stack_height(layout, panels) as f32;
     (
-        (WIDTH as f32 * scale + ((WIDTH as f32 + 1.0) * spacing)).ceil() as u32,
-        (HEIGHT as f32 * scale + ((HEIGHT as f32 + 1.0) * spacing)).ceil() as u32,
+        (width * scale + ((width + 1.0) * spacing)).ceil() as u32,
+        (height * scale + ((height + 1.0) * spacing)).ceil() as u32,
     )
 }
 
 #[derive(Clone, Debug)]
 pub enum Message {
-    NewSnapshot(TransmittedData),
-    Stop,
+    /// A frame was received from the source with this index
+    NewSnapshot(usize, TransmittedData),
+    /// The connection to the source with this index was lost
+    Disconnected(usize),
+    /// Time to draw the next frame of a scrolling message or an animation
+    Frame,
     NoAction,
 }
 
 #[derive(Debug)]
 pub struct SimRefBoxApp {
-    buffer: Rc<Mutex<DisplayBuffer<WIDTH, HEIGHT>>>,
+    panels: Vec<SimPanel>,
+    layout: &'static PanelLayout,
+    listeners: Vec<SnapshotListener>,
+}
+
+/// One of the panels shown in the window
+#[derive(Debug)]
+struct SimPanel {
+    buffer: Rc<Mutex<DisplayBuffer>>,
     cache: Cache,
-    listener: SnapshotListener,
+    /// Where the panel gets its frames from
+    source: PanelSource,
+    /// The index of the listener for `source`
+    listener: usize,
+    data: Option<TransmittedData>,
+    /// When the current message was first received
+    message_start: Instant,
+    /// When the current animation was first received
+    animation_start: Instant,
 }
 
 #[derive(Clone, Debug)]
 pub struct SimRefBoxAppFlags {
-    pub tcp_port: u16,
+    /// The panels to show, from top to bottom
+    pub sources: Vec<PanelSource>,
+    /// The baud rate of any serial ports in `sources`
+    pub baud_rate: u32,
+    pub layout: &'static PanelLayout,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -83,39 +111,112 @@ impl Application for SimRefBoxApp {
     type Flags = SimRefBoxAppFlags;
 
     fn new(flags: Self::Flags) -> (Self, Command<Message>) {
-        let Self::Flags { tcp_port } = flags;
+        let Self::Flags {
+            sources,
+            baud_rate,
+            layout,
+        } = flags;
+
+        // Panels that share a source share its listener, since a UDP or serial port can only be
+        // opened once
+        let mut listeners: Vec<SnapshotListener> = vec![];
+        let panels = sources
+            .into_iter()
+            .map(|source| {
+                let listener = match listeners.iter().position(|l| l.source == source.frames) {
+                    Some(index) => index,
+                    None => {
+                        listeners.push(SnapshotListener {
+                            index: listeners.len(),
+                            source: source.frames.clone(),
+                            baud_rate,
+                        });
+                        listeners.len() - 1
+                    }
+                };
+                SimPanel {
+                    buffer: Rc::new(Mutex::new(DisplayBuffer::new(layout.size))),
+                    cache: Cache::new(),
+                    source,
+                    listener,
+                    data: None,
+                    message_start: Instant::now(),
+                    animation_start: Instant::now(),
+                }
+            })
+            .collect();
 
         (
             Self {
-                buffer: Rc::new(Mutex::new(Default::default())),
-                cache: Cache::new(),
-                listener: SnapshotListener { port: tcp_port },
+                panels,
+                layout,
+                listeners,
             },
             Command::none(),
         )
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        Subscription::from_recipe(self.listener.clone())
+        let listeners = self
+            .listeners
+            .iter()
+            .map(|listener| Subscription::from_recipe(listener.clone()));
+        let moving = self.panels.iter().any(|panel| {
+            let scrolling = panel
+                .data
+                .as_ref()
+                .and_then(|data| data.snapshot.message)
+                .is_some_and(|message| !self.layout.message.fits(message.as_str()));
+            scrolling || panel.animation_millis().is_some()
+        });
+        if moving {
+            Subscription::batch(
+                listeners.chain([iced::time::every(FRAME_INTERVAL).map(|_| Message::Frame)]),
+            )
+        } else {
+            Subscription::batch(listeners)
+        }
     }
 
     fn title(&self) -> String {
-        "Panel Simulator".into()
+        let sources: Vec<_> = self
+            .panels
+            .iter()
+            .map(|panel| panel.source.to_string())
+            .collect();
+        format!(
+            "Panel Simulator {} ({})",
+            self.layout.name,
+            sources.join(", ")
+        )
     }
 
     fn update(&mut self, message: Message) -> Command<Message> {
         trace!("Handling message: {message:?}");
+        let layout = self.layout;
         match message {
-            Message::NewSnapshot(data) => {
-                let mut buffer = self.buffer.lock().unwrap();
-                buffer.clear_buffer();
-                draw_panels(&mut *buffer, data.snapshot, data.white_on_right, data.flash).unwrap();
-                self.cache.clear();
-                Command::none()
+            Message::NewSnapshot(listener, data) => {
+                for panel in self.panels.iter_mut().filter(|p| p.listener == listener) {
+                    panel.update(data.clone());
+                    panel.redraw(layout);
+                }
+            }
+            Message::Disconnected(listener) => {
+                // Blank the panels, so that nobody mistakes the last frame for a live one
+                for panel in self.panels.iter_mut().filter(|p| p.listener == listener) {
+                    panel.data = None;
+                    panel.buffer.lock().unwrap().clear_buffer();
+                    panel.cache.clear();
+                }
+            }
+            Message::Frame => {
+                for panel in self.panels.iter_mut() {
+                    panel.redraw(layout);
+                }
             }
-            Message::Stop => Command::single(command::Action::Window(window::Action::Close)),
-            Message::NoAction => Command::none(),
+            Message::NoAction => {}
         }
+        Command::none()
     }
 
     fn view(&self) -> Element<Message> {
@@ -126,6 +227,72 @@ impl Application for SimRefBoxApp {
     }
 }
 
+impl SimPanel {
+    fn update(&mut self, data: TransmittedData) {
+        let old_message = self.data.as_ref().and_then(|old| old.snapshot.message);
+        if data.snapshot.message != old_message {
+            self.message_start = Instant::now();
+        }
+        let old_animation = self
+            .data
+            .as_ref()
+            .and_then(|old| old.snapshot.panel_animation);
+        if data.snapshot.panel_animation.is_some() && data.snapshot.panel_animation != old_animation
+        {
+            self.animation_start = Instant::now();
+        }
+        self.data = Some(data);
+    }
+
+    /// How far through the current animation the panels are, if one is still playing
+    fn animation_millis(&self) -> Option<u32> {
+        let animation = self.data.as_ref()?.snapshot.panel_animation?;
+        let millis = self
+            .animation_start
+            .elapsed()
+            .as_millis()
+            .try_into()
+            .unwrap_or(u32::MAX);
+        (millis < animation.millis()).then_some(millis)
+    }
+
+    fn redraw(&mut self, layout: &PanelLayout) {
+        let Some(data) = self.data.clone() else {
+            return;
+        };
+        let message_millis = self
+            .message_start
+            .elapsed()
+            .as_millis()
+            .try_into()
+            .unwrap_or(u32::MAX);
+        let mut buffer = self.buffer.lock().unwrap();
+        buffer.clear_buffer();
+        let colors = data.colors();
+        match (data.snapshot.panel_animation, self.animation_millis()) {
+            (Some(animation), Some(millis)) if !data.flash => draw_animation_frame(
+                &mut *buffer,
+                &data.snapshot,
+                animation,
+                layout,
+                &colors,
+                millis,
+            ),
+            _ => draw_panels_frame(
+                &mut *buffer,
+                data.snapshot,
+                data.white_on_right != self.source.swap_sides,
+                data.flash,
+                layout,
+                &colors,
+                message_millis,
+            ),
+        }
+        .unwrap();
+        self.cache.clear();
+    }
+}
+
 impl<Message> Program<Message, Renderer> for SimRefBoxApp {
     type State = ();
 
@@ -137,132 +304,41 @@ impl<Message> Program<Message, Renderer> for SimRefBoxApp {
         bounds: Rectangle,
         _cursor: Cursor,
     ) -> Vec<Geometry> {
-        let buffer_ = self.buffer.clone();
-        let panel =
-            self.cache.draw(renderer, bounds.size(), |frame| {
-                let buffer = buffer_.lock().unwrap();
-
-                let horiz_spacing = frame.width() / ((WIDTH * 5 + 1) as f32);
-                let vert_spacing = frame.height() / ((HEIGHT * 5 + 1) as f32);
-                let spacing = if horiz_spacing > vert_spacing {
-                    vert_spacing
-                } else {
-                    horiz_spacing
-                };
-                let scale = spacing * 4.0;
-
-                for (x, y, maybe) in buffer.iter().enumerate().flat_map(|(y, row)| {
-                    row.iter().enumerate().map(move |(x, maybe)| (x, y, maybe))
-                }) {
-                    if let Some(color) = maybe {
-                        let x = spacing + x as f32 * (scale + spacing);
-                        let y = spacing + y as f32 * (scale + spacing);
-                        frame.fill_rectangle(
-                            Point::new(x, y),
-                            Size::new(scale, scale),
-                            Fill::from(*color),
-                        );
-                    }
-                }
-            });
-
-        vec![panel]
-    }
-}
-
-#[derive(Clone, Debug)]
-struct SnapshotListener {
-    port: u16,
-}
-
-impl Recipe for SnapshotListener {
-    type Output = Message;
-
-    fn hash(&self, state: &mut iced_core::Hasher) {
-        use std::hash::Hash;
-
-        "SnapshotListener".hash(state);
-    }
-
-    fn stream(self: Box<Self>, _input: EventStream) -> BoxStream<'static, Self::Output> {
-        info!("Sim: starting listener");
-
-        #[derive(Debug)]
-        struct State {
-            stream: Option<TcpStream>,
-            stop: bool,
-            fail_count: u8,
-        }
-
-        let state = State {
-            stream: None,
-            stop: false,
-            fail_count: 0,
+        let size = self.layout.size;
+        let height = stack_height(self.layout, self.panels.len());
+        let horiz_spacing = bounds.width / ((size.width * 5 + 1) as f32);
+        let vert_spacing = bounds.height / ((height * 5 + 1) as f32);
+        let spacing = if horiz_spacing > vert_spacing {
+            vert_spacing
+        } else {
+            horiz_spacing
         };
-
-        let port = self.port;
-
-        Box::pin(stream::unfold(state, move |mut state| async move {
-            use tokio::io::AsyncReadExt;
-
-            if state.stop {
-                let pend: Pending<()> = pending();
-                // Won't ever return
-                pend.await;
-            }
-
-            if state.stream.is_none() {
-                match TcpStream::connect(("localhost", port)).await {
-                    Ok(conn) => state.stream = Some(conn),
-                    Err(e) => {
-                        warn!("Sim: Failed to connect to refbox: {e:?}");
-                        state.fail_count += 1;
-                        time::sleep(Duration::from_millis(500)).await;
-                        if state.fail_count > 20 {
-                            state.stop = true;
-                            error!("Failed to connect to refbox too many times. Quitting");
-                            return Some((Message::Stop, state));
+        let scale = spacing * 4.0;
+
+        self.panels
+            .iter()
+            .enumerate()
+            .map(|(i, panel)| {
+                let top = (i as u32 * (size.height + PANEL_GAP)) as f32 * (scale + spacing);
+                panel.cache.draw(renderer, bounds.size(), |frame| {
+                    let buffer = panel.buffer.lock().unwrap();
+                    frame.translate(Vector::new(0.0, top));
+
+                    for (x, y, maybe) in buffer.iter().enumerate().flat_map(|(y, row)| {
+                        row.iter().enumerate().map(move |(x, maybe)| (x, y, maybe))
+                    }) {
+                        if let Some(color) = maybe {
+                            let x = spacing + x as f32 * (scale + spacing);
+                            let y = spacing + y as f32 * (scale + spacing);
+                            frame.fill_rectangle(
+                                Point::new(x, y),
+                                Size::new(scale, scale),
+                                Fill::from(*color),
+                            );
                         }
-                        return Some((Message::NoAction, state));
                     }
-                };
-            }
-
-            // Make the buffer longer than needed so that we can detect messages that are too long
-            let mut buffer = [0u8; TransmittedData::ENCODED_LEN + 1];
-
-            match state.stream.as_mut().unwrap().read(&mut buffer).await {
-                Ok(val) if val == TransmittedData::ENCODED_LEN => {}
-                Ok(0) => {
-                    error!("Sim: TCP connection closed, stopping");
-                    state.stop = true;
-                    return Some((Message::Stop, state));
-                }
-                Ok(val) => {
-                    warn!("Sim: Received message of wrong length: {val}");
-                    return Some((Message::NoAction, state));
-                }
-                Err(e) => {
-                    error!("Sim: TCP error: {e:?}");
-                    error!("Sim: Stopping");
-                    state.stop = true;
-                    return Some((Message::Stop, state));
-                }
-            }
-
-            let data = match TransmittedData::decode(array_ref![
-                buffer,
-                0,
-                TransmittedData::ENCODED_LEN
-            ]) {
-                Ok(val) => val,
-                Err(e) => {
-                    warn!("Sim: Decoding error: {e:?}");
-                    return Some((Message::NoAction, state));
-                }
-            };
-
-            Some((Message::NewSnapshot(data), state))
-        }))
+                })
+            })
+            .collect()
     }
 }
@@ -0,0 +1,513 @@
+use super::{BuzzerSound, SoundMessage, SoundSettings};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use uwh_common::game_snapshot::{GamePeriod, GameSnapshot, TimeoutSnapshot};
+
+/// A sound that is played automatically when the clock reaches a certain time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SoundCue {
+    pub period: CuePeriod,
+    pub secs_remaining: u32,
+    pub sound: CueSound,
+    pub channels: CueChannels,
+}
+
+/// Which clock a `SoundCue` watches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CuePeriod {
+    /// Any period of play (the halves and the overtime halves)
+    Play,
+    /// Any break that ends with play starting
+    Break,
+    /// One specific period. Sudden death counts up, so cues for it never play
+    Period(GamePeriod),
+    /// Either team's timeout
+    TeamTimeout,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CueSound {
+    Whistle,
+    /// The buzzer sound selected in the sound settings
+    Buzzer,
+    BuzzerSound(BuzzerSound),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CueChannels {
+    AboveWater,
+    UnderWater,
+    Both,
+}
+
+/// The cue added by the cue editor, which the user then changes to suit
+impl Default for SoundCue {
+    fn default() -> Self {
+        Self {
+            period: CuePeriod::Break,
+            secs_remaining: 30,
+            sound: CueSound::Whistle,
+            channels: CueChannels::AboveWater,
+        }
+    }
+}
+
+impl SoundCue {
+    pub fn default_cues() -> Vec<Self> {
+        vec![
+            Self {
+                period: CuePeriod::Break,
+                secs_remaining: 30,
+                sound: CueSound::Whistle,
+                channels: CueChannels::AboveWater,
+            },
+            Self {
+                period: CuePeriod::Break,
+                secs_remaining: 0,
+                sound: CueSound::Buzzer,
+                channels: CueChannels::Both,
+            },
+            Self {
+                period: CuePeriod::Play,
+                secs_remaining: 0,
+                sound: CueSound::Buzzer,
+                channels: CueChannels::Both,
+            },
+            Self {
+                period: CuePeriod::TeamTimeout,
+                secs_remaining: 15,
+                sound: CueSound::Whistle,
+                channels: CueChannels::AboveWater,
+            },
+            Self {
+                period: CuePeriod::TeamTimeout,
+                secs_remaining: 0,
+                sound: CueSound::Buzzer,
+                channels: CueChannels::Both,
+            },
+        ]
+    }
+
+    fn is_due(&self, old: &GameSnapshot, new: &GameSnapshot, settings: &SoundSettings) -> bool {
+        match new.timeout {
+            TimeoutSnapshot::Black(time) | TimeoutSnapshot::White(time) => {
+                let changed = match old.timeout {
+                    TimeoutSnapshot::Black(old_time) | TimeoutSnapshot::White(old_time) => {
+                        time != old_time
+                    }
+                    _ => false,
+                };
+
+                self.period == CuePeriod::TeamTimeout
+                    && changed
+                    && u32::from(time) == self.secs_remaining
+            }
+            TimeoutSnapshot::Ref(_) | TimeoutSnapshot::PenaltyShot(_) => false,
+            TimeoutSnapshot::None => {
+                if new.current_period == GamePeriod::SuddenDeath
+                    || new.secs_in_period == old.secs_in_period
+                    || new.secs_in_period != self.secs_remaining
+                    || !self.period.includes(new.current_period)
+                {
+                    return false;
+                }
+
+                // Cues at the end of a period are controlled by the auto sound settings
+                if self.secs_remaining == 0 {
+                    if CuePeriod::Break.includes(new.current_period) {
+                        settings.auto_sound_start_play
+                    } else {
+                        settings.auto_sound_stop_play
+                    }
+                } else {
+                    true
+                }
+            }
+        }
+    }
+}
+
+impl CuePeriod {
+    /// The choices offered by the cue editor, in order. Sudden death is left out because its clock
+    /// counts up
+    pub const CHOICES: [Self; 12] = [
+        Self::Play,
+        Self::Break,
+        Self::TeamTimeout,
+        Self::Period(GamePeriod::BetweenGames),
+        Self::Period(GamePeriod::FirstHalf),
+        Self::Period(GamePeriod::HalfTime),
+        Self::Period(GamePeriod::SecondHalf),
+        Self::Period(GamePeriod::PreOvertime),
+        Self::Period(GamePeriod::OvertimeFirstHalf),
+        Self::Period(GamePeriod::OvertimeHalfTime),
+        Self::Period(GamePeriod::OvertimeSecondHalf),
+        Self::Period(GamePeriod::PreSuddenDeath),
+    ];
+
+    fn includes(&self, period: GamePeriod) -> bool {
+        match self {
+            Self::Play => matches!(
+                period,
+                GamePeriod::FirstHalf
+                    | GamePeriod::SecondHalf
+                    | GamePeriod::OvertimeFirstHalf
+                    | GamePeriod::OvertimeSecondHalf
+            ),
+            Self::Break => matches!(
+                period,
+                GamePeriod::BetweenGames
+                    | GamePeriod::HalfTime
+                    | GamePeriod::PreOvertime
+                    | GamePeriod::OvertimeHalfTime
+                    | GamePeriod::PreSuddenDeath
+            ),
+            Self::Period(p) => *p == period,
+            Self::TeamTimeout => false,
+        }
+    }
+}
+
+impl CueSound {
+    /// The whistle, then the selected buzzer, then each buzzer sound in turn
+    pub fn next(&self, custom: &[String]) -> Self {
+        match self {
+            Self::Whistle => Self::Buzzer,
+            Self::Buzzer => match BuzzerSound::next_optional(None, custom) {
+                Some(sound) => Self::BuzzerSound(sound),
+                None => Self::Whistle,
+            },
+            Self::BuzzerSound(sound) => match BuzzerSound::next_optional(Some(sound), custom) {
+                Some(sound) => Self::BuzzerSound(sound),
+                None => Self::Whistle,
+            },
+        }
+    }
+}
+
+impl Display for CuePeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Play => write!(f, "Play"),
+            Self::Break => write!(f, "Break"),
+            Self::TeamTimeout => write!(f, "Team T/O"),
+            Self::Period(period) => match period {
+                GamePeriod::BetweenGames => write!(f, "Between Games"),
+                GamePeriod::FirstHalf => write!(f, "1st Half"),
+                GamePeriod::HalfTime => write!(f, "Half Time"),
+                GamePeriod::SecondHalf => write!(f, "2nd Half"),
+                GamePeriod::PreOvertime => write!(f, "Pre OT"),
+                GamePeriod::OvertimeFirstHalf => write!(f, "OT 1st Half"),
+                GamePeriod::OvertimeHalfTime => write!(f, "OT Half Time"),
+                GamePeriod::OvertimeSecondHalf => write!(f, "OT 2nd Half"),
+                GamePeriod::PreSuddenDeath => write!(f, "Pre SD"),
+                GamePeriod::SuddenDeath => write!(f, "Sudden Death"),
+            },
+        }
+    }
+}
+
+impl Display for CueSound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Whistle => write!(f, "Whistle"),
+            Self::Buzzer => write!(f, "Buzzer"),
+            Self::BuzzerSound(sound) => write!(f, "{sound}"),
+        }
+    }
+}
+
+impl Display for CueChannels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AboveWater => write!(f, "Above"),
+            Self::UnderWater => write!(f, "Under"),
+            Self::Both => write!(f, "Both"),
+        }
+    }
+}
+
+impl SoundSettings {
+    /// Finds the first cue that should be played because of the change from `old` to `new`
+    pub fn due_cue(&self, old: &GameSnapshot, new: &GameSnapshot) -> Option<&SoundCue> {
+        self.cues.iter().find(|cue| cue.is_due(old, new, self))
+    }
+
+    /// The message for the sound controller caused by the change from `old` to `new`, if any
+    pub(super) fn cue_message(
+        &self,
+        old: &GameSnapshot,
+        new: &GameSnapshot,
+    ) -> Option<SoundMessage> {
+        if let Some(cue) = self.due_cue(old, new) {
+            Some(SoundMessage::TriggerCue(cue.sound.clone(), cue.channels))
+        } else if new.timeout == TimeoutSnapshot::None
+            && new.clock_stopped_by_rule
+            && !old.clock_stopped_by_rule
+            && self.auto_sound_stop_play
+        {
+            Some(SoundMessage::TriggerCue(
+                CueSound::Buzzer,
+                CueChannels::Both,
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(current_period: GamePeriod, secs_in_period: u32) -> GameSnapshot {
+        GameSnapshot {
+            current_period,
+            secs_in_period,
+            ..Default::default()
+        }
+    }
+
+    fn timeout_snapshot(timeout: TimeoutSnapshot) -> GameSnapshot {
+        GameSnapshot {
+            current_period: GamePeriod::FirstHalf,
+            secs_in_period: 300,
+            timeout,
+            ..Default::default()
+        }
+    }
+
+    fn due_sound(
+        settings: &SoundSettings,
+        old: &GameSnapshot,
+        new: &GameSnapshot,
+    ) -> Option<CueSound> {
+        settings.due_cue(old, new).map(|cue| cue.sound.clone())
+    }
+
+    #[test]
+    fn test_cue_sound_next() {
+        let custom = ["Siren".to_string()];
+        let mut sound = CueSound::Whistle;
+        let mut seen = vec![];
+        for _ in 0..(BuzzerSound::BUILT_IN.len() + 3) {
+            seen.push(sound.clone());
+            sound = sound.next(&custom);
+        }
+        assert_eq!(sound, CueSound::Whistle);
+        assert_eq!(seen[1], CueSound::Buzzer);
+        assert_eq!(
+            seen[2],
+            CueSound::BuzzerSound(BuzzerSound::BUILT_IN[0].clone())
+        );
+        assert_eq!(
+            seen.last(),
+            Some(&CueSound::BuzzerSound(BuzzerSound::Custom(
+                "Siren".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_cue_period_choices() {
+        for choice in CuePeriod::CHOICES {
+            assert_ne!(choice, CuePeriod::Period(GamePeriod::SuddenDeath));
+        }
+        assert!(CuePeriod::CHOICES.contains(&SoundCue::default().period));
+    }
+
+    #[test]
+    fn test_default_period_cues() {
+        let settings = SoundSettings::default();
+
+        for period in [
+            GamePeriod::BetweenGames,
+            GamePeriod::HalfTime,
+            GamePeriod::PreOvertime,
+            GamePeriod::OvertimeHalfTime,
+            GamePeriod::PreSuddenDeath,
+        ] {
+            assert_eq!(
+                due_sound(&settings, &snapshot(period, 31), &snapshot(period, 30)),
+                Some(CueSound::Whistle)
+            );
+            assert_eq!(
+                due_sound(&settings, &snapshot(period, 30), &snapshot(period, 30)),
+                None
+            );
+            assert_eq!(
+                due_sound(&settings, &snapshot(period, 1), &snapshot(period, 0)),
+                Some(CueSound::Buzzer)
+            );
+        }
+
+        for period in [
+            GamePeriod::FirstHalf,
+            GamePeriod::SecondHalf,
+            GamePeriod::OvertimeFirstHalf,
+            GamePeriod::OvertimeSecondHalf,
+        ] {
+            assert_eq!(
+                due_sound(&settings, &snapshot(period, 31), &snapshot(period, 30)),
+                None
+            );
+            assert_eq!(
+                due_sound(&settings, &snapshot(period, 1), &snapshot(period, 0)),
+                Some(CueSound::Buzzer)
+            );
+        }
+
+        assert_eq!(
+            due_sound(
+                &settings,
+                &snapshot(GamePeriod::SuddenDeath, 29),
+                &snapshot(GamePeriod::SuddenDeath, 30)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_auto_sound_settings() {
+        let mut settings = SoundSettings {
+            auto_sound_start_play: false,
+            ..Default::default()
+        };
+
+        let half_time = GamePeriod::HalfTime;
+        let first_half = GamePeriod::FirstHalf;
+
+        assert_eq!(
+            due_sound(&settings, &snapshot(half_time, 1), &snapshot(half_time, 0)),
+            None
+        );
+        assert_eq!(
+            due_sound(
+                &settings,
+                &snapshot(half_time, 31),
+                &snapshot(half_time, 30)
+            ),
+            Some(CueSound::Whistle)
+        );
+        assert_eq!(
+            due_sound(
+                &settings,
+                &snapshot(first_half, 1),
+                &snapshot(first_half, 0)
+            ),
+            Some(CueSound::Buzzer)
+        );
+
+        settings.auto_sound_start_play = true;
+        settings.auto_sound_stop_play = false;
+
+        assert_eq!(
+            due_sound(&settings, &snapshot(half_time, 1), &snapshot(half_time, 0)),
+            Some(CueSound::Buzzer)
+        );
+        assert_eq!(
+            due_sound(
+                &settings,
+                &snapshot(first_half, 1),
+                &snapshot(first_half, 0)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_default_timeout_cues() {
+        let settings = SoundSettings::default();
+
+        let white = |t| timeout_snapshot(TimeoutSnapshot::White(t));
+        let black = |t| timeout_snapshot(TimeoutSnapshot::Black(t));
+        let referee = |t| timeout_snapshot(TimeoutSnapshot::Ref(t));
+
+        assert_eq!(
+            due_sound(&settings, &white(16), &white(15)),
+            Some(CueSound::Whistle)
+        );
+        assert_eq!(
+            due_sound(&settings, &black(1), &black(0)),
+            Some(CueSound::Buzzer)
+        );
+        assert_eq!(due_sound(&settings, &black(15), &black(15)), None);
+        assert_eq!(due_sound(&settings, &referee(14), &referee(15)), None);
+
+        // Starting a timeout doesn't play anything, even if its time matches a cue
+        assert_eq!(
+            due_sound(
+                &settings,
+                &timeout_snapshot(TimeoutSnapshot::None),
+                &white(15)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_custom_cues() {
+        let siren = BuzzerSound::Custom("Siren".to_string());
+        let settings = SoundSettings {
+            cues: vec![
+                SoundCue {
+                    period: CuePeriod::Play,
+                    secs_remaining: 60,
+                    sound: CueSound::Whistle,
+                    channels: CueChannels::Both,
+                },
+                SoundCue {
+                    period: CuePeriod::Period(GamePeriod::HalfTime),
+                    secs_remaining: 120,
+                    sound: CueSound::BuzzerSound(siren.clone()),
+                    channels: CueChannels::UnderWater,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let first_half = GamePeriod::FirstHalf;
+        let half_time = GamePeriod::HalfTime;
+
+        assert_eq!(
+            due_sound(
+                &settings,
+                &snapshot(first_half, 61),
+                &snapshot(first_half, 60)
+            ),
+            Some(CueSound::Whistle)
+        );
+        assert_eq!(
+            due_sound(
+                &settings,
+                &snapshot(first_half, 1),
+                &snapshot(first_half, 0)
+            ),
+            None
+        );
+        assert_eq!(
+            due_sound(
+                &settings,
+                &snapshot(half_time, 121),
+                &snapshot(half_time, 120)
+            ),
+            Some(CueSound::BuzzerSound(siren))
+        );
+        assert_eq!(
+            due_sound(
+                &settings,
+                &snapshot(GamePeriod::PreOvertime, 121),
+                &snapshot(GamePeriod::PreOvertime, 120)
+            ),
+            None
+        );
+        assert_eq!(
+            due_sound(
+                &settings,
+                &snapshot(half_time, 31),
+                &snapshot(half_time, 30)
+            ),
+            None
+        );
+    }
+}
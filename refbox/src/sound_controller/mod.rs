@@ -1,6 +1,3 @@
-#[cfg(target_os = "linux")]
-use arrayref::array_ref;
-#[cfg(target_os = "linux")]
 use core::{
     future::Future,
     pin::Pin,
@@ -8,30 +5,23 @@ use core::{
 };
 use derivative::Derivative;
 use enum_derive_2018::{EnumDisplay, EnumFromStr};
-#[cfg(target_os = "linux")]
 use futures_lite::future::FutureExt;
 use log::*;
 use macro_attr_2018::macro_attr;
-#[cfg(target_os = "linux")]
-use rppal::gpio::{Gpio, InputPin, Level, Trigger};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-#[cfg(target_os = "linux")]
-use tokio::{
-    sync::watch::Receiver,
-    time::{sleep_until, Instant},
-};
+use std::{collections::BTreeSet, path::Path, sync::Arc};
 use tokio::{
     sync::{
         mpsc::{unbounded_channel, UnboundedSender},
-        watch::{self, Sender},
+        watch::{self, Receiver, Sender},
     },
     task::{self, JoinHandle},
-    time::{sleep, Duration},
+    time::{sleep, sleep_until, Duration, Instant},
 };
 use toml::Table;
+use uwh_common::game_snapshot::GameSnapshot;
 use web_audio_api::{
-    context::{AudioContext, AudioContextOptions, BaseAudioContext},
+    context::{AudioContext, BaseAudioContext},
     media_devices,
     node::{
         AudioBufferSourceNode, AudioNode, AudioScheduledSourceNode, ChannelInterpretation,
@@ -45,16 +35,17 @@ const FADE_WAIT: Duration = Duration::from_millis(50); // TODO: base this on `FA
 
 const SOUND_LEN: f64 = 2.0;
 
-#[cfg(target_os = "linux")]
-const MESSAGE_LEN: usize = 24;
-#[cfg(target_os = "linux")]
-const ID_LEN: usize = 20;
-#[cfg(target_os = "linux")]
-const DATA_LEN: usize = MESSAGE_LEN - ID_LEN;
-
-#[cfg(target_os = "linux")]
 const BUTTON_TIMEOUT: Duration = Duration::from_millis(500);
 
+mod cues;
+pub use cues::*;
+mod offline;
+mod outputs;
+pub use outputs::*;
+mod remote_input;
+pub use remote_input::*;
+mod sound_pack;
+pub use sound_pack::*;
 mod sounds;
 pub use sounds::*;
 
@@ -77,6 +68,9 @@ pub struct SoundSettings {
     #[derivative(Default(value = "true"))]
     pub auto_sound_stop_play: bool,
     pub remotes: Vec<RemoteInfo>,
+    #[derivative(Default(value = "SoundCue::default_cues()"))]
+    pub cues: Vec<SoundCue>,
+    pub channel_map: ChannelMap,
 }
 
 impl SoundSettings {
@@ -91,6 +85,8 @@ impl SoundSettings {
             mut auto_sound_start_play,
             mut auto_sound_stop_play,
             mut remotes,
+            mut cues,
+            mut channel_map,
         } = Default::default();
 
         if let Some(old_sound_enabled) = old.get("sound_enabled") {
@@ -105,9 +101,7 @@ impl SoundSettings {
         }
         if let Some(old_buzzer_sound) = old.get("buzzer_sound") {
             if let Some(old_buzzer_sound) = old_buzzer_sound.as_str() {
-                if let Ok(sound) = old_buzzer_sound.parse() {
-                    buzzer_sound = sound;
-                }
+                buzzer_sound = old_buzzer_sound.to_string().into();
             }
         }
         if let Some(old_whistle_vol) = old.get("whistle_vol") {
@@ -157,6 +151,16 @@ impl SoundSettings {
                     .collect();
             }
         }
+        if let Some(old_cues) = old.get("cues") {
+            if let Ok(old_cues) = old_cues.clone().try_into() {
+                cues = old_cues;
+            }
+        }
+        if let Some(old_channel_map) = old.get("channel_map") {
+            if let Ok(old_channel_map) = old_channel_map.clone().try_into() {
+                channel_map = old_channel_map;
+            }
+        }
 
         Self {
             sound_enabled,
@@ -168,6 +172,8 @@ impl SoundSettings {
             auto_sound_start_play,
             auto_sound_stop_play,
             remotes,
+            cues,
+            channel_map,
         }
     }
 }
@@ -204,30 +210,116 @@ pub struct RemoteInfo {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum SoundMessage {
-    TriggerBuzzer,
-    TriggerWhistle,
-    #[cfg(target_os = "linux")]
+pub enum SoundMessage {
+    TriggerCue(CueSound, CueChannels),
     StartBuzzer(Option<BuzzerSound>),
-    #[cfg(target_os = "linux")]
     StopBuzzer,
+    PreviewBuzzer(SoundSettings),
+    TestTone(LogicalChannel, SoundSettings),
+}
+
+/// What to play in response to a `SoundMessage`
+struct PlayRequest {
+    buffer: AudioBuffer,
+    volumes: ChannelVolumes,
+    repeat: bool,
+    timed: bool,
+    flash: bool,
+    /// Channel map to play on instead of the current one, for checking settings before they
+    /// are applied
+    channel_map: Option<ChannelMap>,
+}
+
+impl SoundMessage {
+    fn play_request(self, settings: &SoundSettings, library: &SoundLibrary) -> Option<PlayRequest> {
+        let request = match self {
+            SoundMessage::TriggerCue(CueSound::Whistle, channels) => {
+                info!("Playing whistle once");
+                PlayRequest {
+                    buffer: library.whistle().clone(),
+                    volumes: ChannelVolumes::for_cue(settings, &CueSound::Whistle, channels),
+                    repeat: false,
+                    timed: false,
+                    flash: false,
+                    channel_map: None,
+                }
+            }
+            SoundMessage::TriggerCue(cue_sound, channels) => {
+                info!("Auto-triggering buzzer");
+                let buzzer_sound = match cue_sound {
+                    CueSound::BuzzerSound(ref buzzer_sound) => buzzer_sound,
+                    _ => &settings.buzzer_sound,
+                };
+                PlayRequest {
+                    buffer: library[buzzer_sound].clone(),
+                    volumes: ChannelVolumes::for_cue(settings, &cue_sound, channels),
+                    repeat: true,
+                    timed: true,
+                    flash: true,
+                    channel_map: None,
+                }
+            }
+            SoundMessage::StartBuzzer(sound_option) => {
+                info!("Starting buzzer");
+                let buzzer_sound = sound_option.as_ref().unwrap_or(&settings.buzzer_sound);
+                PlayRequest {
+                    buffer: library[buzzer_sound].clone(),
+                    volumes: ChannelVolumes::new(settings, false),
+                    repeat: true,
+                    timed: false,
+                    flash: true,
+                    channel_map: None,
+                }
+            }
+            SoundMessage::StopBuzzer => {
+                info!("Stopped buzzer");
+                return None;
+            }
+            SoundMessage::PreviewBuzzer(settings) => {
+                info!("Previewing buzzer sound {}", settings.buzzer_sound);
+                PlayRequest {
+                    buffer: library[&settings.buzzer_sound].clone(),
+                    volumes: ChannelVolumes::new(&settings, false),
+                    repeat: true,
+                    timed: true,
+                    flash: false,
+                    channel_map: Some(settings.channel_map),
+                }
+            }
+            SoundMessage::TestTone(channel, settings) => {
+                info!("Playing test tone on the {channel:?} channel");
+                PlayRequest {
+                    buffer: library.test_tone().clone(),
+                    volumes: ChannelVolumes::for_cue(&settings, &CueSound::Buzzer, channel.into()),
+                    repeat: false,
+                    timed: false,
+                    flash: false,
+                    channel_map: Some(settings.channel_map),
+                }
+            }
+        };
+
+        Some(request)
+    }
 }
 
 pub struct SoundController {
-    _context: Arc<AudioContext>,
     msg_tx: UnboundedSender<SoundMessage>,
     settings_tx: Sender<SoundSettings>,
     stop_tx: Sender<bool>,
     tasks: Vec<JoinHandle<()>>,
-    #[cfg(target_os = "linux")]
     remote_id_rx: Option<Receiver<u32>>,
-    #[cfg(target_os = "linux")]
-    _pins: Option<(InputPin, InputPin)>,
+    custom_sounds: Vec<String>,
+    output_devices: Vec<String>,
 }
 
 impl SoundController {
-    #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
-    pub fn new<F>(mut settings: SoundSettings, trigger_flash: F) -> Self
+    pub fn new<F>(
+        mut settings: SoundSettings,
+        remote_sources: &[RemoteInputSource],
+        sound_pack: Option<&Path>,
+        trigger_flash: F,
+    ) -> Self
     where
         F: Send
             + Fn() -> Result<(), tokio::sync::mpsc::error::TrySendError<ServerMessage>>
@@ -237,19 +329,27 @@ impl SoundController {
 
         info!("Available audio devices:\n{:#?}", available_devices);
 
-        let opts = AudioContextOptions {
-            sample_rate: Some(SAMPLE_RATE),
-            ..AudioContextOptions::default()
-        };
-
-        let context = AudioContext::new(opts);
-        info!("Audio context created with sink {:?}", context.sink_id());
+        let output_devices = output_device_labels(&available_devices);
+        let outputs = AudioOutputs::new(&settings.channel_map);
 
-        let context = Arc::new(context);
+        let custom_sounds = match sound_pack.map(load_sound_pack) {
+            Some(Ok((sounds, errors))) => {
+                for e in errors {
+                    error!("Skipping custom buzzer sound: {e}");
+                }
+                sounds
+            }
+            Some(Err(e)) => {
+                error!("Failed to load sound pack: {e}");
+                vec![]
+            }
+            None => vec![],
+        };
 
-        let library = SoundLibrary::new(&context);
+        let library = SoundLibrary::new(&*outputs.above_water.context, &custom_sounds);
+        let custom_sounds = custom_sounds.into_iter().map(|sound| sound.name).collect();
 
-        let (msg_tx, mut msg_rx) = unbounded_channel();
+        let (msg_tx, mut msg_rx) = unbounded_channel::<SoundMessage>();
 
         let (settings_tx, mut settings_rx) = watch::channel(settings.clone());
         settings_rx.borrow_and_update();
@@ -259,13 +359,13 @@ impl SoundController {
 
         let mut _stop_rx = stop_rx.clone();
         let mut _settings_rx = settings_rx.clone();
-        #[cfg_attr(not(target_os = "linux"), allow(clippy::redundant_clone))]
         let mut _settings = settings.clone();
-        let _context = context.clone();
 
         let handler = task::spawn(async move {
-            #[cfg_attr(not(target_os = "linux"), allow(unused_assignments))]
-            let mut last_sound: Option<Sound> = None;
+            let mut outputs = outputs;
+            // Outputs for a channel map that is being tried out in the settings
+            let mut preview_outputs = None;
+            let mut last_sound: Option<Sound<AudioContext>> = None;
 
             loop {
                 tokio::select! {
@@ -276,33 +376,17 @@ impl SoundController {
                                     sound.stop().await;
                                 }
 
-                                match msg {
-                                    SoundMessage::TriggerBuzzer => {
-                                        info!("Auto-triggering buzzer");
-                                        let volumes = ChannelVolumes::new(&_settings, false);
-                                        let sound = Sound::new(_context.clone(), volumes, library[_settings.buzzer_sound].clone(), true, true);
-                                        trigger_flash().unwrap();
-                                        last_sound = Some(sound);
-                                    }
-                                    SoundMessage::TriggerWhistle => {
-                                        info!("Playing whistle once");
-                                        let volumes = ChannelVolumes::new(&_settings, true);
-                                        let sound = Sound::new(_context.clone(), volumes, library.whistle().clone(), false, false);
-                                        last_sound = Some(sound);
-                                    }
-                                    #[cfg(target_os = "linux")]
-                                    SoundMessage::StartBuzzer(sound_option) => {
-                                        info!("Starting buzzer");
-                                        let buzzer_sound = sound_option.unwrap_or(_settings.buzzer_sound);
-                                        let volumes = ChannelVolumes::new(&_settings, false);
-                                        let sound = Sound::new(_context.clone(), volumes, library[buzzer_sound].clone(), true, false);
+                                if let Some(request) = msg.play_request(&_settings, &library) {
+                                    if request.flash {
                                         trigger_flash().unwrap();
-                                        last_sound = Some(sound);
-                                    }
-                                    #[cfg(target_os = "linux")]
-                                    SoundMessage::StopBuzzer => {
-                                        info!("Stopped buzzer");
                                     }
+                                    let outputs = match request.channel_map {
+                                        Some(ref map) => {
+                                            outputs.for_preview(map, &mut preview_outputs)
+                                        }
+                                        None => outputs.clone(),
+                                    };
+                                    last_sound = Some(Sound::new(&outputs, &request, None));
                                 }
                             },
                             None => break,
@@ -312,6 +396,11 @@ impl SoundController {
                         match maybe_err {
                             Ok(()) => {
                                 _settings = _settings_rx.borrow().clone();
+                                if let Some(sound) = last_sound.take() {
+                                    sound.stop().await;
+                                }
+                                outputs = outputs
+                                    .switch_to(&_settings.channel_map, &mut preview_outputs);
                             }
                             Err(_) => break,
                         }
@@ -323,159 +412,38 @@ impl SoundController {
             }
         });
 
-        #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
         let mut tasks = vec![handler];
 
-        #[cfg(target_os = "linux")]
-        let (_pins, remote_id_rx) = if let Ok(sys_info) = rppal::system::DeviceInfo::new() {
-            info!("Detected a Raspberry Pi system: {sys_info:?}, starting GPIO processes");
-
-            let gpio = Gpio::new().unwrap();
-
-            let mut ant_pin = gpio.get(16).unwrap().into_input_pullup();
-            let (ant_tx, mut ant_rx) = unbounded_channel();
-            ant_pin
-                .set_async_interrupt(Trigger::Both, None, move |event| {
-                    ant_tx.send((event.trigger, Instant::now())).unwrap()
-                })
-                .unwrap();
-
-            let mut wired_pin = gpio.get(12).unwrap().into_input_pullup();
-            let (wired_tx, mut wired_rx) = unbounded_channel();
-            wired_pin
-                .set_async_interrupt(Trigger::Both, None, move |event| {
-                    wired_tx.send(event.trigger).unwrap()
-                })
-                .unwrap();
-
-            let ant_start_state = match ant_pin.read() {
-                Level::Low => Trigger::FallingEdge,
-                Level::High => Trigger::RisingEdge,
-            };
+        let (remote_tx, mut remote_rx) = unbounded_channel();
+        let mut num_inputs = 0;
+        for source in remote_sources {
+            match source.open() {
+                Ok(input) => {
+                    let events = RemoteEventSender::new(num_inputs, remote_tx.clone());
+                    tasks.extend(input.start(events, stop_rx.clone()));
+                    num_inputs += 1;
+                }
+                Err(RemoteInputError::NotARaspberryPi) => {
+                    info!("Not running on a Raspberry Pi, the GPIO remotes are disabled")
+                }
+                Err(e) => error!("Failed to open remote input {source:?}: {e}"),
+            }
+        }
 
-            let (wireless_tx, mut wireless_rx) = unbounded_channel();
+        let remote_id_rx = if num_inputs > 0 {
             let (remote_id_tx, mut remote_id_rx) = watch::channel(0);
             remote_id_rx.borrow_and_update();
 
-            let mut _stop_rx = stop_rx.clone();
-
-            let wireless_button_listener = task::spawn(async move {
-                let mut state = RemoteDetectorState {
-                    preamble_detected: false,
-                    bits: vec![],
-                    last_pin_edge: ant_start_state,
-                    last_edge_time: Instant::now(),
-                    last_pulse: None,
-                };
-
-                loop {
-                    tokio::select! {
-                        pin_update = ant_rx.recv() => {
-                            match pin_update {
-                                Some((l @ Trigger::RisingEdge, now)) => {
-                                    if state.last_pin_edge != l {
-                                        let pulse = now.duration_since(state.last_edge_time).as_micros();
-                                        trace!("Detected LOW  pulse {pulse:>5}us long");
-                                        state.last_pin_edge = l;
-                                        state.last_edge_time = now;
-
-                                        let maybe_pulse_type = identify_pulse(pulse);
-                                        debug!("Detected a LOW  pulse of length {maybe_pulse_type:?}");
-
-                                        if let Some(pulse_type) = maybe_pulse_type {
-                                            if !state.preamble_detected {
-                                                if matches!(state.last_pulse, Some(PulseType::Short))
-                                                    && pulse_type == PulseType::Preamble
-                                                {
-                                                    debug!("Detected a preamble");
-                                                    state.preamble_detected = true;
-                                                }
-                                            } else {
-                                                if matches!(state.last_pulse, Some(PulseType::Short))
-                                                    && pulse_type == PulseType::Long
-                                                {
-                                                    debug!("Detected a low bit");
-                                                    state.bits.push(false);
-                                                } else if matches!(state.last_pulse, Some(PulseType::Long))
-                                                    && pulse_type == PulseType::Short
-                                                {
-                                                    debug!("Detected a high bit");
-                                                    state.bits.push(true);
-                                                } else {
-                                                    info!("Detected an invalid pulse sequence");
-                                                    state.preamble_detected = false;
-                                                    state.bits.clear();
-                                                }
-
-                                                if state.bits.len() == MESSAGE_LEN {
-                                                    let message: String = state
-                                                        .bits
-                                                        .iter()
-                                                        .map(|bit| if *bit { '1' } else { '0' })
-                                                        .collect();
-                                                    debug!("Received a complete message: 0b{message}");
-
-                                                    let remote_id = state.bits[..ID_LEN]
-                                                        .iter()
-                                                        .fold(0, |acc, &b| acc * 2 + b as u32);
-                                                    let data = array_ref![state.bits, ID_LEN, DATA_LEN];
-
-                                                    debug!("Remote {remote_id} sent data {data:?}");
-                                                    wireless_tx.send(remote_id).unwrap();
-                                                    remote_id_tx.send(remote_id).unwrap();
-
-                                                    state.preamble_detected = false;
-                                                    state.bits.clear();
-                                                }
-                                            }
-                                        } else {
-                                            trace!("Detected an invalid pulse");
-                                            state.preamble_detected = false;
-                                            state.bits.clear();
-                                        }
-
-                                        state.last_pulse = maybe_pulse_type;
-                                    }
-                                }
-                                Some((l @ Trigger::FallingEdge, now)) => {
-                                    if state.last_pin_edge != l {
-                                        let pulse = now.duration_since(state.last_edge_time).as_micros();
-                                        trace!("Detected HIGH pulse {pulse:>5}us long");
-                                        state.last_pin_edge = l;
-                                        state.last_edge_time = now;
-
-                                        let maybe_pulse_type = identify_pulse(pulse);
-                                        debug!("Detected a LOW  pulse of length {maybe_pulse_type:?}");
-
-                                        if maybe_pulse_type.is_none() {
-                                            trace!("Detected an invalid pulse");
-                                            state.preamble_detected = false;
-                                            state.bits.clear();
-                                        }
-
-                                        state.last_pulse = maybe_pulse_type;
-                                    }
-                                }
-                                Some((Trigger::Disabled, _))
-                                | Some((Trigger::Both, _)) => panic!("Imposible pin state"),
-                                None => panic!("The Pin has been dropped"),
-                            }
-                        }
-                        _ = _stop_rx.changed() => {
-                            break;
-                        }
-                    }
-                }
-            });
-
-            tasks.push(wireless_button_listener);
-
             let mut _msg_tx = msg_tx.clone();
             let mut _stop_rx = stop_rx.clone();
             let mut _settings_rx = settings_rx.clone();
 
             let button_listener = task::spawn(async move {
-                let mut wired_pressed = false;
+                // Keeps the channel open once the inputs are finished (e.g. at the end of a
+                // simulation), so that a remote that is still pressed times out normally
+                let _remote_tx = remote_tx;
+                // The inputs whose wired buttons are held, the buzzer sounds until all are released
+                let mut wired_pressed = BTreeSet::new();
                 let mut wireless_pressed = false;
                 let mut wireless_expires = None;
                 let mut sound = None;
@@ -493,24 +461,22 @@ impl SoundController {
                     tokio::pin!(wireless_expiration);
 
                     tokio::select! {
-                        event = wired_rx.recv() => {
+                        event = remote_rx.recv() => {
                             match event {
-                                Some(Trigger::FallingEdge) => wired_pressed = false,
-                                Some(Trigger::RisingEdge) => {
-                                    wired_pressed = true;
+                                Some((input, RemoteEvent::Wired(false))) => {
+                                    wired_pressed.remove(&input);
+                                }
+                                Some((input, RemoteEvent::Wired(true))) => {
+                                    wired_pressed.insert(input);
                                     sound = None;
                                 }
-                                Some(Trigger::Disabled)
-                                | Some(Trigger::Both) => panic!("Imposible pin state"),
-                                None => break,
-                            }
-                        }
-                        remote = wireless_rx.recv() => {
-                            match remote {
-                                Some(id) => if let Some(rem) = settings.remotes.iter().find(|rem| rem.id == id) {
-                                    wireless_pressed = true;
-                                    wireless_expires = Some(Instant::now() + BUTTON_TIMEOUT);
-                                    sound = rem.sound;
+                                Some((_, RemoteEvent::Wireless(id))) => {
+                                    remote_id_tx.send(id).unwrap();
+                                    if let Some(rem) = settings.remotes.iter().find(|rem| rem.id == id) {
+                                        wireless_pressed = true;
+                                        wireless_expires = Some(Instant::now() + BUTTON_TIMEOUT);
+                                        sound = rem.sound.clone();
+                                    }
                                 }
                                 None => break,
                             }
@@ -530,38 +496,36 @@ impl SoundController {
                         _ = _stop_rx.changed() => break,
                     }
 
-                    let pressed = wired_pressed || wireless_pressed;
+                    let pressed = !wired_pressed.is_empty() || wireless_pressed;
                     if pressed != was_pressed || sound != last_sound {
                         _msg_tx
                             .send(if pressed {
-                                SoundMessage::StartBuzzer(sound)
+                                SoundMessage::StartBuzzer(sound.clone())
                             } else {
                                 SoundMessage::StopBuzzer
                             })
                             .unwrap();
                         was_pressed = pressed;
-                        last_sound = sound;
+                        last_sound = sound.clone();
                     }
                 }
             });
 
             tasks.push(button_listener);
 
-            (Some((wired_pin, ant_pin)), Some(remote_id_rx))
+            Some(remote_id_rx)
         } else {
-            (None, None)
+            None
         };
 
         Self {
-            _context: context,
             msg_tx,
             settings_tx,
             stop_tx,
             tasks,
-            #[cfg(target_os = "linux")]
             remote_id_rx,
-            #[cfg(target_os = "linux")]
-            _pins,
+            custom_sounds,
+            output_devices,
         }
     }
 
@@ -569,23 +533,47 @@ impl SoundController {
         self.settings_tx.send(settings).unwrap()
     }
 
-    pub fn trigger_whistle(&self) {
-        self.msg_tx.send(SoundMessage::TriggerWhistle).unwrap()
+    /// Plays whatever sound `settings` calls for as the game moves from `old` to `new`
+    pub fn play_cues(&self, settings: &SoundSettings, old: &GameSnapshot, new: &GameSnapshot) {
+        if let Some(msg) = settings.cue_message(old, new) {
+            info!("Triggering sound cue {msg:?}");
+            self.msg_tx.send(msg).unwrap()
+        }
+    }
+
+    /// Plays the buzzer once using `settings` instead of the current settings, so that
+    /// changes can be heard before they are applied. Does not flash the display.
+    pub fn preview_buzzer(&self, settings: SoundSettings) {
+        self.msg_tx
+            .send(SoundMessage::PreviewBuzzer(settings))
+            .unwrap()
     }
 
-    pub fn trigger_buzzer(&self) {
-        self.msg_tx.send(SoundMessage::TriggerBuzzer).unwrap()
+    /// The names of the buzzer sounds that were loaded from the sound pack
+    pub fn custom_sounds(&self) -> &[String] {
+        &self.custom_sounds
+    }
+
+    /// Plays a tone on one channel only, using `settings` so that changes to the channel map can
+    /// be checked before they are applied
+    pub fn play_test_tone(&self, channel: LogicalChannel, settings: SoundSettings) {
+        self.msg_tx
+            .send(SoundMessage::TestTone(channel, settings))
+            .unwrap()
+    }
+
+    /// The labels of the audio output devices that were available at startup
+    pub fn output_devices(&self) -> &[String] {
+        &self.output_devices
     }
 
     /// Waits for a remote to be detected, then passes the id value to `callback`.
     /// If buttons are not available on the current system, `callback` will never
     /// be called.
-    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
     pub fn request_next_remote_id<F>(&self, callback: F)
     where
         F: FnOnce(u32) + Send + 'static,
     {
-        #[cfg(target_os = "linux")]
         if let Some(mut rx) = self.remote_id_rx.clone() {
             rx.borrow_and_update();
             task::spawn(async move {
@@ -633,144 +621,149 @@ impl ChannelVolumes {
             },
         }
     }
+
+    fn for_cue(settings: &SoundSettings, sound: &CueSound, channels: CueChannels) -> Self {
+        let volumes = match sound {
+            CueSound::Whistle => {
+                let vol = if settings.sound_enabled && settings.whistle_enabled {
+                    settings.whistle_vol.as_f32()
+                } else {
+                    0.0
+                };
+                Self {
+                    left: vol,
+                    right: vol,
+                }
+            }
+            CueSound::Buzzer | CueSound::BuzzerSound(_) => Self::new(settings, false),
+        };
+
+        match channels {
+            CueChannels::AboveWater => Self {
+                right: 0.0,
+                ..volumes
+            },
+            CueChannels::UnderWater => Self {
+                left: 0.0,
+                ..volumes
+            },
+            CueChannels::Both => volumes,
+        }
+    }
 }
 
-struct Sound {
-    _merger: ChannelMergerNode,
-    gain_l: GainNode,
-    gain_r: GainNode,
+/// The nodes playing a `Sound` on one audio context
+struct SoundOutput<C: BaseAudioContext> {
+    context: Arc<C>,
+    merger: ChannelMergerNode,
     source: AudioBufferSourceNode,
-    context: Arc<AudioContext>,
-    volumes: ChannelVolumes,
+    gains: Vec<GainNode>,
+}
+
+struct Sound<C: BaseAudioContext> {
+    outputs: Vec<SoundOutput<C>>,
 }
 
-impl Sound {
-    fn new(
-        context: Arc<AudioContext>,
-        volumes: ChannelVolumes,
-        buffer: AudioBuffer,
-        repeat: bool,
-        timed: bool,
-    ) -> Self {
-        let _merger = context.create_channel_merger(2);
-        _merger.set_channel_interpretation(ChannelInterpretation::Speakers);
-        _merger.connect(&context.destination());
-
-        let gain_l = context.create_gain();
-        gain_l.connect_from_output_to_input(&_merger, 0, 0);
-        gain_l.gain().set_value(volumes.left);
-
-        let gain_r = context.create_gain();
-        gain_r.connect_from_output_to_input(&_merger, 0, 1);
-        gain_r.gain().set_value(volumes.right);
-
-        let mut source = context.create_buffer_source();
-        source.set_buffer(buffer);
-        source.connect(&gain_l);
-        source.connect(&gain_r);
-        source.set_loop(repeat);
-
-        let fade_end = context.current_time() + FADE_LEN;
-
-        // Set the gains so that the start of the fade is now
-        gain_l.gain().set_value(0.0);
-        gain_r.gain().set_value(0.0);
-
-        gain_l
-            .gain()
-            .linear_ramp_to_value_at_time(volumes.left, fade_end);
-        gain_r
-            .gain()
-            .linear_ramp_to_value_at_time(volumes.right, fade_end);
-
-        if timed {
-            let sound_end = fade_end + SOUND_LEN;
-            let fade_out_end = sound_end + FADE_LEN;
-
-            gain_l.gain().set_value_at_time(volumes.left, sound_end);
-            gain_l
-                .gain()
-                .linear_ramp_to_value_at_time(0.0, fade_out_end);
-
-            gain_r.gain().set_value_at_time(volumes.right, sound_end);
-            gain_r
-                .gain()
-                .linear_ramp_to_value_at_time(0.0, fade_out_end);
+impl<C: BaseAudioContext> Sound<C> {
+    /// Starts playing `request` at `start` (in the time of the audio context), or immediately if
+    /// `start` is `None`
+    fn new(outputs: &AudioOutputs<C>, request: &PlayRequest, start: Option<f64>) -> Self {
+        let mut sound_outputs: Vec<SoundOutput<C>> = vec![];
+
+        for (target, volume) in [
+            (&outputs.above_water, request.volumes.left),
+            (&outputs.under_water, request.volumes.right),
+        ] {
+            let context = &target.context;
+
+            // Both channels share the source when they are on the same device
+            let output = match sound_outputs
+                .iter_mut()
+                .position(|o| Arc::ptr_eq(&o.context, context))
+            {
+                Some(i) => &mut sound_outputs[i],
+                None => {
+                    let destination = context.destination();
+                    let merger = context.create_channel_merger(destination.channel_count());
+                    merger.set_channel_interpretation(ChannelInterpretation::Discrete);
+                    merger.connect(&destination);
+
+                    let mut source = context.create_buffer_source();
+                    source.set_buffer(request.buffer.clone());
+                    source.set_loop(request.repeat);
+
+                    sound_outputs.push(SoundOutput {
+                        context: context.clone(),
+                        merger,
+                        source,
+                        gains: vec![],
+                    });
+                    sound_outputs.last_mut().unwrap()
+                }
+            };
+
+            let gain = context.create_gain();
+            gain.connect_from_output_to_input(&output.merger, 0, target.channel);
+            output.source.connect(&gain);
+
+            let start = start.unwrap_or_else(|| context.current_time());
+            let fade_end = start + FADE_LEN;
+
+            gain.gain().set_value_at_time(0.0, start);
+            gain.gain().linear_ramp_to_value_at_time(volume, fade_end);
+
+            if request.timed {
+                let sound_end = fade_end + SOUND_LEN;
+                let fade_out_end = sound_end + FADE_LEN;
+
+                gain.gain().set_value_at_time(volume, sound_end);
+                gain.gain().linear_ramp_to_value_at_time(0.0, fade_out_end);
+            }
+
+            output.gains.push(gain);
         }
 
-        source.start();
+        for output in sound_outputs.iter_mut() {
+            let start = start.unwrap_or_else(|| output.context.current_time());
+            output.source.start_at(start);
+        }
 
         Self {
-            _merger,
-            gain_l,
-            gain_r,
-            source,
-            context,
-            volumes,
+            outputs: sound_outputs,
         }
     }
 
-    async fn stop(mut self) {
-        let fade_end = self.context.current_time() + FADE_LEN;
-
-        // Set the gains so that the start of the fade is now, not when the sound started
-        self.gain_l.gain().set_value(self.volumes.left);
-        self.gain_r.gain().set_value(self.volumes.right);
-
-        self.gain_l
-            .gain()
-            .linear_ramp_to_value_at_time(0.0, fade_end);
-        self.gain_r
-            .gain()
-            .linear_ramp_to_value_at_time(0.0, fade_end);
+    /// Fades the sound out starting at `at` (in the time of the audio context), or immediately
+    /// if `at` is `None`
+    fn fade_out(&mut self, at: Option<f64>) {
+        for output in self.outputs.iter_mut() {
+            let at = at.unwrap_or_else(|| output.context.current_time());
+            let fade_end = at + FADE_LEN;
+
+            // Hold whatever level the gains have reached, so that a timed sound that has already
+            // faded out stays silent
+            for gain in output.gains.iter() {
+                gain.gain().cancel_and_hold_at_time(at);
+                gain.gain().linear_ramp_to_value_at_time(0.0, fade_end);
+            }
 
-        sleep(FADE_WAIT).await;
-        self.source.stop();
+            output.source.stop_at(fade_end);
+        }
     }
 }
 
-#[cfg(target_os = "linux")]
-const fn identify_pulse(len: u128) -> Option<PulseType> {
-    const SHORT_PULSE_BOT_THRESH: u128 = 200;
-    const SHORT_PULSE_TOP_THRESH: u128 = 500;
-    const LONG_PULSE_BOT_THRESH: u128 = 800;
-    const LONG_PULSE_TOP_THRESH: u128 = 1500;
-    const PREAMBLE_PULSE_BOT_THRESH: u128 = 9000;
-    const PREAMBLE_PULSE_TOP_THRESH: u128 = 12000;
-
-    match len {
-        SHORT_PULSE_BOT_THRESH..=SHORT_PULSE_TOP_THRESH => Some(PulseType::Short),
-        LONG_PULSE_BOT_THRESH..=LONG_PULSE_TOP_THRESH => Some(PulseType::Long),
-        PREAMBLE_PULSE_BOT_THRESH..=PREAMBLE_PULSE_TOP_THRESH => Some(PulseType::Preamble),
-        _ => None,
+impl Sound<AudioContext> {
+    async fn stop(mut self) {
+        self.fade_out(None);
+        sleep(FADE_WAIT).await;
     }
 }
 
-#[cfg(target_os = "linux")]
-#[derive(Debug, PartialEq, Eq)]
-struct RemoteDetectorState {
-    preamble_detected: bool,
-    bits: Vec<bool>,
-    last_pin_edge: Trigger,
-    last_edge_time: Instant,
-    last_pulse: Option<PulseType>,
-}
-
-#[cfg(target_os = "linux")]
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum PulseType {
-    Short,
-    Long,
-    Preamble,
-}
-
-#[cfg(target_os = "linux")]
 enum WirelessTimeout {
     Never(core::future::Pending<()>),
     Time(Pin<Box<tokio::time::Sleep>>),
 }
 
-#[cfg(target_os = "linux")]
 impl Future for WirelessTimeout {
     type Output = ();
 
@@ -785,6 +778,7 @@ impl Future for WirelessTimeout {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use uwh_common::game_snapshot::GamePeriod;
 
     #[test]
     fn test_ser_sound_settings() {
@@ -794,6 +788,58 @@ mod tests {
         assert_eq!(deser, Ok(settings));
     }
 
+    #[test]
+    fn test_ser_custom_buzzer_sound() {
+        let settings = SoundSettings {
+            buzzer_sound: BuzzerSound::Custom("Air Horn".to_string()),
+            remotes: vec![
+                RemoteInfo {
+                    id: 1,
+                    sound: Some(BuzzerSound::DeDeDu),
+                },
+                RemoteInfo {
+                    id: 2,
+                    sound: Some(BuzzerSound::Custom("Siren".to_string())),
+                },
+            ],
+            ..Default::default()
+        };
+        let serialized = toml::to_string(&settings).unwrap();
+        assert!(serialized.contains("buzzer_sound = \"Air Horn\""));
+        assert!(serialized.contains("sound = \"DeDeDu\""));
+        assert!(serialized.contains("sound = \"Siren\""));
+        let deser = toml::from_str(&serialized);
+        assert_eq!(deser, Ok(settings));
+    }
+
+    #[test]
+    fn test_cycle_buzzer_sound() {
+        let custom = vec!["Air Horn".to_string(), "Siren".to_string()];
+        let air_horn = BuzzerSound::Custom("Air Horn".to_string());
+        let siren = BuzzerSound::Custom("Siren".to_string());
+
+        assert_eq!(BuzzerSound::Buzz.next(&custom), BuzzerSound::Whoop);
+        assert_eq!(BuzzerSound::TwoTone.next(&custom), air_horn);
+        assert_eq!(air_horn.next(&custom), siren);
+        assert_eq!(siren.next(&custom), BuzzerSound::Buzz);
+        assert_eq!(BuzzerSound::TwoTone.next(&[]), BuzzerSound::Buzz);
+        assert_eq!(siren.next(&[]), BuzzerSound::Buzz);
+
+        assert_eq!(
+            BuzzerSound::next_optional(None, &custom),
+            Some(BuzzerSound::Buzz)
+        );
+        assert_eq!(
+            BuzzerSound::next_optional(Some(&BuzzerSound::TwoTone), &custom),
+            Some(air_horn)
+        );
+        assert_eq!(BuzzerSound::next_optional(Some(&siren), &custom), None);
+        assert_eq!(
+            BuzzerSound::next_optional(Some(&BuzzerSound::TwoTone), &[]),
+            None
+        );
+    }
+
     #[test]
     fn test_ser_volume() {
         #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -906,5 +952,119 @@ mod tests {
                 },
             ]
         );
+        assert_eq!(settings.cues, SoundCue::default_cues());
+        assert_eq!(settings.channel_map, ChannelMap::default());
+    }
+
+    #[test]
+    fn test_migrate_channel_map() {
+        let old: Table = toml::from_str(
+            r#"
+            [channel_map.above_water]
+            channel = 0
+
+            [channel_map.under_water]
+            device = "USB Audio Device"
+            channel = 1
+            "#,
+        )
+        .unwrap();
+
+        let settings = SoundSettings::migrate(&old);
+
+        assert_eq!(
+            settings.channel_map,
+            ChannelMap {
+                above_water: ChannelOutput {
+                    device: None,
+                    channel: 0,
+                },
+                under_water: ChannelOutput {
+                    device: Some("USB Audio Device".to_string()),
+                    channel: 1,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_migrate_sound_cues() {
+        let old: Table = toml::from_str(
+            r#"
+            sound_enabled = true
+
+            [[cues]]
+            period = "Play"
+            secs_remaining = 60
+            sound = "Whistle"
+            channels = "Both"
+
+            [[cues]]
+            period = { Period = "HalfTime" }
+            secs_remaining = 120
+            sound = { BuzzerSound = "Siren" }
+            channels = "UnderWater"
+            "#,
+        )
+        .unwrap();
+
+        let settings = SoundSettings::migrate(&old);
+
+        assert_eq!(
+            settings.cues,
+            vec![
+                SoundCue {
+                    period: CuePeriod::Play,
+                    secs_remaining: 60,
+                    sound: CueSound::Whistle,
+                    channels: CueChannels::Both,
+                },
+                SoundCue {
+                    period: CuePeriod::Period(GamePeriod::HalfTime),
+                    secs_remaining: 120,
+                    sound: CueSound::BuzzerSound(BuzzerSound::Custom("Siren".to_string())),
+                    channels: CueChannels::UnderWater,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cue_channel_volumes() {
+        let settings = SoundSettings {
+            whistle_vol: Volume::Max,
+            above_water_vol: Volume::Low,
+            under_water_vol: Volume::High,
+            ..Default::default()
+        };
+
+        let max = Volume::Max.as_f32();
+        let low = Volume::Low.as_f32();
+        let high = Volume::High.as_f32();
+
+        let vols = |sound, channels| {
+            let vols = ChannelVolumes::for_cue(&settings, &sound, channels);
+            (vols.left, vols.right)
+        };
+
+        assert_eq!(vols(CueSound::Whistle, CueChannels::AboveWater), (max, 0.0));
+        assert_eq!(vols(CueSound::Whistle, CueChannels::UnderWater), (0.0, max));
+        assert_eq!(vols(CueSound::Whistle, CueChannels::Both), (max, max));
+        assert_eq!(vols(CueSound::Buzzer, CueChannels::Both), (low, high));
+        assert_eq!(vols(CueSound::Buzzer, CueChannels::AboveWater), (low, 0.0));
+        assert_eq!(
+            vols(
+                CueSound::BuzzerSound(BuzzerSound::Crazy),
+                CueChannels::UnderWater
+            ),
+            (0.0, high)
+        );
+
+        let settings = SoundSettings {
+            whistle_enabled: false,
+            ..settings
+        };
+        let vols = ChannelVolumes::for_cue(&settings, &CueSound::Whistle, CueChannels::Both);
+        assert_eq!((vols.left, vols.right), (0.0, 0.0));
     }
 }
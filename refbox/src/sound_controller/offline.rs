@@ -0,0 +1,328 @@
+//! Renders the output of the sound controller to a WAV file instead of the speakers, so that the
+//! sounds played during a game can be checked without real time or audio hardware.
+
+use super::*;
+use crate::tournament_manager::TournamentManager;
+use hound::{SampleFormat, WavSpec, WavWriter};
+use tokio::time::Instant;
+use uwh_common::{config::Game as GameConfig, game_snapshot::GamePeriod};
+use web_audio_api::context::OfflineAudioContext;
+
+/// How often the game is checked for cues when simulating one
+const STEP: Duration = Duration::from_millis(50);
+
+impl SoundController {
+    /// Renders the sounds that the `SoundController` would play in response to `messages`, each
+    /// sent at the paired time after the start of the recording, and writes them to `path` as a
+    /// 32 bit float WAV file. Above water is rendered to the left channel and under water to the
+    /// right. Flashes are not rendered, and requests to play on a different channel map are
+    /// played on the default one. The rendered samples are returned as well.
+    pub fn render_offline(
+        settings: &SoundSettings,
+        custom_sounds: &[CustomSound],
+        messages: Vec<(Duration, SoundMessage)>,
+        len: Duration,
+        path: &Path,
+    ) -> Result<AudioBuffer, hound::Error> {
+        let buffer = render(settings, custom_sounds, messages, len);
+        write_wav(&buffer, path)?;
+        Ok(buffer)
+    }
+
+    /// Plays a game with `config` from the start of the break before it, collecting the messages
+    /// that would be sent to the sound controller along the way. Stops once the game is over, or
+    /// after `max_len` if that comes first. Also returns how long a recording of the messages
+    /// needs to be to hear the last sound in full.
+    pub fn game_cues(
+        config: GameConfig,
+        settings: &SoundSettings,
+        max_len: Duration,
+    ) -> (Vec<(Duration, SoundMessage)>, Duration) {
+        let mut tm = TournamentManager::new(config);
+        let start = Instant::now();
+        tm.start_clock(start);
+        let mut old = tm.generate_snapshot(start).unwrap();
+
+        let mut messages = vec![];
+        let mut elapsed = Duration::ZERO;
+        let mut started = false;
+        while elapsed < max_len {
+            elapsed += STEP;
+            let now = start + elapsed;
+            if let Err(e) = tm.update(now) {
+                error!("Stopped simulating the game at {elapsed:?}: {e}");
+                break;
+            }
+            let Some(new) = tm.generate_snapshot(now) else {
+                break;
+            };
+            if let Some(msg) = settings.cue_message(&old, &new) {
+                messages.push((elapsed, msg));
+            }
+
+            started |= new.current_period != GamePeriod::BetweenGames;
+            let over = started && new.current_period == GamePeriod::BetweenGames;
+            old = new;
+            if over {
+                return (messages, elapsed + Duration::from_secs_f64(SOUND_LEN));
+            }
+        }
+
+        (messages, elapsed)
+    }
+}
+
+fn render(
+    settings: &SoundSettings,
+    custom_sounds: &[CustomSound],
+    messages: Vec<(Duration, SoundMessage)>,
+    len: Duration,
+) -> AudioBuffer {
+    let length = (len.as_secs_f64() * SAMPLE_RATE as f64).round() as usize;
+    let context = Arc::new(OfflineAudioContext::new(2, length, SAMPLE_RATE));
+    let library = SoundLibrary::new(&*context, custom_sounds);
+    let outputs = AudioOutputs::offline(context.clone());
+
+    let mut last_sound: Option<Sound<OfflineAudioContext>> = None;
+    for (at, msg) in messages {
+        // Like the live controller, every message stops the previous sound before anything new
+        // is started
+        let mut start = at.as_secs_f64();
+        if let Some(mut sound) = last_sound.take() {
+            sound.fade_out(Some(start));
+            start += FADE_LEN;
+        }
+
+        if let Some(request) = msg.play_request(settings, &library) {
+            last_sound = Some(Sound::new(&outputs, &request, Some(start)));
+        }
+    }
+
+    // The nodes keep playing once scheduled, but the context can only be rendered once nothing
+    // else holds a reference to it
+    drop(last_sound);
+    drop(outputs);
+    let mut context = Arc::into_inner(context).unwrap();
+    context.start_rendering_sync()
+}
+
+/// Writes `buffer` to `path` as a 32 bit float WAV file
+fn write_wav(buffer: &AudioBuffer, path: &Path) -> Result<(), hound::Error> {
+    let spec = WavSpec {
+        channels: buffer.number_of_channels() as u16,
+        sample_rate: buffer.sample_rate() as u32,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Float,
+    };
+    let mut writer = WavWriter::create(path, spec)?;
+
+    let channels: Vec<&[f32]> = (0..buffer.number_of_channels())
+        .map(|i| buffer.get_channel_data(i))
+        .collect();
+    for i in 0..buffer.length() {
+        for channel in channels.iter() {
+            writer.write_sample(channel[i])?;
+        }
+    }
+
+    writer.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Length of the RMS windows used to find sounds, in samples
+    const WINDOW: usize = 441;
+    const THRESHOLD: f32 = 0.01;
+    /// Sounds separated by less than this many windows of silence are treated as one
+    const MIN_GAP: usize = 50;
+    /// How far two times can be apart and still match, in seconds
+    const TOLERANCE: f64 = 0.15;
+
+    /// Breaks just long enough for the 30 second whistle to be heard apart from the buzzers, and
+    /// short halves
+    fn short_game() -> GameConfig {
+        GameConfig {
+            overtime_allowed: false,
+            sudden_death_allowed: false,
+            half_play_duration: Duration::from_secs(10),
+            half_time_duration: Duration::from_secs(34),
+            nominal_break: Duration::from_secs(32),
+            ..Default::default()
+        }
+    }
+
+    fn temp_wav(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("refbox-{name}-{}.wav", std::process::id()))
+    }
+
+    /// Renders through a temporary WAV file, returning only the samples
+    fn render_samples(
+        name: &str,
+        settings: &SoundSettings,
+        messages: Vec<(Duration, SoundMessage)>,
+        len: Duration,
+    ) -> AudioBuffer {
+        let path = temp_wav(name);
+        let buffer = SoundController::render_offline(settings, &[], messages, len, &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        buffer
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// Finds the start time of each sound in `buffer` and the channels it was heard on
+    fn find_sounds(buffer: &AudioBuffer) -> Vec<(f64, CueChannels)> {
+        let left: Vec<f32> = buffer.get_channel_data(0).chunks(WINDOW).map(rms).collect();
+        let right: Vec<f32> = buffer.get_channel_data(1).chunks(WINDOW).map(rms).collect();
+
+        let mut sounds = vec![];
+        let mut last_loud: Option<usize> = None;
+        for i in 0..left.len() {
+            if left[i].max(right[i]) < THRESHOLD {
+                continue;
+            }
+
+            if last_loud.is_none_or(|last| i - last > MIN_GAP) {
+                // Classify the sound once it has finished fading in
+                let j = (i + 10).min(left.len() - 1);
+                let channels = match (left[j] >= THRESHOLD, right[j] >= THRESHOLD) {
+                    (true, false) => CueChannels::AboveWater,
+                    (false, true) => CueChannels::UnderWater,
+                    _ => CueChannels::Both,
+                };
+                sounds.push(((i * WINDOW) as f64 / SAMPLE_RATE as f64, channels));
+            }
+            last_loud = Some(i);
+        }
+
+        sounds
+    }
+
+    fn assert_sounds(found: &[(f64, CueChannels)], expected: &[(f64, CueChannels)]) {
+        assert_eq!(
+            found.len(),
+            expected.len(),
+            "found {found:?}, expected {expected:?}"
+        );
+        for (found, expected) in found.iter().zip(expected) {
+            assert!(
+                (found.0 - expected.0).abs() <= TOLERANCE && found.1 == expected.1,
+                "found {found:?}, expected {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_game() {
+        let settings = SoundSettings::default();
+        let (messages, len) =
+            SoundController::game_cues(short_game(), &settings, Duration::from_secs(600));
+        assert_eq!(len, Duration::from_secs(88));
+        let buffer = render_samples("full-game", &settings, messages, len);
+
+        // Whistles with 30 seconds left in each break, buzzers at the end of every period. The
+        // displayed time counts down as soon as a second has started, so each cue plays a second
+        // before the end of the time it is for.
+        assert_sounds(
+            &find_sounds(&buffer),
+            &[
+                (1.0, CueChannels::AboveWater),
+                (31.0, CueChannels::Both),
+                (41.0, CueChannels::Both),
+                (45.0, CueChannels::AboveWater),
+                (75.0, CueChannels::Both),
+                (85.0, CueChannels::Both),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_custom_cues() {
+        let mut settings = SoundSettings {
+            auto_sound_stop_play: false,
+            ..Default::default()
+        };
+        settings.cues.push(SoundCue {
+            period: CuePeriod::Play,
+            secs_remaining: 5,
+            sound: CueSound::Whistle,
+            channels: CueChannels::UnderWater,
+        });
+
+        let (messages, len) =
+            SoundController::game_cues(short_game(), &settings, Duration::from_secs(600));
+        let buffer = render_samples("custom-cues", &settings, messages, len);
+
+        // No buzzers at the end of the halves
+        assert_sounds(
+            &find_sounds(&buffer),
+            &[
+                (1.0, CueChannels::AboveWater),
+                (31.0, CueChannels::Both),
+                (36.0, CueChannels::UnderWater),
+                (45.0, CueChannels::AboveWater),
+                (75.0, CueChannels::Both),
+                (80.0, CueChannels::UnderWater),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_stopping_finished_sound() {
+        let settings = SoundSettings::default();
+        let messages = vec![
+            (
+                Duration::ZERO,
+                SoundMessage::TriggerCue(CueSound::Buzzer, CueChannels::Both),
+            ),
+            (Duration::from_secs(3), SoundMessage::StopBuzzer),
+        ];
+        let buffer = render_samples("stop-finished", &settings, messages, Duration::from_secs(4));
+
+        // The buzzer must not be heard again when it is stopped after it has faded out
+        assert_sounds(&find_sounds(&buffer), &[(0.0, CueChannels::Both)]);
+        let after = (2.5 * SAMPLE_RATE) as usize;
+        for channel in 0..2 {
+            assert_eq!(rms(&buffer.get_channel_data(channel)[after..]), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_write_wav() {
+        let settings = SoundSettings::default();
+        let messages = vec![
+            (Duration::ZERO, SoundMessage::StartBuzzer(None)),
+            (Duration::from_millis(500), SoundMessage::StopBuzzer),
+        ];
+        let path = temp_wav("write-wav");
+        let buffer = SoundController::render_offline(
+            &settings,
+            &[],
+            messages,
+            Duration::from_secs(1),
+            &path,
+        )
+        .unwrap();
+        let reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        let samples: Vec<f32> = reader
+            .into_samples::<f32>()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(spec.channels, 2);
+        assert_eq!(spec.sample_rate, SAMPLE_RATE as u32);
+        assert_eq!(samples.len(), 2 * SAMPLE_RATE as usize);
+        assert!(rms(&samples) > THRESHOLD);
+        for (i, frame) in samples.chunks_exact(2).enumerate() {
+            assert_eq!(frame[0], buffer.get_channel_data(0)[i]);
+            assert_eq!(frame[1], buffer.get_channel_data(1)[i]);
+        }
+    }
+}
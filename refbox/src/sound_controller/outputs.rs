@@ -0,0 +1,351 @@
+use super::{CueChannels, SAMPLE_RATE};
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use web_audio_api::{
+    context::{AudioContext, AudioContextOptions, BaseAudioContext},
+    media_devices::{self, MediaDeviceInfo, MediaDeviceInfoKind},
+    node::{AudioNode, ChannelInterpretation},
+};
+
+/// Number of output channels that can be selected in the UI
+pub const MAX_OUTPUT_CHANNELS: usize = 8;
+
+const TEST_TONE_FREQ: f32 = 1000.0;
+const TEST_TONE_LEN: f32 = 1.0;
+const TEST_TONE_AMPLITUDE: f32 = 0.5;
+
+/// One of the two speakers that the refbox drives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicalChannel {
+    AboveWater,
+    UnderWater,
+}
+
+impl From<LogicalChannel> for CueChannels {
+    fn from(channel: LogicalChannel) -> Self {
+        match channel {
+            LogicalChannel::AboveWater => Self::AboveWater,
+            LogicalChannel::UnderWater => Self::UnderWater,
+        }
+    }
+}
+
+/// Where the sound for a `LogicalChannel` is played
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelOutput {
+    /// Label of the audio output device, `None` for the system default. Device ids change
+    /// between runs, so the label is stored instead
+    pub device: Option<String>,
+    /// Index of the channel on the device, starting at 0
+    pub channel: usize,
+}
+
+impl ChannelOutput {
+    pub fn next_device(&mut self, devices: &[String]) {
+        self.device = match self.device {
+            None => devices.first().cloned(),
+            Some(ref current) => devices.iter().skip_while(|d| *d != current).nth(1).cloned(),
+        };
+    }
+
+    pub fn next_channel(&mut self) {
+        self.channel = (self.channel + 1) % MAX_OUTPUT_CHANNELS;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelMap {
+    pub above_water: ChannelOutput,
+    pub under_water: ChannelOutput,
+}
+
+impl Default for ChannelMap {
+    fn default() -> Self {
+        Self {
+            above_water: ChannelOutput {
+                device: None,
+                channel: 0,
+            },
+            under_water: ChannelOutput {
+                device: None,
+                channel: 1,
+            },
+        }
+    }
+}
+
+impl ChannelMap {
+    pub fn get_mut(&mut self, channel: LogicalChannel) -> &mut ChannelOutput {
+        match channel {
+            LogicalChannel::AboveWater => &mut self.above_water,
+            LogicalChannel::UnderWater => &mut self.under_water,
+        }
+    }
+}
+
+/// Lists the labels of the audio output devices in `devices`
+pub fn output_device_labels(devices: &[MediaDeviceInfo]) -> Vec<String> {
+    devices
+        .iter()
+        .filter(|d| d.kind() == MediaDeviceInfoKind::AudioOutput)
+        .map(|d| d.label().to_string())
+        .collect()
+}
+
+fn find_sink_id(label: &str) -> Option<String> {
+    media_devices::enumerate_devices_sync()
+        .into_iter()
+        .find(|d| d.kind() == MediaDeviceInfoKind::AudioOutput && d.label() == label)
+        .map(|d| d.device_id().to_string())
+}
+
+/// Picks the number of destination channels needed to reach `channel` on a device with
+/// `max_channels` channels. Never less than stereo unless the device is mono.
+fn channel_count(channel: usize, max_channels: usize) -> usize {
+    let max_channels = max_channels.max(1);
+    (channel + 1).clamp(2.min(max_channels), max_channels)
+}
+
+/// A channel on an open audio context
+pub(super) struct OutputTarget<C> {
+    pub(super) context: Arc<C>,
+    pub(super) channel: usize,
+}
+
+impl<C> Clone for OutputTarget<C> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+            channel: self.channel,
+        }
+    }
+}
+
+/// The audio contexts opened for a `ChannelMap`
+pub(super) struct AudioOutputs<C = AudioContext> {
+    pub(super) above_water: OutputTarget<C>,
+    pub(super) under_water: OutputTarget<C>,
+    map: ChannelMap,
+}
+
+impl<C> Clone for AudioOutputs<C> {
+    fn clone(&self) -> Self {
+        Self {
+            above_water: self.above_water.clone(),
+            under_water: self.under_water.clone(),
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl AudioOutputs<AudioContext> {
+    pub(super) fn new(map: &ChannelMap) -> Self {
+        let above_context = Self::open_context(&map.above_water.device);
+        let under_context = if map.under_water.device == map.above_water.device {
+            above_context.clone()
+        } else {
+            Self::open_context(&map.under_water.device)
+        };
+
+        // Both channels may share a context, in which case its destination must be sized for
+        // the higher of the two
+        let shared = Arc::ptr_eq(&above_context, &under_context);
+        let max_needed = |channel: usize| {
+            if shared {
+                map.above_water.channel.max(map.under_water.channel)
+            } else {
+                channel
+            }
+        };
+
+        let target = |context: Arc<AudioContext>, channel: usize, name: &str| {
+            let destination = context.destination();
+            let count = channel_count(max_needed(channel), destination.max_channel_count());
+            if count != destination.channel_count() {
+                destination.set_channel_count(count);
+                destination.set_channel_interpretation(ChannelInterpretation::Discrete);
+            }
+            let fitted = channel.min(count - 1);
+            if fitted != channel {
+                error!(
+                    "The {name} output device only has {count} channels, using channel {} \
+                     instead of {}",
+                    fitted + 1,
+                    channel + 1
+                );
+            }
+            OutputTarget {
+                context,
+                channel: fitted,
+            }
+        };
+
+        Self {
+            above_water: target(above_context, map.above_water.channel, "above water"),
+            under_water: target(under_context, map.under_water.channel, "under water"),
+            map: map.clone(),
+        }
+    }
+
+    /// Returns these outputs if they already match `map`, otherwise the outputs in `preview`,
+    /// which are opened for `map` if they aren't already. The previous preview outputs are closed,
+    /// so that repeatedly previewing unapplied settings doesn't leave contexts running
+    pub(super) fn for_preview(&self, map: &ChannelMap, preview: &mut Option<Self>) -> Self {
+        if self.map == *map {
+            return self.clone();
+        }
+        match preview {
+            Some(outputs) if outputs.map == *map => outputs.clone(),
+            _ => {
+                if let Some(old) = preview.take() {
+                    old.close();
+                }
+                let outputs = Self::new(map);
+                *preview = Some(outputs.clone());
+                outputs
+            }
+        }
+    }
+
+    /// Switches to outputs for `map`, taking the preview outputs if they match, and closes the
+    /// outputs that are no longer needed
+    pub(super) fn switch_to(self, map: &ChannelMap, preview: &mut Option<Self>) -> Self {
+        if self.map == *map {
+            return self;
+        }
+        let outputs = match preview.take() {
+            Some(outputs) if outputs.map == *map => outputs,
+            Some(other) => {
+                other.close();
+                Self::new(map)
+            }
+            None => Self::new(map),
+        };
+        self.close();
+        outputs
+    }
+
+    /// Stops the contexts. Dropping a running `AudioContext` leaves its stream open, so outputs
+    /// that are replaced have to be closed explicitly
+    fn close(self) {
+        let above = self.above_water.context;
+        let under = self.under_water.context;
+        if !Arc::ptr_eq(&above, &under) {
+            under.close_sync();
+        }
+        above.close_sync();
+        info!("Closed audio outputs for {:?}", self.map);
+    }
+
+    fn open_context(device: &Option<String>) -> Arc<AudioContext> {
+        let sink_id = match device {
+            Some(label) => find_sink_id(label).unwrap_or_else(|| {
+                error!("Audio output device {label:?} not found, using the default");
+                String::new()
+            }),
+            None => String::new(),
+        };
+
+        let opts = AudioContextOptions {
+            sample_rate: Some(SAMPLE_RATE),
+            sink_id,
+            ..AudioContextOptions::default()
+        };
+
+        let context = AudioContext::new(opts);
+        info!("Audio context created with sink {:?}", context.sink_id());
+
+        Arc::new(context)
+    }
+}
+
+impl AudioOutputs<web_audio_api::context::OfflineAudioContext> {
+    /// Outputs for rendering to a stereo buffer, above water on the left and under water on the
+    /// right
+    pub(super) fn offline(context: Arc<web_audio_api::context::OfflineAudioContext>) -> Self {
+        let map = ChannelMap::default();
+        Self {
+            above_water: OutputTarget {
+                context: context.clone(),
+                channel: map.above_water.channel,
+            },
+            under_water: OutputTarget {
+                context,
+                channel: map.under_water.channel,
+            },
+            map,
+        }
+    }
+}
+
+/// A sine wave used to check which speaker a channel is routed to
+pub(super) fn test_tone_samples() -> Vec<f32> {
+    let len = (SAMPLE_RATE * TEST_TONE_LEN) as usize;
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE;
+            TEST_TONE_AMPLITUDE * (2.0 * std::f32::consts::PI * TEST_TONE_FREQ * t).sin()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_count() {
+        assert_eq!(channel_count(0, 2), 2);
+        assert_eq!(channel_count(1, 2), 2);
+        assert_eq!(channel_count(3, 2), 2);
+        assert_eq!(channel_count(3, 8), 4);
+        assert_eq!(channel_count(0, 8), 2);
+        assert_eq!(channel_count(1, 1), 1);
+        assert_eq!(channel_count(0, 0), 1);
+    }
+
+    #[test]
+    fn test_next_device() {
+        let devices = vec!["Speakers".to_string(), "USB Audio".to_string()];
+        let mut output = ChannelOutput {
+            device: None,
+            channel: 0,
+        };
+
+        output.next_device(&devices);
+        assert_eq!(output.device.as_deref(), Some("Speakers"));
+        output.next_device(&devices);
+        assert_eq!(output.device.as_deref(), Some("USB Audio"));
+        output.next_device(&devices);
+        assert_eq!(output.device, None);
+
+        output.device = Some("Unplugged".to_string());
+        output.next_device(&devices);
+        assert_eq!(output.device, None);
+
+        output.next_device(&[]);
+        assert_eq!(output.device, None);
+    }
+
+    #[test]
+    fn test_next_channel() {
+        let mut output = ChannelOutput {
+            device: None,
+            channel: 0,
+        };
+        for expected in (1..MAX_OUTPUT_CHANNELS).chain([0]) {
+            output.next_channel();
+            assert_eq!(output.channel, expected);
+        }
+    }
+
+    #[test]
+    fn test_test_tone() {
+        let tone = test_tone_samples();
+        assert_eq!(tone.len(), SAMPLE_RATE as usize);
+        assert_eq!(tone[0], 0.0);
+        let peak = tone.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+        assert!((peak - TEST_TONE_AMPLITUDE).abs() < 0.001);
+    }
+}
@@ -0,0 +1,663 @@
+use arrayref::array_ref;
+use log::*;
+#[cfg(target_os = "linux")]
+use rppal::gpio::{Gpio, InputPin, Level, Trigger};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::{
+    sync::{
+        mpsc::{error::SendError, UnboundedSender},
+        watch::Receiver,
+    },
+    task::{self, JoinHandle},
+    time::{sleep_until, Duration, Instant},
+};
+
+const MESSAGE_LEN: usize = 24;
+const ID_LEN: usize = 20;
+const DATA_LEN: usize = MESSAGE_LEN - ID_LEN;
+
+#[cfg(target_os = "linux")]
+const ANTENNA_PIN: u8 = 16;
+#[cfg(target_os = "linux")]
+const WIRED_PIN: u8 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteEvent {
+    /// The wired button (or a key standing in for it) was pressed or released
+    Wired(bool),
+    /// A wireless remote sent its id. It counts as pressed until it stops sending
+    Wireless(u32),
+}
+
+/// Sends the events of one `RemoteInput`, tagged with the index of the input so that the wired
+/// buttons of different inputs can be told apart
+#[derive(Debug, Clone)]
+pub struct RemoteEventSender {
+    input: usize,
+    tx: UnboundedSender<(usize, RemoteEvent)>,
+}
+
+impl RemoteEventSender {
+    pub fn new(input: usize, tx: UnboundedSender<(usize, RemoteEvent)>) -> Self {
+        Self { input, tx }
+    }
+
+    pub fn send(&self, event: RemoteEvent) -> Result<(), SendError<(usize, RemoteEvent)>> {
+        self.tx.send((self.input, event))
+    }
+}
+
+/// A source of buzzer remote button presses
+pub trait RemoteInput: Send {
+    /// Starts sending events to `events` until `stop_rx` changes. Returns any tasks that need to
+    /// be joined on shutdown
+    fn start(
+        self: Box<Self>,
+        events: RemoteEventSender,
+        stop_rx: Receiver<bool>,
+    ) -> Vec<JoinHandle<()>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteInputSource {
+    /// The receiver and wired button on the Raspberry Pi hat
+    #[cfg(target_os = "linux")]
+    Gpio,
+    /// A USB HID device (such as a handheld buzzer) that shows up as a keyboard in `/dev/input`
+    #[cfg(target_os = "linux")]
+    Keyboard(PathBuf),
+    /// A file of edge timings, see `SimulatedRemoteInput`
+    Simulated(PathBuf),
+}
+
+impl RemoteInputSource {
+    pub fn open(&self) -> Result<Box<dyn RemoteInput>, RemoteInputError> {
+        Ok(match self {
+            #[cfg(target_os = "linux")]
+            Self::Gpio => Box::new(GpioRemoteInput::new()?),
+            #[cfg(target_os = "linux")]
+            Self::Keyboard(path) => Box::new(KeyboardRemoteInput::new(path)?),
+            Self::Simulated(path) => Box::new(SimulatedRemoteInput::load(path)?),
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RemoteInputError {
+    #[error("Not running on a Raspberry Pi")]
+    NotARaspberryPi,
+    #[cfg(target_os = "linux")]
+    #[error(transparent)]
+    Gpio(#[from] rppal::gpio::Error),
+    #[error("Couldn't open {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("Line {0}: {1}")]
+    Parse(usize, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Rising,
+    Falling,
+}
+
+const fn identify_pulse(len: u128) -> Option<PulseType> {
+    const SHORT_PULSE_BOT_THRESH: u128 = 200;
+    const SHORT_PULSE_TOP_THRESH: u128 = 500;
+    const LONG_PULSE_BOT_THRESH: u128 = 800;
+    const LONG_PULSE_TOP_THRESH: u128 = 1500;
+    const PREAMBLE_PULSE_BOT_THRESH: u128 = 9000;
+    const PREAMBLE_PULSE_TOP_THRESH: u128 = 12000;
+
+    match len {
+        SHORT_PULSE_BOT_THRESH..=SHORT_PULSE_TOP_THRESH => Some(PulseType::Short),
+        LONG_PULSE_BOT_THRESH..=LONG_PULSE_TOP_THRESH => Some(PulseType::Long),
+        PREAMBLE_PULSE_BOT_THRESH..=PREAMBLE_PULSE_TOP_THRESH => Some(PulseType::Preamble),
+        _ => None,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum PulseType {
+    Short,
+    Long,
+    Preamble,
+}
+
+/// Decodes the messages sent by the wireless remotes from the edges on the receiver's output
+#[derive(Debug, PartialEq, Eq)]
+pub struct RemoteDetectorState {
+    preamble_detected: bool,
+    bits: Vec<bool>,
+    last_pin_edge: Edge,
+    last_edge_time: Instant,
+    last_pulse: Option<PulseType>,
+}
+
+impl RemoteDetectorState {
+    /// `start_edge` is the edge that would have put the pin in its current level
+    pub fn new(start_edge: Edge, now: Instant) -> Self {
+        Self {
+            preamble_detected: false,
+            bits: vec![],
+            last_pin_edge: start_edge,
+            last_edge_time: now,
+            last_pulse: None,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.preamble_detected = false;
+        self.bits.clear();
+    }
+
+    /// Handles an edge on the receiver's output, returning the remote's id once a full message
+    /// has been received
+    pub fn edge(&mut self, edge: Edge, now: Instant) -> Option<u32> {
+        if self.last_pin_edge == edge {
+            return None;
+        }
+
+        let pulse = now.duration_since(self.last_edge_time).as_micros();
+        self.last_pin_edge = edge;
+        self.last_edge_time = now;
+        let maybe_pulse_type = identify_pulse(pulse);
+        let mut remote_id = None;
+
+        match edge {
+            Edge::Rising => {
+                trace!("Detected LOW  pulse {pulse:>5}us long");
+                debug!("Detected a LOW  pulse of length {maybe_pulse_type:?}");
+
+                if let Some(pulse_type) = maybe_pulse_type {
+                    if !self.preamble_detected {
+                        if self.last_pulse == Some(PulseType::Short)
+                            && pulse_type == PulseType::Preamble
+                        {
+                            debug!("Detected a preamble");
+                            self.preamble_detected = true;
+                        }
+                    } else {
+                        match (self.last_pulse, pulse_type) {
+                            (Some(PulseType::Short), PulseType::Long) => {
+                                debug!("Detected a low bit");
+                                self.bits.push(false);
+                            }
+                            (Some(PulseType::Long), PulseType::Short) => {
+                                debug!("Detected a high bit");
+                                self.bits.push(true);
+                            }
+                            _ => {
+                                info!("Detected an invalid pulse sequence");
+                                self.reset();
+                            }
+                        }
+
+                        if self.bits.len() == MESSAGE_LEN {
+                            let message: String = self
+                                .bits
+                                .iter()
+                                .map(|bit| if *bit { '1' } else { '0' })
+                                .collect();
+                            debug!("Received a complete message: 0b{message}");
+
+                            let id = self.bits[..ID_LEN]
+                                .iter()
+                                .fold(0, |acc, &b| acc * 2 + b as u32);
+                            let data = array_ref![self.bits, ID_LEN, DATA_LEN];
+
+                            debug!("Remote {id} sent data {data:?}");
+                            remote_id = Some(id);
+                            self.reset();
+                        }
+                    }
+                } else {
+                    trace!("Detected an invalid pulse");
+                    self.reset();
+                }
+            }
+            Edge::Falling => {
+                trace!("Detected HIGH pulse {pulse:>5}us long");
+                debug!("Detected a HIGH pulse of length {maybe_pulse_type:?}");
+
+                if maybe_pulse_type.is_none() {
+                    trace!("Detected an invalid pulse");
+                    self.reset();
+                }
+            }
+        }
+
+        self.last_pulse = maybe_pulse_type;
+        remote_id
+    }
+}
+
+/// The receiver and wired button connected to the GPIO pins of a Raspberry Pi
+#[cfg(target_os = "linux")]
+pub struct GpioRemoteInput {
+    ant_pin: InputPin,
+    wired_pin: InputPin,
+}
+
+#[cfg(target_os = "linux")]
+impl GpioRemoteInput {
+    pub fn new() -> Result<Self, RemoteInputError> {
+        let sys_info =
+            rppal::system::DeviceInfo::new().map_err(|_| RemoteInputError::NotARaspberryPi)?;
+        info!("Detected a Raspberry Pi system: {sys_info:?}, starting GPIO processes");
+
+        let gpio = Gpio::new()?;
+        Ok(Self {
+            ant_pin: gpio.get(ANTENNA_PIN)?.into_input_pullup(),
+            wired_pin: gpio.get(WIRED_PIN)?.into_input_pullup(),
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+const fn trigger_to_edge(trigger: Trigger) -> Option<Edge> {
+    match trigger {
+        Trigger::RisingEdge => Some(Edge::Rising),
+        Trigger::FallingEdge => Some(Edge::Falling),
+        Trigger::Disabled | Trigger::Both => None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl RemoteInput for GpioRemoteInput {
+    fn start(
+        self: Box<Self>,
+        events: RemoteEventSender,
+        mut stop_rx: Receiver<bool>,
+    ) -> Vec<JoinHandle<()>> {
+        let Self {
+            mut ant_pin,
+            mut wired_pin,
+        } = *self;
+
+        let (ant_tx, mut ant_rx) = tokio::sync::mpsc::unbounded_channel();
+        ant_pin
+            .set_async_interrupt(Trigger::Both, None, move |event| {
+                ant_tx.send((event.trigger, Instant::now())).unwrap()
+            })
+            .unwrap();
+
+        let wired_tx = events.clone();
+        wired_pin
+            .set_async_interrupt(Trigger::Both, None, move |event| {
+                match trigger_to_edge(event.trigger) {
+                    Some(edge) => wired_tx
+                        .send(RemoteEvent::Wired(edge == Edge::Rising))
+                        .unwrap(),
+                    None => panic!("Imposible pin state"),
+                }
+            })
+            .unwrap();
+
+        let start_edge = match ant_pin.read() {
+            Level::Low => Edge::Falling,
+            Level::High => Edge::Rising,
+        };
+
+        let listener = task::spawn(async move {
+            // The pins have to be kept alive for their interrupts to keep firing
+            let _pins = (ant_pin, wired_pin);
+            let mut state = RemoteDetectorState::new(start_edge, Instant::now());
+
+            loop {
+                tokio::select! {
+                    pin_update = ant_rx.recv() => {
+                        match pin_update {
+                            Some((trigger, now)) => {
+                                let edge = trigger_to_edge(trigger).expect("Imposible pin state");
+                                if let Some(id) = state.edge(edge, now) {
+                                    events.send(RemoteEvent::Wireless(id)).unwrap();
+                                }
+                            }
+                            None => panic!("The Pin has been dropped"),
+                        }
+                    }
+                    _ = stop_rx.changed() => break,
+                }
+            }
+        });
+
+        vec![listener]
+    }
+}
+
+/// A USB HID device that the OS treats as a keyboard. While any of its keys are held the buzzer
+/// sounds, just like the wired button.
+///
+/// The device is not grabbed exclusively, so it should not be the keyboard used to type into
+/// the refbox.
+#[cfg(target_os = "linux")]
+pub struct KeyboardRemoteInput {
+    file: std::fs::File,
+    path: PathBuf,
+}
+
+#[cfg(target_os = "linux")]
+impl KeyboardRemoteInput {
+    /// `path` is an evdev device, such as `/dev/input/by-id/usb-...-event-kbd`
+    pub fn new(path: &Path) -> Result<Self, RemoteInputError> {
+        let file =
+            std::fs::File::open(path).map_err(|e| RemoteInputError::Io(path.to_path_buf(), e))?;
+        info!("Listening for buzzer keys on {}", path.display());
+        Ok(Self {
+            file,
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl RemoteInput for KeyboardRemoteInput {
+    fn start(
+        self: Box<Self>,
+        events: RemoteEventSender,
+        _stop_rx: Receiver<bool>,
+    ) -> Vec<JoinHandle<()>> {
+        use std::{collections::BTreeSet, ffi::c_long, io::Read};
+
+        // `struct input_event` is a `struct timeval` followed by a u16 type, a u16 code and an
+        // i32 value
+        const TIME_LEN: usize = 2 * std::mem::size_of::<c_long>();
+        const EVENT_LEN: usize = TIME_LEN + 8;
+        const EV_KEY: u16 = 1;
+
+        let Self { mut file, path } = *self;
+
+        // Reads block until a key is pressed, so this can't be a task that gets joined on
+        // shutdown. The thread ends when the next event fails to send after the receiver is gone
+        std::thread::spawn(move || {
+            let mut held = BTreeSet::new();
+            let mut buf = [0u8; EVENT_LEN];
+
+            loop {
+                if let Err(e) = file.read_exact(&mut buf) {
+                    error!("Failed to read from {}: {e}", path.display());
+                    break;
+                }
+
+                let kind = u16::from_ne_bytes([buf[TIME_LEN], buf[TIME_LEN + 1]]);
+                let code = u16::from_ne_bytes([buf[TIME_LEN + 2], buf[TIME_LEN + 3]]);
+                let value = i32::from_ne_bytes(*array_ref![buf, TIME_LEN + 4, 4]);
+                if kind != EV_KEY {
+                    continue;
+                }
+
+                let was_pressed = !held.is_empty();
+                // A value of 2 is an autorepeat, which doesn't change anything
+                match value {
+                    0 => {
+                        held.remove(&code);
+                    }
+                    1 => {
+                        held.insert(code);
+                    }
+                    _ => {}
+                }
+                trace!("Key {code} changed to {value}, held keys: {held:?}");
+
+                let pressed = !held.is_empty();
+                if pressed != was_pressed && events.send(RemoteEvent::Wired(pressed)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        vec![]
+    }
+}
+
+/// Replays a recording of edges on the receiver and wired button, so that remotes can be used
+/// (and tested) without the hardware.
+///
+/// Each line of the file is `<time> <input> <edge>`, where `<time>` is the number of
+/// microseconds since the start of the replay, `<input>` is `wireless` or `wired`, and `<edge>`
+/// is `rising` or `falling`. Blank lines and lines starting with `#` are ignored, and the edges
+/// must be in chronological order. Both inputs start out low.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedRemoteInput {
+    edges: Vec<SimulatedEdge>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulatedEdge {
+    pub at: Duration,
+    pub wireless: bool,
+    pub edge: Edge,
+}
+
+impl SimulatedRemoteInput {
+    pub fn load(path: &Path) -> Result<Self, RemoteInputError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| RemoteInputError::Io(path.to_path_buf(), e))?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, RemoteInputError> {
+        let mut edges: Vec<SimulatedEdge> = vec![];
+
+        for (i, line) in text.lines().enumerate() {
+            let line_num = i + 1;
+            let err = |msg: &str| RemoteInputError::Parse(line_num, msg.to_string());
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let words: Vec<_> = line.split_whitespace().collect();
+            let [time, input, edge] = words[..] else {
+                return Err(err("expected `<time> <input> <edge>`"));
+            };
+
+            let at = Duration::from_micros(time.parse().map_err(|_| err("invalid time"))?);
+            let wireless = match input {
+                "wireless" => true,
+                "wired" => false,
+                _ => return Err(err("the input must be `wireless` or `wired`")),
+            };
+            let edge = match edge {
+                "rising" => Edge::Rising,
+                "falling" => Edge::Falling,
+                _ => return Err(err("the edge must be `rising` or `falling`")),
+            };
+
+            if edges.last().is_some_and(|last| last.at > at) {
+                return Err(err("edges must be in chronological order"));
+            }
+
+            edges.push(SimulatedEdge { at, wireless, edge });
+        }
+
+        Ok(Self { edges })
+    }
+
+    /// Decodes the recording into the events it produces, along with when they happen
+    pub fn events(&self) -> Vec<(Duration, RemoteEvent)> {
+        let start = Instant::now();
+        let mut state = RemoteDetectorState::new(Edge::Falling, start);
+        let mut wired_edge = Edge::Falling;
+
+        self.edges
+            .iter()
+            .filter_map(|sim| {
+                let event = if sim.wireless {
+                    RemoteEvent::Wireless(state.edge(sim.edge, start + sim.at)?)
+                } else if sim.edge != wired_edge {
+                    wired_edge = sim.edge;
+                    RemoteEvent::Wired(sim.edge == Edge::Rising)
+                } else {
+                    return None;
+                };
+                Some((sim.at, event))
+            })
+            .collect()
+    }
+}
+
+impl RemoteInput for SimulatedRemoteInput {
+    fn start(
+        self: Box<Self>,
+        events: RemoteEventSender,
+        mut stop_rx: Receiver<bool>,
+    ) -> Vec<JoinHandle<()>> {
+        let to_send = self.events();
+        info!("Replaying {} simulated remote events", to_send.len());
+
+        let replay = task::spawn(async move {
+            let start = Instant::now();
+            for (at, event) in to_send {
+                tokio::select! {
+                    _ = sleep_until(start + at) => {
+                        if events.send(event).is_err() {
+                            break;
+                        }
+                    }
+                    _ = stop_rx.changed() => break,
+                }
+            }
+        });
+
+        vec![replay]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHORT: Duration = Duration::from_micros(350);
+    const LONG: Duration = Duration::from_micros(1100);
+    const PREAMBLE: Duration = Duration::from_micros(10_500);
+
+    /// Generates the edges that a remote sends, starting from a low pin
+    fn encode(id: u32, data: [bool; DATA_LEN]) -> Vec<(Duration, Edge)> {
+        let mut pulses = vec![(SHORT, Edge::Falling), (PREAMBLE, Edge::Rising)];
+        let bits = (0..ID_LEN).rev().map(|i| (id >> i) & 1 == 1).chain(data);
+        for bit in bits {
+            if bit {
+                pulses.extend([(LONG, Edge::Falling), (SHORT, Edge::Rising)]);
+            } else {
+                pulses.extend([(SHORT, Edge::Falling), (LONG, Edge::Rising)]);
+            }
+        }
+        // The pin first has to go high, after being low for an unknown amount of time
+        let mut at = Duration::from_millis(20);
+        let mut edges = vec![(at, Edge::Rising)];
+        for (len, edge) in pulses {
+            at += len;
+            edges.push((at, edge));
+        }
+        edges
+    }
+
+    fn decode(edges: &[(Duration, Edge)]) -> Vec<u32> {
+        let start = Instant::now();
+        let mut state = RemoteDetectorState::new(Edge::Falling, start);
+        edges
+            .iter()
+            .filter_map(|(at, edge)| state.edge(*edge, start + *at))
+            .collect()
+    }
+
+    #[test]
+    fn test_identify_pulse() {
+        assert_eq!(identify_pulse(100), None);
+        assert_eq!(identify_pulse(200), Some(PulseType::Short));
+        assert_eq!(identify_pulse(500), Some(PulseType::Short));
+        assert_eq!(identify_pulse(650), None);
+        assert_eq!(identify_pulse(1000), Some(PulseType::Long));
+        assert_eq!(identify_pulse(5000), None);
+        assert_eq!(identify_pulse(10_000), Some(PulseType::Preamble));
+        assert_eq!(identify_pulse(13_000), None);
+    }
+
+    #[test]
+    fn test_decode_message() {
+        let edges = encode(0xA_BC12, [true, false, true, false]);
+        assert_eq!(decode(&edges), vec![0xA_BC12]);
+
+        // Two messages back to back, without the pin idling low in between
+        let mut edges = encode(1, [false; 4]);
+        let offset = edges.last().unwrap().0 - edges[0].0;
+        edges.extend(
+            encode(0xF_FFFF, [true; 4])
+                .into_iter()
+                .skip(1)
+                .map(|(at, edge)| (at + offset, edge)),
+        );
+        assert_eq!(decode(&edges), vec![1, 0xF_FFFF]);
+    }
+
+    #[test]
+    fn test_decode_ignores_repeated_edges() {
+        let edges: Vec<_> = encode(12345, [false; 4])
+            .into_iter()
+            .flat_map(|(at, edge)| [(at, edge), (at + Duration::from_micros(10), edge)])
+            .collect();
+        assert_eq!(decode(&edges), vec![12345]);
+    }
+
+    #[test]
+    fn test_decode_invalid_pulse() {
+        // A corrupted pulse in the middle of the message drops it
+        let mut edges = encode(12345, [false; 4]);
+        for (at, _) in edges.iter_mut().skip(20) {
+            *at += Duration::from_micros(3000);
+        }
+        assert_eq!(decode(&edges), Vec::<u32>::new());
+
+        // Without a preamble, nothing is decoded
+        let edges: Vec<_> = encode(12345, [false; 4])
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !(1..=2).contains(i))
+            .map(|(_, edge)| edge)
+            .collect();
+        assert_eq!(decode(&edges), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_simulated_events() {
+        let mut file = String::from("# A wired press, then a wireless message\n\n");
+        file.push_str("1000 wired rising\n2000 wired rising\n5000 wired falling\n");
+        for (at, edge) in encode(0x1_2345, [false; 4]) {
+            let edge = match edge {
+                Edge::Rising => "rising",
+                Edge::Falling => "falling",
+            };
+            file.push_str(&format!("{} wireless {edge}\n", at.as_micros() + 10_000));
+        }
+
+        let sim = SimulatedRemoteInput::parse(&file).unwrap();
+        let events = sim.events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[..2],
+            [
+                (Duration::from_micros(1000), RemoteEvent::Wired(true)),
+                (Duration::from_micros(5000), RemoteEvent::Wired(false)),
+            ]
+        );
+        assert_eq!(events[2].1, RemoteEvent::Wireless(0x1_2345));
+    }
+
+    #[test]
+    fn test_parse_simulated_errors() {
+        let check = |text: &str, line: usize| match SimulatedRemoteInput::parse(text) {
+            Err(RemoteInputError::Parse(l, _)) => assert_eq!(l, line, "{text}"),
+            other => panic!("Expected a parse error for {text:?}, got {other:?}"),
+        };
+
+        check("10 wired", 1);
+        check("# comment\n10 wired rising\nabc wired falling", 3);
+        check("10 antenna rising", 1);
+        check("10 wired up", 1);
+        check("10 wired rising\n5 wired falling", 2);
+    }
+}
@@ -0,0 +1,313 @@
+use super::{BuzzerSound, SAMPLE_RATE};
+use hound::{SampleFormat, WavReader};
+use log::*;
+use std::{
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// Longest custom sound that will be accepted, in seconds
+pub const MAX_CUSTOM_SOUND_LEN: f32 = 10.0;
+
+#[derive(Debug, Error)]
+pub enum SoundPackError {
+    #[error("Could not read the sound pack directory {0:?}: {1}")]
+    Dir(PathBuf, io::Error),
+    #[error("Could not decode {0:?}: {1}")]
+    Wav(PathBuf, hound::Error),
+    #[error("The name of {0:?} is not valid UTF-8")]
+    InvalidName(PathBuf),
+    #[error("{0:?} has the same name as a built in buzzer sound")]
+    ReservedName(PathBuf),
+    #[error("{0:?} has the same name as another sound in the pack")]
+    DuplicateName(PathBuf),
+    #[error("{0:?} contains no audio")]
+    Empty(PathBuf),
+    #[error("{0:?} is {1:.1}s long, the limit is {MAX_CUSTOM_SOUND_LEN}s")]
+    TooLong(PathBuf, f32),
+    #[error("{0:?} contains samples outside the range -1.0 to 1.0")]
+    OutOfRange(PathBuf),
+}
+
+/// A buzzer sound loaded from a sound pack, as mono samples at `SAMPLE_RATE`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomSound {
+    pub name: String,
+    pub samples: Vec<f32>,
+}
+
+/// Loads every `.wav` file in `dir` as a custom buzzer sound named after the file. Files that
+/// fail validation are skipped and their errors returned alongside the sounds that loaded.
+pub fn load_sound_pack(
+    dir: &Path,
+) -> Result<(Vec<CustomSound>, Vec<SoundPackError>), SoundPackError> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|e| SoundPackError::Dir(dir.to_path_buf(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+        })
+        .collect();
+    paths.sort();
+
+    let mut sounds: Vec<CustomSound> = vec![];
+    let mut errors = vec![];
+
+    for path in paths {
+        let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(name) => name.to_string(),
+            None => {
+                errors.push(SoundPackError::InvalidName(path));
+                continue;
+            }
+        };
+
+        if BuzzerSound::is_built_in_name(&name) {
+            errors.push(SoundPackError::ReservedName(path));
+            continue;
+        }
+
+        // Names are shown in upper case, so they need to be unique without regard to case
+        if sounds.iter().any(|s| s.name.eq_ignore_ascii_case(&name)) {
+            errors.push(SoundPackError::DuplicateName(path));
+            continue;
+        }
+
+        let file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                errors.push(SoundPackError::Wav(path, e.into()));
+                continue;
+            }
+        };
+
+        match CustomSound::decode(name, io::BufReader::new(file), &path) {
+            Ok(sound) => {
+                info!(
+                    "Loaded custom buzzer sound {:?} from {path:?}",
+                    sound.name.as_str()
+                );
+                sounds.push(sound);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    Ok((sounds, errors))
+}
+
+impl CustomSound {
+    /// Decodes and validates a WAV file, mixing it down to mono and resampling it to
+    /// `SAMPLE_RATE`. `path` is only used for error reporting.
+    pub fn decode<R: Read>(name: String, reader: R, path: &Path) -> Result<Self, SoundPackError> {
+        let wav_err = |e| SoundPackError::Wav(path.to_path_buf(), e);
+
+        let reader = WavReader::new(reader).map_err(wav_err)?;
+        let spec = reader.spec();
+
+        let interleaved: Vec<f32> = match spec.sample_format {
+            SampleFormat::Float => reader
+                .into_samples::<f32>()
+                .collect::<Result<_, _>>()
+                .map_err(wav_err)?,
+            SampleFormat::Int => {
+                let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .into_samples::<i32>()
+                    .map(|s| s.map(|s| s as f32 * scale))
+                    .collect::<Result<_, _>>()
+                    .map_err(wav_err)?
+            }
+        };
+
+        let channels = spec.channels as usize;
+        let mono: Vec<f32> = interleaved
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect();
+
+        if mono.is_empty() {
+            return Err(SoundPackError::Empty(path.to_path_buf()));
+        }
+
+        let len = mono.len() as f32 / spec.sample_rate as f32;
+        if len > MAX_CUSTOM_SOUND_LEN {
+            return Err(SoundPackError::TooLong(path.to_path_buf(), len));
+        }
+
+        // NaN fails this check too
+        if !mono.iter().all(|s| (-1.0..=1.0).contains(s)) {
+            return Err(SoundPackError::OutOfRange(path.to_path_buf()));
+        }
+
+        Ok(Self {
+            name,
+            samples: resample(&mono, spec.sample_rate as f32, SAMPLE_RATE),
+        })
+    }
+}
+
+/// Linearly interpolates `samples` from `from_rate` to `to_rate`
+fn resample(samples: &[f32], from_rate: f32, to_rate: f32) -> Vec<f32> {
+    if from_rate == to_rate || samples.len() < 2 {
+        return samples.to_vec();
+    }
+
+    let step = from_rate as f64 / to_rate as f64;
+    let out_len = ((samples.len() - 1) as f64 / step).floor() as usize + 1;
+
+    (0..out_len)
+        .map(|i| {
+            let pos = i as f64 * step;
+            let idx = pos.floor() as usize;
+            let frac = (pos - idx as f64) as f32;
+            match samples.get(idx + 1) {
+                Some(next) => samples[idx] + (next - samples[idx]) * frac,
+                None => samples[idx],
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hound::{WavSpec, WavWriter};
+    use std::io::Cursor;
+
+    fn make_wav(channels: u16, sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+        let spec = WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut data = Cursor::new(vec![]);
+        let mut writer = WavWriter::new(&mut data, spec).unwrap();
+        for s in samples {
+            writer.write_sample(*s).unwrap();
+        }
+        writer.finalize().unwrap();
+        data.into_inner()
+    }
+
+    fn make_float_wav(sample_rate: u32, samples: &[f32]) -> Vec<u8> {
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+        };
+        let mut data = Cursor::new(vec![]);
+        let mut writer = WavWriter::new(&mut data, spec).unwrap();
+        for s in samples {
+            writer.write_sample(*s).unwrap();
+        }
+        writer.finalize().unwrap();
+        data.into_inner()
+    }
+
+    fn decode(data: Vec<u8>) -> Result<CustomSound, SoundPackError> {
+        CustomSound::decode("Test".to_string(), Cursor::new(data), Path::new("test.wav"))
+    }
+
+    #[test]
+    fn test_resample() {
+        assert_eq!(resample(&[0.0, 1.0], 44100.0, 44100.0), vec![0.0, 1.0]);
+        assert_eq!(
+            resample(&[0.0, 1.0, 0.0], 22050.0, 44100.0),
+            vec![0.0, 0.5, 1.0, 0.5, 0.0]
+        );
+        assert_eq!(
+            resample(&[0.0, 0.5, 1.0, 0.5, 0.0], 88200.0, 44100.0),
+            vec![0.0, 1.0, 0.0]
+        );
+        assert_eq!(resample(&[0.25], 22050.0, 44100.0), vec![0.25]);
+    }
+
+    #[test]
+    fn test_decode_mixes_to_mono() {
+        let data = make_wav(2, 44100, &[16384, 0, -16384, -16384, 0, 16384]);
+        let sound = decode(data).unwrap();
+        assert_eq!(sound.name, "Test");
+        assert_eq!(sound.samples, vec![0.25, -0.5, 0.25]);
+    }
+
+    #[test]
+    fn test_decode_resamples() {
+        let data = make_wav(1, 22050, &[0, 16384, 0]);
+        let sound = decode(data).unwrap();
+        assert_eq!(sound.samples, vec![0.0, 0.25, 0.5, 0.25, 0.0]);
+
+        let data = make_float_wav(22050, &[0.0, 1.0, 0.0]);
+        let sound = decode(data).unwrap();
+        assert_eq!(sound.samples, vec![0.0, 0.5, 1.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_decode_validation() {
+        assert!(matches!(
+            decode(make_wav(1, 44100, &[])),
+            Err(SoundPackError::Empty(_))
+        ));
+
+        let too_long = vec![0; 8000 * (MAX_CUSTOM_SOUND_LEN as usize + 1)];
+        assert!(matches!(
+            decode(make_wav(1, 8000, &too_long)),
+            Err(SoundPackError::TooLong(_, len)) if len == MAX_CUSTOM_SOUND_LEN + 1.0
+        ));
+
+        assert!(matches!(
+            decode(make_float_wav(44100, &[0.0, 1.5, 0.0])),
+            Err(SoundPackError::OutOfRange(_))
+        ));
+        assert!(matches!(
+            decode(make_float_wav(44100, &[0.0, f32::NAN])),
+            Err(SoundPackError::OutOfRange(_))
+        ));
+
+        assert!(matches!(
+            decode(b"not a wav file".to_vec()),
+            Err(SoundPackError::Wav(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_load_sound_pack() {
+        let dir = std::env::temp_dir().join(format!("refbox-sound-pack-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let valid = make_wav(1, 44100, &[0, 8192, 0]);
+        std::fs::write(dir.join("Horn.wav"), &valid).unwrap();
+        std::fs::write(dir.join("horn.WAV"), &valid).unwrap();
+        std::fs::write(dir.join("Air Horn.wav"), &valid).unwrap();
+        std::fs::write(dir.join("Buzz.wav"), &valid).unwrap();
+        std::fs::write(dir.join("De De Du.wav"), &valid).unwrap();
+        std::fs::write(dir.join("Empty.wav"), make_wav(1, 44100, &[])).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a sound").unwrap();
+
+        let (sounds, errors) = load_sound_pack(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<_> = sounds.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Air Horn", "Horn"]);
+        assert_eq!(sounds[1].samples, vec![0.0, 0.25, 0.0]);
+
+        assert_eq!(errors.len(), 4);
+        assert!(matches!(&errors[0], SoundPackError::ReservedName(p) if p.ends_with("Buzz.wav")));
+        assert!(
+            matches!(&errors[1], SoundPackError::ReservedName(p) if p.ends_with("De De Du.wav"))
+        );
+        assert!(matches!(&errors[2], SoundPackError::Empty(p) if p.ends_with("Empty.wav")));
+        assert!(matches!(&errors[3], SoundPackError::DuplicateName(p) if p.ends_with("horn.WAV")));
+
+        assert!(matches!(
+            load_sound_pack(&dir),
+            Err(SoundPackError::Dir(_, _))
+        ));
+    }
+}
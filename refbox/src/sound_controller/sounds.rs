@@ -1,13 +1,10 @@
+use super::{test_tone_samples, CustomSound};
 use array_concat::*;
 use derivative::Derivative;
-use enum_derive_2018::EnumFromStr;
-use macro_attr_2018::macro_attr;
+use log::*;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Display, ops::Index};
-use web_audio_api::{
-    context::{AudioContext, BaseAudioContext},
-    AudioBuffer,
-};
+use std::{collections::HashMap, convert::Infallible, fmt::Display, ops::Index, str::FromStr};
+use web_audio_api::{context::BaseAudioContext, AudioBuffer};
 
 const fn process_array<const N: usize, const M: usize>(input: &[u8; M]) -> [f32; N] {
     let mut output = [0f32; N];
@@ -56,16 +53,105 @@ static TWO_TONE: [f32; TWO_TONE_LEN] =
 
 pub const SAMPLE_RATE: f32 = 44100.0;
 
-macro_attr! {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Derivative, EnumFromStr!)]
+/// A buzzer sound, either one of the built in sounds or one loaded from a sound pack
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Derivative)]
+#[derivative(Default)]
+#[serde(from = "String", into = "String")]
+pub enum BuzzerSound {
     #[derivative(Default)]
-    pub enum BuzzerSound {
-        #[derivative(Default)]
-        Buzz,
-        Whoop,
-        Crazy,
-        DeDeDu,
-        TwoTone,
+    Buzz,
+    Whoop,
+    Crazy,
+    DeDeDu,
+    TwoTone,
+    Custom(String),
+}
+
+impl BuzzerSound {
+    pub const BUILT_IN: [Self; 5] = [
+        Self::Buzz,
+        Self::Whoop,
+        Self::Crazy,
+        Self::DeDeDu,
+        Self::TwoTone,
+    ];
+
+    /// Whether `name` would be confused with one of the built in sounds, either as stored in
+    /// the config file or as displayed (e.g. "DeDeDu" or "De De Du")
+    pub fn is_built_in_name(name: &str) -> bool {
+        let name = name.replace(' ', "");
+        Self::BUILT_IN
+            .iter()
+            .any(|sound| name.eq_ignore_ascii_case(sound.as_ref()))
+    }
+
+    /// Returns the sound after this one, going through the built in sounds and then the
+    /// `custom` ones. A custom sound that is not in `custom` is followed by the default.
+    pub fn next(&self, custom: &[String]) -> Self {
+        Self::next_option(Some(self), custom, false).unwrap_or_default()
+    }
+
+    /// Like `next`, but with `None` (meaning use the default sound) as the last choice
+    pub fn next_optional(current: Option<&Self>, custom: &[String]) -> Option<Self> {
+        Self::next_option(current, custom, true)
+    }
+
+    fn next_option(current: Option<&Self>, custom: &[String], with_none: bool) -> Option<Self> {
+        let mut choices: Vec<_> = Self::BUILT_IN
+            .into_iter()
+            .chain(custom.iter().cloned().map(Self::Custom))
+            .map(Some)
+            .collect();
+        if with_none {
+            choices.push(None);
+        }
+
+        match choices.iter().position(|choice| choice.as_ref() == current) {
+            Some(i) => choices[(i + 1) % choices.len()].clone(),
+            None => Some(Self::default()),
+        }
+    }
+}
+
+impl AsRef<str> for BuzzerSound {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Buzz => "Buzz",
+            Self::Whoop => "Whoop",
+            Self::Crazy => "Crazy",
+            Self::DeDeDu => "DeDeDu",
+            Self::TwoTone => "TwoTone",
+            Self::Custom(name) => name,
+        }
+    }
+}
+
+impl FromStr for BuzzerSound {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::BUILT_IN
+            .into_iter()
+            .find(|sound| sound.as_ref() == s)
+            .unwrap_or_else(|| Self::Custom(s.to_string())))
+    }
+}
+
+impl From<String> for BuzzerSound {
+    fn from(s: String) -> Self {
+        match s.parse() {
+            Ok(sound) => sound,
+            Err(e) => match e {},
+        }
+    }
+}
+
+impl From<BuzzerSound> for String {
+    fn from(sound: BuzzerSound) -> Self {
+        match sound {
+            BuzzerSound::Custom(name) => name,
+            sound => sound.as_ref().to_string(),
+        }
     }
 }
 
@@ -77,6 +163,7 @@ impl Display for BuzzerSound {
             Self::Crazy => write!(f, "Crazy"),
             Self::DeDeDu => write!(f, "De De Du"),
             Self::TwoTone => write!(f, "Two Tone"),
+            Self::Custom(name) => write!(f, "{name}"),
         }
     }
 }
@@ -88,24 +175,30 @@ pub(super) struct SoundLibrary {
     de_de_du: AudioBuffer,
     two_tone: AudioBuffer,
     whistle: AudioBuffer,
+    test_tone: AudioBuffer,
+    custom: HashMap<String, AudioBuffer>,
 }
 
-impl Index<BuzzerSound> for SoundLibrary {
+impl Index<&BuzzerSound> for SoundLibrary {
     type Output = AudioBuffer;
 
-    fn index(&self, sound: BuzzerSound) -> &Self::Output {
+    fn index(&self, sound: &BuzzerSound) -> &Self::Output {
         match sound {
             BuzzerSound::Buzz => &self.buzz,
             BuzzerSound::Whoop => &self.whoop,
             BuzzerSound::Crazy => &self.crazy,
             BuzzerSound::DeDeDu => &self.de_de_du,
             BuzzerSound::TwoTone => &self.two_tone,
+            BuzzerSound::Custom(name) => self.custom.get(name).unwrap_or_else(|| {
+                warn!("Custom buzzer sound {name:?} is not loaded, using the default");
+                &self[&BuzzerSound::default()]
+            }),
         }
     }
 }
 
 impl SoundLibrary {
-    pub(super) fn new(context: &AudioContext) -> Self {
+    pub(super) fn new(context: &impl BaseAudioContext, custom_sounds: &[CustomSound]) -> Self {
         let mut buzz = context.create_buffer(1, BUZZ_LEN, SAMPLE_RATE);
         buzz.copy_to_channel(&BUZZ, 0);
 
@@ -124,6 +217,19 @@ impl SoundLibrary {
         let mut whistle = context.create_buffer(1, WHISTLE_LEN, SAMPLE_RATE);
         whistle.copy_to_channel(&WHISTLE, 0);
 
+        let tone = test_tone_samples();
+        let mut test_tone = context.create_buffer(1, tone.len(), SAMPLE_RATE);
+        test_tone.copy_to_channel(&tone, 0);
+
+        let custom = custom_sounds
+            .iter()
+            .map(|sound| {
+                let mut buffer = context.create_buffer(1, sound.samples.len(), SAMPLE_RATE);
+                buffer.copy_to_channel(&sound.samples, 0);
+                (sound.name.clone(), buffer)
+            })
+            .collect();
+
         Self {
             buzz,
             whoop,
@@ -131,10 +237,16 @@ impl SoundLibrary {
             de_de_du,
             two_tone,
             whistle,
+            test_tone,
+            custom,
         }
     }
 
     pub(super) fn whistle(&self) -> &AudioBuffer {
         &self.whistle
     }
+
+    pub(super) fn test_tone(&self) -> &AudioBuffer {
+        &self.test_tone
+    }
 }
@@ -0,0 +1,364 @@
+//! Property based tests that drive a `TournamentManager` with random sequences of operations and
+//! check that its invariants hold after every one of them.
+//!
+//! Operations are only applied when the refbox UI would allow them (e.g. timeouts are gated on the
+//! matching `can_start_*` check), and every call that the UI unwraps must succeed.
+
+use super::{penalty::PenaltyKind, BlackWhiteBundle, TournamentManager};
+use proptest::{prelude::*, test_runner::TestCaseError};
+use std::time::Duration;
+use tokio::time::Instant;
+use uwh_common::{
+    config::Game as GameConfig,
+    game_snapshot::{Color, GamePeriod, Infraction, PenaltyTime},
+};
+
+#[derive(Debug, Clone)]
+enum Op {
+    Advance(Duration),
+    StartClock,
+    StopClock,
+    TeamTimeout(Color),
+    RefTimeout,
+    PenaltyShot,
+    SwitchToTeamTimeout(Color),
+    SwitchToRefTimeout,
+    SwitchToPenaltyShot,
+    EndTimeout,
+    Penalty(Color, u8, PenaltyKind),
+    DeletePenalty(Color, usize),
+    Goal(Color, u8),
+    SetScores(u8, u8),
+    SetConfig(GameConfig),
+    StartPlayNow,
+    ResetGame,
+}
+
+fn color() -> impl Strategy<Value = Color> {
+    prop_oneof![Just(Color::Black), Just(Color::White)]
+}
+
+fn penalty_kind() -> impl Strategy<Value = PenaltyKind> {
+    prop_oneof![
+        Just(PenaltyKind::ThirtySecond),
+        Just(PenaltyKind::OneMinute),
+        Just(PenaltyKind::TwoMinute),
+        Just(PenaltyKind::FourMinute),
+        Just(PenaltyKind::FiveMinute),
+        Just(PenaltyKind::TotalDismissal),
+    ]
+}
+
+fn secs(range: std::ops::RangeInclusive<u64>) -> impl Strategy<Value = Duration> {
+    range.prop_map(Duration::from_secs)
+}
+
+/// Configs with short periods, so that a few operations can cover a whole game
+fn game_config() -> impl Strategy<Value = GameConfig> {
+    (
+        (0u16..=3, any::<bool>(), any::<bool>(), any::<bool>()),
+        (any::<bool>(), secs(0..=30)),
+        (secs(10..=120), secs(5..=60), secs(5..=60), secs(5..=60)),
+        (secs(10..=60), secs(5..=30), secs(5..=30), secs(5..=30)),
+        (secs(5..=60), secs(5..=120), secs(5..=60)),
+    )
+        .prop_map(
+            |(
+                (num_team_timeouts_allowed, timeouts_counted_per_half, overtime, sudden_death),
+                (stop_clock_on_goal, stopped_clock_final_duration),
+                (half_play, half_time, team_timeout, penalty_shot),
+                (ot_half_play, ot_half_time, pre_overtime, pre_sudden_death),
+                (post_game, nominal_break, minimum_break),
+            )| GameConfig {
+                num_team_timeouts_allowed,
+                timeouts_counted_per_half,
+                overtime_allowed: overtime,
+                sudden_death_allowed: sudden_death,
+                stop_clock_on_goal,
+                half_play_duration: half_play,
+                half_time_duration: half_time,
+                team_timeout_duration: team_timeout,
+                penalty_shot_duration: penalty_shot,
+                ot_half_play_duration: ot_half_play,
+                ot_half_time_duration: ot_half_time,
+                pre_overtime_break: pre_overtime,
+                pre_sudden_death_duration: pre_sudden_death,
+                post_game_duration: post_game,
+                nominal_break,
+                minimum_break,
+                stopped_clock_final_duration,
+            },
+        )
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        8 => (0u64..=90_000).prop_map(|ms| Op::Advance(Duration::from_millis(ms))),
+        3 => Just(Op::StartClock),
+        2 => Just(Op::StopClock),
+        2 => color().prop_map(Op::TeamTimeout),
+        1 => Just(Op::RefTimeout),
+        1 => Just(Op::PenaltyShot),
+        1 => color().prop_map(Op::SwitchToTeamTimeout),
+        1 => Just(Op::SwitchToRefTimeout),
+        1 => Just(Op::SwitchToPenaltyShot),
+        2 => Just(Op::EndTimeout),
+        2 => (color(), 0u8..=99, penalty_kind()).prop_map(|(c, n, k)| Op::Penalty(c, n, k)),
+        1 => (color(), 0usize..4).prop_map(|(c, i)| Op::DeletePenalty(c, i)),
+        2 => (color(), 0u8..=99).prop_map(|(c, n)| Op::Goal(c, n)),
+        1 => (0u8..=10, 0u8..=10).prop_map(|(b, w)| Op::SetScores(b, w)),
+        1 => game_config().prop_map(Op::SetConfig),
+        1 => Just(Op::StartPlayNow),
+        1 => Just(Op::ResetGame),
+    ]
+}
+
+fn to_test_err(e: impl ToString) -> TestCaseError {
+    TestCaseError::fail(e.to_string())
+}
+
+fn is_break(period: GamePeriod) -> bool {
+    matches!(
+        period,
+        GamePeriod::BetweenGames
+            | GamePeriod::HalfTime
+            | GamePeriod::PreOvertime
+            | GamePeriod::OvertimeHalfTime
+            | GamePeriod::PreSuddenDeath
+    )
+}
+
+/// Whether the game can get from `from` to `to` without going backwards. Going to `BetweenGames`
+/// is always allowed, because a game can end (or be reset) from any period
+fn period_reachable(from: GamePeriod, to: GamePeriod) -> bool {
+    to == from
+        || to == GamePeriod::BetweenGames
+        || std::iter::successors(Some(from), |p| {
+            let next = p.next_period()?;
+            (next != *p).then_some(next)
+        })
+        .any(|p| p == to)
+}
+
+/// Steps the `TournamentManager` through every update between `now` and `target`, checking that
+/// the periods only ever move forwards
+fn advance(
+    tm: &mut TournamentManager,
+    now: &mut Instant,
+    target: Instant,
+) -> Result<(), TestCaseError> {
+    loop {
+        let prev_period = tm.current_period();
+        let next = match tm.next_update_time(*now) {
+            // Nothing changes while all the clocks are stopped, but the update times don't move
+            // either, so skip straight to the end
+            _ if !tm.clock_is_running() => target,
+            // When `now` is on a whole second the next update time is `now` itself
+            Some(next) if next <= *now => *now + Duration::from_secs(1),
+            Some(next) if next <= target => next,
+            _ => target,
+        };
+        *now = next.min(target);
+        tm.update(*now).map_err(to_test_err)?;
+        let period = tm.current_period();
+        prop_assert!(
+            period_reachable(prev_period, period),
+            "period went from {prev_period:?} to {period:?}"
+        );
+        if *now == target {
+            return Ok(());
+        }
+    }
+}
+
+fn apply(
+    tm: &mut TournamentManager,
+    op: &Op,
+    now: &mut Instant,
+    rugby: bool,
+) -> Result<(), TestCaseError> {
+    let now_val = *now;
+    match op {
+        Op::Advance(dur) => advance(tm, now, now_val + *dur)?,
+        Op::StartClock => tm.start_clock(now_val),
+        Op::StopClock => tm.stop_clock(now_val).map_err(to_test_err)?,
+        Op::TeamTimeout(color) => {
+            if tm.can_start_team_timeout(*color).is_ok() {
+                tm.start_team_timeout(*color, now_val)
+                    .map_err(to_test_err)?;
+            }
+        }
+        Op::RefTimeout => {
+            if tm.can_start_ref_timeout().is_ok() {
+                tm.start_ref_timeout(now_val).map_err(to_test_err)?;
+            }
+        }
+        Op::PenaltyShot => {
+            if rugby {
+                if tm.can_start_rugby_penalty_shot().is_ok() {
+                    tm.start_rugby_penalty_shot(now_val).map_err(to_test_err)?;
+                }
+            } else if tm.can_start_penalty_shot().is_ok() {
+                tm.start_penalty_shot(now_val).map_err(to_test_err)?;
+            }
+        }
+        Op::SwitchToTeamTimeout(color) => {
+            if tm.can_switch_to_team_timeout(*color).is_ok() {
+                tm.switch_to_team_timeout(*color).map_err(to_test_err)?;
+            }
+        }
+        Op::SwitchToRefTimeout => {
+            if tm.can_switch_to_ref_timeout().is_ok() {
+                tm.switch_to_ref_timeout(now_val).map_err(to_test_err)?;
+            }
+        }
+        Op::SwitchToPenaltyShot => {
+            if rugby {
+                if tm.can_switch_to_rugby_penalty_shot().is_ok() {
+                    tm.switch_to_rugby_penalty_shot(now_val)
+                        .map_err(to_test_err)?;
+                }
+            } else if tm.can_switch_to_penalty_shot().is_ok() {
+                tm.switch_to_penalty_shot().map_err(to_test_err)?;
+            }
+        }
+        Op::EndTimeout => {
+            if tm.timeout_clock_time(now_val).is_some() {
+                if tm
+                    .timeout_end_would_end_game(now_val)
+                    .map_err(to_test_err)?
+                {
+                    tm.halt_clock(now_val, true).map_err(to_test_err)?;
+                } else {
+                    tm.end_timeout(now_val).map_err(to_test_err)?;
+                    tm.update(now_val).map_err(to_test_err)?;
+                }
+            }
+        }
+        Op::Penalty(color, number, kind) => tm
+            .start_penalty(*color, *number, *kind, now_val, Infraction::Unknown)
+            .map_err(to_test_err)?,
+        Op::DeletePenalty(color, index) => {
+            if *index < tm.penalties[*color].len() {
+                tm.delete_penalty(*color, *index).map_err(to_test_err)?;
+            }
+        }
+        Op::Goal(color, number) => tm.add_score(*color, *number, now_val),
+        Op::SetScores(black, white) => tm.set_scores(
+            BlackWhiteBundle {
+                black: *black,
+                white: *white,
+            },
+            now_val,
+        ),
+        Op::SetConfig(config) => {
+            if tm.current_period() == GamePeriod::BetweenGames {
+                tm.set_config(config.clone()).map_err(to_test_err)?;
+            }
+        }
+        Op::StartPlayNow => {
+            if is_break(tm.current_period()) && tm.timeout_clock_time(now_val).is_none() {
+                tm.start_play_now(now_val).map_err(to_test_err)?;
+            }
+        }
+        Op::ResetGame => tm.reset_game(now_val),
+    }
+    Ok(())
+}
+
+/// Checks the state of the `TournamentManager` after an op. `new_game_allowed` is set when the op
+/// may have run past the end of the game and on into the next one
+fn check_invariants(
+    tm: &mut TournamentManager,
+    prev_period: GamePeriod,
+    new_game_allowed: bool,
+    now: Instant,
+) -> Result<(), TestCaseError> {
+    let period = tm.current_period();
+    prop_assert!(
+        period_reachable(prev_period, period)
+            || (new_game_allowed && period_reachable(GamePeriod::BetweenGames, period)),
+        "period went from {prev_period:?} to {period:?}"
+    );
+
+    if period != GamePeriod::BetweenGames {
+        for (color, used) in tm.timeouts_used.iter() {
+            prop_assert!(
+                *used <= tm.config.num_team_timeouts_allowed,
+                "{color} used {used} timeouts, only {} are allowed",
+                tm.config.num_team_timeouts_allowed
+            );
+        }
+    }
+
+    let cur_time = tm.game_clock_time(now);
+    prop_assert!(cur_time.is_some(), "no game clock time");
+    let cur_time = cur_time.unwrap();
+
+    let snapshot = tm.generate_snapshot(now);
+    prop_assert!(snapshot.is_some(), "failed to generate a snapshot");
+    let snapshot = snapshot.unwrap();
+
+    for (color, pen_snapshots) in [
+        (Color::Black, &snapshot.b_penalties),
+        (Color::White, &snapshot.w_penalties),
+    ] {
+        prop_assert_eq!(tm.penalties[color].len(), pen_snapshots.len());
+        for (pen, pen_snapshot) in tm.penalties[color].iter().zip(pen_snapshots) {
+            match (pen.kind.as_duration(), &pen_snapshot.time) {
+                (Some(duration), PenaltyTime::Seconds(secs)) => {
+                    let remaining = pen.time_remaining(period, cur_time, &tm.config);
+                    prop_assert!(remaining.is_ok(), "{:?}", remaining);
+                    let remaining = remaining.unwrap();
+                    prop_assert!(
+                        remaining <= duration,
+                        "{color} {:?} penalty has {:?} remaining",
+                        pen.kind,
+                        remaining
+                    );
+                    prop_assert!(
+                        u64::from(*secs) <= duration.as_secs(),
+                        "{color} {:?} penalty shows {secs}s remaining",
+                        pen.kind
+                    );
+                }
+                (None, PenaltyTime::TotalDismissal) => {}
+                // Once the game is over, every penalty from it has been served
+                (None, PenaltyTime::Seconds(0))
+                    if period == GamePeriod::BetweenGames
+                        && pen.start_period != GamePeriod::BetweenGames => {}
+                (dur, time) => prop_assert!(false, "penalty of {dur:?} shown as {time:?}"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_ops(config: GameConfig, rugby: bool, ops: &[Op]) -> Result<(), TestCaseError> {
+    let mut now = Instant::now();
+    let mut tm = TournamentManager::new(config);
+    tm.start_clock(now);
+    check_invariants(&mut tm, GamePeriod::BetweenGames, false, now)?;
+
+    for op in ops {
+        let prev_period = tm.current_period();
+        apply(&mut tm, op, &mut now, rugby)?;
+        // `advance` checks each step along the way, but a long enough advance can finish one game
+        // and start the next
+        check_invariants(&mut tm, prev_period, matches!(op, Op::Advance(_)), now)?;
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn test_random_operations(
+        config in game_config(),
+        rugby in any::<bool>(),
+        ops in prop::collection::vec(op(), 1..60),
+    ) {
+        run_ops(config, rugby, &ops)?;
+    }
+}
@@ -1,7 +1,11 @@
-use super::{Color, Duration, GamePeriod, Instant, OffsetDateTime, Penalty, PenaltyKind};
+use super::{
+    AnnulmentReason, Color, Duration, GamePeriod, Infraction, Instant, OffsetDateTime, Penalty,
+    PenaltyKind, RecordedGoal,
+};
 use serde::Serialize;
 use std::cmp::Ordering;
 use time::format_description::well_known::{iso8601, Iso8601};
+use uwh_common::game_snapshot::{PenaltySnapshot, PenaltyTime};
 
 const CONFIG: iso8601::EncodedConfig = iso8601::Config::DEFAULT
     .set_year_is_six_digits(false)
@@ -73,14 +77,114 @@ impl GameStats {
                 _ => Some(penalty.kind.as_duration().unwrap().as_secs()),
             },
             is_total_dismissal: penalty.kind == PenaltyKind::TotalDismissal,
+            infraction: penalty.infraction,
         };
         self.events.push(event);
     }
 
+    /// The penalties recorded against `color`, with `time` set to the length of each penalty
+    pub(crate) fn penalties(&self, color: Color) -> Vec<PenaltySnapshot> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Penalty {
+                    player_cap_number,
+                    side,
+                    duration,
+                    infraction,
+                    ..
+                } if color_from_side(side) == color => Some(PenaltySnapshot {
+                    player_number: *player_cap_number,
+                    time: match duration {
+                        Some(secs) => PenaltyTime::Seconds(*secs as u16),
+                        None => PenaltyTime::TotalDismissal,
+                    },
+                    infraction: *infraction,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn goals(&self) -> Vec<RecordedGoal> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Goal {
+                    player_cap_number,
+                    side,
+                    game_period,
+                    period_time,
+                    ..
+                } => Some(RecordedGoal {
+                    color: color_from_side(side),
+                    player_number: *player_cap_number,
+                    period: *game_period,
+                    time: Duration::from_secs_f32(*period_time),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Removes the goal at `index` (as ordered by `goals()`) and records its annulment. Returns
+    /// the removed goal and whether it was the most recently recorded goal.
+    pub(crate) fn annul_goal(
+        &mut self,
+        index: usize,
+        reason: AnnulmentReason,
+        period: GamePeriod,
+        time_left_in_period: Option<Duration>,
+        instant: Instant,
+    ) -> Option<(RecordedGoal, bool)> {
+        let goal_indices: Vec<usize> = self
+            .events
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| matches!(event, Event::Goal { .. }))
+            .map(|(i, _)| i)
+            .collect();
+        let event_index = *goal_indices.get(index)?;
+        let was_latest = index + 1 == goal_indices.len();
+
+        let Event::Goal {
+            player_cap_number,
+            side,
+            game_period,
+            period_time,
+            occurred_on,
+        } = self.events.remove(event_index)
+        else {
+            unreachable!()
+        };
+
+        let goal = RecordedGoal {
+            color: color_from_side(&side),
+            player_number: player_cap_number,
+            period: game_period,
+            time: Duration::from_secs_f32(period_time),
+        };
+
+        self.events.push(Event::GoalAnnulled {
+            player_cap_number,
+            side,
+            goal_game_period: game_period,
+            goal_period_time: period_time,
+            goal_occurred_on: occurred_on,
+            reason,
+            game_period: period,
+            period_time: time_left_in_period.unwrap_or(Duration::ZERO).as_secs_f32(),
+            occurred_on: calculate_timestamp(instant),
+        });
+
+        Some((goal, was_latest))
+    }
+
     pub(crate) fn as_json(&self) -> String {
         let mut events = self.events.clone();
         events.sort_unstable_by_key(|event| match event {
             Event::Goal { occurred_on, .. } => *occurred_on,
+            Event::GoalAnnulled { occurred_on, .. } => *occurred_on,
             Event::Penalty { occurred_on, .. } => *occurred_on,
         });
         serde_json::to_string(&events).unwrap()
@@ -103,6 +207,27 @@ enum Event {
         #[serde(rename = "occurredOn")]
         occurred_on: OffsetDateTime,
     },
+    #[serde(rename = "goalAnnulled")]
+    GoalAnnulled {
+        #[serde(rename = "playerCapNumber")]
+        player_cap_number: u8,
+        side: String,
+        #[serde(rename = "goalGamePeriod")]
+        goal_game_period: GamePeriod,
+        #[serde(rename = "goalPeriodTime")]
+        goal_period_time: f32,
+        #[serde(with = "iso8601_short_year")]
+        #[serde(rename = "goalOccurredOn")]
+        goal_occurred_on: OffsetDateTime,
+        reason: AnnulmentReason,
+        #[serde(rename = "gamePeriod")]
+        game_period: GamePeriod,
+        #[serde(rename = "periodTime")]
+        period_time: f32,
+        #[serde(with = "iso8601_short_year")]
+        #[serde(rename = "occurredOn")]
+        occurred_on: OffsetDateTime,
+    },
     #[serde(rename = "penalty")]
     Penalty {
         #[serde(rename = "playerCapNumber")]
@@ -118,9 +243,19 @@ enum Event {
         duration: Option<u64>,
         #[serde(rename = "isTotalDismissal")]
         is_total_dismissal: bool,
+        #[serde(skip)]
+        infraction: Infraction,
     },
 }
 
+fn color_from_side(side: &str) -> Color {
+    if side == "light" {
+        Color::White
+    } else {
+        Color::Black
+    }
+}
+
 fn calculate_timestamp(instant: Instant) -> OffsetDateTime {
     let now = Instant::now();
     let mut timestamp = OffsetDateTime::now_utc();
@@ -0,0 +1,38 @@
+use derivative::Derivative;
+use enum_iterator::Sequence;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use tokio::time::Duration;
+use uwh_common::game_snapshot::{Color, GamePeriod};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedGoal {
+    pub color: Color,
+    pub player_number: u8,
+    pub period: GamePeriod,
+    pub time: Duration,
+}
+
+#[derive(Derivative, Debug, Clone, Copy, PartialEq, Eq, Serialize, Sequence)]
+#[derivative(Default)]
+#[serde(rename_all = "camelCase")]
+pub enum AnnulmentReason {
+    #[derivative(Default)]
+    Unknown,
+    FoulByScoringTeam,
+    AfterPeriodEnded,
+    PuckNotInGoal,
+    EnteredInError,
+}
+
+impl Display for AnnulmentReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown => write!(f, "Unknown"),
+            Self::FoulByScoringTeam => write!(f, "Foul By Scoring Team"),
+            Self::AfterPeriodEnded => write!(f, "After Period Ended"),
+            Self::PuckNotInGoal => write!(f, "Puck Not In Goal"),
+            Self::EnteredInError => write!(f, "Entered In Error"),
+        }
+    }
+}
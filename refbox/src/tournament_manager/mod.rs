@@ -16,7 +16,10 @@ use tokio::{
 use uwh_common::{
     config::Game as GameConfig,
     drawing_support::*,
-    game_snapshot::{Color, GamePeriod, GameSnapshot, Infraction, TimeoutSnapshot},
+    game_snapshot::{
+        Color, GamePeriod, GameSnapshot, GoalSnapshot, Infraction, PanelAnimation, PenaltySnapshot,
+        TimeoutSnapshot,
+    },
     uwhscores::TimingRules,
 };
 
@@ -26,9 +29,17 @@ use penalty::*;
 pub mod infraction;
 use infraction::*;
 
+pub mod goal;
+use goal::*;
+
 mod game_stats;
 use game_stats::*;
 
+#[cfg(test)]
+mod fuzz;
+#[cfg(test)]
+mod scenario;
+
 use crate::penalty_editor::IterHelp;
 
 const MAX_TIME_VAL: Duration = Duration::from_secs(MAX_LONG_STRINGABLE_SECS as u64);
@@ -43,6 +54,8 @@ pub struct TournamentManager {
     clock_state: ClockState,
     timeout_state: TimeoutState,
     timeouts_used: BlackWhiteBundle<u16>,
+    /// Unlike `timeouts_used`, not reset at half time when timeouts are counted per half
+    game_timeouts_used: BlackWhiteBundle<u16>,
     scores: BlackWhiteBundle<u8>,
     penalties: BlackWhiteBundle<Vec<Penalty>>,
     warnings: BlackWhiteBundle<Vec<InfractionDetails>>,
@@ -57,6 +70,12 @@ pub struct TournamentManager {
     recent_goal: Option<(Color, u8, GamePeriod, Duration)>,
     current_game_stats: GameStats,
     last_game_stats: Option<GameStats>,
+    starting_white_on_right: bool,
+    white_on_right: bool,
+    auto_switch_sides: bool,
+    clock_stopped_by_rule: bool,
+    tenths_threshold: Duration,
+    animation: Option<(PanelAnimation, Instant)>,
 }
 
 impl TournamentManager {
@@ -71,6 +90,7 @@ impl TournamentManager {
             },
             timeout_state: TimeoutState::None,
             timeouts_used: Default::default(),
+            game_timeouts_used: Default::default(),
             scores: Default::default(),
             penalties: Default::default(),
             warnings: Default::default(),
@@ -86,6 +106,12 @@ impl TournamentManager {
             recent_goal: None,
             current_game_stats: GameStats::new(0),
             last_game_stats: None,
+            starting_white_on_right: false,
+            white_on_right: false,
+            auto_switch_sides: false,
+            clock_stopped_by_rule: false,
+            tenths_threshold: Duration::ZERO,
+            animation: None,
         }
     }
 
@@ -114,11 +140,65 @@ impl TournamentManager {
         self.recent_goal = self
             .game_clock_time(now)
             .map(|time| (color, player_num, self.current_period, time));
+        self.start_animation(PanelAnimation::Goal(color, player_num), now);
+        if let Err(e) = self.apply_clock_policy(true, now) {
+            error!(
+                "{} Failed to stop the clock after the goal: {e}",
+                self.status_string(now)
+            );
+        }
         let mut scores = self.scores;
         scores[color] += 1;
         self.set_scores(scores, now);
     }
 
+    pub fn get_goals(&self) -> Vec<RecordedGoal> {
+        self.current_game_stats.goals()
+    }
+
+    /// Removes a goal recorded with `add_score`, lowering the score of the team that scored it
+    pub fn annul_goal(
+        &mut self,
+        index: usize,
+        reason: AnnulmentReason,
+        now: Instant,
+    ) -> Result<()> {
+        if self.current_period == GamePeriod::BetweenGames {
+            return Err(TournamentManagerError::NoGameInProgress);
+        }
+
+        let (goal, was_latest) = self
+            .current_game_stats
+            .annul_goal(
+                index,
+                reason,
+                self.current_period,
+                self.game_clock_time(now),
+                now,
+            )
+            .ok_or(TournamentManagerError::InvalidGoalIndex(index))?;
+        info!(
+            "{} Annulling goal by {} player #{} in {} at {:?}. Reason: {reason}",
+            self.status_string(now),
+            goal.color,
+            goal.player_number,
+            goal.period,
+            goal.time,
+        );
+
+        if was_latest {
+            self.recent_goal = None;
+            if matches!(self.animation, Some((PanelAnimation::Goal(..), _))) {
+                self.animation = None;
+            }
+        }
+
+        let mut scores = self.scores;
+        scores[goal.color] = scores[goal.color].saturating_sub(1);
+        self.set_scores(scores, now);
+        Ok(())
+    }
+
     pub fn get_scores(&self) -> BlackWhiteBundle<u8> {
         self.scores
     }
@@ -135,6 +215,55 @@ impl TournamentManager {
         self.timezone = timezone;
     }
 
+    /// Sets the side white starts each game on. Also moves the teams to those sides immediately
+    pub fn set_white_on_right(&mut self, white_on_right: bool) {
+        self.starting_white_on_right = white_on_right;
+        self.white_on_right = white_on_right;
+    }
+
+    /// When enabled, the teams switch sides at half time and at overtime half time
+    pub fn set_auto_switch_sides(&mut self, auto_switch_sides: bool) {
+        self.auto_switch_sides = auto_switch_sides;
+    }
+
+    /// The game clock is shown to the tenth of a second when there is less than `threshold` left
+    /// in a half. `Duration::ZERO` turns this off
+    pub fn set_tenths_threshold(&mut self, threshold: Duration) {
+        self.tenths_threshold = threshold;
+    }
+
+    /// Whether the game clock should be shown to the tenth of a second when it reads `clock_time`
+    fn shows_tenths(&self, clock_time: Duration) -> bool {
+        matches!(
+            self.current_period,
+            GamePeriod::FirstHalf
+                | GamePeriod::SecondHalf
+                | GamePeriod::OvertimeFirstHalf
+                | GamePeriod::OvertimeSecondHalf
+        ) && clock_time < self.tenths_threshold
+    }
+
+    /// Starts `animation` on the panels, replacing any that is still playing
+    fn start_animation(&mut self, animation: PanelAnimation, now: Instant) {
+        self.animation = Some((animation, now));
+    }
+
+    /// When the animation that was last started stops playing
+    fn animation_end(&self) -> Option<Instant> {
+        self.animation
+            .map(|(animation, start)| start + Duration::from_millis(animation.millis().into()))
+    }
+
+    /// Manually swaps the sides the teams are shown on, until the next automatic switch or reset
+    pub fn switch_sides(&mut self, now: Instant) {
+        self.white_on_right ^= true;
+        info!(
+            "{} Sides switched, white is now on the {}",
+            self.status_string(now),
+            if self.white_on_right { "right" } else { "left" }
+        );
+    }
+
     pub fn current_period(&self) -> GamePeriod {
         self.current_period
     }
@@ -208,7 +337,10 @@ impl TournamentManager {
         self.penalties.iter_mut().for_each(|(_, p)| p.clear());
         self.warnings.iter_mut().for_each(|(_, w)| w.clear());
         self.fouls.iter_mut().for_each(|(_, f)| f.clear());
+        self.game_timeouts_used = Default::default();
         self.current_game_stats = GameStats::new(self.next_game_number());
+        self.white_on_right = self.starting_white_on_right;
+        self.clock_stopped_by_rule = false;
         self.has_reset = true;
     }
 
@@ -384,6 +516,8 @@ impl TournamentManager {
         };
         self.timeout_state = TimeoutState::Team(color, cs);
         self.timeouts_used[color] += 1;
+        self.game_timeouts_used[color] += 1;
+        self.start_animation(PanelAnimation::Timeout, now);
         Ok(())
     }
 
@@ -401,6 +535,7 @@ impl TournamentManager {
                 clock_time: Duration::ZERO,
             });
         }
+        self.start_animation(PanelAnimation::Timeout, now);
         Ok(())
     }
 
@@ -418,6 +553,7 @@ impl TournamentManager {
                 clock_time: Duration::ZERO,
             });
         }
+        self.start_animation(PanelAnimation::Timeout, now);
         Ok(())
     }
 
@@ -434,6 +570,7 @@ impl TournamentManager {
                 clock_time: self.config.penalty_shot_duration,
             });
         }
+        self.start_animation(PanelAnimation::Timeout, now);
         Ok(())
     }
 
@@ -446,6 +583,9 @@ impl TournamentManager {
         self.timeouts_used[new_color] += 1;
         self.timeouts_used[new_color.other()] =
             self.timeouts_used[new_color.other()].saturating_sub(1);
+        self.game_timeouts_used[new_color] += 1;
+        self.game_timeouts_used[new_color.other()] =
+            self.game_timeouts_used[new_color.other()].saturating_sub(1);
         Ok(())
     }
 
@@ -454,11 +594,21 @@ impl TournamentManager {
         info!("Switching to a ref timeout");
         if let TimeoutState::PenaltyShot(cs) = &self.timeout_state {
             self.timeout_state = TimeoutState::Ref(cs.clone());
-        } else if let TimeoutState::RugbyPenaltyShot(_) = &self.timeout_state {
-            self.timeout_state = TimeoutState::Ref(ClockState::CountingUp {
-                start_time: now,
-                time_at_start: Duration::ZERO,
-            });
+        } else if let TimeoutState::RugbyPenaltyShot(cs) = &self.timeout_state {
+            let cs = if cs.is_running() {
+                // The game clock keeps running during a rugby penalty shot, but not during a ref
+                // timeout
+                self.stop_game_clock(now)?;
+                ClockState::CountingUp {
+                    start_time: now,
+                    time_at_start: Duration::ZERO,
+                }
+            } else {
+                ClockState::Stopped {
+                    clock_time: Duration::ZERO,
+                }
+            };
+            self.timeout_state = TimeoutState::Ref(cs);
         }
         Ok(())
     }
@@ -612,7 +762,7 @@ impl TournamentManager {
             infraction,
         };
         self.penalties[color].push(penalty);
-        Ok(())
+        self.apply_clock_policy(false, now)
     }
 
     pub fn delete_penalty(&mut self, color: Color, index: usize) -> Result<()> {
@@ -767,7 +917,7 @@ impl TournamentManager {
             infraction,
         };
         self.fouls[color].push(foul);
-        Ok(())
+        self.apply_clock_policy(false, now)
     }
 
     pub fn get_warnings(&self) -> &BlackWhiteBundle<Vec<InfractionDetails>> {
@@ -943,6 +1093,8 @@ impl TournamentManager {
         let was_running = self.clock_is_running();
 
         self.current_period = GamePeriod::BetweenGames;
+        // A goal in sudden death can end the game during a timeout
+        self.timeout_state = TimeoutState::None;
 
         info!(
             "{} Ending game {}. Score is {}",
@@ -1015,6 +1167,7 @@ impl TournamentManager {
         self.game_start_time = start_time;
         self.timeouts_used.black = 0;
         self.timeouts_used.white = 0;
+        self.game_timeouts_used = Default::default();
         self.has_reset = false;
 
         let sched_start = self.next_scheduled_start.unwrap_or(start_time);
@@ -1070,6 +1223,39 @@ impl TournamentManager {
                     && (self.scores.are_not_equal() || !self.config.sudden_death_allowed))))
     }
 
+    /// Whether the clock is in the final part of the second half, where every stoppage stops it
+    fn in_stopped_clock_period(&self, now: Instant) -> bool {
+        self.current_period == GamePeriod::SecondHalf
+            && !self.config.stopped_clock_final_duration.is_zero()
+            && self
+                .game_clock_time(now)
+                .is_some_and(|time| time <= self.config.stopped_clock_final_duration)
+    }
+
+    /// Stops the game clock after a goal or other stoppage if the clock rules require it. The
+    /// clock stays stopped until the ref restarts it
+    fn apply_clock_policy(&mut self, is_goal: bool, now: Instant) -> Result<()> {
+        let in_play = matches!(
+            self.current_period,
+            GamePeriod::FirstHalf
+                | GamePeriod::SecondHalf
+                | GamePeriod::OvertimeFirstHalf
+                | GamePeriod::OvertimeSecondHalf
+        );
+        if !in_play || self.timeout_state != TimeoutState::None {
+            return Ok(());
+        }
+
+        if ((is_goal && self.config.stop_clock_on_goal) || self.in_stopped_clock_period(now))
+            && self.stop_game_clock(now)?
+        {
+            info!("{} Game clock stopped by rule", self.status_string(now));
+            self.clock_stopped_by_rule = true;
+            self.send_clock_running(false);
+        }
+        Ok(())
+    }
+
     pub(super) fn update(&mut self, now: Instant) -> Result<()> {
         // Case of clock running, with no timeout and not SD
         if let ClockState::CountingDown {
@@ -1260,11 +1446,16 @@ impl TournamentManager {
     }
 
     fn end_first_half(&mut self, now: Instant) {
+        self.start_animation(PanelAnimation::PeriodEnd, now);
         info!("{} Entering half time", self.status_string(now));
         self.current_period = GamePeriod::HalfTime;
+        if self.auto_switch_sides {
+            self.switch_sides(now);
+        }
     }
 
     fn end_second_half(&mut self, now: Instant) {
+        self.start_animation(PanelAnimation::PeriodEnd, now);
         if self.scores.are_not_equal()
             || (!self.config.overtime_allowed && !self.config.sudden_death_allowed)
         {
@@ -1287,11 +1478,16 @@ impl TournamentManager {
     }
 
     fn end_overtime_first_half(&mut self, now: Instant) {
+        self.start_animation(PanelAnimation::PeriodEnd, now);
         info!("{} Entering overtime half time", self.status_string(now));
         self.current_period = GamePeriod::OvertimeHalfTime;
+        if self.auto_switch_sides {
+            self.switch_sides(now);
+        }
     }
 
     fn end_overtime_second_half(&mut self, now: Instant) {
+        self.start_animation(PanelAnimation::PeriodEnd, now);
         if self.scores.are_not_equal() || !self.config.sudden_death_allowed {
             self.end_game(now);
         } else {
@@ -1308,13 +1504,13 @@ impl TournamentManager {
         &mut self,
         now: Instant,
         start_time: Instant,
-        time_remaining_at_start: Duration,
+        pen_shot_remaining_at_start: Duration,
     ) -> Result<()> {
         info!(
             "{} Handling end of rugby penalty shot",
             self.status_string(now)
         );
-        if let ClockState::Stopped { clock_time } = self.clock_state {
+        if let ClockState::Stopped { mut clock_time } = self.clock_state {
             if clock_time == Duration::ZERO {
                 match self.current_period {
                     GamePeriod::FirstHalf => {
@@ -1348,10 +1544,27 @@ impl TournamentManager {
                         return Err(TournamentManagerError::InvalidState);
                     }
                 }
+                // `end_game()` has already started the countdown to the next game
+                if self.current_period == GamePeriod::BetweenGames {
+                    self.timeout_state = TimeoutState::None;
+                    return Ok(());
+                }
+                clock_time = self
+                    .current_period
+                    .duration(&self.config)
+                    .ok_or(TournamentManagerError::InvalidState)?;
             }
-            self.clock_state = ClockState::CountingDown {
-                start_time: min(now, start_time + time_remaining_at_start),
-                time_remaining_at_start: self.current_period.duration(&self.config).unwrap(),
+            let start_time = min(now, start_time + pen_shot_remaining_at_start);
+            self.clock_state = if self.current_period == GamePeriod::SuddenDeath {
+                ClockState::CountingUp {
+                    start_time,
+                    time_at_start: clock_time,
+                }
+            } else {
+                ClockState::CountingDown {
+                    start_time,
+                    time_remaining_at_start: clock_time,
+                }
             }
         }
         self.timeout_state = TimeoutState::None;
@@ -1367,6 +1580,7 @@ impl TournamentManager {
     fn start_game_clock(&mut self, now: Instant) -> bool {
         if let ClockState::Stopped { clock_time } = self.clock_state {
             info!("{} Starting the game clock", self.status_string(now));
+            self.clock_stopped_by_rule = false;
             match self.current_period {
                 GamePeriod::SuddenDeath => {
                     self.clock_state = ClockState::CountingUp {
@@ -1837,6 +2051,30 @@ impl TournamentManager {
             .next_period_dur(&self.config)
             .map(|dur| dur.as_secs().try_into().unwrap_or(0));
 
+        let mut goals: BlackWhiteBundle<Vec<GoalSnapshot>> = Default::default();
+        for goal in self.current_game_stats.goals() {
+            goals[goal.color].push(GoalSnapshot {
+                player_number: goal.player_number,
+                period: goal.period,
+                secs_in_period: goal.time.as_secs().try_into().unwrap_or(u32::MAX),
+            });
+        }
+
+        // Penalties are only recorded in the stats once served, except at the end of the game
+        // when all of them are
+        let game_penalties = |color: Color| {
+            let mut penalties = self.current_game_stats.penalties(color);
+            if self.current_period != GamePeriod::BetweenGames {
+                penalties.extend(self.penalties[color].iter().map(|pen| PenaltySnapshot {
+                    player_number: pen.player_number,
+                    time: pen.kind.as_penalty_time(),
+                    infraction: pen.infraction,
+                }));
+            }
+            penalties
+        };
+        trace!("Got game stats");
+
         Some(GameSnapshot {
             current_period: self.current_period,
             secs_in_period,
@@ -1856,11 +2094,32 @@ impl TournamentManager {
             tournament_id: 0,
             recent_goal: self.recent_goal.map(|(c, n, _, _)| (c, n)),
             next_period_len_secs,
+            white_on_right: self.white_on_right,
+            clock_stopped_by_rule: self.clock_stopped_by_rule,
+            b_game_penalties: game_penalties(Color::Black),
+            w_game_penalties: game_penalties(Color::White),
+            b_goals: goals.black,
+            w_goals: goals.white,
+            b_timeouts_used: self.game_timeouts_used.black,
+            w_timeouts_used: self.game_timeouts_used.white,
+            // The manager doesn't know the teams, the app fills these in like `tournament_id`
+            b_team_code: None,
+            w_team_code: None,
+            message: None,
+            tenths_in_period: self
+                .shows_tenths(cur_time)
+                .then(|| (cur_time.subsec_millis() / 100) as u8),
+            panel_animation: self
+                .animation
+                .filter(|_| self.animation_end().is_some_and(|end| end > now))
+                .map(|(animation, _)| animation),
         })
     }
 
     pub fn next_update_time(&self, now: Instant) -> Option<Instant> {
-        match (&self.timeout_state, self.current_period) {
+        // The panels need to be told when an animation has finished, even with the clock stopped
+        let animation_end = self.animation_end().filter(|&end| end > now);
+        let clock_update = match (&self.timeout_state, self.current_period) {
             // cases where the clock is counting up
             (TimeoutState::Ref(cs), _) | (TimeoutState::PenaltyShot(cs), _) => cs
                 .clock_time(now)
@@ -1890,10 +2149,17 @@ impl TournamentManager {
                     time_to_period_update.or(time_to_pen_update)
                 }
             }
-            (TimeoutState::None, _) => self
-                .clock_state
-                .clock_time(now)
-                .map(|ct| now + Duration::from_nanos(ct.subsec_nanos() as u64)),
+            (TimeoutState::None, _) => self.clock_state.clock_time(now).map(|ct| {
+                if self.shows_tenths(ct) {
+                    now + Duration::from_nanos((ct.subsec_nanos() % 100_000_000) as u64)
+                } else {
+                    now + Duration::from_nanos(ct.subsec_nanos() as u64)
+                }
+            }),
+        };
+        match (clock_update, animation_end) {
+            (Some(update), Some(end)) => Some(update.min(end)),
+            (update, end) => update.or(end),
         }
     }
 
@@ -2261,6 +2527,8 @@ pub enum TournamentManagerError {
     AlreadyInPlayPeriod,
     #[error("Action impossible unless in BetweenGames period")]
     GameInProgress,
+    #[error("Action impossible during BetweenGames period")]
+    NoGameInProgress,
     #[error("Too many active penalties, can't limit list to {0} values")]
     TooManyPenalties(usize),
     #[error("No {0} penalty exists at the index {1}")]
@@ -2269,6 +2537,8 @@ pub enum TournamentManagerError {
     InvalidWarnIndex(Color, usize),
     #[error("No {0:?} penalty exists at the index {1}")]
     InvalidFoulIndex(Option<Color>, usize),
+    #[error("No goal exists at the index {0}")]
+    InvalidGoalIndex(usize),
     #[error("Can't halt game from the current state")]
     InvalidState,
     #[error("Next Game Info is needed to perform this action")]
@@ -3518,6 +3788,26 @@ mod test {
                 time_at_start: Duration::ZERO,
             })
         );
+
+        // The game clock runs during a rugby penalty shot, but not during a ref timeout
+        tm.set_timeout_state(TimeoutState::None);
+        tm.start_clock(start);
+        assert_eq!(tm.start_rugby_penalty_shot(start), Ok(()));
+        assert!(tm.clock_state.is_running());
+        assert_eq!(tm.switch_to_ref_timeout(later), Ok(()));
+        assert_eq!(
+            tm.clock_state,
+            ClockState::Stopped {
+                clock_time: Duration::from_secs(28)
+            }
+        );
+        assert_eq!(
+            tm.timeout_state,
+            TimeoutState::Ref(ClockState::CountingUp {
+                start_time: later,
+                time_at_start: Duration::ZERO,
+            })
+        );
     }
 
     #[test]
@@ -4120,6 +4410,375 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_annul_goal() {
+        initialize();
+        let start = Instant::now();
+        let first_goal = start + Duration::from_secs(2);
+        let second_goal = start + Duration::from_secs(5);
+        let annul_time = start + Duration::from_secs(8);
+
+        let mut tm = TournamentManager::new(Default::default());
+
+        assert_eq!(
+            tm.annul_goal(0, AnnulmentReason::Unknown, start),
+            Err(TournamentManagerError::NoGameInProgress)
+        );
+
+        tm.set_period_and_game_clock_time(GamePeriod::FirstHalf, Duration::from_secs(25));
+        tm.start_game_clock(start);
+        tm.add_score(Color::Black, 4, first_goal);
+        tm.add_score(Color::White, 7, second_goal);
+        assert_eq!(tm.get_scores(), BlackWhiteBundle::new(1, 1));
+        assert_eq!(
+            tm.get_goals(),
+            vec![
+                RecordedGoal {
+                    color: Color::Black,
+                    player_number: 4,
+                    period: GamePeriod::FirstHalf,
+                    time: Duration::from_secs(23),
+                },
+                RecordedGoal {
+                    color: Color::White,
+                    player_number: 7,
+                    period: GamePeriod::FirstHalf,
+                    time: Duration::from_secs(20),
+                },
+            ]
+        );
+
+        assert_eq!(
+            tm.annul_goal(2, AnnulmentReason::Unknown, annul_time),
+            Err(TournamentManagerError::InvalidGoalIndex(2))
+        );
+
+        // Annulling an older goal leaves the recent goal in place
+        tm.annul_goal(0, AnnulmentReason::FoulByScoringTeam, annul_time)
+            .unwrap();
+        assert_eq!(tm.get_scores(), BlackWhiteBundle::new(0, 1));
+        assert_eq!(tm.get_goals().len(), 1);
+        assert_eq!(
+            tm.generate_snapshot(annul_time).unwrap().recent_goal,
+            Some((Color::White, 7))
+        );
+
+        tm.annul_goal(0, AnnulmentReason::PuckNotInGoal, annul_time)
+            .unwrap();
+        assert_eq!(tm.get_scores(), BlackWhiteBundle::new(0, 0));
+        assert_eq!(tm.get_goals(), vec![]);
+        assert_eq!(tm.generate_snapshot(annul_time).unwrap().recent_goal, None);
+
+        let json = tm.current_game_stats.as_json();
+        assert_eq!(json.matches("\"$type\":\"goalAnnulled\"").count(), 2);
+        assert!(json.contains("\"reason\":\"foulByScoringTeam\""));
+        assert!(json.contains("\"reason\":\"puckNotInGoal\""));
+        assert!(!json.contains("\"$type\":\"goal\""));
+    }
+
+    #[test]
+    fn test_snapshot_game_stats() {
+        initialize();
+        let config = GameConfig {
+            half_play_duration: Duration::from_secs(10),
+            half_time_duration: Duration::from_secs(3),
+            num_team_timeouts_allowed: 1,
+            timeouts_counted_per_half: true,
+            overtime_allowed: false,
+            sudden_death_allowed: false,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        let mut tm = TournamentManager::new(config);
+        tm.set_period_and_game_clock_time(GamePeriod::FirstHalf, Duration::from_secs(10));
+        tm.start_game_clock(start);
+
+        tm.add_score(Color::Black, 4, at(2));
+        tm.start_penalty(
+            Color::White,
+            3,
+            PenaltyKind::FiveMinute,
+            at(3),
+            Infraction::FreeArm,
+        )
+        .unwrap();
+        tm.start_team_timeout(Color::Black, at(4)).unwrap();
+        tm.end_timeout(at(5)).unwrap();
+
+        let snapshot = tm.generate_snapshot(at(6)).unwrap();
+        assert_eq!(
+            snapshot.b_goals,
+            vec![GoalSnapshot {
+                player_number: 4,
+                period: GamePeriod::FirstHalf,
+                secs_in_period: 8,
+            }]
+        );
+        assert_eq!(snapshot.w_goals, vec![]);
+        let penalty = PenaltySnapshot {
+            player_number: 3,
+            time: PenaltyTime::Seconds(300),
+            infraction: Infraction::FreeArm,
+        };
+        assert_eq!(snapshot.w_game_penalties, vec![penalty.clone()]);
+        assert_eq!(snapshot.b_game_penalties, vec![]);
+        assert_eq!(snapshot.b_timeouts_used, 1);
+        assert_eq!(snapshot.w_timeouts_used, 0);
+
+        // The timeouts for the game are still counted when they are reset for the second half
+        tm.update(at(12)).unwrap();
+        tm.update(at(15)).unwrap();
+        assert_eq!(tm.current_period(), GamePeriod::SecondHalf);
+        tm.start_team_timeout(Color::Black, at(16)).unwrap();
+        tm.end_timeout(at(17)).unwrap();
+
+        // Once the game is over the penalty is not counted twice
+        tm.update(at(30)).unwrap();
+        assert_eq!(tm.current_period(), GamePeriod::BetweenGames);
+        let snapshot = tm.generate_snapshot(at(30)).unwrap();
+        assert_eq!(snapshot.b_timeouts_used, 2);
+        assert_eq!(snapshot.w_game_penalties, vec![penalty]);
+        assert_eq!(snapshot.b_goals.len(), 1);
+
+        tm.reset_game(at(31));
+        let snapshot = tm.generate_snapshot(at(31)).unwrap();
+        assert_eq!(snapshot.b_timeouts_used, 0);
+        assert_eq!(snapshot.w_game_penalties, vec![]);
+        assert_eq!(snapshot.b_goals, vec![]);
+    }
+
+    #[test]
+    fn test_switch_sides() {
+        initialize();
+        let start = Instant::now();
+        let config = GameConfig {
+            overtime_allowed: true,
+            ..Default::default()
+        };
+
+        let mut tm = TournamentManager::new(config);
+        tm.set_white_on_right(true);
+        tm.set_auto_switch_sides(true);
+
+        tm.set_period_and_game_clock_time(GamePeriod::FirstHalf, Duration::from_secs(1));
+        tm.start_game_clock(start);
+        assert!(tm.generate_snapshot(start).unwrap().white_on_right);
+
+        let now = start + Duration::from_secs(2);
+        tm.update(now).unwrap();
+        assert_eq!(tm.current_period(), GamePeriod::HalfTime);
+        assert!(!tm.generate_snapshot(now).unwrap().white_on_right);
+
+        tm.switch_sides(now);
+        assert!(tm.white_on_right);
+        tm.switch_sides(now);
+        assert!(!tm.white_on_right);
+
+        tm.stop_game_clock(now).unwrap();
+        tm.set_period_and_game_clock_time(GamePeriod::OvertimeFirstHalf, Duration::from_secs(1));
+        tm.start_game_clock(now);
+        let now = now + Duration::from_secs(2);
+        tm.update(now).unwrap();
+        assert_eq!(tm.current_period(), GamePeriod::OvertimeHalfTime);
+        assert!(tm.generate_snapshot(now).unwrap().white_on_right);
+
+        tm.switch_sides(now);
+        tm.reset_game(now);
+        assert!(tm.white_on_right);
+
+        tm.set_auto_switch_sides(false);
+        tm.stop_game_clock(now).unwrap();
+        tm.set_period_and_game_clock_time(GamePeriod::FirstHalf, Duration::from_secs(1));
+        tm.start_game_clock(now);
+        let now = now + Duration::from_secs(2);
+        tm.update(now).unwrap();
+        assert_eq!(tm.current_period(), GamePeriod::HalfTime);
+        assert!(tm.white_on_right);
+    }
+
+    #[test]
+    fn test_tenths() {
+        initialize();
+        let start = Instant::now();
+        let mut tm = TournamentManager::new(Default::default());
+        tm.set_period_and_game_clock_time(GamePeriod::SecondHalf, Duration::from_secs(62));
+        tm.start_game_clock(start);
+
+        // Off by default
+        let now = start + Duration::from_millis(5_250);
+        assert_eq!(tm.generate_snapshot(now).unwrap().tenths_in_period, None);
+        assert_eq!(
+            tm.next_update_time(now),
+            Some(now + Duration::from_millis(750))
+        );
+
+        tm.set_tenths_threshold(Duration::from_secs(60));
+        let now = start + Duration::from_millis(1_250);
+        let snapshot = tm.generate_snapshot(now).unwrap();
+        assert_eq!(snapshot.secs_in_period, 60);
+        assert_eq!(snapshot.tenths_in_period, None);
+        assert_eq!(
+            tm.next_update_time(now),
+            Some(now + Duration::from_millis(750))
+        );
+
+        let now = start + Duration::from_millis(2_250);
+        let snapshot = tm.generate_snapshot(now).unwrap();
+        assert_eq!(snapshot.secs_in_period, 59);
+        assert_eq!(snapshot.tenths_in_period, Some(7));
+        assert_eq!(
+            tm.next_update_time(now),
+            Some(now + Duration::from_millis(50))
+        );
+
+        // Only the halves count down to the end of play
+        tm.stop_game_clock(now).unwrap();
+        tm.set_period_and_game_clock_time(GamePeriod::HalfTime, Duration::from_secs(30));
+        tm.start_game_clock(now);
+        let now = now + Duration::from_millis(250);
+        assert_eq!(tm.generate_snapshot(now).unwrap().tenths_in_period, None);
+        assert_eq!(
+            tm.next_update_time(now),
+            Some(now + Duration::from_millis(750))
+        );
+
+        // A stopped clock keeps its tenths
+        tm.stop_game_clock(now).unwrap();
+        tm.set_period_and_game_clock_time(
+            GamePeriod::OvertimeSecondHalf,
+            Duration::from_millis(4_380),
+        );
+        let snapshot = tm.generate_snapshot(now).unwrap();
+        assert_eq!(snapshot.secs_in_period, 4);
+        assert_eq!(snapshot.tenths_in_period, Some(3));
+    }
+
+    #[test]
+    fn test_panel_animation() {
+        initialize();
+        let start = Instant::now();
+        let mut tm = TournamentManager::new(Default::default());
+        tm.set_period_and_game_clock_time(GamePeriod::FirstHalf, Duration::from_secs(30));
+        tm.start_game_clock(start);
+        let animation =
+            |tm: &mut TournamentManager, now| tm.generate_snapshot(now).unwrap().panel_animation;
+        assert_eq!(animation(&mut tm, start), None);
+
+        let now = start + Duration::from_millis(1_500);
+        tm.add_score(Color::White, 7, now);
+        assert_eq!(
+            animation(&mut tm, now),
+            Some(PanelAnimation::Goal(Color::White, 7))
+        );
+        // The update after the animation is early, so that the panels are told it has finished
+        let later = now + Duration::from_millis(2_750);
+        assert_eq!(
+            animation(&mut tm, later),
+            Some(PanelAnimation::Goal(Color::White, 7))
+        );
+        assert_eq!(
+            tm.next_update_time(later),
+            Some(now + Duration::from_secs(3))
+        );
+        let now = now + Duration::from_secs(3);
+        assert_eq!(animation(&mut tm, now), None);
+        assert_eq!(
+            tm.next_update_time(now),
+            Some(now + Duration::from_millis(500))
+        );
+
+        // Taking back the goal stops its animation
+        tm.add_score(Color::Black, 3, now);
+        tm.annul_goal(1, AnnulmentReason::EnteredInError, now)
+            .unwrap();
+        assert_eq!(animation(&mut tm, now), None);
+
+        tm.start_team_timeout(Color::Black, now).unwrap();
+        assert_eq!(animation(&mut tm, now), Some(PanelAnimation::Timeout));
+        tm.end_timeout(now).unwrap();
+
+        tm.start_clock(now);
+        let end = now + Duration::from_millis(25_500);
+        tm.update(end).unwrap();
+        assert_eq!(tm.current_period(), GamePeriod::HalfTime);
+        assert_eq!(animation(&mut tm, end), Some(PanelAnimation::PeriodEnd));
+        assert_eq!(animation(&mut tm, end + Duration::from_secs(3)), None);
+    }
+
+    #[test]
+    fn test_clock_policies() {
+        initialize();
+        let start = Instant::now();
+
+        // Clock keeps running after a goal by default
+        let mut tm = TournamentManager::new(Default::default());
+        tm.set_period_and_game_clock_time(GamePeriod::FirstHalf, Duration::from_secs(100));
+        tm.start_game_clock(start);
+        tm.add_score(Color::Black, 3, start + Duration::from_secs(1));
+        assert!(tm.clock_is_running());
+
+        let config = GameConfig {
+            stop_clock_on_goal: true,
+            ..Default::default()
+        };
+        let mut tm = TournamentManager::new(config);
+        tm.set_period_and_game_clock_time(GamePeriod::FirstHalf, Duration::from_secs(100));
+        tm.start_game_clock(start);
+
+        let now = start + Duration::from_secs(1);
+        tm.add_foul(Some(Color::White), Some(2), Infraction::Unknown, now)
+            .unwrap();
+        assert!(tm.clock_is_running());
+
+        tm.add_score(Color::Black, 3, now);
+        assert!(!tm.clock_is_running());
+        assert_eq!(tm.get_scores(), BlackWhiteBundle::new(1, 0));
+        let snapshot = tm.generate_snapshot(now).unwrap();
+        assert!(snapshot.clock_stopped_by_rule);
+        assert_eq!(snapshot.secs_in_period, 99);
+
+        let now = now + Duration::from_secs(5);
+        tm.start_clock(now);
+        assert!(tm.clock_is_running());
+        assert!(!tm.generate_snapshot(now).unwrap().clock_stopped_by_rule);
+
+        let config = GameConfig {
+            stopped_clock_final_duration: Duration::from_secs(120),
+            ..Default::default()
+        };
+        let mut tm = TournamentManager::new(config);
+        tm.set_period_and_game_clock_time(GamePeriod::SecondHalf, Duration::from_secs(125));
+        tm.start_game_clock(start);
+
+        // Before the final minutes, stoppages don't stop the clock
+        let now = start + Duration::from_secs(2);
+        tm.add_foul(None, None, Infraction::Unknown, now).unwrap();
+        tm.add_score(Color::White, 5, now);
+        assert!(tm.clock_is_running());
+
+        let now = start + Duration::from_secs(10);
+        tm.start_penalty(
+            Color::Black,
+            7,
+            PenaltyKind::OneMinute,
+            now,
+            Infraction::Unknown,
+        )
+        .unwrap();
+        assert!(!tm.clock_is_running());
+        assert!(tm.generate_snapshot(now).unwrap().clock_stopped_by_rule);
+
+        let now = now + Duration::from_secs(10);
+        tm.start_clock(now);
+        let now = now + Duration::from_secs(5);
+        tm.add_foul(Some(Color::White), None, Infraction::Unknown, now)
+            .unwrap();
+        assert!(!tm.clock_is_running());
+        assert_eq!(tm.game_clock_time(now), Some(Duration::from_secs(110)));
+    }
+
     // Test setup with rugby penalties that are incomplete when the period ends
     struct PenaltyTransitionTestSetup {
         config: GameConfig,
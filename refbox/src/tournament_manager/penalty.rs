@@ -32,6 +32,14 @@ impl PenaltyKind {
             Self::TotalDismissal => None,
         }
     }
+
+    /// The full length of the penalty, as shown in a snapshot
+    pub(crate) fn as_penalty_time(self) -> PenaltyTime {
+        match self.as_duration() {
+            Some(duration) => PenaltyTime::Seconds(duration.as_secs() as u16),
+            None => PenaltyTime::TotalDismissal,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -0,0 +1,536 @@
+//! A small scripting language for driving a `TournamentManager` with synthetic time.
+//!
+//! A scenario is a list of lines. Blank lines and lines starting with `#` are ignored. Lines
+//! starting with `config` hold a `GameConfig` value in TOML syntax, all other lines are steps of
+//! the form `at <time> <command>`, where `<time>` is measured from the start of the scenario and
+//! is written as either `62s` or `1:02`. Steps must be in chronological order. Config keys that
+//! `GameConfig` doesn't have, or values it can't parse, fail the scenario.
+//!
+//! ```text
+//! config half_play_duration = 60
+//! at 0s start clock
+//! at 62s goal black 5
+//! at 70s team timeout white
+//! at 960s expect period SecondHalf
+//! ```
+//!
+//! Commands:
+//! - `start clock`, `stop clock`, `start play now`
+//! - `set period <period> <time>` (only while the clock is stopped)
+//! - `goal <color> <number>`, `scores <black> <white>`
+//! - `team timeout <color>`, `ref timeout`, `penalty shot`, `rugby penalty shot`, `end timeout`
+//! - `penalty <color> <number> <30s|1m|2m|4m|5m|dismissal>`
+//! - `expect period <period>`, `expect clock <time>`, `expect score <black> <white>`,
+//!   `expect timeout <none|black|white|ref|penalty shot>`, `expect running`, `expect stopped`,
+//!   `expect penalties <color> <count>`, `expect penalty <color> <index> <time|dismissal>`
+
+use super::{penalty::PenaltyKind, TournamentManager};
+use std::time::Duration;
+use tokio::time::Instant;
+use toml::Table;
+use uwh_common::{
+    config::Game as GameConfig,
+    game_snapshot::{Color, GamePeriod, Infraction, PenaltyTime, TimeoutSnapshot},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Scenario {
+    pub config: GameConfig,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Step {
+    pub line: usize,
+    pub at: Duration,
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Action {
+    StartClock,
+    StopClock,
+    StartPlayNow,
+    SetPeriod(GamePeriod, Duration),
+    Goal(Color, u8),
+    SetScores(u8, u8),
+    TeamTimeout(Color),
+    RefTimeout,
+    PenaltyShot,
+    RugbyPenaltyShot,
+    EndTimeout,
+    Penalty(Color, u8, PenaltyKind),
+    Expect(Expectation),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Expectation {
+    Period(GamePeriod),
+    Clock(Duration),
+    Score(u8, u8),
+    Timeout(TimeoutKind),
+    Running(bool),
+    PenaltyCount(Color, usize),
+    Penalty(Color, usize, PenaltyTime),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TimeoutKind {
+    None,
+    Black,
+    White,
+    Ref,
+    PenaltyShot,
+}
+
+impl TimeoutKind {
+    fn of(timeout: TimeoutSnapshot) -> Self {
+        match timeout {
+            TimeoutSnapshot::None => Self::None,
+            TimeoutSnapshot::Black(_) => Self::Black,
+            TimeoutSnapshot::White(_) => Self::White,
+            TimeoutSnapshot::Ref(_) => Self::Ref,
+            TimeoutSnapshot::PenaltyShot(_) => Self::PenaltyShot,
+        }
+    }
+}
+
+impl Scenario {
+    pub fn parse(script: &str) -> Result<Self, String> {
+        let mut config = String::new();
+        let mut steps = Vec::new();
+
+        for (i, line) in script.lines().enumerate() {
+            let line_num = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(value) = line.strip_prefix("config ") {
+                config.push_str(value);
+                config.push('\n');
+                continue;
+            }
+
+            let step = parse_step(line).map_err(|e| format!("line {line_num}: {e}"))?;
+            if let Some(prev) = steps.last().map(|s: &Step| s.at) {
+                if step.0 < prev {
+                    return Err(format!(
+                        "line {line_num}: steps must be in chronological order"
+                    ));
+                }
+            }
+            steps.push(Step {
+                line: line_num,
+                at: step.0,
+                action: step.1,
+            });
+        }
+
+        Ok(Self {
+            config: parse_config(&config).map_err(|e| format!("invalid config: {e}"))?,
+            steps,
+        })
+    }
+
+    /// Runs the scenario against a fresh `TournamentManager`, returning the first failed step
+    pub fn run(&self) -> Result<(), String> {
+        let start = Instant::now();
+        let mut now = start;
+        let mut tm = TournamentManager::new(self.config.clone());
+
+        for step in self.steps.iter() {
+            let target = start + step.at;
+            advance(&mut tm, &mut now, target)
+                .and_then(|_| apply(&mut tm, &step.action, now))
+                .map_err(|e| format!("line {} ({:?}): {e}", step.line, step.action))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Applies the `config` lines on top of the default `GameConfig`. Unlike loading the app's config
+/// file, unknown keys and values of the wrong type are errors, so that a typo can't leave a
+/// scenario silently running with the defaults
+fn parse_config(config: &str) -> Result<GameConfig, String> {
+    let overrides: Table = toml::from_str(config).map_err(|e| e.to_string())?;
+    let mut table = Table::try_from(GameConfig::default()).map_err(|e| e.to_string())?;
+    for (key, value) in overrides {
+        match table.get(&key) {
+            Some(default) if default.type_str() == value.type_str() => {
+                table.insert(key, value);
+            }
+            Some(default) => {
+                return Err(format!(
+                    "`{key}` must be {}, not {}",
+                    default.type_str(),
+                    value.type_str()
+                ))
+            }
+            None => return Err(format!("unknown key `{key}`")),
+        }
+    }
+    table
+        .try_into()
+        .map_err(|e: toml::de::Error| e.message().to_string())
+}
+
+/// Parses, runs, and panics with the failing step if the scenario doesn't pass
+pub(super) fn run_scenario(script: &str) {
+    if let Err(e) = Scenario::parse(script).and_then(|s| s.run()) {
+        panic!("Scenario failed at {e}");
+    }
+}
+
+/// Steps the `TournamentManager` forward one clock tick at a time, as the app would
+fn advance(tm: &mut TournamentManager, now: &mut Instant, target: Instant) -> Result<(), String> {
+    while let Some(mut next) = tm.next_update_time(*now) {
+        // When `now` is on a whole second the next update time is `now` itself
+        if next <= *now {
+            next = *now + Duration::from_secs(1);
+        }
+        if next > target {
+            break;
+        }
+        *now = next;
+        tm.update(*now).map_err(|e| e.to_string())?;
+    }
+    *now = target;
+    tm.update(*now).map_err(|e| e.to_string())
+}
+
+fn apply(tm: &mut TournamentManager, action: &Action, now: Instant) -> Result<(), String> {
+    match *action {
+        Action::StartClock => tm.start_clock(now),
+        Action::StopClock => tm.stop_clock(now).map_err(|e| e.to_string())?,
+        Action::StartPlayNow => tm.start_play_now(now).map_err(|e| e.to_string())?,
+        Action::SetPeriod(period, time) => {
+            if tm.clock_is_running() {
+                return Err("can't set the period while the clock is running".to_string());
+            }
+            tm.set_period_and_game_clock_time(period, time);
+        }
+        Action::Goal(color, number) => tm.add_score(color, number, now),
+        Action::SetScores(black, white) => {
+            tm.set_scores(super::BlackWhiteBundle::new(black, white), now)
+        }
+        Action::TeamTimeout(color) => tm
+            .start_team_timeout(color, now)
+            .map_err(|e| e.to_string())?,
+        Action::RefTimeout => tm.start_ref_timeout(now).map_err(|e| e.to_string())?,
+        Action::PenaltyShot => tm.start_penalty_shot(now).map_err(|e| e.to_string())?,
+        Action::RugbyPenaltyShot => tm
+            .start_rugby_penalty_shot(now)
+            .map_err(|e| e.to_string())?,
+        Action::EndTimeout => tm.end_timeout(now).map_err(|e| e.to_string())?,
+        Action::Penalty(color, number, kind) => tm
+            .start_penalty(color, number, kind, now, Infraction::Unknown)
+            .map_err(|e| e.to_string())?,
+        Action::Expect(ref expectation) => check(tm, expectation, now)?,
+    }
+    Ok(())
+}
+
+fn check(
+    tm: &mut TournamentManager,
+    expectation: &Expectation,
+    now: Instant,
+) -> Result<(), String> {
+    let snapshot = tm
+        .generate_snapshot(now)
+        .ok_or_else(|| "could not generate a snapshot".to_string())?;
+
+    let mismatch = |expected: &dyn std::fmt::Debug, found: &dyn std::fmt::Debug| {
+        Err(format!("expected {expected:?}, found {found:?}"))
+    };
+
+    match *expectation {
+        Expectation::Period(period) => {
+            if snapshot.current_period != period {
+                return mismatch(&period, &snapshot.current_period);
+            }
+        }
+        Expectation::Clock(time) => {
+            if u64::from(snapshot.secs_in_period) != time.as_secs() {
+                return mismatch(&time.as_secs(), &snapshot.secs_in_period);
+            }
+        }
+        Expectation::Score(black, white) => {
+            if (snapshot.b_score, snapshot.w_score) != (black, white) {
+                return mismatch(&(black, white), &(snapshot.b_score, snapshot.w_score));
+            }
+        }
+        Expectation::Timeout(kind) => {
+            if TimeoutKind::of(snapshot.timeout) != kind {
+                return mismatch(&kind, &snapshot.timeout);
+            }
+        }
+        Expectation::Running(running) => {
+            if tm.clock_is_running() != running {
+                return mismatch(&running, &tm.clock_is_running());
+            }
+        }
+        Expectation::PenaltyCount(color, count) => {
+            let found = tm.get_penalties()[color].len();
+            if found != count {
+                return mismatch(&count, &found);
+            }
+        }
+        Expectation::Penalty(color, index, time) => {
+            let penalties = match color {
+                Color::Black => &snapshot.b_penalties,
+                Color::White => &snapshot.w_penalties,
+            };
+            let found = penalties.get(index).map(|p| p.time);
+            if found != Some(time) {
+                return mismatch(&Some(time), &found);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_step(line: &str) -> Result<(Duration, Action), String> {
+    let words: Vec<String> = line.split_whitespace().map(str::to_lowercase).collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+    let (at, command) = match words.as_slice() {
+        ["at", time, command @ ..] => (parse_time(time)?, command),
+        _ => return Err("steps must start with `at <time>`".to_string()),
+    };
+
+    let action = match command {
+        ["start", "clock"] => Action::StartClock,
+        ["stop", "clock"] => Action::StopClock,
+        ["start", "play", "now"] => Action::StartPlayNow,
+        ["set", "period", period, time] => {
+            Action::SetPeriod(parse_period(period)?, parse_time(time)?)
+        }
+        ["goal", color, number] => Action::Goal(parse_color(color)?, parse_num(number)?),
+        ["scores", black, white] => Action::SetScores(parse_num(black)?, parse_num(white)?),
+        ["team", "timeout", color] => Action::TeamTimeout(parse_color(color)?),
+        ["ref", "timeout"] => Action::RefTimeout,
+        ["penalty", "shot"] => Action::PenaltyShot,
+        ["rugby", "penalty", "shot"] => Action::RugbyPenaltyShot,
+        ["end", "timeout"] => Action::EndTimeout,
+        ["penalty", color, number, kind] => Action::Penalty(
+            parse_color(color)?,
+            parse_num(number)?,
+            parse_penalty_kind(kind)?,
+        ),
+        ["expect", expectation @ ..] => Action::Expect(parse_expectation(expectation)?),
+        _ => return Err(format!("unknown command `{}`", command.join(" "))),
+    };
+
+    Ok((at, action))
+}
+
+fn parse_expectation(words: &[&str]) -> Result<Expectation, String> {
+    Ok(match words {
+        ["period", period] => Expectation::Period(parse_period(period)?),
+        ["clock", time] => Expectation::Clock(parse_time(time)?),
+        ["score", black, white] => Expectation::Score(parse_num(black)?, parse_num(white)?),
+        ["timeout", "none"] => Expectation::Timeout(TimeoutKind::None),
+        ["timeout", "black"] => Expectation::Timeout(TimeoutKind::Black),
+        ["timeout", "white"] => Expectation::Timeout(TimeoutKind::White),
+        ["timeout", "ref"] => Expectation::Timeout(TimeoutKind::Ref),
+        ["timeout", "penalty", "shot"] => Expectation::Timeout(TimeoutKind::PenaltyShot),
+        ["running"] => Expectation::Running(true),
+        ["stopped"] => Expectation::Running(false),
+        ["penalties", color, count] => {
+            Expectation::PenaltyCount(parse_color(color)?, parse_num(count)?)
+        }
+        ["penalty", color, index, "dismissal"] => Expectation::Penalty(
+            parse_color(color)?,
+            parse_num(index)?,
+            PenaltyTime::TotalDismissal,
+        ),
+        ["penalty", color, index, time] => Expectation::Penalty(
+            parse_color(color)?,
+            parse_num(index)?,
+            PenaltyTime::Seconds(parse_time(time)?.as_secs() as u16),
+        ),
+        _ => return Err(format!("unknown expectation `{}`", words.join(" "))),
+    })
+}
+
+fn parse_time(word: &str) -> Result<Duration, String> {
+    let secs = if let Some(secs) = word.strip_suffix('s') {
+        secs.parse::<u64>().ok()
+    } else if let Some((mins, secs)) = word.split_once(':') {
+        mins.parse::<u64>()
+            .ok()
+            .zip(secs.parse::<u64>().ok().filter(|s| *s < 60))
+            .map(|(m, s)| m * 60 + s)
+    } else {
+        None
+    };
+    secs.map(Duration::from_secs)
+        .ok_or_else(|| format!("invalid time `{word}`"))
+}
+
+fn parse_num<T: std::str::FromStr>(word: &str) -> Result<T, String> {
+    word.trim_start_matches('#')
+        .parse()
+        .map_err(|_| format!("invalid number `{word}`"))
+}
+
+fn parse_color(word: &str) -> Result<Color, String> {
+    match word {
+        "black" => Ok(Color::Black),
+        "white" => Ok(Color::White),
+        _ => Err(format!("invalid color `{word}`")),
+    }
+}
+
+fn parse_period(word: &str) -> Result<GamePeriod, String> {
+    Ok(match word {
+        "betweengames" => GamePeriod::BetweenGames,
+        "firsthalf" => GamePeriod::FirstHalf,
+        "halftime" => GamePeriod::HalfTime,
+        "secondhalf" => GamePeriod::SecondHalf,
+        "preovertime" => GamePeriod::PreOvertime,
+        "overtimefirsthalf" => GamePeriod::OvertimeFirstHalf,
+        "overtimehalftime" => GamePeriod::OvertimeHalfTime,
+        "overtimesecondhalf" => GamePeriod::OvertimeSecondHalf,
+        "presuddendeath" => GamePeriod::PreSuddenDeath,
+        "suddendeath" => GamePeriod::SuddenDeath,
+        _ => return Err(format!("invalid period `{word}`")),
+    })
+}
+
+fn parse_penalty_kind(word: &str) -> Result<PenaltyKind, String> {
+    Ok(match word {
+        "30s" => PenaltyKind::ThirtySecond,
+        "1m" => PenaltyKind::OneMinute,
+        "2m" => PenaltyKind::TwoMinute,
+        "4m" => PenaltyKind::FourMinute,
+        "5m" => PenaltyKind::FiveMinute,
+        "dismissal" => PenaltyKind::TotalDismissal,
+        _ => return Err(format!("invalid penalty kind `{word}`")),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let scenario = Scenario::parse(
+            "# A comment\n\
+            config half_play_duration = 60\n\
+            config overtime_allowed = false\n\
+            \n\
+            at 0s start clock\n\
+            at 1:02 goal Black #5\n\
+            at 62s expect period FirstHalf\n\
+            at 70s penalty white 3 2m\n\
+            at 70s expect penalty white 0 2:00\n",
+        )
+        .unwrap();
+
+        assert_eq!(scenario.config.half_play_duration, Duration::from_secs(60));
+        assert!(!scenario.config.overtime_allowed);
+        assert_eq!(
+            scenario.config.half_time_duration,
+            GameConfig::default().half_time_duration
+        );
+        assert_eq!(
+            scenario.steps.iter().map(|s| &s.action).collect::<Vec<_>>(),
+            vec![
+                &Action::StartClock,
+                &Action::Goal(Color::Black, 5),
+                &Action::Expect(Expectation::Period(GamePeriod::FirstHalf)),
+                &Action::Penalty(Color::White, 3, PenaltyKind::TwoMinute),
+                &Action::Expect(Expectation::Penalty(
+                    Color::White,
+                    0,
+                    PenaltyTime::Seconds(120)
+                )),
+            ]
+        );
+        assert_eq!(scenario.steps[1].line, 6);
+        assert_eq!(scenario.steps[1].at, Duration::from_secs(62));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Scenario::parse("start clock"),
+            Err("line 1: steps must start with `at <time>`".to_string())
+        );
+        assert_eq!(
+            Scenario::parse("at 5s start clock\nat 4s stop clock"),
+            Err("line 2: steps must be in chronological order".to_string())
+        );
+        assert_eq!(
+            Scenario::parse("at 5x start clock"),
+            Err("line 1: invalid time `5x`".to_string())
+        );
+        assert_eq!(
+            Scenario::parse("at 5s goal red 4"),
+            Err("line 1: invalid color `red`".to_string())
+        );
+        assert_eq!(
+            Scenario::parse("at 5s jump"),
+            Err("line 1: unknown command `jump`".to_string())
+        );
+        assert_eq!(
+            Scenario::parse("config half_play_duraton = 60"),
+            Err("invalid config: unknown key `half_play_duraton`".to_string())
+        );
+        assert_eq!(
+            Scenario::parse("config overtime_allowed = \"no\""),
+            Err("invalid config: `overtime_allowed` must be boolean, not string".to_string())
+        );
+        assert!(Scenario::parse("config half_play_duration = -60")
+            .unwrap_err()
+            .starts_with("invalid config: "));
+        assert!(Scenario::parse("config half_play_duration = ").is_err());
+    }
+
+    #[test]
+    fn test_failed_expectation() {
+        let scenario = Scenario::parse(
+            "config nominal_break = 10\n\
+            at 0s start clock\n\
+            at 5s expect period FirstHalf",
+        )
+        .unwrap();
+        assert_eq!(
+            scenario.run(),
+            Err(
+                "line 3 (Expect(Period(FirstHalf))): expected FirstHalf, found BetweenGames"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_full_game() {
+        run_scenario(include_str!("scenarios/full_game.txt"));
+    }
+
+    #[test]
+    fn test_overtime() {
+        run_scenario(include_str!("scenarios/overtime.txt"));
+    }
+
+    #[test]
+    fn test_sudden_death() {
+        run_scenario(include_str!("scenarios/sudden_death.txt"));
+    }
+
+    #[test]
+    fn test_rugby_penalty_shot() {
+        run_scenario(include_str!("scenarios/rugby_penalty_shot.txt"));
+    }
+
+    #[test]
+    fn test_penalty_culling() {
+        run_scenario(include_str!("scenarios/penalty_culling.txt"));
+    }
+}
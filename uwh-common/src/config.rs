@@ -11,6 +11,8 @@ pub struct Game {
     pub timeouts_counted_per_half: bool,
     pub overtime_allowed: bool,
     pub sudden_death_allowed: bool,
+    /// Whether the game clock stops after every goal until the ref restarts it
+    pub stop_clock_on_goal: bool,
     #[serde(with = "secs_only_duration")]
     pub half_play_duration: Duration,
     #[serde(with = "secs_only_duration")]
@@ -33,6 +35,10 @@ pub struct Game {
     pub nominal_break: Duration,
     #[serde(with = "secs_only_duration")]
     pub minimum_break: Duration,
+    /// Length of the end of the second half during which the game clock stops for every stoppage.
+    /// Zero disables the stopped clock
+    #[serde(with = "secs_only_duration")]
+    pub stopped_clock_final_duration: Duration,
 }
 
 impl Default for Game {
@@ -42,6 +48,7 @@ impl Default for Game {
             timeouts_counted_per_half: true,
             overtime_allowed: true,
             sudden_death_allowed: true,
+            stop_clock_on_goal: false,
             half_play_duration: Duration::from_secs(900),
             half_time_duration: Duration::from_secs(180),
             team_timeout_duration: Duration::from_secs(60),
@@ -53,6 +60,7 @@ impl Default for Game {
             post_game_duration: Duration::from_secs(120),
             nominal_break: Duration::from_secs(900),
             minimum_break: Duration::from_secs(240),
+            stopped_clock_final_duration: Duration::ZERO,
         }
     }
 }
@@ -64,6 +72,7 @@ impl Game {
             mut timeouts_counted_per_half,
             mut overtime_allowed,
             mut sudden_death_allowed,
+            mut stop_clock_on_goal,
             mut half_play_duration,
             mut half_time_duration,
             mut team_timeout_duration,
@@ -75,6 +84,7 @@ impl Game {
             mut post_game_duration,
             mut nominal_break,
             mut minimum_break,
+            mut stopped_clock_final_duration,
         } = Default::default();
 
         let process_duration = |old: &Table, name: &str, save: &mut Duration| {
@@ -120,6 +130,11 @@ impl Game {
                 sudden_death_allowed = old_sudden_death_allowed;
             }
         }
+        if let Some(old_stop_clock_on_goal) = old.get("stop_clock_on_goal") {
+            if let Some(old_stop_clock_on_goal) = old_stop_clock_on_goal.as_bool() {
+                stop_clock_on_goal = old_stop_clock_on_goal;
+            }
+        }
         process_duration(old, "half_play_duration", &mut half_play_duration);
         process_duration(old, "half_time_duration", &mut half_time_duration);
         process_duration(old, "team_timeout_duration", &mut team_timeout_duration);
@@ -135,12 +150,18 @@ impl Game {
         process_duration(old, "post_game_duration", &mut post_game_duration);
         process_duration(old, "nominal_break", &mut nominal_break);
         process_duration(old, "minimum_break", &mut minimum_break);
+        process_duration(
+            old,
+            "stopped_clock_final_duration",
+            &mut stopped_clock_final_duration,
+        );
 
         Self {
             num_team_timeouts_allowed,
             timeouts_counted_per_half,
             overtime_allowed,
             sudden_death_allowed,
+            stop_clock_on_goal,
             half_play_duration,
             half_time_duration,
             team_timeout_duration,
@@ -152,6 +173,7 @@ impl Game {
             post_game_duration,
             nominal_break,
             minimum_break,
+            stopped_clock_final_duration,
         }
     }
 }
@@ -225,6 +247,11 @@ pub mod test {
         old.insert("post_game_duration".to_string(), toml::Value::Integer(12));
         old.insert("nominal_break".to_string(), toml::Value::Integer(345));
         old.insert("minimum_break".to_string(), toml::Value::Integer(111));
+        old.insert("stop_clock_on_goal".to_string(), toml::Value::Boolean(true));
+        old.insert(
+            "stopped_clock_final_duration".to_string(),
+            toml::Value::Integer(120),
+        );
 
         let gm = Game::migrate(&old);
         assert_eq!(gm.num_team_timeouts_allowed, 2);
@@ -241,5 +268,7 @@ pub mod test {
         assert_eq!(gm.post_game_duration, Duration::from_secs(12));
         assert_eq!(gm.nominal_break, Duration::from_secs(345));
         assert_eq!(gm.minimum_break, Duration::from_secs(111));
+        assert!(gm.stop_clock_on_goal);
+        assert_eq!(gm.stopped_clock_final_duration, Duration::from_secs(120));
     }
 }
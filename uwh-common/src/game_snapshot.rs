@@ -2,7 +2,7 @@
 use crate::config::Game;
 use crate::drawing_support::*;
 use arrayref::array_ref;
-use arrayvec::ArrayVec;
+use arrayvec::{ArrayString, ArrayVec};
 use core::cmp::{Ordering, PartialOrd};
 #[cfg(feature = "std")]
 use core::{cmp::min, time::Duration};
@@ -34,6 +34,18 @@ pub struct GameSnapshotNoHeap {
     pub b_penalties: ArrayVec<PenaltySnapshot, PANEL_PENALTY_COUNT>,
     pub w_penalties: ArrayVec<PenaltySnapshot, PANEL_PENALTY_COUNT>,
     pub is_old_game: bool,
+    /// Not part of the encoded snapshot, deployed panels can't decode any more bits in it. The
+    /// `TransmittedData` wrapper carries it instead
+    pub clock_stopped_by_rule: bool,
+    /// Also not part of the encoded snapshot, the `TransmittedData` wrapper carries them
+    pub b_team_code: Option<TeamCode>,
+    pub w_team_code: Option<TeamCode>,
+    /// Scrolled across the panels instead of the period, also carried by `TransmittedData`
+    pub message: Option<PanelMessage>,
+    /// Carried in the flags of `TransmittedData`, see `GameSnapshot::tenths_in_period`
+    pub tenths_in_period: Option<u8>,
+    /// Also carried by `TransmittedData`
+    pub panel_animation: Option<PanelAnimation>,
 }
 
 /// All the information needed by a UI to draw the current state of the game. Requires the `std`
@@ -59,6 +71,44 @@ pub struct GameSnapshot {
     pub tournament_id: u32,
     pub recent_goal: Option<(Color, u8)>,
     pub next_period_len_secs: Option<u32>,
+    /// Whether white is currently on the right side of the panels, as seen from the front
+    #[serde(default)]
+    pub white_on_right: bool,
+    /// Whether the game clock was stopped automatically by the clock rules, rather than by the ref
+    #[serde(default)]
+    pub clock_stopped_by_rule: bool,
+    /// Goals scored in the current game, in the order they were scored
+    #[serde(default)]
+    pub b_goals: Vec<GoalSnapshot>,
+    #[serde(default)]
+    pub w_goals: Vec<GoalSnapshot>,
+    /// Every penalty given in the current game, including those already served. `time` is the
+    /// length of the penalty rather than the time remaining
+    #[serde(default)]
+    pub b_game_penalties: Vec<PenaltySnapshot>,
+    #[serde(default)]
+    pub w_game_penalties: Vec<PenaltySnapshot>,
+    /// Team timeouts taken in the current game, over all periods
+    #[serde(default)]
+    pub b_timeouts_used: u16,
+    #[serde(default)]
+    pub w_timeouts_used: u16,
+    /// Short names of the teams for the panels, if they are known
+    #[serde(default)]
+    pub b_team_code: Option<TeamCode>,
+    #[serde(default)]
+    pub w_team_code: Option<TeamCode>,
+    /// Message to show on the panels instead of the period, if there is one
+    #[serde(default)]
+    pub message: Option<PanelMessage>,
+    /// Tenths of a second on the game clock, after `secs_in_period`. Only set near the end of a
+    /// period, when the clock should be shown to the tenth of a second
+    #[serde(default)]
+    pub tenths_in_period: Option<u8>,
+    /// An animation the panels should be playing, set for `PanelAnimation::millis()` after the
+    /// event that started it
+    #[serde(default)]
+    pub panel_animation: Option<PanelAnimation>,
 }
 
 #[cfg(feature = "std")]
@@ -91,6 +141,12 @@ impl From<GameSnapshot> for GameSnapshotNoHeap {
             b_penalties: process_penalties(snapshot.b_penalties),
             w_penalties: process_penalties(snapshot.w_penalties),
             is_old_game: snapshot.is_old_game,
+            clock_stopped_by_rule: snapshot.clock_stopped_by_rule,
+            b_team_code: snapshot.b_team_code,
+            w_team_code: snapshot.w_team_code,
+            message: snapshot.message,
+            tenths_in_period: snapshot.tenths_in_period,
+            panel_animation: snapshot.panel_animation,
         }
     }
 }
@@ -102,12 +158,252 @@ pub struct PenaltySnapshot {
     pub infraction: Infraction,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct GoalSnapshot {
+    pub player_number: u8,
+    pub period: GamePeriod,
+    /// The time on the game clock when the goal was scored
+    pub secs_in_period: u32,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct InfractionSnapshot {
     pub player_number: Option<u8>,
     pub infraction: Infraction,
 }
 
+/// The most characters in a `TeamCode`
+pub const TEAM_CODE_LEN: usize = 5;
+
+/// The most bytes of UTF-8 in a `TeamCode`, since a Latin-1 character takes up to two
+pub const TEAM_CODE_CAPACITY: usize = 2 * TEAM_CODE_LEN;
+
+/// How many letters of a one word team name are used for its code
+const SHORT_TEAM_CODE_LEN: usize = 3;
+
+/// Whether `c` can be sent to the panels: a printable Latin-1 character, which all of the panel
+/// fonts can draw, that isn't a lower case letter
+fn is_panel_char(c: char) -> bool {
+    matches!(c, ' '..='~' | '\u{a0}'..='\u{ff}') && to_panel_uppercase(c) == c
+}
+
+/// `c` in upper case, if Latin-1 has an upper case form of it
+fn to_panel_uppercase(c: char) -> char {
+    let mut upper = c.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(upper), None) if upper <= '\u{ff}' => upper,
+        _ => c,
+    }
+}
+
+/// Checks that `s` is 1 to `max_len` characters that can be sent to the panels, returning the
+/// first that can't
+fn check_panel_chars(s: &str, max_len: usize) -> Result<(), char> {
+    if let Some(c) = s.chars().find(|&c| !is_panel_char(c)) {
+        return Err(c);
+    }
+    match s.chars().nth(max_len) {
+        Some(c) => Err(c),
+        None if s.is_empty() => Err('\0'),
+        None => Ok(()),
+    }
+}
+
+/// Decodes Latin-1 `bytes`, padded with zeros, into a string of characters that can be sent to
+/// the panels, returning the first byte that can't
+fn decode_panel_chars<const CAP: usize>(bytes: &[u8]) -> Result<ArrayString<CAP>, u8> {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    if let Some(&b) = bytes[len..].iter().find(|&&b| b != 0) {
+        return Err(b);
+    }
+    let mut s = ArrayString::new();
+    for &b in &bytes[..len] {
+        if !is_panel_char(b as char) {
+            return Err(b);
+        }
+        s.push(b as char);
+    }
+    Ok(s)
+}
+
+/// Encodes `s` as Latin-1, one byte per character, padded with zeros
+fn encode_panel_chars<const LEN: usize>(s: &str) -> [u8; LEN] {
+    let mut val = [0u8; LEN];
+    for (b, c) in val.iter_mut().zip(s.chars()) {
+        *b = c as u8;
+    }
+    val
+}
+
+/// A short name for a team that is drawn on the panels, e.g. `NZL`. Holds 1 to `TEAM_CODE_LEN`
+/// printable Latin-1 characters without lower case letters, which all of the panel fonts can draw
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(
+    try_from = "ArrayString<TEAM_CODE_CAPACITY>",
+    into = "ArrayString<TEAM_CODE_CAPACITY>"
+)]
+pub struct TeamCode(ArrayString<TEAM_CODE_CAPACITY>);
+
+impl TeamCode {
+    /// Makes a code for the team called `name`, which can also be a code typed by the ref. Letters
+    /// are made upper case and anything else that can't be drawn is left out. Names that are then
+    /// short enough are used as they are, longer ones are shortened to the initials of their words,
+    /// or the first three letters if there is only one word. `None` if nothing drawable is left
+    pub fn from_name(name: &str) -> Option<Self> {
+        let words = || {
+            name.split_whitespace().filter_map(|word| {
+                let mut chars = word
+                    .chars()
+                    .map(to_panel_uppercase)
+                    .filter(|&c| c != ' ' && is_panel_char(c))
+                    .peekable();
+                chars.peek().is_some().then_some(chars)
+            })
+        };
+
+        let word_count = words().count();
+        let len = words().map(|word| word.count()).sum::<usize>() + word_count.saturating_sub(1);
+        let mut code = ArrayString::new();
+        if word_count == 0 {
+            return None;
+        } else if len <= TEAM_CODE_LEN {
+            for (i, word) in words().enumerate() {
+                if i > 0 {
+                    code.push(' ');
+                }
+                word.for_each(|c| code.push(c));
+            }
+        } else if word_count > 1 {
+            words()
+                .filter_map(|mut word| word.next())
+                .take(TEAM_CODE_LEN)
+                .for_each(|c| code.push(c));
+        } else {
+            words()
+                .flatten()
+                .take(SHORT_TEAM_CODE_LEN)
+                .for_each(|c| code.push(c));
+        }
+        Some(Self(code))
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<ArrayString<TEAM_CODE_CAPACITY>> for TeamCode {
+    type Error = DecodingError;
+
+    fn try_from(code: ArrayString<TEAM_CODE_CAPACITY>) -> Result<Self, Self::Error> {
+        check_panel_chars(&code, TEAM_CODE_LEN).map_err(DecodingError::InvalidTeamCode)?;
+        Ok(Self(code))
+    }
+}
+
+impl From<TeamCode> for ArrayString<TEAM_CODE_CAPACITY> {
+    fn from(code: TeamCode) -> Self {
+        code.0
+    }
+}
+
+impl core::fmt::Display for TeamCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The most characters in a `PanelMessage`
+pub const PANEL_MESSAGE_LEN: usize = 32;
+
+/// The most bytes of UTF-8 in a `PanelMessage`, since a Latin-1 character takes up to two
+pub const PANEL_MESSAGE_CAPACITY: usize = 2 * PANEL_MESSAGE_LEN;
+
+/// A line of text scrolled across the panels, e.g. `FINAL AT 14:00`. Holds 1 to
+/// `PANEL_MESSAGE_LEN` printable Latin-1 characters without lower case letters, which all of the
+/// panel fonts can draw
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(
+    try_from = "ArrayString<PANEL_MESSAGE_CAPACITY>",
+    into = "ArrayString<PANEL_MESSAGE_CAPACITY>"
+)]
+pub struct PanelMessage(ArrayString<PANEL_MESSAGE_CAPACITY>);
+
+impl PanelMessage {
+    /// Makes a message from text typed by the ref. Letters are made upper case, runs of whitespace
+    /// become a single space and anything else that can't be drawn is left out. Text that is too
+    /// long is cut short. `None` if nothing drawable is left
+    pub fn from_text(text: &str) -> Option<Self> {
+        let mut message = ArrayString::new();
+        let chars = text
+            .split_whitespace()
+            .flat_map(|word| {
+                core::iter::once(' ').chain(
+                    word.chars()
+                        .map(to_panel_uppercase)
+                        .filter(|&c| c != ' ' && is_panel_char(c)),
+                )
+            })
+            .skip_while(|&c| c == ' ')
+            .take(PANEL_MESSAGE_LEN);
+        for c in chars {
+            message.push(c);
+        }
+        let len = message.trim_end().len();
+        message.truncate(len);
+        (!message.is_empty()).then_some(Self(message))
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<ArrayString<PANEL_MESSAGE_CAPACITY>> for PanelMessage {
+    type Error = DecodingError;
+
+    fn try_from(message: ArrayString<PANEL_MESSAGE_CAPACITY>) -> Result<Self, Self::Error> {
+        check_panel_chars(&message, PANEL_MESSAGE_LEN)
+            .map_err(DecodingError::InvalidPanelMessage)?;
+        Ok(Self(message))
+    }
+}
+
+impl From<PanelMessage> for ArrayString<PANEL_MESSAGE_CAPACITY> {
+    fn from(message: PanelMessage) -> Self {
+        message.0
+    }
+}
+
+impl core::fmt::Display for PanelMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An animation the panels play instead of the game for a moment after something happens. The
+/// panels play it from when it first appears in what they are sent
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum PanelAnimation {
+    /// A goal, by the team and cap number
+    Goal(Color, u8),
+    /// A timeout started, the kind of timeout is in the snapshot
+    Timeout,
+    /// The game clock ran out at the end of a period
+    PeriodEnd,
+}
+
+impl PanelAnimation {
+    /// How long the animation plays for, in milliseconds
+    pub const fn millis(self) -> u32 {
+        match self {
+            Self::Goal(..) => 3_000,
+            Self::Timeout => 2_000,
+            Self::PeriodEnd => 3_000,
+        }
+    }
+}
+
 #[derive(Derivative, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[derivative(Debug, Default, Clone, Copy)]
 pub enum GamePeriod {
@@ -450,6 +746,8 @@ pub enum EncodingError {
     PenaltyTimeTooLarge(u16),
     /// Timeout time was too large for a u16: {0}
     TimeoutTimeTooLarge(u16),
+    /// Tenths of a second was more than one digit: {0}
+    TenthsTooLarge(u8),
 }
 
 #[cfg(feature = "std")]
@@ -462,6 +760,18 @@ pub enum DecodingError {
     InvalidTimeoutType(u16),
     /// Invalid game period: {0:#04x}
     InvalidGamePeriod(u8),
+    /// Invalid character in team code: {0:?}
+    InvalidTeamCode(char),
+    /// Invalid character in panel message: {0:?}
+    InvalidPanelMessage(char),
+    /// Invalid tenths of a second: {0}
+    InvalidTenths(u8),
+    /// Invalid panel animation: {0:#04x}
+    InvalidPanelAnimation(u8),
+    /// Invalid panel theme: {0}
+    InvalidPanelTheme(u8),
+    /// Invalid panel brightness: {0}
+    InvalidPanelBrightness(u8),
 }
 
 #[cfg(feature = "std")]
@@ -503,6 +813,78 @@ impl PenaltySnapshot {
     }
 }
 
+impl TeamCode {
+    pub const ENCODED_LEN: usize = TEAM_CODE_LEN;
+
+    /// The characters of the code in Latin-1, padded with zeros
+    pub fn encode(&self) -> [u8; Self::ENCODED_LEN] {
+        encode_panel_chars(&self.0)
+    }
+
+    pub fn encode_none() -> [u8; Self::ENCODED_LEN] {
+        [0u8; Self::ENCODED_LEN]
+    }
+
+    pub fn decode(bytes: &[u8; Self::ENCODED_LEN]) -> Result<Option<Self>, DecodingError> {
+        let code =
+            decode_panel_chars(bytes).map_err(|b| DecodingError::InvalidTeamCode(b as char))?;
+        Ok((!code.is_empty()).then_some(Self(code)))
+    }
+}
+
+impl PanelMessage {
+    pub const ENCODED_LEN: usize = PANEL_MESSAGE_LEN;
+
+    /// The characters of the message in Latin-1, padded with zeros
+    pub fn encode(&self) -> [u8; Self::ENCODED_LEN] {
+        encode_panel_chars(&self.0)
+    }
+
+    pub fn encode_none() -> [u8; Self::ENCODED_LEN] {
+        [0u8; Self::ENCODED_LEN]
+    }
+
+    pub fn decode(bytes: &[u8; Self::ENCODED_LEN]) -> Result<Option<Self>, DecodingError> {
+        let message =
+            decode_panel_chars(bytes).map_err(|b| DecodingError::InvalidPanelMessage(b as char))?;
+        Ok((!message.is_empty()).then_some(Self(message)))
+    }
+}
+
+impl PanelAnimation {
+    pub const ENCODED_LEN: usize = 2;
+
+    /// The kind of animation, then the cap number of a goal
+    pub fn encode(&self) -> Result<[u8; Self::ENCODED_LEN], EncodingError> {
+        match *self {
+            Self::Goal(_, player_number @ 100..) => {
+                Err(EncodingError::PlayerNumTooLarge(player_number))
+            }
+            Self::Goal(Color::Black, player_number) => Ok([1, player_number]),
+            Self::Goal(Color::White, player_number) => Ok([2, player_number]),
+            Self::Timeout => Ok([3, 0]),
+            Self::PeriodEnd => Ok([4, 0]),
+        }
+    }
+
+    pub fn encode_none() -> [u8; Self::ENCODED_LEN] {
+        [0u8; Self::ENCODED_LEN]
+    }
+
+    pub fn decode(bytes: &[u8; Self::ENCODED_LEN]) -> Result<Option<Self>, DecodingError> {
+        let animation = match *bytes {
+            [0, 0] => return Ok(None),
+            [1, player_number @ ..=99] => Self::Goal(Color::Black, player_number),
+            [2, player_number @ ..=99] => Self::Goal(Color::White, player_number),
+            [3, 0] => Self::Timeout,
+            [4, 0] => Self::PeriodEnd,
+            [0..=4, b] => return Err(DecodingError::InvalidPanelAnimation(b)),
+            [kind, _] => return Err(DecodingError::InvalidPanelAnimation(kind)),
+        };
+        Ok(Some(animation))
+    }
+}
+
 impl TimeoutSnapshot {
     pub fn encode(&self) -> Result<[u8; 2], EncodingError> {
         match self {
@@ -638,6 +1020,12 @@ impl GameSnapshotNoHeap {
             b_penalties,
             w_penalties,
             is_old_game: ((bytes[0] & 0x80) != 0x00),
+            clock_stopped_by_rule: false,
+            b_team_code: None,
+            w_team_code: None,
+            message: None,
+            tenths_in_period: None,
+            panel_animation: None,
         })
     }
 }
@@ -1080,6 +1468,12 @@ mod test {
             b_penalties: ArrayVec::new(),
             w_penalties: ArrayVec::new(),
             is_old_game: false,
+            clock_stopped_by_rule: false,
+            b_team_code: None,
+            w_team_code: None,
+            message: None,
+            tenths_in_period: None,
+            panel_animation: None,
         };
 
         let test_state =
@@ -1178,6 +1572,202 @@ mod test {
 
         test_state(&mut state)?;
 
+        // The flag is left out so that the encoding matches what deployed panels decode
+        state.current_period = GamePeriod::SecondHalf;
+        state.timeout = TimeoutSnapshot::None;
+        state.clock_stopped_by_rule = true;
+        let encoded = state.encode()?;
+        assert_eq!(encoded[0], GamePeriod::SecondHalf.encode());
+        let decoded = GameSnapshotNoHeap::decode(&encoded)?;
+        assert!(!decoded.clock_stopped_by_rule);
+        assert_eq!(
+            decoded,
+            GameSnapshotNoHeap {
+                clock_stopped_by_rule: false,
+                ..state
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_team_code_from_name() {
+        let code = |name| TeamCode::from_name(name).map(|code| code.to_string());
+        assert_eq!(code("NZL"), Some("NZL".to_string()));
+        assert_eq!(code("nzl"), Some("NZL".to_string()));
+        assert_eq!(code("  Aus 2 "), Some("AUS 2".to_string()));
+        assert_eq!(code("Canada"), Some("CAN".to_string()));
+        assert_eq!(code("New Zealand"), Some("NZ".to_string()));
+        assert_eq!(code("Team USA Elite Men"), Some("TUEM".to_string()));
+        assert_eq!(code("A B C D E F G"), Some("ABCDE".to_string()));
+        assert_eq!(code("Österreich"), Some("ÖST".to_string()));
+        assert_eq!(code("Köln"), Some("KÖLN".to_string()));
+        assert_eq!(code("Straße"), Some("STR".to_string()));
+        assert_eq!(code("St. Kilda"), Some("SK".to_string()));
+        assert_eq!(code("U-19"), Some("U-19".to_string()));
+        assert_eq!(code("A+B"), Some("A+B".to_string()));
+        assert_eq!(code("éèêëç"), Some("ÉÈÊËÇ".to_string()));
+        assert_eq!(code(""), None);
+        assert_eq!(code(" \u{2603} \t "), None);
+    }
+
+    #[test]
+    fn test_team_code_encoding() -> Result<(), Box<dyn std::error::Error>> {
+        for name in ["NZL", "A", "AUS 2", "12345"] {
+            let code = TeamCode::from_name(name).unwrap();
+            let encoded = code.encode();
+            assert_eq!(&encoded[..name.len()], name.as_bytes());
+            assert_eq!(TeamCode::decode(&encoded)?, Some(code));
+        }
+        assert_eq!(TeamCode::decode(&TeamCode::encode_none())?, None);
+
+        // Each character is one byte of Latin-1
+        let code = TeamCode::from_name("ÖÄÜÉÑ").unwrap();
+        assert_eq!(code.encode(), [0xd6, 0xc4, 0xdc, 0xc9, 0xd1]);
+        assert_eq!(TeamCode::decode(&code.encode())?, Some(code));
+
+        assert_eq!(
+            TeamCode::decode(b"nzl\0\0"),
+            Err(DecodingError::InvalidTeamCode('n'))
+        );
+        assert_eq!(
+            TeamCode::decode(b"NZ\0L\0"),
+            Err(DecodingError::InvalidTeamCode('L'))
+        );
+        assert_eq!(
+            TeamCode::decode(&[b'A', 0x96, 0, 0, 0]),
+            Err(DecodingError::InvalidTeamCode('\u{96}'))
+        );
+        assert_eq!(
+            TeamCode::decode(&[b'A', 0xf6, 0, 0, 0]),
+            Err(DecodingError::InvalidTeamCode('ö'))
+        );
+
+        // Deserializing checks the code in the same way
+        let code: TeamCode = serde_json::from_str("\"NZL\"")?;
+        assert_eq!(serde_json::to_string(&code)?, "\"NZL\"");
+        let code: TeamCode = serde_json::from_str("\"ÖÖÖÖÖ\"")?;
+        assert_eq!(code.as_str(), "ÖÖÖÖÖ");
+        assert!(serde_json::from_str::<TeamCode>("\"nzl\"").is_err());
+        assert!(serde_json::from_str::<TeamCode>("\"\"").is_err());
+        assert!(serde_json::from_str::<TeamCode>("\"TOO LONG\"").is_err());
+        assert!(serde_json::from_str::<TeamCode>("\"NZ\u{2603}\"").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_panel_message_from_text() {
+        let message = |text| PanelMessage::from_text(text).map(|message| message.to_string());
+        assert_eq!(
+            message("Final at 14:00"),
+            Some("FINAL AT 14:00".to_string())
+        );
+        assert_eq!(
+            message("  Court  #2\tclosed! "),
+            Some("COURT #2 CLOSED!".to_string())
+        );
+        assert_eq!(
+            message("[Results] at uwhscores.com/t"),
+            Some("[RESULTS] AT UWHSCORES.COM/T".to_string())
+        );
+        assert_eq!(
+            message("Finale in Köln, 14:00 €5"),
+            Some("FINALE IN KÖLN, 14:00 5".to_string())
+        );
+        assert_eq!(
+            message("Ünterwasser-Rugby ist auch schön, aber"),
+            Some("ÜNTERWASSER-RUGBY IST AUCH SCHÖN".to_string())
+        );
+        assert_eq!(
+            message("A very long message that will not fit at all"),
+            Some("A VERY LONG MESSAGE THAT WILL NO".to_string())
+        );
+        assert_eq!(
+            message("0123456789012345678901234567890 and more"),
+            Some("0123456789012345678901234567890".to_string())
+        );
+        assert_eq!(message(""), None);
+        assert_eq!(message(" \u{2603} "), None);
+    }
+
+    #[test]
+    fn test_panel_message_encoding() -> Result<(), Box<dyn std::error::Error>> {
+        let message = PanelMessage::from_text("Final at 14:00").unwrap();
+        let encoded = message.encode();
+        assert_eq!(&encoded[..14], b"FINAL AT 14:00");
+        assert!(encoded[14..].iter().all(|&b| b == 0));
+        assert_eq!(PanelMessage::decode(&encoded)?, Some(message));
+        assert_eq!(PanelMessage::decode(&PanelMessage::encode_none())?, None);
+
+        let message = PanelMessage::from_text("Spiel in Köln").unwrap();
+        let encoded = message.encode();
+        assert_eq!(&encoded[..13], b"SPIEL IN K\xd6LN");
+        assert_eq!(PanelMessage::decode(&encoded)?, Some(message));
+
+        let mut invalid = encoded;
+        invalid[20] = b'A';
+        assert_eq!(
+            PanelMessage::decode(&invalid),
+            Err(DecodingError::InvalidPanelMessage('A'))
+        );
+        let mut invalid = encoded;
+        invalid[0] = b'f';
+        assert_eq!(
+            PanelMessage::decode(&invalid),
+            Err(DecodingError::InvalidPanelMessage('f'))
+        );
+        let mut invalid = encoded;
+        invalid[0] = 0x7f;
+        assert_eq!(
+            PanelMessage::decode(&invalid),
+            Err(DecodingError::InvalidPanelMessage('\u{7f}'))
+        );
+
+        let message: PanelMessage = serde_json::from_str("\"NEXT: FINAL\"")?;
+        assert_eq!(serde_json::to_string(&message)?, "\"NEXT: FINAL\"");
+        let message: PanelMessage = serde_json::from_str(&format!("\"{}\"", "Ö".repeat(32)))?;
+        assert_eq!(message.as_str().chars().count(), PANEL_MESSAGE_LEN);
+        assert!(serde_json::from_str::<PanelMessage>(&format!("\"{}\"", "Ö".repeat(33))).is_err());
+        assert!(serde_json::from_str::<PanelMessage>("\"final\"").is_err());
+        assert!(serde_json::from_str::<PanelMessage>("\"\"").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_panel_animation_encoding() -> Result<(), DecodingError> {
+        for (animation, encoded) in [
+            (PanelAnimation::Goal(Color::Black, 7), [1, 7]),
+            (PanelAnimation::Goal(Color::White, 99), [2, 99]),
+            (PanelAnimation::Timeout, [3, 0]),
+            (PanelAnimation::PeriodEnd, [4, 0]),
+        ] {
+            assert_eq!(animation.encode(), Ok(encoded));
+            assert_eq!(PanelAnimation::decode(&encoded)?, Some(animation));
+        }
+        assert_eq!(
+            PanelAnimation::decode(&PanelAnimation::encode_none())?,
+            None
+        );
+
+        assert_eq!(
+            PanelAnimation::Goal(Color::White, 100).encode(),
+            Err(EncodingError::PlayerNumTooLarge(100))
+        );
+        assert_eq!(
+            PanelAnimation::decode(&[1, 100]),
+            Err(DecodingError::InvalidPanelAnimation(100))
+        );
+        assert_eq!(
+            PanelAnimation::decode(&[3, 1]),
+            Err(DecodingError::InvalidPanelAnimation(1))
+        );
+        assert_eq!(
+            PanelAnimation::decode(&[5, 0]),
+            Err(DecodingError::InvalidPanelAnimation(5))
+        );
         Ok(())
     }
 }